@@ -0,0 +1,116 @@
+use std::collections::HashSet;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use edtui::{EditorState, Index2};
+use edtui_jagged::Jagged;
+use ratatui::{backend::TestBackend, Terminal};
+use thoth_note::app::AppState;
+use thoth_note::models::note::Note;
+use thoth_note::ui::{from_str, render_preview, PreviewCache};
+
+/// Width of the preview panel in the three-panel layout of a 200 column terminal
+const WIDTH: u16 = 80;
+
+/// A long note mixing the blocks the preview renders, about 3000 lines
+fn large_note() -> String {
+    let mut content = String::from("# Large note\n\n");
+    for section in 0..100 {
+        content.push_str(&format!("## Section {}\n\n", section));
+        for paragraph in 0..3 {
+            content.push_str(&format!(
+                "Paragraph {} of section {} with **bold**, *italic* and `code` \
+                 long enough to wrap around the preview at least once.\n\n",
+                paragraph, section
+            ));
+        }
+        content.push_str("- first item\n- second item\n  - nested item\n\n");
+        content.push_str("```rust\nfn main() {\n    println!(\"Hello\");\n}\n```\n\n");
+        content.push_str("> A quote\n\n");
+    }
+    content
+}
+
+fn state_with(content: &str) -> AppState {
+    AppState {
+        notes: vec![Note {
+            title: "Large note".to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        }],
+        editor_state: EditorState::new(Jagged::from(content)),
+        ..Default::default()
+    }
+}
+
+/// Render until the background worker has highlighted every code block
+fn warm_up(cache: &mut PreviewCache, lines: &edtui::Lines) {
+    cache.update(lines, WIDTH, &HashSet::new());
+    while cache.is_waiting() {
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        cache.update(lines, WIDTH, &HashSet::new());
+    }
+}
+
+fn render(c: &mut Criterion) {
+    let content = large_note();
+    let lines = Jagged::from(content.as_str());
+
+    c.bench_function("full render", |b| b.iter(|| from_str(&content, WIDTH)));
+
+    c.bench_function("cached render, unchanged", |b| {
+        let mut cache = PreviewCache::default();
+        warm_up(&mut cache, &lines);
+        b.iter(|| {
+            cache.update(&lines, WIDTH, &HashSet::new());
+            cache.visible(1500, 40)
+        })
+    });
+
+    c.bench_function("cached render, one block edited", |b| {
+        let mut cache = PreviewCache::default();
+        warm_up(&mut cache, &lines);
+        let mut edited = lines.clone();
+        let mut typed = false;
+        b.iter(|| {
+            // Type and delete a character in turn, so the note keeps its size
+            let index = Index2::new(1500, 0);
+            if typed {
+                edited.remove(index);
+            } else {
+                edited.insert(index, 'x');
+            }
+            typed = !typed;
+            cache.update(&edited, WIDTH, &HashSet::new());
+            cache.visible(1500, 40)
+        })
+    });
+
+    c.bench_function("cached render, width changed", |b| {
+        let mut cache = PreviewCache::default();
+        warm_up(&mut cache, &lines);
+        let mut width = WIDTH;
+        b.iter(|| {
+            width = if width == WIDTH { WIDTH + 1 } else { WIDTH };
+            cache.update(&lines, width, &HashSet::new());
+        })
+    });
+
+    c.bench_function("preview frame, typing", |b| {
+        let mut terminal = Terminal::new(TestBackend::new(WIDTH, 50)).unwrap();
+        let mut state = state_with(&content);
+        terminal
+            .draw(|frame| render_preview(frame, &mut state, frame.area()))
+            .unwrap();
+        b.iter(|| {
+            let cursor = state.editor_state.cursor;
+            state.editor_state.lines.insert(cursor, 'x');
+            terminal
+                .draw(|frame| render_preview(frame, &mut state, frame.area()))
+                .unwrap();
+            state.editor_state.lines.remove(cursor);
+        })
+    });
+}
+
+criterion_group!(benches, render);
+criterion_main!(benches);
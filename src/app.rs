@@ -1,16 +1,63 @@
-use chrono::Local;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::stdout;
+use std::mem;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use chrono::{Days, Local, Months, NaiveDate};
 use color_eyre::Result;
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
-use edtui::{EditorEventHandler, EditorState};
+use crossterm::event::{
+    self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+    Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use edtui::clipboard::{Clipboard, ClipboardTrait};
+use edtui::{EditorEventHandler, EditorMode, EditorState, Index2, RowIndex};
 use edtui_jagged::Jagged;
+use ratatui::layout::{Position, Rect};
 use ratatui::{DefaultTerminal, Frame};
 use tui_widget_list::ListState;
 
+use crate::clipboard::{self, SystemClipboard};
 use crate::commands::Command;
+use crate::completion::Completion;
+use crate::export::{find_exporter, Exporter, PlainExporter, DEFAULT_WIDTH};
+use crate::external_editor;
+use crate::journal;
+use crate::keymap::{self, EditingMode, ExCommand, KeymapSettings, LeaderMatch};
+use crate::links::{self, LinkTarget};
+use crate::markdown_edit::{self, Format};
 use crate::models::note::Note;
-use crate::storage::{fs::FSStorage, Storage};
+use crate::outline::{self, Heading};
+use crate::sorting::{SortKey, SortSettings};
+use crate::spellcheck::{self, Misspelling, SpellChecker, SpellSettings};
+use crate::stats::{Activity, NoteStats, VaultStats};
+use crate::storage::{
+    fs::FSStorage,
+    git::{GitRepo, Revision},
+    Storage,
+};
+use crate::sync::{error::SyncResult, sync_notes, SyncProgress, SyncReport, SyncSettings};
+use crate::templates::{self, Template};
 use crate::theme::AppTheme;
-use crate::ui;
+use crate::ui::{self, Areas, CardLayout, LayoutSettings, PreviewCache, SourceMap};
+
+/// Rows the mouse wheel scrolls at a time
+const MOUSE_SCROLL_ROWS: usize = 3;
+
+/// Modifiers of `Alt` with an uppercase letter
+const ALT_SHIFT: KeyModifiers = KeyModifiers::ALT.union(KeyModifiers::SHIFT);
+const CONTROL_SHIFT: KeyModifiers = KeyModifiers::CONTROL.union(KeyModifiers::SHIFT);
+
+/// Actions of the note list context menu, in order
+pub const CONTEXT_MENU_ITEMS: [&str; 4] = ["Rename", "Delete", "Move up", "Move down"];
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum View {
@@ -20,10 +67,97 @@ pub enum View {
     Rename,
     LivePreview,
     DeleteConfirm,
+    Passphrase,
+    History,
+    Calendar,
+    TemplateVariable,
+    Outline,
+    ContextMenu,
+    /// Two open notes edited side by side
+    Split,
+    /// Statistics and dates of the selected note
+    NoteInfo,
+    /// Archived notes, which can be restored
+    Archive,
+    /// Statistics of the whole vault
+    Dashboard,
+}
+
+/// What to do once a new passphrase has been chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PassphraseAction {
+    Change,
+    EncryptNote,
+    EncryptVault,
+}
+
+/// A note being created from a template, waiting for its custom variables
+#[derive(Debug, Clone)]
+pub struct TemplatePrompt {
+    pub title: String,
+    pub template: Template,
+    pub variables: Vec<String>,
+    pub values: HashMap<String, String>,
+}
+
+impl TemplatePrompt {
+    /// Variable the dialog is asking for
+    pub fn current_variable(&self) -> Option<&str> {
+        self.variables.get(self.values.len()).map(String::as_str)
+    }
+}
+
+/// Context menu opened by right-clicking a note
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextMenu {
+    /// Where the note list was clicked
+    pub x: u16,
+    pub y: u16,
+    pub selected: usize,
+    /// Where the menu was drawn on the last frame
+    pub area: Rect,
+}
+
+/// A note open in the tab bar, with the editor state it had when it was left
+pub struct OpenTab {
+    pub title: String,
+    /// Empty while the tab is the active one, whose state is `AppState::editor_state`
+    pub editor: EditorState,
+    /// Replaced by the next note picked in the list, until it is edited or kept
+    pub transient: bool,
+}
+
+/// Second note of the split view
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Split {
+    /// Title of the note in the pane not taking input
+    pub other: String,
+    /// Whether the pane taking input is the left (or top) one
+    pub focus_left: bool,
+}
+
+/// Border between two panes, which can be dragged to resize them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PaneBorder {
+    List,
+    Editor,
+}
+
+/// Stage of the passphrase dialog
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PassphrasePrompt {
+    Unlock,
+    New(PassphraseAction),
+    /// Repeat the passphrase entered in the previous stage
+    Confirm(PassphraseAction, String),
 }
 
 pub struct AppState {
     pub notes: Vec<Note>,
+    /// Archived notes, left out of the list
+    pub archive: Vec<Note>,
+    /// Note selected in the archive view
+    pub archive_selected: usize,
     pub list_state: ListState,
     pub editor_state: EditorState,
     pub preview_scroll_offset: usize,
@@ -31,13 +165,126 @@ pub struct AppState {
     pub theme: AppTheme,
     pub rename_buffer: String,
     pub creating_new_note: bool,
+    /// Templates offered in the new note dialog
+    pub templates: Vec<Template>,
+    /// Template picked for the new note, `None` for an empty note
+    pub template_selected: Option<usize>,
+    pub template_prompt: Option<TemplatePrompt>,
     pub confirm_delete: bool,
+    pub passphrase_buffer: String,
+    pub passphrase_prompt: PassphrasePrompt,
+    pub passphrase_error: Option<String>,
+    /// Commits of the selected note, shown in the history view
+    pub history: Vec<Revision>,
+    pub history_selected: usize,
+    pub history_diff: String,
+    pub history_scroll: usize,
+    /// Message shown at the bottom until the next key press
+    pub status_message: Option<String>,
+    /// Day highlighted in the calendar popup
+    pub calendar_date: NaiveDate,
+    /// Headings of the selected note, shown in the outline
+    pub outline: Vec<Heading>,
+    pub outline_selected: usize,
+    /// Heading of the section at the cursor or scroll position
+    pub outline_current: Option<usize>,
+    /// Headings whose sections are hidden in the preview
+    pub folded_headings: HashSet<usize>,
+    /// Preview row of each heading, updated on every render
+    pub preview_heading_rows: Vec<usize>,
+    /// Heading the preview scrolls to on the next render
+    pub preview_heading_jump: Option<usize>,
+    /// Preview rows of the note lines, updated on every render
+    pub preview_source_map: SourceMap,
+    /// Rendered preview, redone when the note changes
+    pub preview_cache: PreviewCache,
+    /// Cursor row and line count the preview last scrolled to
+    pub preview_followed: Option<(usize, usize)>,
+    /// Whether scrolling the preview moves the editor cursor along
+    pub scroll_editor_with_preview: bool,
+    pub layout: LayoutSettings,
+    /// Order and sections of the note list
+    pub sorting: SortSettings,
+    /// Where the panes were drawn on the last frame, for mouse clicks
+    pub areas: Areas,
+    pub context_menu: Option<ContextMenu>,
+    /// Notes open in the tab bar, in order
+    pub tabs: Vec<OpenTab>,
+    pub active_tab: usize,
+    pub split: Option<Split>,
+    /// Where each tab was drawn on the last frame
+    pub tab_areas: Vec<Rect>,
+    pub keymap: KeymapSettings,
+    /// Command being typed after `:`
+    pub command_line: Option<String>,
+    /// Keys typed after the leader so far
+    pub leader_keys: Option<String>,
+    /// Suggestions for the text being typed in the editor
+    pub completion: Option<Completion>,
+    /// Misspelled words of the note being edited, from the last check
+    pub misspellings: Vec<Misspelling>,
+    /// Statistics of the note being edited
+    pub note_stats: NoteStats,
+    /// Edits made on each day, for the dashboard
+    pub activity: Activity,
+    /// Statistics shown on the dashboard, collected when it was opened
+    pub dashboard: Option<VaultStats>,
+    /// Recently edited note selected on the dashboard
+    pub dashboard_selected: usize,
+}
+
+impl AppState {
+    /// Title of the note in the editor taking input
+    pub fn active_title(&self) -> Option<&str> {
+        self.tabs.get(self.active_tab).map(|tab| tab.title.as_str())
+    }
+
+    /// Notes a batch action applies to: the marked ones, or the selected note
+    /// when none are marked
+    pub fn batch_indices(&self) -> Vec<usize> {
+        let marked: Vec<usize> = self
+            .notes
+            .iter()
+            .enumerate()
+            .filter(|(_, note)| note.marked)
+            .map(|(index, _)| index)
+            .collect();
+        if !marked.is_empty() {
+            return marked;
+        }
+        self.list_state
+            .selected
+            .filter(|&selected| selected < self.notes.len())
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Messages from the background sync thread
+enum SyncEvent {
+    Progress(SyncProgress),
+    Finished(SyncResult<SyncReport>),
 }
 
 pub struct App {
     state: AppState,
     editor_event_handler: EditorEventHandler,
     storage: Box<dyn Storage>,
+    /// Directory holding the notes
+    root_dir: PathBuf,
+    /// Repository versioning the notes, when enabled with `thoth git init`
+    git: Option<GitRepo>,
+    /// Progress of the running sync, if any
+    sync_events: Option<Receiver<SyncEvent>>,
+    /// Border being dragged with the mouse
+    resizing: Option<PaneBorder>,
+    /// Whether to hand the terminal to an external editor after this event
+    edit_externally: bool,
+    spell_checker: SpellChecker,
+    /// Clipboard shared by the editors of all tabs
+    clipboard: Clipboard,
+    /// Hash of the editor lines when they were last looked at
+    editor_hash: Option<u64>,
     running: bool,
 }
 
@@ -47,6 +294,8 @@ impl Default for AppState {
         list_state.select(Some(0));
         Self {
             notes: Vec::new(),
+            archive: Vec::new(),
+            archive_selected: 0,
             list_state,
             editor_state: EditorState::default(),
             preview_scroll_offset: 0,
@@ -54,7 +303,46 @@ impl Default for AppState {
             theme: AppTheme::default(),
             rename_buffer: String::new(),
             creating_new_note: false,
+            templates: Vec::new(),
+            template_selected: None,
+            template_prompt: None,
             confirm_delete: false,
+            passphrase_buffer: String::new(),
+            passphrase_prompt: PassphrasePrompt::Unlock,
+            passphrase_error: None,
+            history: Vec::new(),
+            history_selected: 0,
+            history_diff: String::new(),
+            history_scroll: 0,
+            status_message: None,
+            calendar_date: journal::today(),
+            outline: Vec::new(),
+            outline_selected: 0,
+            outline_current: None,
+            folded_headings: HashSet::new(),
+            preview_heading_rows: Vec::new(),
+            preview_heading_jump: None,
+            preview_source_map: SourceMap::default(),
+            preview_cache: PreviewCache::default(),
+            preview_followed: None,
+            scroll_editor_with_preview: false,
+            layout: LayoutSettings::default(),
+            sorting: SortSettings::default(),
+            areas: Areas::default(),
+            context_menu: None,
+            tabs: Vec::new(),
+            active_tab: 0,
+            split: None,
+            tab_areas: Vec::new(),
+            keymap: KeymapSettings::default(),
+            command_line: None,
+            leader_keys: None,
+            completion: None,
+            misspellings: Vec::new(),
+            note_stats: NoteStats::default(),
+            activity: Activity::default(),
+            dashboard: None,
+            dashboard_selected: 0,
         }
     }
 }
@@ -66,6 +354,8 @@ impl App {
 
         // Create storage
         let storage = Box::new(FSStorage::new());
+        let root_dir = storage.root_dir().to_path_buf();
+        let git = GitRepo::open(&root_dir);
 
         // Initialize storage
         if let Err(e) = storage.init() {
@@ -79,7 +369,7 @@ impl App {
             let all_zero = notes.iter().all(|note| note.order == 0);
             if all_zero && !notes.is_empty() {
                 // Sort by updated_at first to maintain previous order
-                notes.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+                notes.sort_by_key(|note| std::cmp::Reverse(note.updated_at));
 
                 // Assign orders based on position
                 for (i, note) in notes.iter_mut().enumerate() {
@@ -104,32 +394,66 @@ impl App {
             }
         }
 
-        state.notes = loaded_notes;
+        (state.archive, state.notes) = loaded_notes.into_iter().partition(|note| note.archived);
+        state.layout = LayoutSettings::load(&root_dir);
+        state.keymap = KeymapSettings::load(&root_dir);
+        state.sorting = SortSettings::load(&root_dir);
+        state.sorting.sort(&mut state.notes);
+        state.activity = Activity::load(&root_dir, &state.notes);
+        let editor_event_handler = state.keymap.mode.event_handler();
+        let spell_checker = SpellChecker::start(SpellSettings::load(&root_dir), &root_dir);
 
-        // Set initial editor content
-        if !state.notes.is_empty() {
-            let content = state.notes[0].content.clone();
-            state.editor_state.lines = Jagged::from(content);
+        // Commit anything changed since the last session, such as edits made outside Thoth
+        if let Some(git) = &git {
+            if let Err(e) = git.commit_all("Record changes made outside Thoth") {
+                state.status_message = Some(format!("Failed to commit: {}", e));
+            }
+            if let Ok(conflicts) = git.conflicted_notes() {
+                for note in state.notes.iter_mut() {
+                    note.conflicted = conflicts.contains(&note.title);
+                }
+            }
         }
 
-        Self {
+        // Ask for the passphrase up front when there are encrypted notes
+        if state
+            .notes
+            .iter()
+            .chain(&state.archive)
+            .any(|note| note.locked)
+        {
+            state.current_view = View::Passphrase;
+        }
+
+        let mut app = Self {
             state,
-            editor_event_handler: EditorEventHandler::default(),
+            editor_event_handler,
             storage,
+            root_dir,
+            git,
+            sync_events: None,
+            resizing: None,
+            edit_externally: false,
+            spell_checker,
+            clipboard: Clipboard::new(SystemClipboard::default()),
+            editor_hash: None,
             running: false,
-        }
+        };
+        // Set initial editor content
+        app.load_note_to_editor(0);
+        app
     }
 
     fn create_example_notes() -> Vec<Note> {
-        let welcome_content = include_str!("welcome.md");
-        vec![Note {
+        let mut welcome = Note {
             title: "Welcome to Thoth".to_string(),
-            content: welcome_content.to_string(),
+            content: templates::WELCOME.to_string(),
             created_at: Local::now(),
             updated_at: Local::now(),
-            selected: false,
-            order: 0,
-        }]
+            ..Default::default()
+        };
+        welcome.update_text_info();
+        vec![welcome]
     }
 
     /// Run the application's main loop.
@@ -141,237 +465,2261 @@ impl App {
 
         self.running = true;
         while self.running {
+            let edited = self.editor_changed();
+            self.update_spelling(edited);
+            if edited {
+                self.update_stats();
+            }
             terminal.draw(|frame| self.draw(frame))?;
             self.handle_events()?;
+            if mem::take(&mut self.edit_externally) {
+                self.edit_in_external_editor(&mut terminal)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Edit the selected note in `$VISUAL` or `$EDITOR`, suspending the
+    /// interface until the editor exits.
+    ///
+    /// The editor works on the note's file when storage keeps it as plain text,
+    /// and on a temporary copy otherwise.
+    fn edit_in_external_editor(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        self.save_editor_content_to_current_note();
+        // The note of the focused tab, the list selection may have moved on
+        let Some(selected) = self
+            .state
+            .active_title()
+            .and_then(|title| self.state.notes.iter().position(|note| note.title == title))
+        else {
+            return Ok(());
+        };
+        let note = &mut self.state.notes[selected];
+        if note.locked {
+            self.state.status_message = Some("Unlock the note to edit it".to_string());
+            return Ok(());
+        }
+
+        // The editor opens the note as it is on screen
+        if let Err(e) = self.storage.write_note(note) {
+            self.state.status_message = Some(format!("Failed to save '{}': {}", note.title, e));
+            return Ok(());
+        }
+        note.dirty = false;
+        // Held until the edit is read back; dropping it removes the copy
+        let (path, temporary) = match self.storage.plain_note_path(note) {
+            Some(path) => (path, None),
+            None => match external_editor::write_temp_file(&note.title, &note.content) {
+                Ok(temp) => (temp.path().to_path_buf(), Some(temp)),
+                Err(e) => {
+                    self.state.status_message = Some(format!("Failed to write a copy: {}", e));
+                    return Ok(());
+                }
+            },
+        };
+
+        execute!(
+            stdout(),
+            DisableMouseCapture,
+            DisableBracketedPaste,
+            LeaveAlternateScreen
+        )?;
+        disable_raw_mode()?;
+        let edited = external_editor::edit_file(&path).and_then(|()| fs::read_to_string(&path));
+        enable_raw_mode()?;
+        execute!(
+            stdout(),
+            EnterAlternateScreen,
+            EnableMouseCapture,
+            EnableBracketedPaste
+        )?;
+        terminal.clear()?;
+
+        if let Some(temp) = temporary {
+            if let Err(e) = temp.close() {
+                self.state.status_message =
+                    Some(format!("Failed to remove {}: {}", path.display(), e));
+            }
+        }
+        match edited {
+            Ok(content) => self.apply_external_edit(selected, content),
+            Err(e) => self.state.status_message = Some(format!("Editing failed: {}", e)),
         }
         Ok(())
     }
 
+    fn apply_external_edit(&mut self, index: usize, content: String) {
+        let Some(note) = self.state.notes.get_mut(index) else {
+            return;
+        };
+        // Editors end files with a newline the note may not have had
+        if content == note.content || content.strip_suffix('\n') == Some(note.content.as_str()) {
+            return;
+        }
+        note.content = content;
+        note.updated_at = Local::now();
+        note.update_text_info();
+        if let Err(e) = self.storage.write_note(note) {
+            self.state.status_message = Some(format!("Failed to save '{}': {}", note.title, e));
+            return;
+        }
+        note.dirty = false;
+        let message = format!("Update '{}'", note.title);
+        self.record_change(&message);
+        self.record_activity();
+
+        if let Some(tab) = self.state.tabs.get_mut(self.state.active_tab) {
+            tab.transient = false;
+        }
+        self.refresh_tabs();
+    }
+
     /// Renders the user interface.
     fn draw(&mut self, frame: &mut Frame) {
         ui::render(frame, &mut self.state);
     }
 
     fn handle_events(&mut self) -> Result<()> {
-        if let Event::Key(key) = event::read()? {
-            if key.kind == KeyEventKind::Press {
-                if let Some(command) = self.key_to_command(key) {
-                    self.execute_command(command);
-                } else {
-                    match self.state.current_view {
-                        View::Editor | View::LivePreview => {
-                            self.editor_event_handler
-                                .on_event(Event::Key(key), &mut self.state.editor_state);
-                        }
-                        View::Rename => {
-                            self.handle_rename_input(key);
-                        }
-                        _ => {}
-                    }
-                }
+        // Keep redrawing while a sync reports progress
+        if self.sync_events.is_some()
+            || self.state.preview_cache.is_waiting()
+            || self.spell_checker.is_waiting()
+        {
+            self.poll_sync();
+            if !event::poll(Duration::from_millis(100))? {
+                return Ok(());
             }
         }
+
+        match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => self.handle_key(key),
+            Event::Mouse(mouse) => self.handle_mouse(mouse),
+            Event::Paste(text) => self.handle_paste(&text),
+            _ => {}
+        }
         Ok(())
     }
 
-    fn key_to_command(&self, key: KeyEvent) -> Option<Command> {
-        if self.state.current_view == View::DeleteConfirm {
-            match key.code {
-                KeyCode::Enter => Some(Command::DeleteNote),
-                KeyCode::Esc => Some(Command::CancelRename), // Reuse CancelRename as it does the same thing
-                _ => None,
-            }
+    fn handle_key(&mut self, key: KeyEvent) {
+        self.state.status_message = None;
+        if self.state.command_line.is_some() {
+            return self.handle_command_line_input(key);
+        }
+        if self.state.leader_keys.is_some() {
+            return self.handle_leader_input(key);
+        }
+        if self.start_vim_command(key) || self.handle_completion_input(key) {
+            return;
+        }
+        if let Some(command) = self.key_to_command(key) {
+            self.state.completion = None;
+            self.run_command(command);
         } else {
-            match (key.modifiers, key.code) {
-                (KeyModifiers::CONTROL, KeyCode::Char('q')) => Some(Command::Quit),
-                (KeyModifiers::CONTROL, KeyCode::Down) => Some(Command::NextNote),
-                (KeyModifiers::CONTROL, KeyCode::Up) => Some(Command::PreviousNote),
-                (KeyModifiers::ALT, KeyCode::Up) => Some(Command::MoveNoteUp),
-                (KeyModifiers::ALT, KeyCode::Down) => Some(Command::MoveNoteDown),
-                (KeyModifiers::CONTROL, KeyCode::Char('e')) => {
-                    Some(Command::SwitchView(View::Editor))
+            match self.state.current_view {
+                // Locked notes have no content to edit, and notes are not edited while syncing
+                View::Editor | View::LivePreview | View::Split if self.can_edit() => {
+                    self.edit(key);
                 }
-                (KeyModifiers::CONTROL, KeyCode::Char('l')) => {
-                    Some(Command::SwitchView(View::LivePreview))
+                View::Rename | View::TemplateVariable => {
+                    self.handle_rename_input(key);
                 }
-                (KeyModifiers::CONTROL, KeyCode::Char('p')) => {
-                    Some(Command::SwitchView(View::Preview))
+                View::Passphrase => {
+                    self.handle_passphrase_input(key);
                 }
-                (KeyModifiers::CONTROL, KeyCode::Char('n')) => Some(Command::NewNote),
-                (KeyModifiers::CONTROL, KeyCode::Char('s')) => Some(Command::SaveNote),
-                (KeyModifiers::CONTROL, KeyCode::Char('d')) => Some(Command::DeleteNote),
-                (KeyModifiers::CONTROL, KeyCode::Char('j')) => Some(Command::ScrollDown),
-                (KeyModifiers::CONTROL, KeyCode::Char('k')) => Some(Command::ScrollUp),
-                (KeyModifiers::CONTROL, KeyCode::Char('r')) => Some(Command::RenameNote),
-                (KeyModifiers::NONE, KeyCode::Enter)
-                    if matches!(self.state.current_view, View::Rename) =>
-                {
-                    Some(Command::SubmitRename)
+                View::History => {
+                    self.handle_history_input(key);
                 }
-                (KeyModifiers::NONE, KeyCode::Esc)
-                    if matches!(self.state.current_view, View::Rename) =>
-                {
-                    Some(Command::CancelRename)
+                View::Calendar => {
+                    self.handle_calendar_input(key);
                 }
-                _ => None,
+                View::Dashboard => {
+                    self.handle_dashboard_input(key);
+                }
+                View::Archive => {
+                    self.handle_archive_input(key);
+                }
+                View::Outline => {
+                    self.handle_outline_input(key);
+                }
+                View::ContextMenu => {
+                    self.handle_context_menu_input(key);
+                }
+                _ => {}
             }
         }
     }
 
-    fn handle_rename_input(&mut self, key: KeyEvent) {
+    /// Pass a key to the editor, handling Markdown lists on the way
+    fn edit(&mut self, key: KeyEvent) {
+        let standard = self.state.keymap.mode == EditingMode::Standard;
+        let editor = &mut self.state.editor_state;
+        if standard && editor.mode == EditorMode::Normal {
+            keymap::capture_undo(editor);
+            editor.mode = EditorMode::Insert;
+        }
+
+        if editor.mode == EditorMode::Insert && editor.selection.is_none() {
+            let handled = match (key.modifiers, key.code) {
+                (KeyModifiers::NONE, KeyCode::Enter) => markdown_edit::continue_list(editor),
+                (KeyModifiers::NONE, KeyCode::Tab) => markdown_edit::indent_item(editor, false),
+                (_, KeyCode::BackTab) => markdown_edit::indent_item(editor, true),
+                _ => false,
+            };
+            if handled {
+                return;
+            }
+        }
+
+        if standard {
+            match key.code {
+                // There is no normal mode to go back to
+                KeyCode::Esc if editor.mode == EditorMode::Insert => return,
+                // Undo goes back a word at a time
+                KeyCode::Char(' ') | KeyCode::Enter if editor.mode == EditorMode::Insert => {
+                    keymap::capture_undo(editor);
+                }
+                _ => {}
+            }
+        }
+
+        self.editor_event_handler.on_event(Event::Key(key), editor);
+        // Leaving a selection goes back to typing
+        if standard && editor.mode == EditorMode::Normal {
+            editor.mode = EditorMode::Insert;
+        }
+
+        self.state.completion = if editor.mode == EditorMode::Insert && editor.selection.is_none() {
+            Completion::update(
+                self.state.completion.as_ref(),
+                &self.state.editor_state,
+                &self.state.notes,
+                self.state.active_title(),
+            )
+        } else {
+            None
+        };
+    }
+
+    /// Take pasted text as a whole, rather than as a key press per character
+    fn handle_paste(&mut self, text: &str) {
+        self.state.status_message = None;
+        // Prompts take a single line
+        let line = text.lines().next().unwrap_or_default();
+        if let Some(command_line) = &mut self.state.command_line {
+            command_line.push_str(line);
+            return;
+        }
+        match self.state.current_view {
+            View::Editor | View::LivePreview | View::Split if self.can_edit() => {
+                let editor = &mut self.state.editor_state;
+                if self.state.keymap.mode == EditingMode::Standard {
+                    editor.mode = EditorMode::Insert;
+                }
+                clipboard::paste_text(editor, text);
+                self.state.completion = None;
+            }
+            View::Rename | View::TemplateVariable => self.state.rename_buffer.push_str(line),
+            View::Passphrase => self.state.passphrase_buffer.push_str(line),
+            _ => {}
+        }
+    }
+
+    /// Move through or take the suggestions shown while typing
+    fn handle_completion_input(&mut self, key: KeyEvent) -> bool {
+        let Some(completion) = self
+            .state
+            .completion
+            .as_mut()
+            .filter(|completion| !completion.dismissed)
+        else {
+            return false;
+        };
+        if key.modifiers != KeyModifiers::NONE {
+            return false;
+        }
         match key.code {
-            KeyCode::Char(c) => {
-                self.state.rename_buffer.push(c);
+            KeyCode::Up => completion.select_next(true),
+            KeyCode::Down => completion.select_next(false),
+            KeyCode::Tab | KeyCode::Enter if completion.adds_word() => {
+                let word = completion.query.clone();
+                self.state.completion = None;
+                self.add_to_dictionary(&word);
+            }
+            KeyCode::Tab | KeyCode::Enter => {
+                completion.accept(&mut self.state.editor_state);
+                self.state.completion = None;
+            }
+            KeyCode::Esc => completion.dismissed = true,
+            _ => return false,
+        }
+        true
+    }
+
+    /// Start a leader mapping or the `:` command line from vim normal mode
+    fn start_vim_command(&mut self, key: KeyEvent) -> bool {
+        let normal_mode = self.state.keymap.mode == EditingMode::Vim
+            && self.state.editor_state.mode == EditorMode::Normal
+            && matches!(
+                self.state.current_view,
+                View::Editor | View::LivePreview | View::Split | View::Preview
+            );
+        if !normal_mode || !matches!(key.modifiers, KeyModifiers::NONE | KeyModifiers::SHIFT) {
+            return false;
+        }
+        match key.code {
+            KeyCode::Char(c) if c == self.state.keymap.leader => {
+                self.state.leader_keys = Some(String::new());
+            }
+            KeyCode::Char(':') => self.state.command_line = Some(String::new()),
+            _ => return false,
+        }
+        true
+    }
+
+    fn handle_leader_input(&mut self, key: KeyEvent) {
+        let Some(mut keys) = self.state.leader_keys.take() else {
+            return;
+        };
+        // Any other key cancels the mapping
+        let KeyCode::Char(c) = key.code else {
+            return;
+        };
+        keys.push(c);
+        match self.state.keymap.leader_command(&keys) {
+            LeaderMatch::Command(command) => self.run_command(command),
+            LeaderMatch::Pending => self.state.leader_keys = Some(keys),
+            LeaderMatch::None => {
+                self.state.status_message = Some(format!("No mapping for <leader>{}", keys));
+            }
+        }
+    }
+
+    fn handle_command_line_input(&mut self, key: KeyEvent) {
+        let Some(line) = self.state.command_line.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Enter => {
+                let line = mem::take(line);
+                self.state.command_line = None;
+                self.run_ex_command(&line);
             }
+            KeyCode::Esc => self.state.command_line = None,
+            KeyCode::Backspace if line.is_empty() => self.state.command_line = None,
             KeyCode::Backspace => {
-                self.state.rename_buffer.pop();
+                line.pop();
             }
+            KeyCode::Tab => self.complete_command_line(),
+            KeyCode::Char(c) => line.push(c),
             _ => {}
         }
     }
 
-    #[allow(dead_code)]
-    fn start_delete(&mut self) {
-        self.state.current_view = View::DeleteConfirm;
-        self.state.confirm_delete = false;
+    /// Complete the title of the note given to `:e`, as far as the matching titles agree
+    fn complete_command_line(&mut self) {
+        let Some(line) = self.state.command_line.as_mut() else {
+            return;
+        };
+        let Some((name, typed)) = line.split_once(' ') else {
+            return;
+        };
+        if name != "e" && name != "edit" {
+            return;
+        }
+        let typed = typed.to_lowercase();
+        let mut matches = self
+            .state
+            .notes
+            .iter()
+            .map(|note| note.title.as_str())
+            .filter(|title| title.to_lowercase().starts_with(&typed));
+        let Some(first) = matches.next() else {
+            return;
+        };
+        let completed = matches.fold(first, |common, title| {
+            let length = common
+                .char_indices()
+                .zip(title.chars())
+                .take_while(|((_, a), b)| a.eq_ignore_ascii_case(b))
+                .last()
+                .map_or(0, |((index, a), _)| index + a.len_utf8());
+            &common[..length]
+        });
+        if completed.len() >= typed.len() {
+            *line = format!("{} {}", name, completed);
+        }
     }
 
-    fn execute_command(&mut self, command: Command) {
+    fn run_ex_command(&mut self, line: &str) {
+        let command = match ExCommand::parse(line) {
+            Ok(command) => command,
+            Err(message) => {
+                self.state.status_message = Some(message);
+                return;
+            }
+        };
+        if self.sync_events.is_some() && command.modifies_notes() {
+            self.state.status_message = Some("Wait for the sync to finish".to_string());
+            return;
+        }
         match command {
-            Command::Quit => self.quit(),
-            Command::NextNote => self.select_next_note(),
-            Command::PreviousNote => self.select_previous_note(),
-            Command::SwitchView(view) => self.state.current_view = view,
-            Command::NewNote => self.create_new_note(),
-            Command::SaveNote => self.save_current_note(),
-            Command::DeleteNote => {
-                if self.state.current_view == View::DeleteConfirm {
-                    self.state.confirm_delete = true;
-                    self.delete_current_note();
-                    self.state.current_view = View::LivePreview;
-                } else {
-                    self.state.current_view = View::DeleteConfirm;
-                    self.state.confirm_delete = false;
-                    self.state.current_view = View::DeleteConfirm;
+            ExCommand::Write => self.run_command(Command::SaveNote),
+            ExCommand::Quit => self.run_command(Command::Quit),
+            ExCommand::WriteQuit => {
+                // Keep running when the note could not be saved
+                let syncing = self.sync_events.is_some();
+                self.run_command(Command::SaveNote);
+                if !syncing {
+                    self.quit();
                 }
             }
-            Command::ScrollDown => {
-                if let View::Preview | View::LivePreview = self.state.current_view {
-                    self.state.preview_scroll_offset += 5;
+            ExCommand::Edit(title) => {
+                match self
+                    .state
+                    .notes
+                    .iter()
+                    .position(|note| note.title.eq_ignore_ascii_case(&title))
+                {
+                    Some(index) => self.select_note(index),
+                    None => self.state.status_message = Some(format!("No note named '{}'", title)),
                 }
             }
-            Command::ScrollUp => {
-                if let View::Preview | View::LivePreview = self.state.current_view {
-                    if self.state.preview_scroll_offset > 0 {
-                        self.state.preview_scroll_offset -= 5;
-                    }
+            ExCommand::Rename(title) => {
+                self.run_command(Command::RenameNote);
+                if let (Some(title), View::Rename) = (title, self.state.current_view) {
+                    self.state.rename_buffer = title;
+                    self.run_command(Command::SubmitRename);
                 }
             }
-            Command::RenameNote => self.start_rename(),
-            Command::SubmitRename => self.submit_rename(),
-            Command::CancelRename => {
-                if self.state.current_view == View::DeleteConfirm {
-                    self.state.current_view = View::LivePreview;
-                } else {
-                    self.cancel_rename()
-                }
+            ExCommand::New => self.run_command(Command::NewNote),
+            ExCommand::Sort(key, descending) => self.change_sorting(SortSettings {
+                key,
+                descending,
+                ..self.state.sorting
+            }),
+            ExCommand::Group(grouping) => self.change_sorting(SortSettings {
+                grouping,
+                ..self.state.sorting
+            }),
+            ExCommand::Cards(cards) => self.change_cards(cards),
+            ExCommand::Folder(folder) => self.move_to_folder(folder),
+            ExCommand::Tag(tag) => self.change_tag(&tag, true),
+            ExCommand::Untag(tag) => self.change_tag(&tag, false),
+            ExCommand::Export(format, directory) => self.export_notes(&format, directory),
+            ExCommand::Merge(title) => self.merge_notes(title),
+            ExCommand::Line(number) => {
+                let editor = &mut self.state.editor_state;
+                let last_row = editor.lines.len().saturating_sub(1);
+                editor.cursor = Index2::new(number.saturating_sub(1).min(last_row), 0);
             }
-            Command::MoveNoteUp => self.move_note_up(),
-            Command::MoveNoteDown => self.move_note_down(),
         }
     }
 
-    fn move_note_up(&mut self) {
-        if let Some(selected) = self.state.list_state.selected {
-            if selected > 0 {
-                // Get the orders
-                let current_order = self.state.notes[selected].order;
-                let prev_order = self.state.notes[selected - 1].order;
-
-                // Swap orders
-                self.state.notes[selected].order = prev_order;
-                self.state.notes[selected - 1].order = current_order;
+    /// Execute a command unless it writes notes while a sync is running
+    fn run_command(&mut self, command: Command) {
+        if self.sync_events.is_some() && command.modifies_notes() {
+            self.state.status_message = Some("Wait for the sync to finish".to_string());
+        } else {
+            self.execute_command(command);
+        }
+    }
 
-                // Save both notes to persist order changes
-                if let Err(e) = self.storage.write_note(&self.state.notes[selected]) {
-                    eprintln!("Failed to save note order: {}", e);
-                }
-                if let Err(e) = self.storage.write_note(&self.state.notes[selected - 1]) {
-                    eprintln!("Failed to save note order: {}", e);
-                }
+    /// Whether the editor takes input
+    fn can_edit(&self) -> bool {
+        !self.selected_note_locked() && self.sync_events.is_none()
+    }
 
-                // Resort the notes
-                self.state.notes.sort_by_key(|note| note.order);
+    /// Whether the editor is shown and takes input
+    fn editing(&self) -> bool {
+        matches!(
+            self.state.current_view,
+            View::Editor | View::LivePreview | View::Split
+        ) && self.can_edit()
+    }
 
-                // Update selection
-                self.state.list_state.select(Some(selected - 1));
-            }
-        }
+    /// Whether keys go to the editor in vim normal or visual mode
+    fn vim_editing(&self) -> bool {
+        self.state.keymap.mode == EditingMode::Vim
+            && matches!(
+                self.state.editor_state.mode,
+                EditorMode::Normal | EditorMode::Visual
+            )
+            && self.editing()
     }
 
-    fn move_note_down(&mut self) {
-        if let Some(selected) = self.state.list_state.selected {
-            if selected < self.state.notes.len() - 1 {
-                // Get the orders
-                let current_order = self.state.notes[selected].order;
-                let next_order = self.state.notes[selected + 1].order;
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        if mouse.kind != MouseEventKind::Moved {
+            self.state.completion = None;
+        }
+        let position = Position::new(mouse.column, mouse.row);
+        let areas = self.state.areas;
+        let in_list = areas.note_list.is_some_and(|area| area.contains(position));
+        let in_editor = areas.editor.is_some_and(|area| area.contains(position));
+        let in_preview = areas.preview.is_some_and(|area| area.contains(position));
+        let in_other_editor = areas
+            .other_editor
+            .is_some_and(|area| area.contains(position));
+        let editing = matches!(
+            self.state.current_view,
+            View::Editor | View::LivePreview | View::Split
+        );
 
-                // Swap orders
-                self.state.notes[selected].order = next_order;
-                self.state.notes[selected + 1].order = current_order;
+        match self.state.current_view {
+            View::ContextMenu => return self.handle_context_menu_mouse(mouse),
+            // Dialogs are answered with the keyboard
+            View::Rename
+            | View::TemplateVariable
+            | View::DeleteConfirm
+            | View::Passphrase
+            | View::Calendar
+            | View::NoteInfo
+            | View::Dashboard
+            | View::Archive => return,
+            _ => {}
+        }
 
-                // Save both notes to persist order changes
-                if let Err(e) = self.storage.write_note(&self.state.notes[selected]) {
-                    eprintln!("Failed to save note order: {}", e);
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                if let Some(border) = self.border_at(position) {
+                    self.resizing = Some(border);
+                } else if let Some(tab) = self.tab_at(position) {
+                    self.switch_tab(tab);
+                } else if in_other_editor {
+                    // The click lands in the note once its pane has the focus
+                    self.focus_other_pane();
+                    if self.can_edit() {
+                        self.editor_event_handler
+                            .on_mouse_event(mouse, &mut self.state.editor_state);
+                    }
+                } else if in_list && self.state.current_view != View::Outline {
+                    match self.note_at(position) {
+                        Some(index) if mouse.modifiers.contains(KeyModifiers::CONTROL) => {
+                            self.state.notes[index].marked ^= true;
+                            self.show_marked();
+                        }
+                        Some(index) if mouse.modifiers.contains(KeyModifiers::SHIFT) => {
+                            self.mark_range(index);
+                        }
+                        Some(index) => self.select_note(index),
+                        None => {}
+                    }
+                } else if in_editor && editing && self.can_edit() {
+                    self.editor_event_handler
+                        .on_mouse_event(mouse, &mut self.state.editor_state);
+                } else if in_preview
+                    && matches!(self.state.current_view, View::Preview | View::LivePreview)
+                {
+                    self.follow_link_at(position);
                 }
-                if let Err(e) = self.storage.write_note(&self.state.notes[selected + 1]) {
-                    eprintln!("Failed to save note order: {}", e);
+            }
+            MouseEventKind::Drag(MouseButton::Left) => match self.resizing {
+                Some(border) => self.resize_pane(border, position),
+                // Select text in the editor
+                None if in_editor && editing && self.can_edit() => {
+                    self.editor_event_handler
+                        .on_mouse_event(mouse, &mut self.state.editor_state);
+                }
+                None => {}
+            },
+            MouseEventKind::Up(MouseButton::Left) if self.resizing.take().is_some() => {
+                self.save_layout();
+            }
+            MouseEventKind::Down(MouseButton::Right)
+                if in_list && self.state.current_view != View::Outline =>
+            {
+                if let Some(index) = self.note_at(position) {
+                    self.open_context_menu(index, position);
+                }
+            }
+            MouseEventKind::ScrollDown | MouseEventKind::ScrollUp => {
+                let down = mouse.kind == MouseEventKind::ScrollDown;
+                if in_list {
+                    self.scroll_list(down);
+                } else if in_editor && editing {
+                    self.scroll_editor(down);
+                } else if in_other_editor {
+                    self.scroll_other_editor(down);
+                } else if in_preview {
+                    self.scroll_preview(down);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Border of a pane under the mouse, either the last column or row of a
+    /// pane or the first one of the next pane
+    fn border_at(&self, position: Position) -> Option<PaneBorder> {
+        let areas = self.state.areas;
+        let on_right_edge = |left: Rect| {
+            position.y >= left.y
+                && position.y < left.bottom()
+                && (position.x + 1 == left.right() || position.x == left.right())
+        };
+        if areas.note_list.is_some_and(on_right_edge) {
+            return Some(PaneBorder::List);
+        }
+        let (Some(editor), Some(preview)) = (areas.editor, areas.preview) else {
+            return None;
+        };
+        let on_bottom_edge = position.x >= editor.x
+            && position.x < editor.right()
+            && (position.y + 1 == editor.bottom() || position.y == editor.bottom());
+        let stacked = preview.y > editor.y;
+        (if stacked {
+            on_bottom_edge
+        } else {
+            on_right_edge(editor)
+        })
+        .then_some(PaneBorder::Editor)
+    }
+
+    /// Move a border to the position of the mouse
+    fn resize_pane(&mut self, border: PaneBorder, position: Position) {
+        let areas = self.state.areas;
+        // Share of the space from `start` to `end` that is before `at`
+        let percent = |start: u16, end: u16, at: u16| {
+            let size = end.saturating_sub(start).max(1) as u32;
+            ((at + 1).saturating_sub(start) as u32 * 100 / size) as i16
+        };
+        let layout = &mut self.state.layout;
+        match (border, areas.note_list, areas.editor, areas.preview) {
+            (PaneBorder::List, Some(list), ..) => {
+                let Some(right) = areas.preview.or(areas.editor).map(|area| area.right()) else {
+                    return;
+                };
+                let list = percent(list.x, right, position.x);
+                layout.resize_list(list - layout.list as i16);
+            }
+            (PaneBorder::Editor, _, Some(editor), Some(preview)) => {
+                let editor = if preview.y > editor.y {
+                    percent(editor.y, preview.bottom(), position.y)
+                } else {
+                    percent(editor.x, preview.right(), position.x)
+                };
+                layout.resize_editor(editor - layout.editor as i16);
+            }
+            _ => {}
+        }
+    }
+
+    fn toggle_editing_mode(&mut self) {
+        let keymap = &mut self.state.keymap;
+        keymap.mode = keymap.mode.toggle();
+        self.editor_event_handler = keymap.mode.event_handler();
+        self.state.editor_state.mode = EditorMode::Normal;
+        self.state.editor_state.selection = None;
+        self.state.status_message = Some(format!("Editing mode: {}", keymap.mode.label()));
+        if let Err(e) = self.state.keymap.save(&self.root_dir) {
+            self.state.status_message = Some(format!("Failed to save keymap: {}", e));
+        }
+    }
+
+    /// Copy the note being edited as Markdown, or as the text the preview shows
+    fn copy_note(&mut self, rendered: bool) {
+        let Some(title) = self.state.active_title().map(str::to_string) else {
+            return;
+        };
+        let content = editor_text(&self.state.editor_state);
+        let (text, format) = if rendered {
+            let note = Note {
+                title: title.clone(),
+                content,
+                ..Note::default()
+            };
+            match PlainExporter::new(DEFAULT_WIDTH).export(&note) {
+                Ok(text) => (text, "text"),
+                Err(e) => {
+                    self.state.status_message =
+                        Some(format!("Failed to render '{}': {}", title, e));
+                    return;
+                }
+            }
+        } else {
+            (content, "Markdown")
+        };
+        self.clipboard.set_text(text);
+        self.state.status_message = Some(format!("Copied '{}' as {}", title, format));
+    }
+
+    /// Whether the editor lines differ from the last time this was asked,
+    /// without flattening them into a string
+    fn editor_changed(&mut self) -> bool {
+        let hash = lines_hash(&self.state.editor_state);
+        self.editor_hash.replace(hash) != Some(hash)
+    }
+
+    /// Send the note being edited to the spell checker when it changed, and
+    /// take the misspellings it found
+    fn update_spelling(&mut self, edited: bool) {
+        if !self.spell_checker.settings.enabled {
+            self.state.misspellings.clear();
+            return;
+        }
+        if edited || self.spell_checker.wants_text() {
+            self.spell_checker
+                .request(&editor_text(&self.state.editor_state));
+        }
+        if let Some(found) = self.spell_checker.poll() {
+            self.state.misspellings = found;
+        }
+    }
+
+    /// Count the statistics of the note being edited
+    fn update_stats(&mut self) {
+        let text = editor_text(&self.state.editor_state);
+        self.state.note_stats = NoteStats::of(&text);
+        // Typing makes the note differ from what was saved
+        if let Some(note) = self
+            .state
+            .list_state
+            .selected
+            .and_then(|selected| self.state.notes.get_mut(selected))
+            .filter(|note| !note.locked && note.content != text)
+        {
+            note.dirty = true;
+        }
+    }
+
+    /// Word under the editor cursor and the column it starts at
+    fn word_under_cursor(&mut self) -> Option<(usize, String)> {
+        let editor = &self.state.editor_state;
+        let line = editor.lines.get(RowIndex::new(editor.cursor.row));
+        let found = line.and_then(|line| {
+            let (start, end) = spellcheck::word_at(line, editor.cursor.col)?;
+            Some((start, end, line[start..end].iter().collect::<String>()))
+        });
+        let Some((start, end, word)) = found else {
+            self.state.status_message = Some("No word under the cursor".to_string());
+            return None;
+        };
+        self.state.editor_state.cursor.col = end;
+        Some((start, word))
+    }
+
+    /// Open the corrections of the misspelled word under the cursor
+    fn suggest_spelling(&mut self) {
+        if !self.editing() {
+            return;
+        }
+        let Some((start, word)) = self.word_under_cursor() else {
+            return;
+        };
+        let suggestions = match self.spell_checker.check(&word) {
+            Ok(true) => {
+                self.state.status_message = Some(format!("\"{}\" is spelled correctly", word));
+                return;
+            }
+            Ok(false) => self.spell_checker.suggest(&word),
+            Err(e) => Err(e),
+        };
+        match suggestions {
+            Ok(suggestions) => {
+                let start = Index2::new(self.state.editor_state.cursor.row, start);
+                self.state.completion = Some(Completion::spelling(start, &word, suggestions));
+            }
+            Err(e) => self.state.status_message = Some(e),
+        }
+    }
+
+    fn add_to_dictionary(&mut self, word: &str) {
+        self.state.status_message = Some(match self.spell_checker.add_word(word) {
+            Ok(()) => format!("Added \"{}\" to the dictionary", word),
+            Err(e) => format!("Failed to save the dictionary: {}", e),
+        });
+    }
+
+    fn toggle_spellcheck(&mut self) {
+        let settings = &mut self.spell_checker.settings;
+        settings.enabled = !settings.enabled;
+        self.state.status_message = Some(
+            if settings.enabled {
+                "Spell checking on"
+            } else {
+                "Spell checking off"
+            }
+            .to_string(),
+        );
+        self.spell_checker.recheck();
+        if let Err(e) = self.spell_checker.settings.save(&self.root_dir) {
+            self.state.status_message = Some(format!("Failed to save spelling settings: {}", e));
+        }
+    }
+
+    fn change_cards(&mut self, cards: CardLayout) {
+        self.state.layout.cards = cards;
+        self.state.status_message = Some(format!("Note cards: {}", cards.label()));
+        self.save_layout();
+    }
+
+    fn save_layout(&mut self) {
+        if let Err(e) = self.state.layout.save(&self.root_dir) {
+            self.state.status_message = Some(format!("Failed to save layout: {}", e));
+        }
+    }
+
+    /// Note shown at a position of the note list
+    fn note_at(&self, position: Position) -> Option<usize> {
+        let area = self.state.areas.note_list?;
+        // Skip the border around the list
+        let row = position.y.checked_sub(area.y + 1)?;
+        if row + 2 >= area.height {
+            return None;
+        }
+        // Notes starting a section have their heading above them
+        let mut top = 0;
+        for index in self.state.list_state.scroll_offset_index()..self.state.notes.len() {
+            let header = self.state.sorting.header(&self.state.notes, index);
+            top += usize::from(self.state.layout.cards.height(&self.state.notes[index]))
+                + usize::from(header.is_some());
+            if (row as usize) < top {
+                return Some(index);
+            }
+        }
+        None
+    }
+
+    fn select_note(&mut self, index: usize) {
+        if self.state.list_state.selected == Some(index) {
+            return;
+        }
+        self.save_editor_content_to_current_note();
+        self.state.list_state.select(Some(index));
+        self.load_note_to_editor(index);
+    }
+
+    fn scroll_list(&mut self, down: bool) {
+        if self.state.current_view == View::Outline {
+            let last = self.state.outline.len().saturating_sub(1);
+            let selected = &mut self.state.outline_selected;
+            *selected = if down {
+                (*selected + 1).min(last)
+            } else {
+                selected.saturating_sub(1)
+            };
+            return;
+        }
+        // Unlike the keyboard, the wheel stops at the ends of the list
+        let selected = self.state.list_state.selected.unwrap_or(0);
+        let index = if down {
+            (selected + 1).min(self.state.notes.len().saturating_sub(1))
+        } else {
+            selected.saturating_sub(1)
+        };
+        self.select_note(index);
+    }
+
+    fn scroll_editor(&mut self, down: bool) {
+        scroll_cursor(&mut self.state.editor_state, down);
+    }
+
+    /// Scroll the pane of the split view not taking input, leaving the focus where it is
+    fn scroll_other_editor(&mut self, down: bool) {
+        let Some(split) = &self.state.split else {
+            return;
+        };
+        if let Some(tab) = self
+            .state
+            .tabs
+            .iter_mut()
+            .find(|tab| tab.title == split.other)
+        {
+            scroll_cursor(&mut tab.editor, down);
+        }
+    }
+
+    /// Tab drawn at a position of the tab bar
+    fn tab_at(&self, position: Position) -> Option<usize> {
+        self.state.areas.tabs?;
+        self.state
+            .tab_areas
+            .iter()
+            .position(|area| area.contains(position))
+    }
+
+    fn scroll_preview(&mut self, down: bool) {
+        let offset = match self.state.current_view {
+            View::History => &mut self.state.history_scroll,
+            View::Preview | View::LivePreview | View::Outline => {
+                &mut self.state.preview_scroll_offset
+            }
+            _ => return,
+        };
+        *offset = if down {
+            *offset + MOUSE_SCROLL_ROWS
+        } else {
+            offset.saturating_sub(MOUSE_SCROLL_ROWS)
+        };
+        self.move_editor_with_preview();
+    }
+
+    /// Follow the link shown at a position of the preview
+    fn follow_link_at(&mut self, position: Position) {
+        let Some(area) = self.state.areas.preview else {
+            return;
+        };
+        if self.selected_note_locked() {
+            return;
+        }
+        // Skip the border around the preview
+        let (Some(row), Some(column)) = (
+            position.y.checked_sub(area.y + 1),
+            position.x.checked_sub(area.x + 1),
+        ) else {
+            return;
+        };
+        let row = self.state.preview_scroll_offset + row as usize;
+        if let Some(target) = self.state.preview_cache.link_at(row, column as usize) {
+            self.follow_link(&target);
+        }
+    }
+
+    /// Jump to the heading or note a link points to, or open it outside Thoth
+    fn follow_link(&mut self, target: &str) {
+        match LinkTarget::parse(target) {
+            LinkTarget::Heading(anchor) => {
+                let headings = outline::headings(&self.editor_content());
+                match headings
+                    .iter()
+                    .position(|heading| outline::slug(&heading.title) == anchor.to_lowercase())
+                {
+                    Some(index) => {
+                        self.state.outline = headings;
+                        self.jump_to_heading(index);
+                    }
+                    None => {
+                        self.state.status_message = Some(format!("No heading '#{}'", anchor));
+                    }
+                }
+            }
+            LinkTarget::Note(title) => {
+                match self
+                    .state
+                    .notes
+                    .iter()
+                    .position(|note| note.title.eq_ignore_ascii_case(&title))
+                {
+                    Some(index) => self.select_note(index),
+                    None => self.state.status_message = Some(format!("No note named '{}'", title)),
+                }
+            }
+            LinkTarget::External(url) => {
+                self.state.status_message = Some(match links::open_external(&url) {
+                    Ok(()) => format!("Opened {}", url),
+                    Err(e) => format!("Failed to open {}: {}", url, e),
+                });
+            }
+        }
+    }
+
+    fn open_context_menu(&mut self, index: usize, position: Position) {
+        self.select_note(index);
+        self.state.context_menu = Some(ContextMenu {
+            x: position.x,
+            y: position.y,
+            selected: 0,
+            area: Rect::default(),
+        });
+        self.state.current_view = View::ContextMenu;
+    }
+
+    fn handle_context_menu_input(&mut self, key: KeyEvent) {
+        let Some(menu) = self.state.context_menu.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') if menu.selected + 1 < CONTEXT_MENU_ITEMS.len() => {
+                menu.selected += 1;
+            }
+            KeyCode::Up | KeyCode::Char('k') if menu.selected > 0 => menu.selected -= 1,
+            _ => {}
+        }
+    }
+
+    fn handle_context_menu_mouse(&mut self, mouse: MouseEvent) {
+        let Some(menu) = self.state.context_menu.as_mut() else {
+            return;
+        };
+        let position = Position::new(mouse.column, mouse.row);
+        // Items are inside the border of the menu
+        let item = menu
+            .area
+            .contains(position)
+            .then(|| (mouse.row - menu.area.y).checked_sub(1))
+            .flatten()
+            .map(usize::from)
+            .filter(|&item| item < CONTEXT_MENU_ITEMS.len());
+
+        match mouse.kind {
+            MouseEventKind::Moved => {
+                if let Some(item) = item {
+                    menu.selected = item;
+                }
+            }
+            MouseEventKind::Down(_) => match item {
+                Some(item) => {
+                    menu.selected = item;
+                    self.submit_context_menu();
+                }
+                // Clicking anywhere else closes the menu
+                None if !menu.area.contains(position) => self.close_context_menu(),
+                None => {}
+            },
+            _ => {}
+        }
+    }
+
+    fn submit_context_menu(&mut self) {
+        let Some(menu) = self.state.context_menu.take() else {
+            return;
+        };
+        self.state.current_view = View::LivePreview;
+        let command = match menu.selected {
+            0 => Command::RenameNote,
+            1 => Command::DeleteNote,
+            2 => Command::MoveNoteUp,
+            _ => Command::MoveNoteDown,
+        };
+        self.run_command(command);
+    }
+
+    fn close_context_menu(&mut self) {
+        self.state.context_menu = None;
+        self.state.current_view = View::LivePreview;
+    }
+
+    fn key_to_command(&self, key: KeyEvent) -> Option<Command> {
+        if self.state.current_view == View::DeleteConfirm {
+            match key.code {
+                KeyCode::Enter => Some(Command::DeleteNote),
+                KeyCode::Esc => Some(Command::CancelRename), // Reuse CancelRename as it does the same thing
+                _ => None,
+            }
+        } else if self.state.current_view == View::Passphrase {
+            match key.code {
+                KeyCode::Enter => Some(Command::SubmitPassphrase),
+                KeyCode::Esc => Some(Command::CancelPassphrase),
+                _ => None,
+            }
+        } else if self.state.current_view == View::TemplateVariable {
+            match key.code {
+                KeyCode::Enter => Some(Command::SubmitTemplateVariable),
+                KeyCode::Esc => Some(Command::CancelTemplateVariable),
+                _ => None,
+            }
+        } else if self.state.current_view == View::Outline {
+            match key.code {
+                KeyCode::Enter => Some(Command::JumpToHeading),
+                KeyCode::Esc => Some(Command::CloseOutline),
+                _ => None,
+            }
+        } else if self.state.current_view == View::ContextMenu {
+            match key.code {
+                KeyCode::Enter => Some(Command::SubmitContextMenu),
+                KeyCode::Esc => Some(Command::CloseContextMenu),
+                _ => None,
+            }
+        } else if self.state.current_view == View::Calendar {
+            match key.code {
+                KeyCode::Enter => Some(Command::SubmitCalendar),
+                KeyCode::Esc => Some(Command::CloseCalendar),
+                _ => None,
+            }
+        } else if self.state.current_view == View::History {
+            match key.code {
+                KeyCode::Esc => Some(Command::CloseHistory),
+                _ => None,
+            }
+        } else if self.state.current_view == View::Archive {
+            match key.code {
+                KeyCode::Enter => Some(Command::RestoreNote),
+                KeyCode::Esc => Some(Command::CloseArchive),
+                _ => None,
+            }
+        } else if self.state.current_view == View::NoteInfo {
+            match key.code {
+                KeyCode::Esc | KeyCode::Enter => Some(Command::CloseNoteInfo),
+                _ => None,
+            }
+        } else if self.state.current_view == View::Dashboard {
+            match key.code {
+                KeyCode::Enter => Some(Command::SubmitDashboard),
+                KeyCode::Esc => Some(Command::CloseDashboard),
+                _ => None,
+            }
+        } else {
+            match (key.modifiers, key.code) {
+                // Redo, scrolling and jumps in vim, run from the leader instead
+                (KeyModifiers::CONTROL, KeyCode::Char('r' | 'd' | 'u' | 'e' | 'o'))
+                    if self.vim_editing() =>
+                {
+                    None
+                }
+                (KeyModifiers::CONTROL, KeyCode::Char('q')) => Some(Command::Quit),
+                (KeyModifiers::CONTROL, KeyCode::Down) => Some(Command::NextNote),
+                (KeyModifiers::CONTROL, KeyCode::Up) => Some(Command::PreviousNote),
+                (KeyModifiers::ALT, KeyCode::Up) => Some(Command::MoveNoteUp),
+                (KeyModifiers::ALT, KeyCode::Down) => Some(Command::MoveNoteDown),
+                (KeyModifiers::CONTROL, KeyCode::Char('e')) => {
+                    Some(Command::SwitchView(View::Editor))
+                }
+                (KeyModifiers::CONTROL, KeyCode::Char('l')) => {
+                    Some(Command::SwitchView(View::LivePreview))
+                }
+                (KeyModifiers::CONTROL, KeyCode::Char('p')) => {
+                    Some(Command::SwitchView(View::Preview))
+                }
+                (KeyModifiers::CONTROL, KeyCode::Char('n')) => Some(Command::NewNote),
+                (KeyModifiers::CONTROL, KeyCode::Char('s')) => Some(Command::SaveNote),
+                (KeyModifiers::CONTROL, KeyCode::Char('d')) => Some(Command::DeleteNote),
+                (KeyModifiers::CONTROL, KeyCode::Char('j')) => Some(Command::ScrollDown),
+                (KeyModifiers::CONTROL, KeyCode::Char('k')) => Some(Command::ScrollUp),
+                (KeyModifiers::CONTROL, KeyCode::Char('r')) => Some(Command::RenameNote),
+                (KeyModifiers::CONTROL, KeyCode::Char('u')) => Some(Command::Unlock),
+                (KeyModifiers::ALT, KeyCode::Char('e')) => Some(Command::ToggleNoteEncryption),
+                (KeyModifiers::ALT, KeyCode::Char('v')) => Some(Command::ToggleVaultEncryption),
+                (KeyModifiers::ALT, KeyCode::Char('p')) => Some(Command::ChangePassphrase),
+                (KeyModifiers::ALT, KeyCode::Char('h')) => Some(Command::ShowHistory),
+                (KeyModifiers::ALT, KeyCode::Char('d')) => Some(Command::Pull),
+                (KeyModifiers::ALT, KeyCode::Char('u')) => Some(Command::Push),
+                (KeyModifiers::ALT, KeyCode::Char('s')) => Some(Command::Sync),
+                (KeyModifiers::ALT, KeyCode::Char('j')) => Some(Command::OpenJournal),
+                (KeyModifiers::ALT, KeyCode::Char('c')) => Some(Command::ShowCalendar),
+                (KeyModifiers::ALT, KeyCode::Left) => Some(Command::PreviousJournalDay),
+                (KeyModifiers::ALT, KeyCode::Right) => Some(Command::NextJournalDay),
+                (KeyModifiers::ALT, KeyCode::Char('o')) => Some(Command::ShowOutline),
+                (KeyModifiers::ALT, KeyCode::Char('t')) => Some(Command::InsertToc),
+                (KeyModifiers::ALT, KeyCode::Char('l')) => Some(Command::ToggleScrollSync),
+                (KeyModifiers::ALT, KeyCode::Char('b')) => Some(Command::ToggleNoteList),
+                (KeyModifiers::ALT, KeyCode::Char(',')) => Some(Command::ResizeNoteList(-5)),
+                (KeyModifiers::ALT, KeyCode::Char('.')) => Some(Command::ResizeNoteList(5)),
+                (KeyModifiers::ALT, KeyCode::Char('-')) => Some(Command::ResizeEditor(-5)),
+                (KeyModifiers::ALT, KeyCode::Char('=')) => Some(Command::ResizeEditor(5)),
+                (KeyModifiers::ALT, KeyCode::Char('w')) => Some(Command::CycleArrangement),
+                (KeyModifiers::CONTROL, KeyCode::Char('t')) => Some(Command::KeepTab),
+                (KeyModifiers::CONTROL, KeyCode::Char('w')) => Some(Command::CloseTab),
+                (KeyModifiers::CONTROL, KeyCode::PageDown) => Some(Command::NextTab),
+                (KeyModifiers::CONTROL, KeyCode::PageUp) => Some(Command::PreviousTab),
+                (KeyModifiers::ALT, KeyCode::Char(digit @ '1'..='9')) => {
+                    Some(Command::SelectTab(digit as usize - '1' as usize))
+                }
+                (KeyModifiers::ALT, KeyCode::Char('x')) => Some(Command::ToggleSplit),
+                (KeyModifiers::CONTROL, KeyCode::Char('o')) => Some(Command::FocusOtherPane),
+                (KeyModifiers::ALT, KeyCode::Char('m')) => Some(Command::ToggleEditingMode),
+                (KeyModifiers::ALT, KeyCode::Char('i')) => Some(Command::EditExternally),
+                (KeyModifiers::CONTROL, KeyCode::Char('b')) => Some(Command::Format(Format::Bold)),
+                (KeyModifiers::ALT, KeyCode::Char('_')) => Some(Command::Format(Format::Italic)),
+                (KeyModifiers::ALT, KeyCode::Char('`')) => Some(Command::Format(Format::Code)),
+                (KeyModifiers::ALT, KeyCode::Char('k')) => Some(Command::Format(Format::Link)),
+                (KeyModifiers::ALT, KeyCode::Char('f')) => Some(Command::FormatTable),
+                (KeyModifiers::ALT, KeyCode::Char('z')) => Some(Command::SpellingSuggestions),
+                (KeyModifiers::ALT, KeyCode::Char('g')) => Some(Command::AddToDictionary),
+                (KeyModifiers::ALT, KeyCode::Char('y')) => Some(Command::ToggleSpellcheck),
+                (KeyModifiers::ALT, KeyCode::Char('a')) => Some(Command::CopyNote),
+                (KeyModifiers::ALT, KeyCode::Char('r')) => Some(Command::CopyNoteText),
+                (KeyModifiers::ALT, KeyCode::Char('n')) => Some(Command::ShowNoteInfo),
+                (KeyModifiers::ALT, KeyCode::Char('q')) => Some(Command::ShowDashboard),
+                (ALT_SHIFT, KeyCode::Char('S')) => Some(Command::CycleSort),
+                (ALT_SHIFT, KeyCode::Char('R')) => Some(Command::ReverseSort),
+                (ALT_SHIFT, KeyCode::Char('G')) => Some(Command::CycleGrouping),
+                (ALT_SHIFT, KeyCode::Char('C')) => Some(Command::CycleCards),
+                (ALT_SHIFT, KeyCode::Char('P')) => Some(Command::TogglePin),
+                (ALT_SHIFT, KeyCode::Char('A')) => Some(Command::ArchiveNote),
+                (ALT_SHIFT, KeyCode::Char('V')) => Some(Command::ShowArchive),
+                (ALT_SHIFT, KeyCode::Char('X')) => Some(Command::ToggleMark),
+                (ALT_SHIFT, KeyCode::Char('Z')) => Some(Command::MarkAll),
+                (CONTROL_SHIFT, KeyCode::Up) => Some(Command::MarkUp),
+                (CONTROL_SHIFT, KeyCode::Down) => Some(Command::MarkDown),
+                (KeyModifiers::NONE, KeyCode::Enter)
+                    if matches!(self.state.current_view, View::Rename) =>
+                {
+                    Some(Command::SubmitRename)
+                }
+                (KeyModifiers::NONE, KeyCode::Esc)
+                    if matches!(self.state.current_view, View::Rename) =>
+                {
+                    Some(Command::CancelRename)
+                }
+                _ => None,
+            }
+        }
+    }
+
+    fn handle_rename_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char(c) => {
+                self.state.rename_buffer.push(c);
+            }
+            KeyCode::Backspace => {
+                self.state.rename_buffer.pop();
+            }
+            // Pick the template of a new note
+            KeyCode::Tab if self.state.creating_new_note => {
+                let count = self.state.templates.len();
+                self.state.template_selected = match self.state.template_selected {
+                    None if count > 0 => Some(0),
+                    Some(index) if index + 1 < count => Some(index + 1),
+                    _ => None,
+                };
+            }
+            KeyCode::BackTab if self.state.creating_new_note => {
+                let count = self.state.templates.len();
+                self.state.template_selected = match self.state.template_selected {
+                    None => count.checked_sub(1),
+                    Some(index) => index.checked_sub(1),
+                };
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_passphrase_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char(c) => {
+                self.state.passphrase_buffer.push(c);
+            }
+            KeyCode::Backspace => {
+                self.state.passphrase_buffer.pop();
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_history_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j')
+                if self.state.history_selected + 1 < self.state.history.len() =>
+            {
+                self.state.history_selected += 1;
+                self.load_history_diff();
+            }
+            KeyCode::Up | KeyCode::Char('k') if self.state.history_selected > 0 => {
+                self.state.history_selected -= 1;
+                self.load_history_diff();
+            }
+            KeyCode::PageDown => self.state.history_scroll += 10,
+            KeyCode::PageUp => {
+                self.state.history_scroll = self.state.history_scroll.saturating_sub(10);
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_outline_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j')
+                if self.state.outline_selected + 1 < self.state.outline.len() =>
+            {
+                self.state.outline_selected += 1;
+            }
+            KeyCode::Up | KeyCode::Char('k') if self.state.outline_selected > 0 => {
+                self.state.outline_selected -= 1;
+            }
+            KeyCode::Char(' ') if !self.state.outline.is_empty() => {
+                let index = self.state.outline_selected;
+                if !self.state.folded_headings.remove(&index) {
+                    self.state.folded_headings.insert(index);
+                }
+            }
+            // Unfold everything
+            KeyCode::Char('u') => self.state.folded_headings.clear(),
+            _ => {}
+        }
+    }
+
+    fn show_outline(&mut self) {
+        if self.selected_note_locked() {
+            return;
+        }
+        let headings = outline::headings(&self.editor_content());
+
+        // Follow the preview when it is shown alone, otherwise the cursor
+        self.state.outline_current = if self.state.current_view == View::Preview {
+            self.state
+                .preview_heading_rows
+                .iter()
+                .rposition(|&row| row <= self.state.preview_scroll_offset)
+        } else {
+            outline::section_at(&headings, self.state.editor_state.cursor.row)
+        };
+        self.state.outline_selected = self.state.outline_current.unwrap_or(0);
+        self.state.outline = headings;
+        self.state.current_view = View::Outline;
+    }
+
+    /// Move the editor cursor and the preview to a heading of the outline
+    fn jump_to_heading(&mut self, index: usize) {
+        let Some(heading) = self.state.outline.get(index) else {
+            return;
+        };
+        self.state.editor_state.cursor = Index2::new(heading.line, 0);
+
+        // Unfold the sections hiding the heading
+        let outline = &self.state.outline;
+        self.state
+            .folded_headings
+            .retain(|&folded| folded >= index || outline::section_end(outline, folded) <= index);
+        // The preview finds the row once the sections are unfolded
+        self.state.preview_heading_jump = Some(index);
+    }
+
+    /// Put the editor cursor on the line shown at the top of the preview
+    fn move_editor_with_preview(&mut self) {
+        if !self.state.scroll_editor_with_preview || self.state.current_view != View::LivePreview {
+            return;
+        }
+        let line = self
+            .state
+            .preview_source_map
+            .line_for_row(self.state.preview_scroll_offset);
+        let last_row = self.state.editor_state.lines.len().saturating_sub(1);
+        self.state.editor_state.cursor = Index2::new(line.min(last_row), 0);
+
+        // The preview is already where it should be
+        self.state.preview_followed = Some((
+            self.state.editor_state.cursor.row,
+            self.state.editor_state.lines.len(),
+        ));
+    }
+
+    /// Insert a table of contents at the top of the note, or refresh the existing one
+    fn insert_toc(&mut self) {
+        if self.selected_note_locked() {
+            return;
+        }
+        let content = outline::insert_toc(&self.editor_content());
+        self.state.editor_state.lines = Jagged::from(content);
+
+        let last_row = self.state.editor_state.lines.len().saturating_sub(1);
+        let cursor = &mut self.state.editor_state.cursor;
+        cursor.row = cursor.row.min(last_row);
+        cursor.col = 0;
+        self.state.folded_headings.clear();
+    }
+
+    #[allow(dead_code)]
+    fn start_delete(&mut self) {
+        self.state.current_view = View::DeleteConfirm;
+        self.state.confirm_delete = false;
+    }
+
+    fn execute_command(&mut self, command: Command) {
+        match command {
+            Command::Quit => self.quit(),
+            Command::NextNote => self.select_next_note(),
+            Command::PreviousNote => self.select_previous_note(),
+            Command::SwitchView(view) => {
+                self.close_split();
+                self.state.current_view = view;
+            }
+            Command::NewNote => self.create_new_note(),
+            Command::SaveNote => self.save_current_note(),
+            Command::DeleteNote => {
+                if self.state.current_view == View::DeleteConfirm {
+                    self.state.confirm_delete = true;
+                    self.delete_current_note();
+                    self.state.current_view = View::LivePreview;
+                } else {
+                    self.state.current_view = View::DeleteConfirm;
+                    self.state.confirm_delete = false;
+                    self.state.current_view = View::DeleteConfirm;
+                }
+            }
+            Command::ScrollDown => {
+                if let View::Preview | View::LivePreview = self.state.current_view {
+                    self.state.preview_scroll_offset += 5;
+                    self.move_editor_with_preview();
+                }
+            }
+            Command::ScrollUp => {
+                if let View::Preview | View::LivePreview = self.state.current_view {
+                    if self.state.preview_scroll_offset > 0 {
+                        self.state.preview_scroll_offset -= 5;
+                    }
+                    self.move_editor_with_preview();
+                }
+            }
+            Command::ToggleScrollSync => {
+                self.state.scroll_editor_with_preview = !self.state.scroll_editor_with_preview;
+                self.state.status_message = Some(if self.state.scroll_editor_with_preview {
+                    "Scrolling the preview moves the editor".to_string()
+                } else {
+                    "Scrolling the preview leaves the editor in place".to_string()
+                });
+            }
+            Command::RenameNote => self.start_rename(),
+            Command::SubmitRename => self.submit_rename(),
+            Command::CancelRename => {
+                if self.state.current_view == View::DeleteConfirm {
+                    self.state.current_view = View::LivePreview;
+                } else {
+                    self.cancel_rename()
+                }
+            }
+            Command::MoveNoteUp => self.move_note_up(),
+            Command::MoveNoteDown => self.move_note_down(),
+            Command::Unlock => {
+                if self.storage.has_passphrase() && !self.storage.is_unlocked() {
+                    self.open_passphrase_prompt(PassphrasePrompt::Unlock);
+                }
+            }
+            Command::ToggleNoteEncryption => self.toggle_note_encryption(),
+            Command::ToggleVaultEncryption => self.toggle_vault_encryption(),
+            Command::ChangePassphrase => {
+                if self.storage.has_passphrase() && !self.storage.is_unlocked() {
+                    self.open_passphrase_prompt(PassphrasePrompt::Unlock);
+                } else {
+                    self.open_passphrase_prompt(PassphrasePrompt::New(PassphraseAction::Change));
+                }
+            }
+            Command::SubmitPassphrase => self.submit_passphrase(),
+            Command::CancelPassphrase => {
+                self.state.passphrase_buffer.clear();
+                self.state.current_view = View::LivePreview;
+            }
+            Command::ShowHistory => self.show_history(),
+            Command::CloseHistory => self.state.current_view = View::LivePreview,
+            Command::Pull => self.pull(),
+            Command::Push => self.push(),
+            Command::Sync => self.start_sync(),
+            Command::OpenJournal => self.open_journal_entry(journal::today()),
+            Command::ShowCalendar => self.show_calendar(),
+            Command::SubmitCalendar => self.open_journal_entry(self.state.calendar_date),
+            Command::CloseCalendar => self.state.current_view = View::LivePreview,
+            Command::CycleSort => self.change_sorting(SortSettings {
+                key: self.state.sorting.key.next(),
+                ..self.state.sorting
+            }),
+            Command::ReverseSort => self.change_sorting(SortSettings {
+                descending: !self.state.sorting.descending,
+                ..self.state.sorting
+            }),
+            Command::CycleGrouping => self.change_sorting(SortSettings {
+                grouping: self.state.sorting.grouping.next(),
+                ..self.state.sorting
+            }),
+            Command::TogglePin => self.toggle_pin(),
+            Command::ArchiveNote => self.archive_note(),
+            Command::ShowArchive => self.show_archive(),
+            Command::RestoreNote => self.restore_note(),
+            Command::CloseArchive => self.state.current_view = View::LivePreview,
+            Command::ToggleMark => self.toggle_mark(),
+            Command::MarkUp => self.extend_marks(false),
+            Command::MarkDown => self.extend_marks(true),
+            Command::MarkAll => self.mark_all(),
+            Command::MoveToFolder => self.state.command_line = Some("folder ".to_string()),
+            Command::AddTag => self.state.command_line = Some("tag ".to_string()),
+            Command::RemoveTag => self.state.command_line = Some("untag ".to_string()),
+            Command::ExportNotes => self.state.command_line = Some("export ".to_string()),
+            Command::MergeNotes => self.state.command_line = Some("merge ".to_string()),
+            Command::ShowNoteInfo => self.show_note_info(),
+            Command::CloseNoteInfo => self.state.current_view = View::LivePreview,
+            Command::ShowDashboard => self.show_dashboard(),
+            Command::SubmitDashboard => self.open_dashboard_note(),
+            Command::CloseDashboard => {
+                self.state.dashboard = None;
+                self.state.current_view = View::LivePreview;
+            }
+            Command::PreviousJournalDay => self.step_journal(false),
+            Command::NextJournalDay => self.step_journal(true),
+            Command::SubmitTemplateVariable => self.submit_template_variable(),
+            Command::CancelTemplateVariable => self.cancel_template_variable(),
+            Command::ShowOutline => self.show_outline(),
+            Command::CloseOutline => self.state.current_view = View::LivePreview,
+            Command::JumpToHeading => {
+                self.jump_to_heading(self.state.outline_selected);
+                self.state.current_view = View::LivePreview;
+            }
+            Command::InsertToc => self.insert_toc(),
+            Command::ToggleNoteList => {
+                self.state.layout.list_hidden = !self.state.layout.list_hidden;
+                self.save_layout();
+            }
+            Command::ResizeNoteList(delta) => {
+                // Showing the list again is the first step of widening it
+                if self.state.layout.list_hidden {
+                    self.state.layout.list_hidden = false;
+                } else {
+                    self.state.layout.resize_list(delta);
+                }
+                self.save_layout();
+            }
+            Command::ResizeEditor(delta) => {
+                self.state.layout.resize_editor(delta);
+                self.save_layout();
+            }
+            Command::CycleArrangement => {
+                let layout = &mut self.state.layout;
+                layout.arrangement = layout.arrangement.next();
+                self.state.status_message = Some(format!("Layout: {}", layout.arrangement.label()));
+                self.save_layout();
+            }
+            Command::CycleCards => self.change_cards(self.state.layout.cards.next()),
+            Command::SubmitContextMenu => self.submit_context_menu(),
+            Command::CloseContextMenu => self.close_context_menu(),
+            Command::KeepTab => self.keep_tab(),
+            Command::CloseTab => self.close_tab(),
+            Command::NextTab => self.cycle_tab(true),
+            Command::PreviousTab => self.cycle_tab(false),
+            Command::SelectTab(index) => {
+                if index < self.state.tabs.len() {
+                    self.switch_tab(index);
+                }
+            }
+            Command::ToggleSplit => self.toggle_split(),
+            Command::FocusOtherPane => self.focus_other_pane(),
+            Command::FindNote => self.state.command_line = Some("e ".to_string()),
+            Command::ToggleEditingMode => self.toggle_editing_mode(),
+            Command::EditExternally => self.edit_externally = true,
+            Command::Format(format) => {
+                if self.editing() {
+                    markdown_edit::toggle_format(&mut self.state.editor_state, format);
+                }
+            }
+            Command::CopyNote => self.copy_note(false),
+            Command::CopyNoteText => self.copy_note(true),
+            Command::SpellingSuggestions => self.suggest_spelling(),
+            Command::AddToDictionary => {
+                if let Some((_, word)) = self.word_under_cursor() {
+                    self.add_to_dictionary(&word);
+                }
+            }
+            Command::ToggleSpellcheck => self.toggle_spellcheck(),
+            Command::FormatTable => {
+                if self.editing() && !markdown_edit::format_table(&mut self.state.editor_state) {
+                    self.state.status_message = Some("No table under the cursor".to_string());
+                }
+            }
+        }
+    }
+
+    /// Sync with the configured remote on a background thread
+    fn start_sync(&mut self) {
+        match SyncSettings::load(&self.root_dir) {
+            Ok(settings) if settings.remote.is_some() => {}
+            Ok(_) => {
+                self.state.status_message =
+                    Some("Sync is not set up, run `thoth sync --remote <url>` first".to_string());
+                return;
+            }
+            Err(e) => {
+                self.state.status_message = Some(format!("Sync failed: {}", e));
+                return;
+            }
+        }
+        self.save_current_note();
+
+        let (sender, receiver) = mpsc::channel();
+        let root_dir = self.root_dir.clone();
+        thread::spawn(move || {
+            // Notes are synced as stored, so this storage needs no key for encrypted ones
+            let storage = FSStorage::with_root_dir(&root_dir);
+            let result = sync_notes(&root_dir, &storage, &mut |progress| {
+                let _ = sender.send(SyncEvent::Progress(progress.clone()));
+            });
+            let _ = sender.send(SyncEvent::Finished(result));
+        });
+
+        self.sync_events = Some(receiver);
+        self.state.status_message = Some("Syncing...".to_string());
+    }
+
+    fn poll_sync(&mut self) {
+        let Some(events) = &self.sync_events else {
+            return;
+        };
+
+        let mut finished = None;
+        while let Ok(event) = events.try_recv() {
+            match event {
+                SyncEvent::Progress(progress) => {
+                    self.state.status_message = Some(format!(
+                        "Syncing [{}/{}] {}",
+                        (progress.done + 1).min(progress.total),
+                        progress.total,
+                        progress.name
+                    ));
+                }
+                SyncEvent::Finished(result) => finished = Some(result),
+            }
+        }
+
+        if let Some(result) = finished {
+            self.sync_events = None;
+            // Even a failed sync may have changed some notes
+            self.reload_notes();
+            self.record_change("Sync notes");
+            self.state.status_message = Some(match result {
+                Ok(report) => report.summary(),
+                Err(e) => format!("Sync failed: {}", e),
+            });
+        }
+    }
+
+    fn selected_title(&self) -> Option<String> {
+        self.state
+            .list_state
+            .selected
+            .and_then(|selected| self.state.notes.get(selected))
+            .map(|note| note.title.clone())
+    }
+
+    /// Commit the changes to the notes when they are versioned with git
+    fn record_change(&mut self, message: &str) {
+        if let Some(git) = &self.git {
+            if let Err(e) = git.commit_all(message) {
+                self.state.status_message = Some(format!("Failed to commit: {}", e));
+            }
+            self.refresh_conflicts();
+        }
+    }
+
+    fn refresh_conflicts(&mut self) {
+        let Some(git) = &self.git else {
+            return;
+        };
+        let conflicts = git.conflicted_notes().unwrap_or_default();
+        let had_conflicts = self.state.notes.iter().any(|note| note.conflicted);
+        for note in self.state.notes.iter_mut() {
+            note.conflicted = conflicts.contains(&note.title);
+        }
+        if had_conflicts && conflicts.is_empty() {
+            self.state.status_message = Some("All conflicts resolved".to_string());
+        }
+    }
+
+    fn require_git(&mut self) -> bool {
+        if self.git.is_none() {
+            self.state.status_message =
+                Some("Notes are not versioned, run `thoth git init` to enable history".to_string());
+        }
+        self.git.is_some()
+    }
+
+    fn show_history(&mut self) {
+        if !self.require_git() {
+            return;
+        }
+        let Some(title) = self.selected_title() else {
+            return;
+        };
+        let Some(git) = &self.git else {
+            return;
+        };
+
+        match git.history(&title) {
+            Ok(history) if history.is_empty() => {
+                self.state.status_message = Some(format!("'{}' has no history yet", title));
+            }
+            Ok(history) => {
+                self.state.history = history;
+                self.state.history_selected = 0;
+                self.load_history_diff();
+                self.state.current_view = View::History;
+            }
+            Err(e) => self.state.status_message = Some(e.to_string()),
+        }
+    }
+
+    fn load_history_diff(&mut self) {
+        let (Some(git), Some(revision)) = (
+            &self.git,
+            self.state.history.get(self.state.history_selected),
+        ) else {
+            return;
+        };
+        self.state.history_diff = git.diff(revision).unwrap_or_else(|e| e.to_string());
+        self.state.history_scroll = 0;
+    }
+
+    fn pull(&mut self) {
+        if !self.require_git() {
+            return;
+        }
+        self.save_current_note();
+
+        let Some(git) = &self.git else {
+            return;
+        };
+        let outcome = match git.pull() {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                self.state.status_message = Some(format!("Pull failed: {}", e));
+                return;
+            }
+        };
+
+        self.reload_notes();
+
+        let mut message = if !outcome.conflicts.is_empty() {
+            format!(
+                "Conflicts in {}: remove the markers and save to finish the merge",
+                outcome.conflicts.join(", ")
+            )
+        } else if outcome.updated {
+            "Pulled remote changes".to_string()
+        } else {
+            "Already up to date".to_string()
+        };
+        if !outcome.kept_local.is_empty() {
+            message.push_str(&format!(
+                " (kept the local version of encrypted {})",
+                outcome.kept_local.join(", ")
+            ));
+        }
+        self.state.status_message = Some(message);
+    }
+
+    fn push(&mut self) {
+        if !self.require_git() {
+            return;
+        }
+        self.save_current_note();
+
+        let Some(git) = &self.git else {
+            return;
+        };
+        self.state.status_message = Some(match git.push() {
+            Ok(()) => "Pushed local changes".to_string(),
+            Err(e) => format!("Push failed: {}", e),
+        });
+    }
+
+    /// Reload all notes from storage, keeping the selected note selected
+    fn reload_notes(&mut self) {
+        let selected_title = self.selected_title();
+        match self.storage.list_notes() {
+            Ok(notes) => {
+                (self.state.archive, self.state.notes) =
+                    notes.into_iter().partition(|note| note.archived);
+                self.state.sorting.sort(&mut self.state.notes);
+            }
+            Err(e) => {
+                self.state.status_message = Some(format!("Failed to load notes: {}", e));
+                return;
+            }
+        }
+
+        self.refresh_tabs();
+        let selected = selected_title
+            .and_then(|title| self.state.notes.iter().position(|note| note.title == title))
+            .unwrap_or(0);
+        if self.state.notes.is_empty() {
+            self.create_new_note();
+        } else {
+            self.state.list_state.select(Some(selected));
+            self.load_note_to_editor(selected);
+        }
+        self.refresh_conflicts();
+    }
+
+    fn selected_note_locked(&self) -> bool {
+        self.state
+            .list_state
+            .selected
+            .and_then(|selected| self.state.notes.get(selected))
+            .is_some_and(|note| note.locked)
+    }
+
+    fn open_passphrase_prompt(&mut self, prompt: PassphrasePrompt) {
+        self.state.passphrase_buffer.clear();
+        self.state.passphrase_error = None;
+        self.state.passphrase_prompt = prompt;
+        self.state.current_view = View::Passphrase;
+    }
+
+    fn submit_passphrase(&mut self) {
+        let passphrase = std::mem::take(&mut self.state.passphrase_buffer);
+
+        match self.state.passphrase_prompt.clone() {
+            PassphrasePrompt::Unlock => match self.storage.unlock(&passphrase) {
+                Ok(()) => {
+                    self.refresh_locked_notes();
+                    self.state.current_view = View::LivePreview;
+                }
+                Err(e) => self.state.passphrase_error = Some(e.to_string()),
+            },
+            PassphrasePrompt::New(action) => {
+                if passphrase.is_empty() {
+                    self.state.passphrase_error = Some("Passphrase cannot be empty".to_string());
+                } else {
+                    self.state.passphrase_error = None;
+                    self.state.passphrase_prompt = PassphrasePrompt::Confirm(action, passphrase);
+                }
+            }
+            PassphrasePrompt::Confirm(action, first) => {
+                if passphrase != first {
+                    self.state.passphrase_error = Some("Passphrases do not match".to_string());
+                    self.state.passphrase_prompt = PassphrasePrompt::New(action);
+                    return;
+                }
+                if let Err(e) = self.storage.set_passphrase(&first) {
+                    self.state.passphrase_error = Some(e.to_string());
+                    self.state.passphrase_prompt = PassphrasePrompt::New(action);
+                    return;
+                }
+
+                self.state.current_view = View::LivePreview;
+                match action {
+                    PassphraseAction::Change => self.record_change("Change passphrase"),
+                    PassphraseAction::EncryptNote => self.toggle_note_encryption(),
+                    PassphraseAction::EncryptVault => self.toggle_vault_encryption(),
+                }
+            }
+        }
+    }
+
+    /// Replace locked placeholders with the decrypted notes after unlocking
+    fn refresh_locked_notes(&mut self) {
+        let notes = self.state.notes.iter_mut().chain(&mut self.state.archive);
+        for note in notes.filter(|note| note.locked) {
+            match self.storage.read_note(&note.title) {
+                Ok(unlocked) => {
+                    note.content = unlocked.content;
+                    note.locked = false;
+                    note.update_text_info();
+                }
+                Err(e) => {
+                    self.state.status_message =
+                        Some(format!("Failed to decrypt note '{}': {}", note.title, e));
+                }
+            }
+        }
+        self.refresh_tabs();
+    }
+
+    fn toggle_note_encryption(&mut self) {
+        if !self.storage.has_passphrase() {
+            self.open_passphrase_prompt(PassphrasePrompt::New(PassphraseAction::EncryptNote));
+            return;
+        }
+        if !self.storage.is_unlocked() {
+            self.open_passphrase_prompt(PassphrasePrompt::Unlock);
+            return;
+        }
+
+        self.save_editor_content_to_current_note();
+        if let Some(selected) = self.state.list_state.selected {
+            if let Some(note) = self.state.notes.get_mut(selected) {
+                note.encrypted = !note.encrypted;
+                if let Err(e) = self.storage.write_note(note) {
+                    self.state.status_message = Some(format!(
+                        "Failed to change encryption of '{}': {}",
+                        note.title, e
+                    ));
+                    note.encrypted = !note.encrypted;
+                } else {
+                    note.dirty = false;
+                    let action = if note.encrypted { "Encrypt" } else { "Decrypt" };
+                    let message = format!("{} '{}'", action, note.title);
+                    self.record_change(&message);
+                }
+            }
+        }
+    }
+
+    fn toggle_vault_encryption(&mut self) {
+        if !self.storage.has_passphrase() {
+            self.open_passphrase_prompt(PassphrasePrompt::New(PassphraseAction::EncryptVault));
+            return;
+        }
+        if !self.storage.is_unlocked() {
+            self.open_passphrase_prompt(PassphrasePrompt::Unlock);
+            return;
+        }
+
+        let enabled = !self.storage.encrypts_all();
+        if let Err(e) = self.storage.set_encrypt_all(enabled) {
+            self.state.status_message = Some(format!("Failed to change vault encryption: {}", e));
+        } else if enabled {
+            self.record_change("Encrypt all notes");
+        } else {
+            self.record_change("Stop encrypting all notes");
+        }
+    }
+
+    fn move_note_up(&mut self) {
+        if let Some(selected) = self.state.list_state.selected {
+            if selected > 0 && self.can_move_note(selected, selected - 1) {
+                // Get the orders
+                let current_order = self.state.notes[selected].order;
+                let prev_order = self.state.notes[selected - 1].order;
+
+                // Swap orders
+                self.state.notes[selected].order = prev_order;
+                self.state.notes[selected - 1].order = current_order;
+
+                // Save both notes to persist order changes
+                let moved = [&self.state.notes[selected], &self.state.notes[selected - 1]];
+                if let Err(e) = self.storage.write_notes_metadata(&moved) {
+                    eprintln!("Failed to save note order: {}", e);
+                }
+
+                // Resort the notes
+                self.state.sorting.sort(&mut self.state.notes);
+
+                // Update selection
+                self.state.list_state.select(Some(selected - 1));
+                let message = format!("Move '{}' up", self.state.notes[selected - 1].title);
+                self.record_change(&message);
+            }
+        }
+    }
+
+    fn move_note_down(&mut self) {
+        if let Some(selected) = self.state.list_state.selected {
+            if selected < self.state.notes.len() - 1 && self.can_move_note(selected, selected + 1) {
+                // Get the orders
+                let current_order = self.state.notes[selected].order;
+                let next_order = self.state.notes[selected + 1].order;
+
+                // Swap orders
+                self.state.notes[selected].order = next_order;
+                self.state.notes[selected + 1].order = current_order;
+
+                // Save both notes to persist order changes
+                let moved = [&self.state.notes[selected], &self.state.notes[selected + 1]];
+                if let Err(e) = self.storage.write_notes_metadata(&moved) {
+                    eprintln!("Failed to save note order: {}", e);
                 }
 
                 // Resort the notes
-                self.state.notes.sort_by_key(|note| note.order);
+                self.state.sorting.sort(&mut self.state.notes);
 
                 // Update selection
                 self.state.list_state.select(Some(selected + 1));
+                let message = format!("Move '{}' down", self.state.notes[selected + 1].title);
+                self.record_change(&message);
+            }
+        }
+    }
+
+    /// Whether a note can swap places with the one next to it, which only
+    /// the manual order allows, within a section of the list
+    fn can_move_note(&mut self, index: usize, other: usize) -> bool {
+        let sorting = self.state.sorting;
+        if sorting.key != SortKey::Manual {
+            self.state.status_message = Some(format!(
+                "Notes are sorted by {}, switch to the manual order with Alt+Shift+S to move them",
+                sorting.key.label()
+            ));
+            return false;
+        }
+        let notes = &self.state.notes;
+        if notes[index].pinned != notes[other].pinned {
+            self.state.status_message = Some("Pinned notes stay above the others".to_string());
+            return false;
+        }
+        if sorting.group(&notes[index]) != sorting.group(&notes[other]) {
+            self.state.status_message =
+                Some("Notes cannot be moved out of their group".to_string());
+            return false;
+        }
+        true
+    }
+
+    /// Sort the note list again, keeping the selected note selected
+    fn sort_notes(&mut self) {
+        let selected_title = self.selected_title();
+        self.state.sorting.sort(&mut self.state.notes);
+        let selected = selected_title
+            .and_then(|title| self.state.notes.iter().position(|note| note.title == title));
+        if selected.is_some() {
+            self.state.list_state.select(selected);
+        }
+    }
+
+    /// Change the order or sections of the note list and remember them
+    fn change_sorting(&mut self, sorting: SortSettings) {
+        self.state.sorting = sorting;
+        if let Err(e) = sorting.save(&self.root_dir) {
+            self.state.status_message = Some(format!("Failed to save the sorting: {}", e));
+            return;
+        }
+        self.sort_notes();
+        self.state.status_message = Some(format!("Notes sorted by {}", sorting.describe()));
+    }
+
+    fn select_next_note(&mut self) {
+        self.save_editor_content_to_current_note();
+        self.state.list_state.next();
+        let to_index = self.state.list_state.selected.unwrap_or(0);
+        self.load_note_to_editor(to_index);
+    }
+
+    fn select_previous_note(&mut self) {
+        self.save_editor_content_to_current_note();
+        self.state.list_state.previous();
+        let to_index = self.state.list_state.selected.unwrap_or(0);
+        self.load_note_to_editor(to_index);
+    }
+
+    fn save_editor_content_to_current_note(&mut self) {
+        let content = self.editor_content();
+        if let Some(selected) = self.state.list_state.selected {
+            if let Some(note) = self
+                .state
+                .notes
+                .get_mut(selected)
+                .filter(|note| !note.locked)
+            {
+                if note.content == content {
+                    return;
+                }
+                // Editing a note keeps its tab open
+                if let Some(tab) = self.state.tabs.get_mut(self.state.active_tab) {
+                    tab.transient = false;
+                }
+                note.content = content;
+                note.updated_at = Local::now();
+                note.dirty = true;
+                note.update_text_info();
+                self.record_activity();
+            }
+        }
+    }
+
+    /// Count an edit made today for the dashboard
+    fn record_activity(&mut self) {
+        self.state.activity.record(journal::today());
+        if let Err(e) = self.state.activity.save(&self.root_dir) {
+            self.state.status_message = Some(format!("Failed to save activity: {}", e));
+        }
+    }
+
+    /// Text in the editor
+    fn editor_content(&self) -> String {
+        editor_text(&self.state.editor_state)
+    }
+
+    /// Show a note in the editor, switching to its tab when it is open.
+    ///
+    /// Otherwise the note takes the place of the active tab while that one is
+    /// transient, and opens in a new tab next to it if not.
+    fn load_note_to_editor(&mut self, index: usize) {
+        let Some(note) = self.state.notes.get(index) else {
+            return;
+        };
+        if let Some(tab) = self.tab_index(&note.title) {
+            self.activate_tab(tab);
+            return;
+        }
+
+        let tab = OpenTab {
+            title: note.title.clone(),
+            editor: EditorState::default(),
+            transient: true,
+        };
+        let mut editor = EditorState::new(Jagged::from(note.content.clone()));
+        editor.set_clipboard(self.clipboard.clone());
+        let active = self.state.active_tab;
+        match self.state.tabs.get_mut(active) {
+            Some(current) if current.transient => *current = tab,
+            Some(current) => {
+                current.editor = mem::take(&mut self.state.editor_state);
+                self.state.tabs.insert(active + 1, tab);
+                self.state.active_tab = active + 1;
+            }
+            None => {
+                self.state.tabs.push(tab);
+                self.state.active_tab = self.state.tabs.len() - 1;
+            }
+        }
+        self.state.editor_state = editor;
+        self.state.folded_headings.clear();
+    }
+
+    fn tab_index(&self, title: &str) -> Option<usize> {
+        self.state.tabs.iter().position(|tab| tab.title == title)
+    }
+
+    /// Put the editor of a tab in place of the active one, without saving the
+    /// active note first
+    fn activate_tab(&mut self, index: usize) {
+        let active = self.state.active_tab;
+        if index == active || index >= self.state.tabs.len() {
+            return;
+        }
+        let previous = self.state.tabs.get_mut(active).map(|current| {
+            current.editor = mem::take(&mut self.state.editor_state);
+            current.title.clone()
+        });
+        self.state.editor_state = mem::take(&mut self.state.tabs[index].editor);
+        self.state.active_tab = index;
+
+        // Activating the note of the other pane moves the focus there
+        if let (Some(split), Some(previous)) = (&mut self.state.split, previous) {
+            if self.state.current_view == View::Split && self.state.tabs[index].title == split.other
+            {
+                split.other = previous;
+                split.focus_left = !split.focus_left;
+            }
+        }
+        self.select_active_note();
+        self.state.folded_headings.clear();
+    }
+
+    fn switch_tab(&mut self, index: usize) {
+        self.save_editor_content_to_current_note();
+        self.activate_tab(index);
+    }
+
+    /// Select the note of the active tab in the note list
+    fn select_active_note(&mut self) {
+        let Some(title) = self.state.active_title() else {
+            return;
+        };
+        let index = self.state.notes.iter().position(|note| note.title == title);
+        if index.is_some() {
+            self.state.list_state.select(index);
+        }
+    }
+
+    fn close_tab(&mut self) {
+        if self.state.tabs.len() < 2 {
+            self.state.status_message = Some("The last tab stays open".to_string());
+            return;
+        }
+        self.save_editor_content_to_current_note();
+        if let Some(title) = self.state.active_title().map(str::to_string) {
+            self.drop_tab(&title);
+        }
+    }
+
+    /// Close the tab of a note, moving to the next tab when it is the active one.
+    ///
+    /// The last tab is only marked transient, so that the next note opened replaces it.
+    fn drop_tab(&mut self, title: &str) {
+        let Some(index) = self.tab_index(title) else {
+            return;
+        };
+        let active = self.state.active_tab;
+        if self.state.tabs.len() == 1 {
+            self.state.tabs[0].transient = true;
+        } else if index == active {
+            self.state.tabs.remove(index);
+            let next = index.min(self.state.tabs.len() - 1);
+            self.state.editor_state = mem::take(&mut self.state.tabs[next].editor);
+            self.state.active_tab = next;
+            self.select_active_note();
+            self.state.folded_headings.clear();
+        } else {
+            self.state.tabs.remove(index);
+            if index < active {
+                self.state.active_tab -= 1;
             }
         }
+
+        // Both notes of the split view have to stay open
+        let split_closed = self.state.split.as_ref().is_some_and(|split| {
+            split.other == title
+                || self.state.active_title() == Some(split.other.as_str())
+                || self.state.tabs.len() < 2
+        });
+        if split_closed {
+            self.close_split();
+        }
+    }
+
+    /// Activate the tab after or before the active one, wrapping around
+    fn cycle_tab(&mut self, forward: bool) {
+        let count = self.state.tabs.len();
+        if count < 2 {
+            return;
+        }
+        let active = self.state.active_tab;
+        let index = if forward {
+            (active + 1) % count
+        } else {
+            (active + count - 1) % count
+        };
+        self.switch_tab(index);
+    }
+
+    fn keep_tab(&mut self) {
+        if let Some(tab) = self.state.tabs.get_mut(self.state.active_tab) {
+            tab.transient = false;
+            self.state.status_message = Some(format!("Keeping '{}' open", tab.title));
+        }
     }
 
-    fn select_next_note(&mut self) {
-        self.save_editor_content_to_current_note();
-        self.state.list_state.next();
-        let to_index = self.state.list_state.selected.unwrap_or(0);
-        self.load_note_to_editor(to_index);
+    /// Edit the active note next to the tab beside it, or go back to a single editor
+    fn toggle_split(&mut self) {
+        if self.state.current_view == View::Split {
+            self.close_split();
+            return;
+        }
+        let active = self.state.active_tab;
+        let other = if active > 0 { active - 1 } else { active + 1 };
+        if other >= self.state.tabs.len() {
+            self.state.status_message =
+                Some("Open another note to split the editor with".to_string());
+            return;
+        }
+        // Neither note is replaced while they are side by side
+        for index in [active, other] {
+            self.state.tabs[index].transient = false;
+        }
+        self.state.split = Some(Split {
+            other: self.state.tabs[other].title.clone(),
+            focus_left: other > active,
+        });
+        self.state.current_view = View::Split;
     }
 
-    fn select_previous_note(&mut self) {
-        self.save_editor_content_to_current_note();
-        self.state.list_state.previous();
-        let to_index = self.state.list_state.selected.unwrap_or(0);
-        self.load_note_to_editor(to_index);
+    fn close_split(&mut self) {
+        self.state.split = None;
+        if self.state.current_view == View::Split {
+            self.state.current_view = View::LivePreview;
+        }
     }
 
-    fn save_editor_content_to_current_note(&mut self) {
-        if let Some(selected) = self.state.list_state.selected {
-            if let Some(note) = self.state.notes.get_mut(selected) {
-                let content_rows = self
-                    .state
-                    .editor_state
-                    .lines
-                    .flatten(&Some('\n'))
-                    .iter()
-                    .map(|row| row.to_string())
-                    .collect::<Vec<String>>();
-                note.content = content_rows.join("");
-                note.updated_at = Local::now();
-            }
+    /// Move the focus to the other note of the split view
+    fn focus_other_pane(&mut self) {
+        let other = self
+            .state
+            .split
+            .as_ref()
+            .and_then(|split| self.tab_index(&split.other));
+        if let Some(index) = other {
+            self.switch_tab(index);
         }
     }
 
-    fn load_note_to_editor(&mut self, index: usize) {
-        if let Some(note) = self.state.notes.get_mut(index) {
+    /// Bring the open tabs up to date with the notes after reloading them,
+    /// closing the tabs of notes that are gone
+    fn refresh_tabs(&mut self) {
+        let titles: Vec<String> = self
+            .state
+            .tabs
+            .iter()
+            .map(|tab| tab.title.clone())
+            .collect();
+        for title in titles {
+            let Some(note) = self.state.notes.iter().find(|note| note.title == title) else {
+                self.drop_tab(&title);
+                continue;
+            };
             let content = note.content.clone();
-            self.state.editor_state.lines = Jagged::from(content);
+            let Some(index) = self.tab_index(&title) else {
+                continue;
+            };
+            let editor = if index == self.state.active_tab {
+                &mut self.state.editor_state
+            } else {
+                &mut self.state.tabs[index].editor
+            };
+            if editor_text(editor) != content {
+                editor.lines = Jagged::from(content);
+                editor.selection = None;
+                let last_row = editor.lines.len().saturating_sub(1);
+                let row = editor.cursor.row.min(last_row);
+                let col = editor
+                    .cursor
+                    .col
+                    .min(editor.lines.len_col(row).unwrap_or(0));
+                editor.cursor = Index2::new(row, col);
+            }
         }
     }
 
@@ -379,6 +2727,92 @@ impl App {
         self.state.current_view = View::Rename;
         self.state.rename_buffer = String::new();
         self.state.creating_new_note = true;
+        self.state.templates = templates::load_templates(&self.root_dir);
+        self.state.template_selected = None;
+    }
+
+    /// Create a note from the template picked in the new note dialog,
+    /// asking for its custom variables first
+    fn create_note_from_template(&mut self, title: String) {
+        // Refused before asking for variables that would go unused
+        if self.refuse_taken_title(&title) {
+            return;
+        }
+        let Some(template) = self
+            .state
+            .template_selected
+            .and_then(|index| self.state.templates.get(index))
+            .cloned()
+        else {
+            self.insert_note(Note {
+                title,
+                content: String::new(),
+                created_at: Local::now(),
+                updated_at: Local::now(),
+                ..Default::default()
+            });
+            return;
+        };
+
+        let prompt = TemplatePrompt {
+            title,
+            variables: templates::variables(&template.content),
+            template,
+            values: HashMap::new(),
+        };
+        if prompt.variables.is_empty() {
+            self.finish_template(prompt);
+        } else {
+            self.state.template_prompt = Some(prompt);
+            self.state.current_view = View::TemplateVariable;
+        }
+    }
+
+    fn submit_template_variable(&mut self) {
+        let Some(mut prompt) = self.state.template_prompt.take() else {
+            return;
+        };
+        if let Some(variable) = prompt.current_variable().map(str::to_string) {
+            let value = std::mem::take(&mut self.state.rename_buffer);
+            prompt.values.insert(variable, value);
+        }
+
+        if prompt.current_variable().is_some() {
+            self.state.template_prompt = Some(prompt);
+        } else {
+            self.finish_template(prompt);
+            self.state.current_view = View::LivePreview;
+        }
+    }
+
+    fn cancel_template_variable(&mut self) {
+        self.state.template_prompt = None;
+        self.state.rename_buffer.clear();
+        self.state.current_view = View::LivePreview;
+    }
+
+    fn finish_template(&mut self, prompt: TemplatePrompt) {
+        let expanded = templates::expand(
+            &prompt.template.content,
+            &prompt.title,
+            Local::now().naive_local(),
+            &prompt.values,
+        );
+        self.insert_note(Note {
+            title: prompt.title,
+            content: expanded.content,
+            created_at: Local::now(),
+            updated_at: Local::now(),
+            ..Default::default()
+        });
+        self.place_cursor(expanded.cursor);
+    }
+
+    /// Move the editor cursor to where a template asked for it
+    fn place_cursor(&mut self, cursor: Option<(usize, usize)>) {
+        if let Some((row, col)) = cursor {
+            self.state.editor_state.cursor = Index2::new(row, col);
+        }
     }
 
     fn save_current_note(&mut self) {
@@ -386,12 +2820,22 @@ impl App {
 
         // Save to storage
         if let Some(selected) = self.state.list_state.selected {
-            if let Some(note) = self.state.notes.get(selected) {
-                if let Err(e) = self.storage.write_note(note) {
-                    eprintln!("Failed to save note '{}': {}", note.title, e);
+            if let Some(note) = self.state.notes.get_mut(selected) {
+                match self.storage.write_note(note) {
+                    Ok(()) => {
+                        note.dirty = false;
+                        let message = format!("Update '{}'", note.title);
+                        self.record_change(&message);
+                    }
+                    Err(e) => {
+                        self.state.status_message =
+                            Some(format!("Failed to save note '{}': {}", note.title, e));
+                    }
                 }
             }
         }
+        // A note sorted by its date or size moves once it is saved
+        self.sort_notes();
     }
 
     fn delete_current_note(&mut self) {
@@ -401,46 +2845,717 @@ impl App {
         }
         self.state.confirm_delete = false;
 
+        let indices = self.state.batch_indices();
+        if indices.is_empty() {
+            return;
+        }
+        // Notes that could not be deleted stay in the list
+        let mut deleted = Vec::new();
+        for index in indices {
+            let title = &self.state.notes[index].title;
+            match self.storage.delete_note(title) {
+                Ok(()) => deleted.push(index),
+                Err(e) => {
+                    self.state.status_message =
+                        Some(format!("Failed to delete '{}': {}", title, e));
+                }
+            }
+        }
+        if deleted.is_empty() {
+            return;
+        }
+        let notes = self.take_notes(&deleted);
+
+        // Adjust remaining notes' order
+        let orders: Vec<usize> = notes.iter().map(|note| note.order).collect();
+        self.close_order_gaps(&orders);
+        self.sort_notes();
+        self.record_change(&format!(
+            "Delete {}",
+            describe_notes(notes.iter().map(|note| note.title.as_str()))
+        ));
+    }
+
+    /// Take notes out of the list and close their tabs. When the selected note
+    /// is among them, the note taking its place is selected.
+    fn take_notes(&mut self, indices: &[usize]) -> Vec<Note> {
+        let selected = self.state.list_state.selected.unwrap_or(0);
+        let selected_title = self.selected_title();
+        let mut notes: Vec<Note> = indices
+            .iter()
+            .rev()
+            .map(|&index| self.state.notes.remove(index))
+            .collect();
+        notes.reverse();
+        for note in &mut notes {
+            note.marked = false;
+            self.drop_tab(&note.title);
+        }
+
+        if self.state.notes.is_empty() {
+            self.create_new_note();
+            return notes;
+        }
+        let listed = |title: &str| self.state.notes.iter().position(|note| note.title == title);
+        if let Some(index) = selected_title.as_deref().and_then(listed) {
+            self.state.list_state.select(Some(index));
+        } else if let Some(index) = self.state.active_title().and_then(listed) {
+            // The next tab took over
+            self.state.list_state.select(Some(index));
+        } else {
+            let selected = selected.min(self.state.notes.len() - 1);
+            self.state.list_state.select(Some(selected));
+            self.load_note_to_editor(selected);
+        }
+        notes
+    }
+
+    /// Close the gaps left in the manual order by notes taken out of the list,
+    /// saving the notes that moved in one go
+    fn close_order_gaps(&mut self, removed: &[usize]) {
+        // Archived notes keep their place among the others
+        let mut moved: Vec<&Note> = Vec::new();
+        for note in self
+            .state
+            .notes
+            .iter_mut()
+            .chain(self.state.archive.iter_mut())
+        {
+            let gaps = removed.iter().filter(|&&order| order < note.order).count();
+            if gaps > 0 {
+                note.order -= gaps;
+                moved.push(note);
+            }
+        }
+        if let Err(e) = self.storage.write_notes_metadata(&moved) {
+            eprintln!("Failed to update note order: {}", e);
+        }
+    }
+
+    /// Add a note at the end of the list, save it and select it
+    fn insert_note(&mut self, mut note: Note) {
+        if self.refuse_taken_title(&note.title) {
+            return;
+        }
+        // Find the maximum order and add 1 for the new note
+        let max_order = self
+            .state
+            .notes
+            .iter()
+            .map(|note| note.order)
+            .max()
+            .unwrap_or(0);
+        note.order = max_order + 1;
+        note.update_text_info();
+
+        // Save to storage
+        if let Err(e) = self.storage.write_note(&note) {
+            eprintln!("Failed to save new note: {}", e);
+        }
+        self.record_change(&format!("Create '{}'", note.title));
+
+        self.state.notes.push(note);
+        self.state
+            .list_state
+            .select(Some(self.state.notes.len() - 1));
+        self.sort_notes();
         if let Some(selected) = self.state.list_state.selected {
-            if !self.state.notes.is_empty() {
-                // Get the title before removing from memory
-                let title = self.state.notes[selected].title.clone();
-                let order = self.state.notes[selected].order;
+            self.load_note_to_editor(selected);
+        }
+    }
 
-                // Remove from memory
-                self.state.notes.remove(selected);
+    /// Whether a new note can't take a title, as the file of an active or
+    /// archived note would be overwritten. Tells the user why.
+    fn refuse_taken_title(&mut self, title: &str) -> bool {
+        if self.state.archive.iter().any(|note| note.title == title) {
+            self.state.status_message = Some(format!(
+                "'{}' is in the archive, restore it with Alt+Shift+V",
+                title
+            ));
+            return true;
+        }
+        if self.state.notes.iter().any(|note| note.title == title) {
+            self.state.status_message = Some(format!("There is already a note named '{}'", title));
+            return true;
+        }
+        false
+    }
 
-                // Remove from storage
-                if let Err(e) = self.storage.delete_note(&title) {
-                    eprintln!("Failed to delete note '{}' from storage: {}", title, e);
-                }
+    /// Open the daily note for a date, creating it if there is none
+    fn open_journal_entry(&mut self, date: NaiveDate) {
+        self.save_editor_content_to_current_note();
 
-                // Adjust remaining notes' order
-                for note in self.state.notes.iter_mut() {
-                    if note.order > order {
-                        note.order -= 1;
-                        if let Err(e) = self.storage.write_note(note) {
-                            eprintln!("Failed to update note order: {}", e);
-                        }
-                    }
-                }
+        let title = journal::entry_title(date);
+        let existing = self
+            .state
+            .notes
+            .iter()
+            .position(|note| note.title == title)
+            .or_else(|| {
+                self.state
+                    .notes
+                    .iter()
+                    .position(|note| journal::entry_date(note) == Some(date))
+            });
+
+        match existing {
+            Some(index) => {
+                self.state.list_state.select(Some(index));
+                self.load_note_to_editor(index);
+            }
+            None => {
+                // Custom variables of the journal template are left empty,
+                // as daily notes are opened without any dialog
+                let expanded =
+                    templates::load_template(&self.root_dir, templates::JOURNAL_TEMPLATE).map(
+                        |template| {
+                            let now = date.and_time(Local::now().time());
+                            templates::expand(&template.content, &title, now, &HashMap::new())
+                        },
+                    );
+                let content = match &expanded {
+                    Some(expanded) => expanded.content.clone(),
+                    None => journal::entry_content(date),
+                };
+                self.insert_note(Note {
+                    title,
+                    content,
+                    created_at: Local::now(),
+                    updated_at: Local::now(),
+                    folder: journal::JOURNAL_FOLDER.to_string(),
+                    ..Default::default()
+                });
+                self.place_cursor(expanded.and_then(|expanded| expanded.cursor));
+            }
+        }
+        self.state.preview_scroll_offset = 0;
+        self.state.current_view = View::LivePreview;
+    }
 
-                // Resort the notes after order adjustment
-                self.state.notes.sort_by_key(|note| note.order);
+    /// Go to the closest daily note before or after the selected one.
+    ///
+    /// When there is none in that direction, the note for the adjacent day is
+    /// created, so browsing back through old entries never creates empty ones.
+    fn step_journal(&mut self, forward: bool) {
+        let current = self
+            .state
+            .list_state
+            .selected
+            .and_then(|selected| self.state.notes.get(selected))
+            .and_then(journal::entry_date)
+            .unwrap_or_else(journal::today);
 
-                // Adjust selection if needed
-                if self.state.notes.is_empty() {
-                    self.create_new_note();
-                } else if selected >= self.state.notes.len() {
-                    self.state
-                        .list_state
-                        .select(Some(self.state.notes.len() - 1));
-                }
+        let dates: BTreeSet<NaiveDate> = self
+            .state
+            .notes
+            .iter()
+            .filter_map(journal::entry_date)
+            .collect();
+        let closest = if forward {
+            dates.range(current.succ_opt().unwrap_or(current)..).next()
+        } else {
+            dates.range(..current).next_back()
+        };
+        let adjacent = if forward {
+            current.succ_opt()
+        } else {
+            current.pred_opt()
+        };
+
+        if let Some(date) = closest.copied().or(adjacent) {
+            self.open_journal_entry(date);
+        }
+    }
+
+    fn show_calendar(&mut self) {
+        self.state.calendar_date = self
+            .state
+            .list_state
+            .selected
+            .and_then(|selected| self.state.notes.get(selected))
+            .and_then(journal::entry_date)
+            .unwrap_or_else(journal::today);
+        self.state.current_view = View::Calendar;
+    }
+
+    fn handle_calendar_input(&mut self, key: KeyEvent) {
+        let date = self.state.calendar_date;
+        let moved = match key.code {
+            KeyCode::Left | KeyCode::Char('h') => date.pred_opt(),
+            KeyCode::Right | KeyCode::Char('l') => date.succ_opt(),
+            KeyCode::Up | KeyCode::Char('k') => date.checked_sub_days(Days::new(7)),
+            KeyCode::Down | KeyCode::Char('j') => date.checked_add_days(Days::new(7)),
+            KeyCode::PageUp => date.checked_sub_months(Months::new(1)),
+            KeyCode::PageDown => date.checked_add_months(Months::new(1)),
+            KeyCode::Char('t') => Some(journal::today()),
+            _ => None,
+        };
+        if let Some(date) = moved {
+            self.state.calendar_date = date;
+        }
+    }
+
+    /// Keep the selected note at the top of the list, or let it go back
+    fn toggle_pin(&mut self) {
+        self.save_editor_content_to_current_note();
+        let Some(note) = self
+            .state
+            .list_state
+            .selected
+            .and_then(|selected| self.state.notes.get_mut(selected))
+        else {
+            return;
+        };
+        // Locked notes have no content to write back
+        if note.locked {
+            self.state.status_message = Some("Unlock the note to pin it".to_string());
+            return;
+        }
+        note.pinned = !note.pinned;
+        if let Err(e) = self.storage.write_note(note) {
+            note.pinned = !note.pinned;
+            self.state.status_message = Some(format!("Failed to save '{}': {}", note.title, e));
+            return;
+        }
+        note.dirty = false;
+        let (message, status) = if note.pinned {
+            (
+                format!("Pin '{}'", note.title),
+                format!("Pinned '{}' to the top of the list", note.title),
+            )
+        } else {
+            (
+                format!("Unpin '{}'", note.title),
+                format!("Unpinned '{}'", note.title),
+            )
+        };
+        self.record_change(&message);
+        self.sort_notes();
+        self.state.status_message = Some(status);
+    }
+
+    /// Move the marked notes, or the selected one, out of the list into the archive
+    fn archive_note(&mut self) {
+        self.save_editor_content_to_current_note();
+        let indices = self.state.batch_indices();
+        if indices.is_empty() {
+            return;
+        }
+        for &index in &indices {
+            self.state.notes[index].archived = true;
+        }
+        let notes: Vec<&Note> = indices
+            .iter()
+            .map(|&index| &self.state.notes[index])
+            .collect();
+        if let Err(e) = self.storage.write_notes_metadata(&notes) {
+            for &index in &indices {
+                self.state.notes[index].archived = false;
+            }
+            self.state.status_message = Some(format!("Failed to archive: {}", e));
+            return;
+        }
+
+        let notes = self.take_notes(&indices);
+        let archived = describe_notes(notes.iter().map(|note| note.title.as_str()));
+        self.state.archive.extend(notes);
+        self.record_change(&format!("Archive {}", archived));
+        self.state.status_message = Some(format!(
+            "Archived {}, Alt+Shift+V shows the archive",
+            archived
+        ));
+    }
+
+    fn toggle_mark(&mut self) {
+        if let Some(note) = self
+            .state
+            .list_state
+            .selected
+            .and_then(|selected| self.state.notes.get_mut(selected))
+        {
+            note.marked = !note.marked;
+        }
+        self.show_marked();
+    }
+
+    /// Mark the selected note and the next or previous one, then select it
+    fn extend_marks(&mut self, down: bool) {
+        let Some(selected) = self.state.list_state.selected else {
+            return;
+        };
+        let next = if down {
+            Some(selected + 1)
+        } else {
+            selected.checked_sub(1)
+        };
+        let Some(next) = next.filter(|&next| next < self.state.notes.len()) else {
+            return;
+        };
+        self.state.notes[selected].marked = true;
+        self.state.notes[next].marked = true;
+        self.select_note(next);
+        self.show_marked();
+    }
+
+    /// Mark the notes from the selected one to another, then select it
+    fn mark_range(&mut self, index: usize) {
+        let selected = self.state.list_state.selected.unwrap_or(index);
+        for note in &mut self.state.notes[selected.min(index)..=selected.max(index)] {
+            note.marked = true;
+        }
+        self.select_note(index);
+        self.show_marked();
+    }
+
+    /// Mark every note in the list, or none when they all are
+    fn mark_all(&mut self) {
+        let all = self.state.notes.iter().all(|note| note.marked);
+        for note in &mut self.state.notes {
+            note.marked = !all;
+        }
+        self.show_marked();
+    }
+
+    fn show_marked(&mut self) {
+        let marked = self.state.notes.iter().filter(|note| note.marked).count();
+        self.state.status_message = Some(match marked {
+            0 => "No notes marked".to_string(),
+            1 => "1 note marked".to_string(),
+            marked => format!("{} notes marked", marked),
+        });
+    }
+
+    /// Change the metadata of the marked notes, or the selected one, and save
+    /// them in one go. Returns the notes changed, as described in messages.
+    fn change_notes(&mut self, change: impl Fn(&mut Note)) -> Option<String> {
+        let indices = self.state.batch_indices();
+        if indices.is_empty() {
+            return None;
+        }
+        let previous: Vec<Note> = indices
+            .iter()
+            .map(|&index| self.state.notes[index].clone())
+            .collect();
+        for &index in &indices {
+            change(&mut self.state.notes[index]);
+        }
+        let notes: Vec<&Note> = indices
+            .iter()
+            .map(|&index| &self.state.notes[index])
+            .collect();
+        let changed = describe_notes(notes.iter().map(|note| note.title.as_str()));
+        if let Err(e) = self.storage.write_notes_metadata(&notes) {
+            for (index, note) in indices.into_iter().zip(previous) {
+                self.state.notes[index] = note;
+            }
+            self.state.status_message = Some(format!("Failed to save {}: {}", changed, e));
+            return None;
+        }
+        Some(changed)
+    }
+
+    /// Move the marked notes, or the selected one, into a folder
+    fn move_to_folder(&mut self, folder: String) {
+        let Some(moved) = self.change_notes(|note| note.folder = folder.clone()) else {
+            return;
+        };
+        let message = if folder.is_empty() {
+            format!("Move {} out of their folder", moved)
+        } else {
+            format!("Move {} to {}", moved, folder)
+        };
+        self.record_change(&message);
+        self.sort_notes();
+        self.state.status_message = Some(message);
+    }
+
+    /// Add a tag to the marked notes, or the selected one, or take it off them
+    fn change_tag(&mut self, tag: &str, add: bool) {
+        let Some(changed) = self.change_notes(|note| {
+            if !add {
+                note.tags.retain(|other| other != tag);
+            } else if !note.tags.iter().any(|other| other == tag) {
+                note.tags.push(tag.to_string());
+            }
+        }) else {
+            return;
+        };
+        let message = if add {
+            format!("Tag {} with #{}", changed, tag)
+        } else {
+            format!("Remove #{} from {}", tag, changed)
+        };
+        self.record_change(&message);
+        self.sort_notes();
+        self.state.status_message = Some(message);
+    }
+
+    /// Write the marked notes, or the selected one, to files in a format
+    fn export_notes(&mut self, format: &str, directory: Option<String>) {
+        self.save_editor_content_to_current_note();
+        let exporter = match find_exporter(format, DEFAULT_WIDTH) {
+            Ok(exporter) => exporter,
+            Err(e) => {
+                self.state.status_message = Some(e.to_string());
+                return;
+            }
+        };
+        let directory = PathBuf::from(directory.unwrap_or_else(|| ".".to_string()));
+        if let Err(e) = fs::create_dir_all(&directory) {
+            self.state.status_message =
+                Some(format!("Failed to create {}: {}", directory.display(), e));
+            return;
+        }
+
+        let mut exported = Vec::new();
+        let mut locked = 0;
+        for index in self.state.batch_indices() {
+            let note = &self.state.notes[index];
+            // Locked notes have no content to export
+            if note.locked {
+                locked += 1;
+                continue;
+            }
+            let file_name = format!(
+                "{}.{}",
+                note.title.replace(['/', '\\'], "_"),
+                exporter.extension()
+            );
+            let path = directory.join(file_name);
+            let result = exporter
+                .export(note)
+                .map_err(|e| e.to_string())
+                .and_then(|text| fs::write(&path, text).map_err(|e| e.to_string()));
+            if let Err(e) = result {
+                self.state.status_message =
+                    Some(format!("Failed to export '{}': {}", note.title, e));
+                return;
+            }
+            exported.push(note.title.as_str());
+        }
+
+        let mut message = format!(
+            "Exported {} to {}",
+            describe_notes(exported),
+            directory.display()
+        );
+        if locked > 0 {
+            message.push_str(&format!(", skipped {} locked", locked));
+        }
+        self.state.status_message = Some(message);
+    }
+
+    /// Join the marked notes into a new note, in the order of the list, and
+    /// move them to the archive
+    fn merge_notes(&mut self, title: String) {
+        self.save_editor_content_to_current_note();
+        let indices: Vec<usize> = self
+            .state
+            .notes
+            .iter()
+            .enumerate()
+            .filter(|(_, note)| note.marked)
+            .map(|(index, _)| index)
+            .collect();
+        if indices.len() < 2 {
+            self.state.status_message =
+                Some("Mark the notes to merge with Alt+Shift+X".to_string());
+            return;
+        }
+        if indices.iter().any(|&index| self.state.notes[index].locked) {
+            self.state.status_message = Some("Unlock the notes to merge them".to_string());
+            return;
+        }
+        let taken = self
+            .state
+            .notes
+            .iter()
+            .chain(&self.state.archive)
+            .any(|note| note.title == title);
+        if taken {
+            self.state.status_message = Some(format!("There is already a note named '{}'", title));
+            return;
+        }
 
-                if let Some(new_selected) = self.state.list_state.selected {
-                    self.load_note_to_editor(new_selected);
+        let notes: Vec<&Note> = indices
+            .iter()
+            .map(|&index| &self.state.notes[index])
+            .collect();
+        let order = self
+            .state
+            .notes
+            .iter()
+            .map(|note| note.order + 1)
+            .max()
+            .unwrap_or(0);
+        let merged = Note::merged(&title, &notes, order);
+        if let Err(e) = self.storage.write_note(&merged) {
+            self.state.status_message = Some(format!("Failed to save '{}': {}", title, e));
+            return;
+        }
+
+        // The originals are kept in the archive
+        for &index in &indices {
+            self.state.notes[index].archived = true;
+        }
+        let notes: Vec<&Note> = indices
+            .iter()
+            .map(|&index| &self.state.notes[index])
+            .collect();
+        let archived = self.storage.write_notes_metadata(&notes);
+        self.state.notes.push(merged);
+        self.state
+            .list_state
+            .select(Some(self.state.notes.len() - 1));
+        let count = indices.len();
+        let message = match archived {
+            Ok(()) => {
+                let originals = self.take_notes(&indices);
+                self.state.archive.extend(originals);
+                format!(
+                    "Merged {} notes into '{}', the originals are in the archive",
+                    count, title
+                )
+            }
+            // The originals stay in the list next to the merged note
+            Err(e) => {
+                for &index in &indices {
+                    self.state.notes[index].archived = false;
                 }
+                format!(
+                    "Merged {} notes into '{}', but failed to archive the originals: {}",
+                    count, title, e
+                )
+            }
+        };
+        self.record_change(&format!("Merge {} notes into '{}'", count, title));
+        self.sort_notes();
+        if let Some(selected) = self.state.list_state.selected {
+            self.load_note_to_editor(selected);
+        }
+        self.state.status_message = Some(message);
+    }
+
+    fn show_archive(&mut self) {
+        if self.state.archive.is_empty() {
+            self.state.status_message = Some("The archive is empty".to_string());
+            return;
+        }
+        self.state
+            .archive
+            .sort_by_key(|note| std::cmp::Reverse(note.updated_at));
+        self.state.archive_selected = 0;
+        self.state.current_view = View::Archive;
+    }
+
+    fn handle_archive_input(&mut self, key: KeyEvent) {
+        let last = self.state.archive.len().saturating_sub(1);
+        let selected = &mut self.state.archive_selected;
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => *selected = selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => *selected = (*selected + 1).min(last),
+            _ => {}
+        }
+    }
+
+    /// Put the note selected in the archive back at the end of the list
+    fn restore_note(&mut self) {
+        let index = self.state.archive_selected;
+        if index >= self.state.archive.len() {
+            return;
+        }
+        self.save_editor_content_to_current_note();
+        let mut note = self.state.archive.remove(index);
+        note.archived = false;
+        note.order = self
+            .state
+            .notes
+            .iter()
+            .map(|note| note.order + 1)
+            .max()
+            .unwrap_or(0);
+        if let Err(e) = self.storage.write_note(&note) {
+            self.state.status_message = Some(format!("Failed to save '{}': {}", note.title, e));
+            self.state.archive.insert(index, note);
+            return;
+        }
+
+        let title = note.title.clone();
+        self.record_change(&format!("Restore '{}'", title));
+        self.state.notes.push(note);
+        self.state
+            .list_state
+            .select(Some(self.state.notes.len() - 1));
+        self.sort_notes();
+        if let Some(selected) = self.state.list_state.selected {
+            self.load_note_to_editor(selected);
+        }
+        self.state.status_message = Some(format!("Restored '{}'", title));
+
+        // Stay in the archive while there is more to restore
+        if self.state.archive.is_empty() {
+            self.state.current_view = View::LivePreview;
+        } else {
+            self.state.archive_selected = index.min(self.state.archive.len() - 1);
+        }
+    }
+
+    fn show_note_info(&mut self) {
+        let selected = self
+            .state
+            .list_state
+            .selected
+            .and_then(|selected| self.state.notes.get(selected));
+        match selected {
+            Some(note) if note.locked => {
+                self.state.status_message = Some("Unlock the note to count it".to_string());
+            }
+            Some(_) => {
+                self.save_editor_content_to_current_note();
+                self.update_stats();
+                self.state.current_view = View::NoteInfo;
+            }
+            None => {}
+        }
+    }
+
+    fn show_dashboard(&mut self) {
+        self.save_editor_content_to_current_note();
+        self.state.dashboard = Some(VaultStats::collect(&self.state.notes));
+        self.state.dashboard_selected = 0;
+        self.state.current_view = View::Dashboard;
+    }
+
+    fn handle_dashboard_input(&mut self, key: KeyEvent) {
+        let count = self
+            .state
+            .dashboard
+            .as_ref()
+            .map_or(0, |dashboard| dashboard.recent.len());
+        let selected = &mut self.state.dashboard_selected;
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => *selected = selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                *selected = (*selected + 1).min(count.saturating_sub(1));
             }
+            _ => {}
+        }
+    }
+
+    /// Open the recently edited note selected on the dashboard
+    fn open_dashboard_note(&mut self) {
+        let selected = self.state.dashboard_selected;
+        let title = self
+            .state
+            .dashboard
+            .take()
+            .and_then(|dashboard| dashboard.recent.into_iter().nth(selected))
+            .map(|(title, _)| title);
+        self.state.current_view = View::LivePreview;
+        let index =
+            title.and_then(|title| self.state.notes.iter().position(|note| note.title == title));
+        if let Some(index) = index {
+            self.select_note(index);
         }
     }
 
@@ -465,37 +3580,38 @@ impl App {
             if let Some(selected) = self.state.list_state.selected {
                 // If we're creating a new note
                 if self.state.creating_new_note {
-                    // Find the maximum order and add 1 for the new note
-                    let max_order = self
+                    self.state.creating_new_note = false;
+                    self.state.current_view = View::LivePreview;
+                    self.create_note_from_template(new_title);
+                    return;
+                } else {
+                    // If we're renaming an existing note
+                    if self
+                        .state
+                        .archive
+                        .iter()
+                        .any(|note| note.title == new_title)
+                    {
+                        self.state.status_message = Some(format!(
+                            "'{}' is in the archive, restore it with Alt+Shift+V",
+                            new_title
+                        ));
+                        self.state.current_view = View::LivePreview;
+                        return;
+                    }
+                    // Its file and its tabs would be taken over
+                    if self
                         .state
                         .notes
                         .iter()
-                        .map(|note| note.order)
-                        .max()
-                        .unwrap_or(0);
-
-                    let new_note = Note {
-                        title: new_title,
-                        content: String::new(),
-                        created_at: Local::now(),
-                        updated_at: Local::now(),
-                        selected: false,
-                        order: max_order + 1,
-                    };
-
-                    // Save to storage
-                    if let Err(e) = self.storage.write_note(&new_note) {
-                        eprintln!("Failed to save new note: {}", e);
+                        .enumerate()
+                        .any(|(index, note)| index != selected && note.title == new_title)
+                    {
+                        self.state.status_message =
+                            Some(format!("There is already a note named '{}'", new_title));
+                        self.state.current_view = View::LivePreview;
+                        return;
                     }
-
-                    self.state.notes.push(new_note);
-                    self.state
-                        .list_state
-                        .select(Some(self.state.notes.len() - 1));
-                    self.load_note_to_editor(self.state.notes.len() - 1);
-                    self.state.creating_new_note = false;
-                } else {
-                    // If we're renaming an existing note
                     if let Some(note) = self.state.notes.get_mut(selected) {
                         let old_title = note.title.clone();
                         note.title = new_title;
@@ -506,6 +3622,22 @@ impl App {
                             eprintln!("Failed to rename note: {}", e);
                             // Revert on failure
                             note.title = old_title;
+                        } else {
+                            note.dirty = false;
+                            let new_title = note.title.clone();
+                            for tab in self.state.tabs.iter_mut() {
+                                if tab.title == old_title {
+                                    tab.title = new_title.clone();
+                                }
+                            }
+                            if let Some(split) = &mut self.state.split {
+                                if split.other == old_title {
+                                    split.other = new_title.clone();
+                                }
+                            }
+                            let message = format!("Rename '{}' to '{}'", old_title, new_title);
+                            self.record_change(&message);
+                            self.sort_notes();
                         }
                     }
                 }
@@ -516,6 +3648,7 @@ impl App {
 
     fn cancel_rename(&mut self) {
         self.state.rename_buffer.clear();
+        self.state.creating_new_note = false;
         self.state.current_view = View::LivePreview;
     }
 
@@ -530,3 +3663,46 @@ impl Default for App {
         Self::new()
     }
 }
+
+/// Move the cursor of an editor by a turn of the mouse wheel, which scrolls it along
+fn scroll_cursor(editor: &mut EditorState, down: bool) {
+    let last_row = editor.lines.len().saturating_sub(1);
+    let row = if down {
+        (editor.cursor.row + MOUSE_SCROLL_ROWS).min(last_row)
+    } else {
+        editor.cursor.row.saturating_sub(MOUSE_SCROLL_ROWS)
+    };
+    let col = editor
+        .cursor
+        .col
+        .min(editor.lines.len_col(row).unwrap_or(0));
+    editor.cursor = Index2::new(row, col);
+}
+
+/// `'title'` for a single note, or how many notes there are
+fn describe_notes<'a>(titles: impl IntoIterator<Item = &'a str>) -> String {
+    let titles: Vec<&str> = titles.into_iter().collect();
+    match titles.as_slice() {
+        [title] => format!("'{}'", title),
+        titles => format!("{} notes", titles.len()),
+    }
+}
+
+fn lines_hash(editor: &EditorState) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for row in 0..editor.lines.len() {
+        editor.lines.get(RowIndex::new(row)).hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Text in an editor
+fn editor_text(editor: &EditorState) -> String {
+    editor
+        .lines
+        .flatten(&Some('\n'))
+        .iter()
+        .map(|row| row.to_string())
+        .collect::<Vec<String>>()
+        .join("")
+}
@@ -0,0 +1,375 @@
+use std::fs;
+use std::path::PathBuf;
+
+use color_eyre::eyre::{bail, eyre};
+use color_eyre::Result;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+
+use crate::export::{find_exporter, AnsiExporter, Exporter, DEFAULT_WIDTH};
+use crate::import::{find_importer, import};
+use crate::models::note::Note;
+use crate::storage::{error::StorageError, fs::FSStorage, git::GitRepo, Storage};
+use crate::sync::{sync_notes, RemoteConfig, SyncSettings};
+
+const USAGE: &str = "\
+Usage:
+  thoth                                   Start the note editor
+  thoth cat [--color] [--width N] <title> Print a note to the terminal
+  thoth export <format> [--width N] [--output FILE] <title>
+                                          Export a note (formats: ansi, plain, rst, asciidoc, json)
+  thoth import <format> [--dry-run] <path>
+                                          Import notes (formats: markdown, obsidian, joplin, enex, html)
+  thoth git init [<remote url>]           Version the notes with git, committing every change
+  thoth git remote <url>                  Set the remote to pull from and push to
+  thoth git pull                          Merge changes from the remote
+  thoth git push                          Send changes to the remote
+  thoth git log <title>                   Show the history of a note
+  thoth sync [--remote URL] [--user NAME] Sync notes with a WebDAV collection or a directory
+  thoth help                              Show this message
+
+Encrypted notes ask for the passphrase, or read it from THOTH_PASSPHRASE.
+The WebDAV password is asked for with --user, or read from THOTH_SYNC_PASSWORD.";
+
+/// Commands that run without starting the TUI
+#[derive(Debug, PartialEq, Eq)]
+pub enum CliCommand {
+    Help,
+    Cat {
+        title: String,
+        color: bool,
+        width: u16,
+    },
+    Export {
+        format: String,
+        title: String,
+        output: Option<PathBuf>,
+        width: u16,
+    },
+    Import {
+        format: String,
+        path: PathBuf,
+        dry_run: bool,
+    },
+    Git(GitCommand),
+    Sync {
+        remote: Option<String>,
+        username: Option<String>,
+    },
+}
+
+/// Version control commands for the notes directory
+#[derive(Debug, PartialEq, Eq)]
+pub enum GitCommand {
+    Init { remote: Option<String> },
+    Remote { url: String },
+    Pull,
+    Push,
+    Log { title: String },
+}
+
+/// Parse command line arguments (without the program name).
+///
+/// Returns `None` when no subcommand was given and the TUI should start.
+pub fn parse(args: &[String]) -> Result<Option<CliCommand>> {
+    let Some((subcommand, rest)) = args.split_first() else {
+        return Ok(None);
+    };
+
+    let mut color = false;
+    let mut width = DEFAULT_WIDTH;
+    let mut output = None;
+    let mut dry_run = false;
+    let mut remote = None;
+    let mut username = None;
+    let mut positional = Vec::new();
+
+    let mut iter = rest.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--color" => color = true,
+            "--dry-run" => dry_run = true,
+            "--width" => {
+                let value = iter.next().ok_or_else(|| eyre!("--width needs a value"))?;
+                width = value
+                    .parse()
+                    .map_err(|_| eyre!("Invalid width: {}", value))?;
+            }
+            "--output" | "-o" => {
+                let value = iter.next().ok_or_else(|| eyre!("--output needs a value"))?;
+                output = Some(PathBuf::from(value));
+            }
+            "--remote" => {
+                let value = iter.next().ok_or_else(|| eyre!("--remote needs a value"))?;
+                remote = Some(value.clone());
+            }
+            "--user" => {
+                let value = iter.next().ok_or_else(|| eyre!("--user needs a value"))?;
+                username = Some(value.clone());
+            }
+            _ => positional.push(arg.clone()),
+        }
+    }
+
+    let command = match subcommand.as_str() {
+        "help" | "--help" | "-h" => CliCommand::Help,
+        "cat" => CliCommand::Cat {
+            title: positional.join(" "),
+            color,
+            width,
+        },
+        "export" => {
+            if positional.is_empty() {
+                bail!("Missing export format\n\n{}", USAGE);
+            }
+            let format = positional.remove(0);
+            CliCommand::Export {
+                format,
+                title: positional.join(" "),
+                output,
+                width,
+            }
+        }
+        "import" => {
+            if positional.len() != 2 {
+                bail!("Expected an import format and a path\n\n{}", USAGE);
+            }
+            CliCommand::Import {
+                format: positional.remove(0),
+                path: PathBuf::from(positional.remove(0)),
+                dry_run,
+            }
+        }
+        "git" => {
+            if positional.is_empty() {
+                bail!("Missing git command\n\n{}", USAGE);
+            }
+            let action = positional.remove(0);
+            let command = match (action.as_str(), positional.len()) {
+                ("init", 0) => GitCommand::Init { remote: None },
+                ("init", 1) => GitCommand::Init {
+                    remote: Some(positional.remove(0)),
+                },
+                ("remote", 1) => GitCommand::Remote {
+                    url: positional.remove(0),
+                },
+                ("pull", 0) => GitCommand::Pull,
+                ("push", 0) => GitCommand::Push,
+                ("log", _) => GitCommand::Log {
+                    title: require_title(positional.join(" "))?,
+                },
+                _ => bail!("Invalid git command\n\n{}", USAGE),
+            };
+            CliCommand::Git(command)
+        }
+        "sync" => CliCommand::Sync { remote, username },
+        other => bail!("Unknown command: {}\n\n{}", other, USAGE),
+    };
+
+    Ok(Some(command))
+}
+
+/// Run a command against the default storage
+pub fn run(command: CliCommand) -> Result<()> {
+    let storage = FSStorage::new();
+    storage.init()?;
+
+    match command {
+        CliCommand::Help => println!("{}", USAGE),
+        CliCommand::Cat {
+            title,
+            color,
+            width,
+        } => {
+            let note = read_note(&storage, &require_title(title)?)?;
+            if color {
+                print!("{}", AnsiExporter::new(width).export(&note)?);
+            } else {
+                print!("{}", note.content);
+            }
+        }
+        CliCommand::Export {
+            format,
+            title,
+            output,
+            width,
+        } => {
+            let exporter = find_exporter(&format, width)?;
+            let note = read_note(&storage, &require_title(title)?)?;
+            let exported = exporter.export(&note)?;
+            match output {
+                Some(path) => fs::write(path, exported)?,
+                None => print!("{}", exported),
+            }
+        }
+        CliCommand::Import {
+            format,
+            path,
+            dry_run,
+        } => {
+            let importer = find_importer(&format)?;
+            if storage.encrypts_all() && !dry_run {
+                storage.unlock(&read_passphrase()?)?;
+            }
+            let report = import(importer.as_ref(), &path, &storage, dry_run)?;
+            println!("{}", report.summary());
+            if !dry_run {
+                if let Some(repo) = GitRepo::open(storage.root_dir()) {
+                    repo.commit_all(&format!("Import notes from {}", path.display()))?;
+                }
+            }
+        }
+        CliCommand::Git(command) => run_git(&storage, command)?,
+        CliCommand::Sync { remote, username } => run_sync(&storage, remote, username)?,
+    }
+
+    Ok(())
+}
+
+fn run_git(storage: &FSStorage, command: GitCommand) -> Result<()> {
+    if let GitCommand::Init { remote } = &command {
+        GitRepo::init(storage.root_dir(), remote.as_deref())?;
+        println!("Versioning notes in {}", storage.root_dir().display());
+        return Ok(());
+    }
+
+    let repo = GitRepo::open(storage.root_dir())
+        .ok_or_else(|| eyre!("Notes are not versioned yet, run `thoth git init`"))?;
+    match command {
+        GitCommand::Init { .. } => {}
+        GitCommand::Remote { url } => repo.set_remote(&url)?,
+        GitCommand::Pull => {
+            repo.commit_all("Record local changes")?;
+            let outcome = repo.pull()?;
+            for title in &outcome.kept_local {
+                println!("Kept the local version of encrypted note '{}'", title);
+            }
+            if !outcome.conflicts.is_empty() {
+                println!("Conflicts to resolve in:");
+                for title in &outcome.conflicts {
+                    println!("  {}", title);
+                }
+            } else if outcome.updated {
+                println!("Pulled remote changes");
+            } else {
+                println!("Already up to date");
+            }
+        }
+        GitCommand::Push => {
+            repo.commit_all("Record local changes")?;
+            repo.push()?;
+            println!("Pushed local changes");
+        }
+        GitCommand::Log { title } => {
+            for revision in repo.history(&title)? {
+                println!(
+                    "{}  {}  {}",
+                    revision.short_id(),
+                    revision.date.format("%Y-%m-%d %H:%M"),
+                    revision.summary
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_sync(storage: &FSStorage, remote: Option<String>, username: Option<String>) -> Result<()> {
+    let root_dir = storage.root_dir();
+    if remote.is_some() || username.is_some() {
+        let mut settings = SyncSettings::load(root_dir)?;
+        let url = match (remote, &settings.remote) {
+            (Some(url), _) => url,
+            (None, Some(config)) => config.url.clone(),
+            (None, None) => bail!("Missing --remote\n\n{}", USAGE),
+        };
+        // A password from the environment is not stored
+        let password = match &username {
+            Some(_) if std::env::var("THOTH_SYNC_PASSWORD").is_err() => {
+                Some(read_secret("Password: ")?).filter(|password| !password.is_empty())
+            }
+            _ => None,
+        };
+        settings.set_remote(RemoteConfig {
+            url,
+            username,
+            password,
+        });
+        settings.save(root_dir)?;
+    }
+
+    let report = sync_notes(root_dir, storage, &mut |progress| {
+        if !progress.name.is_empty() {
+            eprint!(
+                "\r\x1b[K[{}/{}] {}",
+                progress.done + 1,
+                progress.total,
+                progress.name
+            );
+        }
+    });
+    eprint!("\r\x1b[K");
+    let report = report?;
+    println!("{}", report.summary());
+
+    if let Some(repo) = GitRepo::open(root_dir) {
+        repo.commit_all("Sync notes")?;
+    }
+    Ok(())
+}
+
+fn require_title(title: String) -> Result<String> {
+    if title.is_empty() {
+        bail!("Missing note title\n\n{}", USAGE);
+    }
+    Ok(title)
+}
+
+/// Read a note, asking for the passphrase if it is encrypted
+fn read_note(storage: &FSStorage, title: &str) -> Result<Note> {
+    match storage.read_note(title) {
+        Err(StorageError::Locked(_)) => {
+            storage.unlock(&read_passphrase()?)?;
+            Ok(storage.read_note(title)?)
+        }
+        result => Ok(result?),
+    }
+}
+
+/// Read the vault passphrase from the environment or, without echo, from the terminal
+fn read_passphrase() -> Result<String> {
+    if let Ok(passphrase) = std::env::var("THOTH_PASSPHRASE") {
+        return Ok(passphrase);
+    }
+    read_secret("Passphrase: ")
+}
+
+/// Read a line from the terminal without echoing it
+fn read_secret(prompt: &str) -> Result<String> {
+    eprint!("{}", prompt);
+    terminal::enable_raw_mode()?;
+    let mut passphrase = String::new();
+    let result = loop {
+        let key = match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => key,
+            Ok(_) => continue,
+            Err(e) => break Err(e.into()),
+        };
+        match key.code {
+            KeyCode::Enter => break Ok(()),
+            KeyCode::Esc => break Err(eyre!("Cancelled")),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                break Err(eyre!("Cancelled"))
+            }
+            KeyCode::Char(c) => passphrase.push(c),
+            KeyCode::Backspace => {
+                passphrase.pop();
+            }
+            _ => {}
+        }
+    };
+    terminal::disable_raw_mode()?;
+    eprintln!();
+
+    result.map(|_| passphrase)
+}
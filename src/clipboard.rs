@@ -0,0 +1,163 @@
+use std::env;
+use std::io::{self, stdout, Write};
+use std::process::{Command, Stdio};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use edtui::clipboard::ClipboardTrait;
+use edtui::{EditorState, Index2, RowIndex};
+use tracing::{debug, warn};
+
+use crate::keymap::capture_undo;
+
+/// Clipboard of the editors, copying through the terminal with OSC 52 so that
+/// it works over SSH, and through the desktop's clipboard tools when there
+/// are any
+#[derive(Debug, Default)]
+pub struct SystemClipboard {
+    /// Text copied last, pasted when the desktop clipboard cannot be read
+    text: String,
+}
+
+impl ClipboardTrait for SystemClipboard {
+    fn set_text(&mut self, text: String) {
+        if let Err(e) = copy_osc52(&text) {
+            warn!("Failed to copy through the terminal: {}", e);
+        }
+        if let Some(tool) = run_tool(&copy_tools(), Some(&text)) {
+            debug!("Copied with {}", tool);
+        }
+        self.text = text;
+    }
+
+    fn get_text(&mut self) -> String {
+        paste_from_tools().unwrap_or_else(|| self.text.clone())
+    }
+}
+
+/// Ask the terminal to put text on the clipboard
+fn copy_osc52(text: &str) -> io::Result<()> {
+    let mut out = stdout();
+    write!(out, "\x1b]52;c;{}\x07", STANDARD.encode(text))?;
+    out.flush()
+}
+
+/// Commands copying their input to the clipboard, the likeliest first
+fn copy_tools() -> Vec<Vec<&'static str>> {
+    if cfg!(target_os = "macos") {
+        return vec![vec!["pbcopy"]];
+    }
+    if cfg!(target_os = "windows") {
+        return vec![vec!["clip"]];
+    }
+    let mut tools = Vec::new();
+    if env::var_os("WAYLAND_DISPLAY").is_some() {
+        tools.push(vec!["wl-copy"]);
+    }
+    if env::var_os("DISPLAY").is_some() {
+        tools.push(vec!["xclip", "-selection", "clipboard"]);
+        tools.push(vec!["xsel", "--clipboard", "--input"]);
+    }
+    tools
+}
+
+/// Commands printing the clipboard, the likeliest first
+fn paste_tools() -> Vec<Vec<&'static str>> {
+    if cfg!(target_os = "macos") {
+        return vec![vec!["pbpaste"]];
+    }
+    let mut tools = Vec::new();
+    if env::var_os("WAYLAND_DISPLAY").is_some() {
+        tools.push(vec!["wl-paste", "--no-newline"]);
+    }
+    if env::var_os("DISPLAY").is_some() {
+        tools.push(vec!["xclip", "-selection", "clipboard", "-o"]);
+        tools.push(vec!["xsel", "--clipboard", "--output"]);
+    }
+    tools
+}
+
+/// Run the first of the tools that is installed, giving it the input, and
+/// return its name when it succeeded
+fn run_tool(tools: &[Vec<&'static str>], input: Option<&str>) -> Option<&'static str> {
+    for tool in tools {
+        // The tools may stay in the background to serve the clipboard, so
+        // nothing waits for their output
+        let child = Command::new(tool[0])
+            .args(&tool[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        let Ok(mut child) = child else {
+            continue;
+        };
+        if let (Some(mut stdin), Some(input)) = (child.stdin.take(), input) {
+            if let Err(e) = stdin.write_all(input.as_bytes()) {
+                warn!("Failed to copy with {}: {}", tool[0], e);
+            }
+        }
+        return match child.wait() {
+            Ok(status) if status.success() => Some(tool[0]),
+            Ok(status) => {
+                warn!("{} exited with {}", tool[0], status);
+                None
+            }
+            Err(e) => {
+                warn!("Failed to copy with {}: {}", tool[0], e);
+                None
+            }
+        };
+    }
+    None
+}
+
+fn paste_from_tools() -> Option<String> {
+    paste_tools().iter().find_map(|tool| {
+        let output = Command::new(tool[0])
+            .args(&tool[1..])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    })
+}
+
+/// Insert pasted text at the cursor as a single edit, leaving the cursor
+/// after it
+pub fn paste_text(editor: &mut EditorState, text: &str) {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    if text.is_empty() {
+        return;
+    }
+    capture_undo(editor);
+    editor.selection = None;
+
+    let cursor = editor.cursor;
+    let mut line = editor
+        .lines
+        .get(RowIndex::new(cursor.row))
+        .cloned()
+        .unwrap_or_default();
+    let tail = line.split_off(cursor.col.min(line.len()));
+    let mut pieces = text.split('\n');
+    line.extend(pieces.next().unwrap_or_default().chars());
+
+    let mut rows = vec![line];
+    rows.extend(pieces.map(|piece| piece.chars().collect()));
+    let last = rows.len() - 1;
+    let col = rows[last].len();
+    rows[last].extend(tail);
+
+    for (offset, row) in rows.into_iter().enumerate() {
+        let index = cursor.row + offset;
+        match editor.lines.get_mut(RowIndex::new(index)) {
+            Some(existing) if offset == 0 => *existing = row,
+            _ => editor.lines.insert(RowIndex::new(index), row),
+        }
+    }
+    editor.cursor = Index2::new(cursor.row + last, col);
+}
@@ -1,4 +1,5 @@
 use crate::app::View;
+use crate::markdown_edit::Format;
 
 pub enum Command {
     Quit,
@@ -15,4 +16,210 @@ pub enum Command {
     CancelRename,
     MoveNoteUp,
     MoveNoteDown,
+    Unlock,
+    ToggleNoteEncryption,
+    ToggleVaultEncryption,
+    ChangePassphrase,
+    SubmitPassphrase,
+    CancelPassphrase,
+    ShowHistory,
+    CloseHistory,
+    Pull,
+    Push,
+    Sync,
+    OpenJournal,
+    ShowCalendar,
+    SubmitCalendar,
+    CloseCalendar,
+    PreviousJournalDay,
+    NextJournalDay,
+    SubmitTemplateVariable,
+    CancelTemplateVariable,
+    ShowOutline,
+    CloseOutline,
+    JumpToHeading,
+    InsertToc,
+    ToggleScrollSync,
+    SubmitContextMenu,
+    CloseContextMenu,
+    ToggleNoteList,
+    /// Widen or narrow the note list, in percent of the width
+    ResizeNoteList(i16),
+    /// Grow or shrink the editor against the preview, in percent
+    ResizeEditor(i16),
+    CycleArrangement,
+    /// Show the notes of the list as compact, normal or expanded cards
+    CycleCards,
+    /// Keep the active tab open when another note is picked
+    KeepTab,
+    CloseTab,
+    NextTab,
+    PreviousTab,
+    /// Activate a tab by its position, from 0
+    SelectTab(usize),
+    ToggleSplit,
+    FocusOtherPane,
+    /// Open the command line to type the title of a note to open
+    FindNote,
+    ToggleEditingMode,
+    /// Edit the note in `$VISUAL` or `$EDITOR`
+    EditExternally,
+    /// Put Markdown syntax around the selection, or take it away
+    Format(Format),
+    /// Align the columns of the table under the cursor
+    FormatTable,
+    /// Copy the note being edited to the clipboard as Markdown
+    CopyNote,
+    /// Copy the note being edited to the clipboard as rendered plain text
+    CopyNoteText,
+    /// Offer corrections of the word under the cursor
+    SpellingSuggestions,
+    /// Accept the word under the cursor from now on
+    AddToDictionary,
+    ToggleSpellcheck,
+    /// Sort the note list by the next key
+    CycleSort,
+    /// Switch the note list between ascending and descending order
+    ReverseSort,
+    /// Split the note list into sections by the next grouping
+    CycleGrouping,
+    /// Keep the selected note at the top of the list, or stop keeping it there
+    TogglePin,
+    /// Move the selected note out of the list into the archive
+    ArchiveNote,
+    ShowArchive,
+    /// Put the note selected in the archive back in the list
+    RestoreNote,
+    CloseArchive,
+    /// Mark the selected note for a batch action, or unmark it
+    ToggleMark,
+    /// Mark the selected note and the one above, selecting it
+    MarkUp,
+    /// Mark the selected note and the one below, selecting it
+    MarkDown,
+    /// Mark every note in the list, or none when all of them are
+    MarkAll,
+    /// Open the command line to type the folder to move the marked notes to
+    MoveToFolder,
+    /// Open the command line to type a tag for the marked notes
+    AddTag,
+    /// Open the command line to type a tag to take off the marked notes
+    RemoveTag,
+    /// Open the command line to type the format to export the marked notes in
+    ExportNotes,
+    /// Open the command line to type the title of the merged notes
+    MergeNotes,
+    /// Show the statistics and dates of the selected note
+    ShowNoteInfo,
+    CloseNoteInfo,
+    /// Show the statistics of the whole vault
+    ShowDashboard,
+    /// Open the note selected on the dashboard
+    SubmitDashboard,
+    CloseDashboard,
+}
+
+impl Command {
+    /// Command run by a leader mapping, by the name used in the keymap file
+    pub fn from_name(name: &str) -> Option<Command> {
+        let command = match name {
+            "quit" => Command::Quit,
+            "next_note" => Command::NextNote,
+            "previous_note" => Command::PreviousNote,
+            "editor" => Command::SwitchView(View::Editor),
+            "preview" => Command::SwitchView(View::Preview),
+            "live_preview" => Command::SwitchView(View::LivePreview),
+            "new_note" => Command::NewNote,
+            "find_note" => Command::FindNote,
+            "save_note" => Command::SaveNote,
+            "delete_note" => Command::DeleteNote,
+            "rename_note" => Command::RenameNote,
+            "move_note_up" => Command::MoveNoteUp,
+            "move_note_down" => Command::MoveNoteDown,
+            "unlock" => Command::Unlock,
+            "toggle_note_encryption" => Command::ToggleNoteEncryption,
+            "toggle_vault_encryption" => Command::ToggleVaultEncryption,
+            "change_passphrase" => Command::ChangePassphrase,
+            "history" => Command::ShowHistory,
+            "pull" => Command::Pull,
+            "push" => Command::Push,
+            "sync" => Command::Sync,
+            "journal" => Command::OpenJournal,
+            "calendar" => Command::ShowCalendar,
+            "previous_journal_day" => Command::PreviousJournalDay,
+            "next_journal_day" => Command::NextJournalDay,
+            "outline" => Command::ShowOutline,
+            "insert_toc" => Command::InsertToc,
+            "toggle_scroll_sync" => Command::ToggleScrollSync,
+            "toggle_note_list" => Command::ToggleNoteList,
+            "cycle_arrangement" => Command::CycleArrangement,
+            "cycle_cards" => Command::CycleCards,
+            "keep_tab" => Command::KeepTab,
+            "close_tab" => Command::CloseTab,
+            "next_tab" => Command::NextTab,
+            "previous_tab" => Command::PreviousTab,
+            "toggle_split" => Command::ToggleSplit,
+            "focus_other_pane" => Command::FocusOtherPane,
+            "toggle_editing_mode" => Command::ToggleEditingMode,
+            "edit_externally" => Command::EditExternally,
+            "bold" => Command::Format(Format::Bold),
+            "italic" => Command::Format(Format::Italic),
+            "inline_code" => Command::Format(Format::Code),
+            "link" => Command::Format(Format::Link),
+            "format_table" => Command::FormatTable,
+            "copy_note" => Command::CopyNote,
+            "copy_note_text" => Command::CopyNoteText,
+            "spelling_suggestions" => Command::SpellingSuggestions,
+            "add_to_dictionary" => Command::AddToDictionary,
+            "toggle_spellcheck" => Command::ToggleSpellcheck,
+            "cycle_sort" => Command::CycleSort,
+            "reverse_sort" => Command::ReverseSort,
+            "cycle_grouping" => Command::CycleGrouping,
+            "toggle_pin" => Command::TogglePin,
+            "archive_note" => Command::ArchiveNote,
+            "archive" => Command::ShowArchive,
+            "toggle_mark" => Command::ToggleMark,
+            "mark_up" => Command::MarkUp,
+            "mark_down" => Command::MarkDown,
+            "mark_all" => Command::MarkAll,
+            "move_to_folder" => Command::MoveToFolder,
+            "add_tag" => Command::AddTag,
+            "remove_tag" => Command::RemoveTag,
+            "export_notes" => Command::ExportNotes,
+            "merge_notes" => Command::MergeNotes,
+            "note_info" => Command::ShowNoteInfo,
+            "dashboard" => Command::ShowDashboard,
+            _ => return None,
+        };
+        Some(command)
+    }
+
+    /// Whether the command writes notes, which has to wait while a sync is running
+    pub fn modifies_notes(&self) -> bool {
+        matches!(
+            self,
+            Command::NewNote
+                | Command::SaveNote
+                | Command::DeleteNote
+                | Command::RenameNote
+                | Command::MoveNoteUp
+                | Command::MoveNoteDown
+                | Command::ToggleNoteEncryption
+                | Command::ToggleVaultEncryption
+                | Command::ChangePassphrase
+                | Command::Pull
+                | Command::Push
+                | Command::Sync
+                | Command::OpenJournal
+                | Command::SubmitCalendar
+                | Command::PreviousJournalDay
+                | Command::NextJournalDay
+                | Command::SubmitTemplateVariable
+                | Command::InsertToc
+                | Command::EditExternally
+                | Command::TogglePin
+                | Command::ArchiveNote
+                | Command::RestoreNote
+        )
+    }
 }
@@ -0,0 +1,379 @@
+use edtui::{EditorState, Index2, RowIndex};
+
+use crate::keymap::capture_undo;
+use crate::models::note::Note;
+use crate::models::text::inline_tags;
+use crate::ui;
+
+/// Most suggestions shown at once
+const MAX_ITEMS: usize = 50;
+
+/// Emoji offered after `:`, by GitHub shortcode
+const EMOJI: &[(&str, &str)] = &[
+    ("+1", "👍"),
+    ("-1", "👎"),
+    ("100", "💯"),
+    ("alarm_clock", "⏰"),
+    ("airplane", "✈️"),
+    ("bangbang", "‼️"),
+    ("battery", "🔋"),
+    ("bell", "🔔"),
+    ("book", "📖"),
+    ("bookmark", "🔖"),
+    ("books", "📚"),
+    ("boom", "💥"),
+    ("brain", "🧠"),
+    ("bug", "🐛"),
+    ("bulb", "💡"),
+    ("calendar", "📆"),
+    ("camera", "📷"),
+    ("chart_with_upwards_trend", "📈"),
+    ("check", "✔️"),
+    ("clap", "👏"),
+    ("clipboard", "📋"),
+    ("coffee", "☕"),
+    ("computer", "💻"),
+    ("confused", "😕"),
+    ("construction", "🚧"),
+    ("cry", "😢"),
+    ("date", "📅"),
+    ("dart", "🎯"),
+    ("email", "📧"),
+    ("eyes", "👀"),
+    ("fire", "🔥"),
+    ("flag", "🚩"),
+    ("gear", "⚙️"),
+    ("gift", "🎁"),
+    ("globe_with_meridians", "🌐"),
+    ("grin", "😁"),
+    ("hammer", "🔨"),
+    ("heart", "❤️"),
+    ("heavy_check_mark", "✔️"),
+    ("hourglass", "⌛"),
+    ("house", "🏠"),
+    ("inbox_tray", "📥"),
+    ("information_source", "ℹ️"),
+    ("joy", "😂"),
+    ("key", "🔑"),
+    ("laughing", "😆"),
+    ("link", "🔗"),
+    ("lock", "🔒"),
+    ("mag", "🔍"),
+    ("memo", "📝"),
+    ("money_with_wings", "💸"),
+    ("moon", "🌙"),
+    ("muscle", "💪"),
+    ("no_entry", "⛔"),
+    ("ok_hand", "👌"),
+    ("package", "📦"),
+    ("paperclip", "📎"),
+    ("pencil2", "✏️"),
+    ("phone", "☎️"),
+    ("pray", "🙏"),
+    ("pushpin", "📌"),
+    ("question", "❓"),
+    ("raised_hands", "🙌"),
+    ("recycle", "♻️"),
+    ("rocket", "🚀"),
+    ("rotating_light", "🚨"),
+    ("scroll", "📜"),
+    ("see_no_evil", "🙈"),
+    ("shopping_cart", "🛒"),
+    ("smile", "😄"),
+    ("smiley", "😃"),
+    ("sob", "😭"),
+    ("sparkles", "✨"),
+    ("star", "⭐"),
+    ("sunny", "☀️"),
+    ("tada", "🎉"),
+    ("thinking", "🤔"),
+    ("thumbsdown", "👎"),
+    ("thumbsup", "👍"),
+    ("trophy", "🏆"),
+    ("unlock", "🔓"),
+    ("warning", "⚠️"),
+    ("wave", "👋"),
+    ("white_check_mark", "✅"),
+    ("wink", "😉"),
+    ("wrench", "🔧"),
+    ("x", "❌"),
+    ("zap", "⚡"),
+];
+
+/// What the text before the cursor is being completed to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    /// Title of a note, after `[[`
+    Note,
+    /// Tag used in a note, after `#`
+    Tag,
+    /// Emoji shortcode, after `:`
+    Emoji,
+    /// Language of a fenced code block
+    Language,
+    /// Correction of a misspelled word, asked for rather than typed
+    Spelling,
+}
+
+impl CompletionKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            CompletionKind::Note => "Notes",
+            CompletionKind::Tag => "Tags",
+            CompletionKind::Emoji => "Emoji",
+            CompletionKind::Language => "Languages",
+            CompletionKind::Spelling => "Spelling",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompletionItem {
+    /// Text shown in the popup
+    pub label: String,
+    /// Text replacing the typed part
+    pub insert: String,
+}
+
+/// Suggestions for the text typed before the cursor
+#[derive(Debug, Clone)]
+pub struct Completion {
+    pub kind: CompletionKind,
+    /// Where the text replaced by a suggestion starts
+    pub start: Index2,
+    pub query: String,
+    pub items: Vec<CompletionItem>,
+    pub selected: usize,
+    /// Closed with `Esc`, until the cursor leaves the text being completed
+    pub dismissed: bool,
+}
+
+impl Completion {
+    /// Suggestions for the text before the cursor, keeping the selection and
+    /// dismissal of the previous ones for the same text
+    pub fn update(
+        previous: Option<&Completion>,
+        editor: &EditorState,
+        notes: &[Note],
+        current_title: Option<&str>,
+    ) -> Option<Completion> {
+        let (kind, start, query) = trigger(editor)?;
+        let items = candidates(kind, &query, notes, current_title);
+        if items.is_empty() {
+            return None;
+        }
+        let same = previous.filter(|previous| previous.kind == kind && previous.start == start);
+        let selected = same
+            .and_then(|previous| previous.items.get(previous.selected))
+            .and_then(|item| items.iter().position(|i| i == item))
+            .unwrap_or(0);
+        Some(Completion {
+            kind,
+            start,
+            query,
+            items,
+            selected,
+            dismissed: same.is_some_and(|previous| previous.dismissed),
+        })
+    }
+
+    /// Corrections of the misspelled word ending at the cursor, and a last
+    /// item adding the word to the personal dictionary
+    pub fn spelling(start: Index2, word: &str, suggestions: Vec<String>) -> Completion {
+        let mut items: Vec<CompletionItem> = suggestions
+            .into_iter()
+            .map(|suggestion| CompletionItem {
+                label: suggestion.clone(),
+                insert: suggestion,
+            })
+            .collect();
+        items.push(CompletionItem {
+            label: format!("Add \"{}\" to dictionary", word),
+            insert: word.to_string(),
+        });
+        Completion {
+            kind: CompletionKind::Spelling,
+            start,
+            query: word.to_string(),
+            items,
+            selected: 0,
+            dismissed: false,
+        }
+    }
+
+    /// Whether the selected item adds the word to the personal dictionary
+    pub fn adds_word(&self) -> bool {
+        self.kind == CompletionKind::Spelling && self.selected + 1 == self.items.len()
+    }
+
+    pub fn select_next(&mut self, up: bool) {
+        let count = self.items.len();
+        self.selected = if up {
+            (self.selected + count - 1) % count
+        } else {
+            (self.selected + 1) % count
+        };
+    }
+
+    /// Replace the typed text with the selected suggestion
+    pub fn accept(&self, editor: &mut EditorState) {
+        let Some(item) = self.items.get(self.selected) else {
+            return;
+        };
+        let cursor = editor.cursor;
+        let Some(line) = editor.lines.get(RowIndex::new(cursor.row)).cloned() else {
+            return;
+        };
+        let end = cursor.col.min(line.len());
+        let rest = &line[end..];
+        // A link is closed after the title, over any brackets already closing it
+        let closed = self.kind == CompletionKind::Note && rest.starts_with(&[']', ']']);
+        let mut updated = line[..self.start.col].to_vec();
+        updated.extend(item.insert.chars());
+        if self.kind == CompletionKind::Note {
+            updated.extend([']', ']']);
+        }
+        let col = updated.len();
+        updated.extend(&rest[if closed { 2 } else { 0 }..]);
+
+        capture_undo(editor);
+        if let Some(row) = editor.lines.get_mut(RowIndex::new(cursor.row)) {
+            *row = updated;
+        }
+        editor.cursor = Index2::new(cursor.row, col);
+    }
+}
+
+/// Kind of completion, start of the replaced text and query for the text
+/// before the cursor
+fn trigger(editor: &EditorState) -> Option<(CompletionKind, Index2, String)> {
+    let cursor = editor.cursor;
+    let line = editor.lines.get(RowIndex::new(cursor.row))?;
+    let before = &line[..cursor.col.min(line.len())];
+
+    // Language after the fence opening a code block
+    let indent = before.iter().take_while(|&&c| c == ' ').count();
+    let fence = &before[indent..];
+    if fence.starts_with(&['`', '`', '`']) || fence.starts_with(&['~', '~', '~']) {
+        let marks = fence.iter().take_while(|&&c| c == fence[0]).count();
+        let query = &fence[marks..];
+        if !query.is_empty() && !query.iter().any(|c| c.is_whitespace() || *c == '`') {
+            let start = Index2::new(cursor.row, indent + marks);
+            return Some((CompletionKind::Language, start, query.iter().collect()));
+        }
+        return None;
+    }
+
+    // Note title inside an unclosed `[[`
+    let opening = before
+        .windows(2)
+        .rposition(|pair| pair == ['[', '['])
+        .filter(|&i| !before[i..].windows(2).any(|pair| pair == [']', ']']));
+    if let Some(i) = opening {
+        let query = &before[i + 2..];
+        if !query.contains(&'|') && !query.contains(&'#') {
+            let start = Index2::new(cursor.row, i + 2);
+            return Some((CompletionKind::Note, start, query.iter().collect()));
+        }
+    }
+
+    // Tag or emoji shortcode starting a word
+    let word = before
+        .iter()
+        .rev()
+        .take_while(|&&c| c.is_alphanumeric() || matches!(c, '_' | '-' | '/' | '+'))
+        .count();
+    let marker_col = before.len().checked_sub(word + 1)?;
+    let marker = before[marker_col];
+    if word == 0 || marker_col > 0 && !before[marker_col - 1].is_whitespace() {
+        return None;
+    }
+    let query: String = before[marker_col + 1..].iter().collect();
+    match marker {
+        '#' if query.chars().any(|c| !c.is_ascii_digit()) => Some((
+            CompletionKind::Tag,
+            Index2::new(cursor.row, marker_col + 1),
+            query,
+        )),
+        ':' => Some((
+            CompletionKind::Emoji,
+            Index2::new(cursor.row, marker_col),
+            query,
+        )),
+        _ => None,
+    }
+}
+
+/// Suggestions matching the query, the ones starting with it first
+fn candidates(
+    kind: CompletionKind,
+    query: &str,
+    notes: &[Note],
+    current_title: Option<&str>,
+) -> Vec<CompletionItem> {
+    let items: Vec<CompletionItem> = match kind {
+        CompletionKind::Note => notes
+            .iter()
+            .filter(|note| Some(note.title.as_str()) != current_title)
+            .map(|note| CompletionItem {
+                label: note.title.clone(),
+                insert: note.title.clone(),
+            })
+            .collect(),
+        CompletionKind::Tag => {
+            let mut tags: Vec<String> = notes
+                .iter()
+                .flat_map(|note| note.tags.iter().cloned().chain(inline_tags(&note.content)))
+                .collect();
+            tags.sort_unstable();
+            tags.dedup();
+            tags.into_iter()
+                .map(|tag| CompletionItem {
+                    label: format!("#{}", tag),
+                    insert: tag,
+                })
+                .collect()
+        }
+        CompletionKind::Emoji => EMOJI
+            .iter()
+            .map(|(code, emoji)| CompletionItem {
+                label: format!("{} :{}:", emoji, code),
+                insert: emoji.to_string(),
+            })
+            .collect(),
+        CompletionKind::Spelling => Vec::new(),
+        CompletionKind::Language => ui::languages()
+            .into_iter()
+            .map(|language| CompletionItem {
+                label: language.clone(),
+                insert: language,
+            })
+            .collect(),
+    };
+
+    let query = query.to_lowercase();
+    let key = |item: &CompletionItem| match kind {
+        CompletionKind::Emoji => item
+            .label
+            .split(':')
+            .nth(1)
+            .unwrap_or_default()
+            .to_lowercase(),
+        _ => item.insert.to_lowercase(),
+    };
+    let (mut prefixed, mut contained): (Vec<_>, Vec<_>) = items
+        .into_iter()
+        .filter(|item| key(item).contains(&query))
+        .partition(|item| key(item).starts_with(&query));
+    // Nothing to complete once the whole text is typed
+    if kind != CompletionKind::Emoji
+        && prefixed.len() == 1
+        && contained.is_empty()
+        && key(&prefixed[0]) == query
+    {
+        return Vec::new();
+    }
+    prefixed.append(&mut contained);
+    prefixed.truncate(MAX_ITEMS);
+    prefixed
+}
@@ -0,0 +1,111 @@
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::Text,
+};
+
+use crate::export::error::ExportResult;
+use crate::export::Exporter;
+use crate::models::note::Note;
+use crate::ui::from_str;
+
+/// Exports a note as ANSI-colored terminal text, as shown in the preview
+pub struct AnsiExporter {
+    width: u16,
+}
+
+impl AnsiExporter {
+    pub fn new(width: u16) -> Self {
+        Self { width }
+    }
+}
+
+impl Exporter for AnsiExporter {
+    fn name(&self) -> &'static str {
+        "ansi"
+    }
+
+    fn extension(&self) -> &'static str {
+        "ans"
+    }
+
+    fn export(&self, note: &Note) -> ExportResult<String> {
+        // The preview reserves two columns for its borders
+        let text = from_str(&note.content, self.width.saturating_add(2));
+        Ok(text_to_ansi(&text))
+    }
+}
+
+/// Serialize rendered text to a string with ANSI escape codes
+pub fn text_to_ansi(text: &Text) -> String {
+    let mut output = String::new();
+    for line in &text.lines {
+        for span in &line.spans {
+            let style = text.style.patch(line.style).patch(span.style);
+            let codes = sgr_codes(style);
+            if codes.is_empty() {
+                output.push_str(&span.content);
+            } else {
+                output.push_str(&format!("\x1b[{}m{}\x1b[0m", codes.join(";"), span.content));
+            }
+        }
+        output.push('\n');
+    }
+    output
+}
+
+fn sgr_codes(style: Style) -> Vec<String> {
+    let mut codes = Vec::new();
+    let modifiers = [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::SLOW_BLINK, "5"),
+        (Modifier::RAPID_BLINK, "6"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::HIDDEN, "8"),
+        (Modifier::CROSSED_OUT, "9"),
+    ];
+    for (modifier, code) in modifiers {
+        if style.add_modifier.contains(modifier) {
+            codes.push(code.to_string());
+        }
+    }
+    if let Some(fg) = style.fg.and_then(|color| color_code(color, false)) {
+        codes.push(fg);
+    }
+    if let Some(bg) = style.bg.and_then(|color| color_code(color, true)) {
+        codes.push(bg);
+    }
+    codes
+}
+
+fn color_code(color: Color, background: bool) -> Option<String> {
+    let (base, bright_base, extended) = if background {
+        (40, 100, 48)
+    } else {
+        (30, 90, 38)
+    };
+    let code = match color {
+        Color::Reset => return None,
+        Color::Black => base.to_string(),
+        Color::Red => (base + 1).to_string(),
+        Color::Green => (base + 2).to_string(),
+        Color::Yellow => (base + 3).to_string(),
+        Color::Blue => (base + 4).to_string(),
+        Color::Magenta => (base + 5).to_string(),
+        Color::Cyan => (base + 6).to_string(),
+        Color::Gray => (base + 7).to_string(),
+        Color::DarkGray => bright_base.to_string(),
+        Color::LightRed => (bright_base + 1).to_string(),
+        Color::LightGreen => (bright_base + 2).to_string(),
+        Color::LightYellow => (bright_base + 3).to_string(),
+        Color::LightBlue => (bright_base + 4).to_string(),
+        Color::LightMagenta => (bright_base + 5).to_string(),
+        Color::LightCyan => (bright_base + 6).to_string(),
+        Color::White => (bright_base + 7).to_string(),
+        Color::Indexed(index) => format!("{};5;{}", extended, index),
+        Color::Rgb(r, g, b) => format!("{};2;{};{};{}", extended, r, g, b),
+    };
+    Some(code)
+}
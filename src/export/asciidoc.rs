@@ -0,0 +1,227 @@
+use pulldown_cmark::{Alignment, CodeBlockKind, Event, LinkType, Parser, Tag, TagEnd};
+
+use crate::export::error::ExportResult;
+use crate::export::markup::MarkupBuffer;
+use crate::export::{parser_options, Exporter};
+use crate::models::note::Note;
+
+/// Exports a note as AsciiDoc
+pub struct AsciiDocExporter;
+
+impl Exporter for AsciiDocExporter {
+    fn name(&self) -> &'static str {
+        "asciidoc"
+    }
+
+    fn extension(&self) -> &'static str {
+        "adoc"
+    }
+
+    fn export(&self, note: &Note) -> ExportResult<String> {
+        let parser = Parser::new_ext(&note.content, parser_options());
+        let mut writer = AsciiDocWriter::default();
+        for event in parser {
+            writer.handle_event(event);
+        }
+        Ok(writer.buffer.finish())
+    }
+}
+
+#[derive(Default)]
+struct AsciiDocWriter {
+    buffer: MarkupBuffer,
+
+    /// Whether each open list is ordered
+    lists: Vec<bool>,
+
+    /// Whether a non-autolink link is being written
+    in_link: bool,
+
+    in_code_block: bool,
+
+    /// Cells of the table row being written, the last one still open
+    row: Option<Vec<String>>,
+}
+
+impl AsciiDocWriter {
+    fn handle_event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start_tag(tag),
+            Event::End(tag) => self.end_tag(tag),
+            Event::Text(text) => self.text(&text),
+            Event::Code(code) => self.push_str(&format!("`+{}+`", code)),
+            Event::Html(html) => self.buffer.push_lines(&html),
+            Event::InlineHtml(html) => self.push_str(&html),
+            Event::SoftBreak => self.push_str(" "),
+            Event::HardBreak => {
+                self.buffer.push_str(" +");
+                self.buffer.newline();
+            }
+            Event::Rule => {
+                self.buffer.blank_line();
+                self.buffer.push_str("'''");
+                self.buffer.blank_line();
+            }
+            Event::TaskListMarker(checked) => self.push_str(if checked { "[x] " } else { "[ ] " }),
+            Event::FootnoteReference(name) => self.push_str(&format!("footnote:{}[]", name)),
+            Event::InlineMath(math) => self.push_str(&format!("stem:[{}]", math)),
+            Event::DisplayMath(math) => {
+                self.buffer.blank_line();
+                self.buffer.push_str("[stem]");
+                self.buffer.newline();
+                self.buffer.push_str("++++");
+                self.buffer.newline();
+                self.buffer.push_lines(&math);
+                self.buffer.push_str("++++");
+                self.buffer.blank_line();
+            }
+        }
+    }
+
+    fn start_tag(&mut self, tag: Tag) {
+        match tag {
+            // A paragraph directly after a list marker continues the marker line
+            Tag::Paragraph if !self.buffer.is_line_open() => self.buffer.blank_line(),
+            Tag::Heading { level, .. } => {
+                self.buffer.blank_line();
+                self.buffer
+                    .push_str(&format!("{} ", "=".repeat(level as usize + 1)));
+            }
+            Tag::BlockQuote(_) => {
+                self.buffer.blank_line();
+                self.buffer.push_str("____");
+                self.buffer.newline();
+            }
+            Tag::CodeBlock(kind) => {
+                self.buffer.blank_line();
+                match kind {
+                    CodeBlockKind::Fenced(lang) if !lang.is_empty() => {
+                        self.buffer.push_str(&format!("[source,{}]", lang));
+                        self.buffer.newline();
+                    }
+                    _ => {}
+                }
+                self.buffer.push_str("----");
+                self.buffer.newline();
+                self.in_code_block = true;
+            }
+            Tag::List(start) => {
+                if self.lists.is_empty() {
+                    self.buffer.blank_line();
+                } else {
+                    self.buffer.end_line();
+                }
+                self.lists.push(start.is_some());
+            }
+            Tag::Item => {
+                self.buffer.end_line();
+                let ordered = self.lists.last().copied().unwrap_or(false);
+                let marker = if ordered { "." } else { "*" };
+                self.buffer
+                    .push_str(&format!("{} ", marker.repeat(self.lists.len())));
+            }
+            Tag::Emphasis => self.push_str("_"),
+            Tag::Strong => self.push_str("*"),
+            Tag::Strikethrough => self.push_str("[line-through]#"),
+            Tag::Link {
+                link_type,
+                dest_url,
+                ..
+            } if link_type != LinkType::Autolink => {
+                self.push_str(&format!("link:{}[", dest_url));
+                self.in_link = true;
+            }
+            Tag::Image { dest_url, .. } => {
+                self.push_str(&format!("image:{}[", dest_url));
+            }
+            Tag::Table(alignments) => {
+                let columns = alignments
+                    .iter()
+                    .map(|alignment| match alignment {
+                        Alignment::None | Alignment::Left => "1",
+                        Alignment::Center => "^1",
+                        Alignment::Right => ">1",
+                    })
+                    .collect::<Vec<_>>();
+                self.buffer.blank_line();
+                self.buffer.push_str(&format!(
+                    "[cols=\"{}\",options=\"header\"]",
+                    columns.join(",")
+                ));
+                self.buffer.newline();
+                self.buffer.push_str("|===");
+                self.buffer.newline();
+            }
+            Tag::TableHead | Tag::TableRow => self.row = Some(Vec::new()),
+            Tag::TableCell => {
+                if let Some(row) = &mut self.row {
+                    row.push(String::new());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn end_tag(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph => self.buffer.end_line(),
+            TagEnd::Heading(_) => self.buffer.blank_line(),
+            TagEnd::BlockQuote(_) => {
+                self.buffer.end_line();
+                self.buffer.push_str("____");
+                self.buffer.blank_line();
+            }
+            TagEnd::CodeBlock => {
+                self.in_code_block = false;
+                self.buffer.push_str("----");
+                self.buffer.blank_line();
+            }
+            TagEnd::List(_) => {
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.buffer.blank_line();
+                }
+            }
+            TagEnd::Item => self.buffer.end_line(),
+            TagEnd::Emphasis => self.push_str("_"),
+            TagEnd::Strong => self.push_str("*"),
+            TagEnd::Strikethrough => self.push_str("#"),
+            TagEnd::Link if self.in_link => {
+                self.in_link = false;
+                self.push_str("]");
+            }
+            TagEnd::Image => self.push_str("]"),
+            TagEnd::Table => {
+                self.buffer.push_str("|===");
+                self.buffer.blank_line();
+            }
+            TagEnd::TableHead | TagEnd::TableRow => {
+                if let Some(row) = self.row.take() {
+                    let cells = row
+                        .iter()
+                        .map(|cell| format!("|{}", cell.trim().replace('|', "\\|")))
+                        .collect::<Vec<_>>();
+                    self.buffer.push_str(&cells.join(" "));
+                    self.buffer.newline();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Write inline text, into the open table cell if there is one
+    fn push_str(&mut self, text: &str) {
+        match self.row.as_mut().and_then(|row| row.last_mut()) {
+            Some(cell) => cell.push_str(text),
+            None => self.buffer.push_str(text),
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.in_code_block {
+            self.buffer.push_lines(text);
+        } else {
+            self.push_str(text);
+        }
+    }
+}
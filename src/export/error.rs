@@ -0,0 +1,12 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ExportError {
+    #[error("Unknown export format: {0}")]
+    UnknownFormat(String),
+
+    #[error("Failed to serialize note: {0}")]
+    Serialize(String),
+}
+
+pub type ExportResult<T> = Result<T, ExportError>;
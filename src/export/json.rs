@@ -0,0 +1,177 @@
+use pulldown_cmark::{CodeBlockKind, Event, Parser, Tag};
+use serde_json::{json, Map, Value};
+
+use crate::export::error::{ExportError, ExportResult};
+use crate::export::{parser_options, Exporter};
+use crate::models::note::Note;
+
+/// Exports a note as a JSON dump of its Markdown syntax tree
+pub struct JsonExporter;
+
+impl Exporter for JsonExporter {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn extension(&self) -> &'static str {
+        "json"
+    }
+
+    fn export(&self, note: &Note) -> ExportResult<String> {
+        let tree = json!({
+            "title": note.title,
+            "created_at": note.created_at,
+            "updated_at": note.updated_at,
+            "ast": to_ast(&note.content),
+        });
+        serde_json::to_string_pretty(&tree).map_err(|e| ExportError::Serialize(e.to_string()))
+    }
+}
+
+/// Build a JSON syntax tree for Markdown content
+pub fn to_ast(content: &str) -> Value {
+    // Stack of open nodes, the document node at the bottom
+    let mut stack = vec![node("document")];
+
+    for event in Parser::new_ext(content, parser_options()) {
+        match event {
+            Event::Start(tag) => stack.push(tag_node(tag)),
+            Event::End(_) => {
+                if stack.len() > 1 {
+                    if let Some(finished) = stack.pop() {
+                        append_child(&mut stack, finished);
+                    }
+                }
+            }
+            Event::Text(text) => append_child(&mut stack, leaf("text", &text)),
+            Event::Code(code) => append_child(&mut stack, leaf("code", &code)),
+            Event::Html(html) => append_child(&mut stack, leaf("html", &html)),
+            Event::InlineHtml(html) => append_child(&mut stack, leaf("inline_html", &html)),
+            Event::InlineMath(math) => append_child(&mut stack, leaf("inline_math", &math)),
+            Event::DisplayMath(math) => append_child(&mut stack, leaf("display_math", &math)),
+            Event::FootnoteReference(name) => {
+                append_child(&mut stack, leaf("footnote_reference", &name))
+            }
+            Event::SoftBreak => append_child(&mut stack, node("soft_break")),
+            Event::HardBreak => append_child(&mut stack, node("hard_break")),
+            Event::Rule => append_child(&mut stack, node("rule")),
+            Event::TaskListMarker(checked) => {
+                let mut marker = node("task_list_marker");
+                marker.insert("checked".to_string(), Value::Bool(checked));
+                append_child(&mut stack, marker);
+            }
+        }
+    }
+
+    // Close anything left open by malformed input
+    while stack.len() > 1 {
+        if let Some(finished) = stack.pop() {
+            append_child(&mut stack, finished);
+        }
+    }
+    Value::Object(stack.pop().unwrap_or_default())
+}
+
+fn node(kind: &str) -> Map<String, Value> {
+    let mut map = Map::new();
+    map.insert("type".to_string(), Value::String(kind.to_string()));
+    map
+}
+
+fn leaf(kind: &str, value: &str) -> Map<String, Value> {
+    let mut map = node(kind);
+    map.insert("value".to_string(), Value::String(value.to_string()));
+    map
+}
+
+fn append_child(stack: &mut [Map<String, Value>], child: Map<String, Value>) {
+    if let Some(parent) = stack.last_mut() {
+        let children = parent
+            .entry("children")
+            .or_insert_with(|| Value::Array(Vec::new()));
+        if let Value::Array(children) = children {
+            children.push(Value::Object(child));
+        }
+    }
+}
+
+fn tag_node(tag: Tag) -> Map<String, Value> {
+    match tag {
+        Tag::Paragraph => node("paragraph"),
+        Tag::Heading { level, id, .. } => {
+            let mut map = node("heading");
+            map.insert("level".to_string(), json!(level as usize));
+            if let Some(id) = id {
+                map.insert("id".to_string(), json!(id.as_ref()));
+            }
+            map
+        }
+        Tag::BlockQuote(kind) => {
+            let mut map = node("block_quote");
+            if let Some(kind) = kind {
+                map.insert(
+                    "kind".to_string(),
+                    json!(format!("{:?}", kind).to_lowercase()),
+                );
+            }
+            map
+        }
+        Tag::CodeBlock(kind) => {
+            let mut map = node("code_block");
+            if let CodeBlockKind::Fenced(lang) = kind {
+                if !lang.is_empty() {
+                    map.insert("language".to_string(), json!(lang.as_ref()));
+                }
+            }
+            map
+        }
+        Tag::List(start) => {
+            let mut map = node("list");
+            map.insert("ordered".to_string(), json!(start.is_some()));
+            if let Some(start) = start {
+                map.insert("start".to_string(), json!(start));
+            }
+            map
+        }
+        Tag::Item => node("item"),
+        Tag::Emphasis => node("emphasis"),
+        Tag::Strong => node("strong"),
+        Tag::Strikethrough => node("strikethrough"),
+        Tag::Link {
+            dest_url, title, ..
+        } => {
+            let mut map = node("link");
+            map.insert("url".to_string(), json!(dest_url.as_ref()));
+            if !title.is_empty() {
+                map.insert("title".to_string(), json!(title.as_ref()));
+            }
+            map
+        }
+        Tag::Image {
+            dest_url, title, ..
+        } => {
+            let mut map = node("image");
+            map.insert("url".to_string(), json!(dest_url.as_ref()));
+            if !title.is_empty() {
+                map.insert("title".to_string(), json!(title.as_ref()));
+            }
+            map
+        }
+        Tag::HtmlBlock => node("html_block"),
+        Tag::FootnoteDefinition(name) => {
+            let mut map = node("footnote_definition");
+            map.insert("name".to_string(), json!(name.as_ref()));
+            map
+        }
+        Tag::Table(_) => node("table"),
+        Tag::TableHead => node("table_head"),
+        Tag::TableRow => node("table_row"),
+        Tag::TableCell => node("table_cell"),
+        Tag::MetadataBlock(_) => node("metadata_block"),
+        Tag::DefinitionList => node("definition_list"),
+        Tag::DefinitionListTitle => node("definition_list_title"),
+        Tag::DefinitionListDefinition => node("definition_list_definition"),
+        Tag::Superscript => node("superscript"),
+        Tag::Subscript => node("subscript"),
+    }
+}
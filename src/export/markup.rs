@@ -0,0 +1,117 @@
+/// Line buffer shared by the line-oriented markup exporters.
+///
+/// Keeps track of the indentation stack so nested blocks (list items,
+/// block quotes, code blocks) only need to push and pop a prefix.
+#[derive(Default)]
+pub(crate) struct MarkupBuffer {
+    lines: Vec<String>,
+    current: Option<String>,
+    indents: Vec<String>,
+}
+
+impl MarkupBuffer {
+    /// Append text to the current line, opening it with the indentation if needed
+    pub fn push_str(&mut self, text: &str) {
+        let indent = self.indents.concat();
+        self.current.get_or_insert(indent).push_str(text);
+    }
+
+    /// Whether text has been written to the current line
+    pub fn is_line_open(&self) -> bool {
+        self.current.is_some()
+    }
+
+    /// Close the current line
+    pub fn newline(&mut self) {
+        let line = self.current.take().unwrap_or_else(|| self.indents.concat());
+        self.lines.push(line.trim_end().to_string());
+    }
+
+    /// Close the current line if one is open
+    pub fn end_line(&mut self) {
+        if self.current.is_some() {
+            self.newline();
+        }
+    }
+
+    /// Make sure the next block starts after an empty line
+    pub fn blank_line(&mut self) {
+        self.end_line();
+        if self
+            .lines
+            .last()
+            .is_some_and(|line| !line.trim().is_empty())
+        {
+            self.lines.push(String::new());
+        }
+    }
+
+    /// Write several lines of text, one output line per input line
+    pub fn push_lines(&mut self, text: &str) {
+        for line in text.lines() {
+            self.push_str(line);
+            self.newline();
+        }
+    }
+
+    pub fn push_indent(&mut self, indent: &str) {
+        self.indents.push(indent.to_string());
+    }
+
+    pub fn pop_indent(&mut self) {
+        self.indents.pop();
+    }
+
+    /// Finish writing and return the output
+    pub fn finish(mut self) -> String {
+        self.end_line();
+        while self.lines.last().is_some_and(|line| line.is_empty()) {
+            self.lines.pop();
+        }
+        let mut output = self.lines.join("\n");
+        output.push('\n');
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indentation_opens_each_line() {
+        let mut buffer = MarkupBuffer::default();
+        buffer.push_str("- ");
+        buffer.push_indent("  ");
+        buffer.push_str("item");
+        buffer.newline();
+        buffer.push_lines("first\nsecond");
+        buffer.pop_indent();
+        buffer.push_str("after");
+        assert_eq!(buffer.finish(), "- item\n  first\n  second\nafter\n");
+    }
+
+    #[test]
+    fn blank_lines_are_not_doubled() {
+        let mut buffer = MarkupBuffer::default();
+        buffer.blank_line();
+        buffer.push_str("one");
+        buffer.blank_line();
+        buffer.blank_line();
+        buffer.push_str("two");
+        buffer.blank_line();
+        assert_eq!(buffer.finish(), "one\n\ntwo\n");
+    }
+
+    #[test]
+    fn indented_empty_lines_are_trimmed() {
+        let mut buffer = MarkupBuffer::default();
+        buffer.push_indent("    ");
+        buffer.push_str("quoted ");
+        buffer.newline();
+        buffer.newline();
+        buffer.push_str("more");
+        assert!(buffer.is_line_open());
+        assert_eq!(buffer.finish(), "    quoted\n\n    more\n");
+    }
+}
@@ -0,0 +1,59 @@
+pub mod ansi;
+pub mod asciidoc;
+pub mod error;
+pub mod json;
+mod markup;
+pub mod plain;
+pub mod rst;
+
+use crate::models::note::Note;
+use error::{ExportError, ExportResult};
+
+pub use ansi::AnsiExporter;
+pub use asciidoc::AsciiDocExporter;
+pub use json::JsonExporter;
+pub use plain::PlainExporter;
+pub use rst::RstExporter;
+
+/// Default line width used when an exporter needs to wrap or draw rules
+pub const DEFAULT_WIDTH: u16 = 80;
+
+/// Exporter trait defines the interface for turning a note into another format
+pub trait Exporter {
+    /// Short name used to select the exporter (e.g. `ansi`, `rst`)
+    fn name(&self) -> &'static str;
+
+    /// File extension used when the export is written to disk
+    fn extension(&self) -> &'static str;
+
+    /// Export a note to a string in the target format
+    fn export(&self, note: &Note) -> ExportResult<String>;
+}
+
+/// All available exporters, rendering at the given width where it applies
+pub fn exporters(width: u16) -> Vec<Box<dyn Exporter>> {
+    vec![
+        Box::new(AnsiExporter::new(width)),
+        Box::new(PlainExporter::new(width)),
+        Box::new(RstExporter),
+        Box::new(AsciiDocExporter),
+        Box::new(JsonExporter),
+    ]
+}
+
+/// Find an exporter by its name
+pub fn find_exporter(name: &str, width: u16) -> ExportResult<Box<dyn Exporter>> {
+    exporters(width)
+        .into_iter()
+        .find(|exporter| exporter.name() == name)
+        .ok_or_else(|| ExportError::UnknownFormat(name.to_string()))
+}
+
+/// Markdown parser options used by the markup exporters
+pub(crate) fn parser_options() -> pulldown_cmark::Options {
+    let mut options = pulldown_cmark::Options::empty();
+    options.insert(pulldown_cmark::Options::ENABLE_STRIKETHROUGH);
+    options.insert(pulldown_cmark::Options::ENABLE_TASKLISTS);
+    options.insert(pulldown_cmark::Options::ENABLE_TABLES);
+    options
+}
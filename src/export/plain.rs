@@ -0,0 +1,77 @@
+use crate::export::error::ExportResult;
+use crate::export::Exporter;
+use crate::models::note::Note;
+use crate::ui::from_str;
+
+/// Exports a note as plain text wrapped to a fixed width
+pub struct PlainExporter {
+    width: u16,
+}
+
+impl PlainExporter {
+    pub fn new(width: u16) -> Self {
+        Self { width }
+    }
+}
+
+impl Exporter for PlainExporter {
+    fn name(&self) -> &'static str {
+        "plain"
+    }
+
+    fn extension(&self) -> &'static str {
+        "txt"
+    }
+
+    fn export(&self, note: &Note) -> ExportResult<String> {
+        // The preview reserves two columns for its borders
+        let text = from_str(&note.content, self.width.saturating_add(2));
+
+        let mut output = String::new();
+        for line in &text.lines {
+            let line: String = line
+                .spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect();
+            for wrapped in wrap_line(line.trim_end(), self.width as usize) {
+                output.push_str(&wrapped);
+                output.push('\n');
+            }
+        }
+        Ok(output)
+    }
+}
+
+/// Greedily wrap a line at whitespace so no row exceeds `width` characters
+pub fn wrap_line(line: &str, width: usize) -> Vec<String> {
+    if width == 0 || line.chars().count() <= width {
+        return vec![line.to_string()];
+    }
+
+    let mut rows = Vec::new();
+    let mut current = String::new();
+    for (i, word) in line.split(' ').enumerate() {
+        if i > 0 {
+            let needed = current.chars().count() + 1 + word.chars().count();
+            if needed > width && !current.trim().is_empty() {
+                rows.push(std::mem::take(&mut current));
+            } else {
+                current.push(' ');
+            }
+        }
+        current.push_str(word);
+
+        // Hard-split words that are longer than a whole row
+        while current.chars().count() > width {
+            let head: String = current.chars().take(width).collect();
+            let tail: String = current.chars().skip(width).collect();
+            rows.push(head);
+            current = tail;
+        }
+    }
+    if !current.is_empty() || rows.is_empty() {
+        rows.push(current);
+    }
+    rows
+}
@@ -0,0 +1,241 @@
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, HeadingLevel, LinkType, Parser, Tag, TagEnd};
+
+use ratatui::text::Span;
+
+use crate::export::error::ExportResult;
+use crate::export::markup::MarkupBuffer;
+use crate::export::{parser_options, Exporter};
+use crate::models::note::Note;
+
+/// Exports a note as reStructuredText
+pub struct RstExporter;
+
+impl Exporter for RstExporter {
+    fn name(&self) -> &'static str {
+        "rst"
+    }
+
+    fn extension(&self) -> &'static str {
+        "rst"
+    }
+
+    fn export(&self, note: &Note) -> ExportResult<String> {
+        let parser = Parser::new_ext(&note.content, parser_options());
+        let mut writer = RstWriter::default();
+        for event in parser {
+            writer.handle_event(event);
+        }
+        Ok(writer.buffer.finish())
+    }
+}
+
+#[derive(Default)]
+struct RstWriter<'a> {
+    buffer: MarkupBuffer,
+
+    /// Text of the heading being written, underlined when the heading ends
+    heading: Option<String>,
+
+    /// Current list index as a stack of indices
+    list_indices: Vec<Option<u64>>,
+
+    /// Destination of the link being written
+    link: Option<CowStr<'a>>,
+
+    /// Text of the table cell being written and whether it starts its row
+    cell: Option<(String, bool)>,
+    row_started: bool,
+
+    in_code_block: bool,
+}
+
+impl<'a> RstWriter<'a> {
+    fn handle_event(&mut self, event: Event<'a>) {
+        match event {
+            Event::Start(tag) => self.start_tag(tag),
+            Event::End(tag) => self.end_tag(tag),
+            Event::Text(text) => self.text(&text),
+            Event::Code(code) => self.inline(&format!("``{}``", code)),
+            Event::Html(html) => self.buffer.push_lines(&html),
+            Event::InlineHtml(html) => self.inline(&html),
+            Event::SoftBreak => self.inline(" "),
+            Event::HardBreak => self.buffer.newline(),
+            Event::Rule => {
+                self.buffer.blank_line();
+                self.buffer.push_str("----");
+                self.buffer.blank_line();
+            }
+            Event::TaskListMarker(checked) => self.inline(if checked { "[x] " } else { "[ ] " }),
+            Event::FootnoteReference(name) => self.inline(&format!("[#{}]_", name)),
+            Event::InlineMath(math) => self.inline(&format!(":math:`{}`", math)),
+            Event::DisplayMath(math) => {
+                self.buffer.blank_line();
+                self.buffer.push_str(".. math::");
+                self.buffer.blank_line();
+                self.buffer.push_indent("   ");
+                self.buffer.push_lines(&math);
+                self.buffer.pop_indent();
+                self.buffer.blank_line();
+            }
+        }
+    }
+
+    fn start_tag(&mut self, tag: Tag<'a>) {
+        match tag {
+            Tag::Paragraph => self.start_paragraph(),
+            Tag::Heading { .. } => {
+                self.buffer.blank_line();
+                self.heading = Some(String::new());
+            }
+            Tag::BlockQuote(_) => {
+                self.buffer.blank_line();
+                self.buffer.push_indent("    ");
+            }
+            Tag::CodeBlock(kind) => {
+                self.buffer.blank_line();
+                match kind {
+                    CodeBlockKind::Fenced(lang) if !lang.is_empty() => {
+                        self.buffer.push_str(&format!(".. code-block:: {}", lang))
+                    }
+                    _ => self.buffer.push_str("::"),
+                }
+                self.buffer.blank_line();
+                self.buffer.push_indent("   ");
+                self.in_code_block = true;
+            }
+            // Nested lists need blank lines around them
+            Tag::List(start) => {
+                self.buffer.blank_line();
+                self.list_indices.push(start);
+            }
+            Tag::Item => {
+                self.buffer.end_line();
+                let marker = match self.list_indices.last_mut() {
+                    Some(Some(index)) => {
+                        *index += 1;
+                        format!("{}. ", *index - 1)
+                    }
+                    _ => "- ".to_string(),
+                };
+                self.buffer.push_str(&marker);
+                self.buffer.push_indent(&" ".repeat(marker.len()));
+            }
+            Tag::Emphasis => self.inline("*"),
+            Tag::Strong => self.inline("**"),
+            Tag::Link {
+                link_type,
+                dest_url,
+                ..
+            } if link_type != LinkType::Autolink => {
+                self.inline("`");
+                self.link = Some(dest_url);
+            }
+            Tag::Image { dest_url, .. } => {
+                self.buffer.blank_line();
+                self.buffer.push_str(&format!(".. image:: {}", dest_url));
+                self.buffer.blank_line();
+            }
+            // Written as a list table, which needs no column widths
+            Tag::Table(_) => {
+                self.buffer.blank_line();
+                self.buffer.push_str(".. list-table::");
+                self.buffer.newline();
+                self.buffer.push_str("   :header-rows: 1");
+                self.buffer.blank_line();
+                self.buffer.push_indent("   ");
+            }
+            Tag::TableHead | Tag::TableRow => self.row_started = false,
+            Tag::TableCell => {
+                self.cell = Some((String::new(), !self.row_started));
+                self.row_started = true;
+            }
+            _ => {}
+        }
+    }
+
+    fn end_tag(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph => self.buffer.end_line(),
+            TagEnd::Heading(level) => {
+                let heading = self.heading.take().unwrap_or_default();
+                let underline = match level {
+                    HeadingLevel::H1 => '=',
+                    HeadingLevel::H2 => '-',
+                    HeadingLevel::H3 => '~',
+                    HeadingLevel::H4 => '^',
+                    HeadingLevel::H5 => '"',
+                    HeadingLevel::H6 => '\'',
+                };
+                self.buffer.push_str(&heading);
+                self.buffer.newline();
+                // Underlines must cover the display width of the heading
+                let width = Span::raw(heading.as_str()).width().max(1);
+                self.buffer.push_str(&underline.to_string().repeat(width));
+                self.buffer.blank_line();
+            }
+            TagEnd::BlockQuote(_) => {
+                self.buffer.end_line();
+                self.buffer.pop_indent();
+                self.buffer.blank_line();
+            }
+            TagEnd::CodeBlock => {
+                self.in_code_block = false;
+                self.buffer.pop_indent();
+                self.buffer.blank_line();
+            }
+            TagEnd::List(_) => {
+                self.list_indices.pop();
+                self.buffer.blank_line();
+            }
+            TagEnd::Item => {
+                self.buffer.end_line();
+                self.buffer.pop_indent();
+            }
+            TagEnd::Emphasis => self.inline("*"),
+            TagEnd::Strong => self.inline("**"),
+            TagEnd::Link => {
+                if let Some(link) = self.link.take() {
+                    self.inline(&format!(" <{}>`_", link));
+                }
+            }
+            TagEnd::Table => {
+                self.buffer.pop_indent();
+                self.buffer.blank_line();
+            }
+            TagEnd::TableCell => {
+                if let Some((text, first)) = self.cell.take() {
+                    let marker = if first { "* - " } else { "  - " };
+                    self.buffer.push_str(&format!("{}{}", marker, text.trim()));
+                    self.buffer.newline();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn start_paragraph(&mut self) {
+        // A paragraph directly after a list marker continues the marker line
+        if !self.buffer.is_line_open() {
+            self.buffer.blank_line();
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.in_code_block {
+            self.buffer.push_lines(text);
+        } else {
+            self.inline(text);
+        }
+    }
+
+    fn inline(&mut self, text: &str) {
+        if let Some((cell, _)) = &mut self.cell {
+            cell.push_str(text);
+            return;
+        }
+        match &mut self.heading {
+            Some(heading) => heading.push_str(text),
+            None => self.buffer.push_str(text),
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use std::env;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use tempfile::TempDir;
+
+use crate::storage::fs::note_file_name;
+
+/// Editor to run, with its arguments, from `$VISUAL` or `$EDITOR`
+pub fn editor_command() -> Vec<String> {
+    let configured = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .map(|value| {
+            value
+                .split_whitespace()
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        })
+        .find(|command| !command.is_empty());
+    configured.unwrap_or_else(|| {
+        let fallback = if cfg!(target_os = "windows") {
+            "notepad"
+        } else {
+            "vi"
+        };
+        vec![fallback.to_string()]
+    })
+}
+
+/// Run the editor on a file and wait for it to exit
+pub fn edit_file(path: &Path) -> io::Result<()> {
+    let command = editor_command();
+    let status = Command::new(&command[0])
+        .args(&command[1..])
+        .arg(path)
+        .status()
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", command[0], e)))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "{} exited with {}",
+            command[0], status
+        )))
+    }
+}
+
+/// A copy of a note in a private directory, removed when dropped
+pub struct TempNote {
+    dir: TempDir,
+    path: PathBuf,
+}
+
+impl TempNote {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Remove the copy now, reporting a failure instead of ignoring it
+    pub fn close(self) -> io::Result<()> {
+        self.dir.close()
+    }
+}
+
+/// Write a note to a file only the user can read, for storage that has no
+/// plain file to edit. The file lives in a fresh directory with a random
+/// name, so no one else can create or redirect it first.
+pub fn write_temp_file(title: &str, content: &str) -> io::Result<TempNote> {
+    let mut builder = tempfile::Builder::new();
+    builder.prefix("thoth-");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        builder.permissions(std::fs::Permissions::from_mode(0o700));
+    }
+    let dir = builder.tempdir()?;
+    let path = dir.path().join(note_file_name(title));
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    // Dropping `dir` on an error removes whatever was written
+    options.open(&path)?.write_all(content.as_bytes())?;
+    Ok(TempNote { dir, path })
+}
@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Local, NaiveDateTime, Utc};
+use md5::{Digest, Md5};
+
+use crate::import::error::ImportResult;
+use crate::import::html::{attribute, html_to_markdown};
+use crate::import::markdown::walk;
+use crate::import::{attachment_link, Attachment, AttachmentSource, ImportedNote, Importer};
+use crate::models::note::Note;
+use crate::tokenizer::{tokenize, Token};
+
+/// Imports Evernote `.enex` exports, one notebook per file
+pub struct EnexImporter;
+
+#[derive(Debug, Default)]
+struct EnexResource {
+    data: String,
+    mime: String,
+    file_name: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct EnexNote {
+    title: String,
+    content: String,
+    created: String,
+    updated: String,
+    tags: Vec<String>,
+    resources: Vec<EnexResource>,
+}
+
+/// A decoded resource, referenced from ENML by the MD5 hash of its data
+struct Resource {
+    name: String,
+    is_image: bool,
+    data: Vec<u8>,
+    used: bool,
+}
+
+impl Importer for EnexImporter {
+    fn name(&self) -> &'static str {
+        "enex"
+    }
+
+    fn scan(&self, path: &Path) -> ImportResult<Vec<ImportedNote>> {
+        let files = if path.is_file() {
+            vec![path.to_path_buf()]
+        } else {
+            let mut files = Vec::new();
+            walk(path, &mut files)?;
+            files.retain(|file| file.extension().is_some_and(|ext| ext == "enex"));
+            files
+        };
+
+        let mut notes = Vec::new();
+        for file in files {
+            // Each export file holds a single notebook
+            let notebook = file
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            let xml = fs::read_to_string(&file)?;
+            for (index, note) in parse_enex(&xml).into_iter().enumerate() {
+                let source = format!("{}#{}", file.display(), index + 1);
+                notes.push(convert_note(note, &notebook, source));
+            }
+        }
+        Ok(notes)
+    }
+}
+
+/// Collect the notes of an ENEX document
+fn parse_enex(xml: &str) -> Vec<EnexNote> {
+    let mut notes = Vec::new();
+    let mut note: Option<EnexNote> = None;
+    let mut resource: Option<EnexResource> = None;
+    let mut element = String::new();
+
+    for token in tokenize(xml) {
+        match token {
+            Token::Open { name, .. } => {
+                match name.as_str() {
+                    "note" => note = Some(EnexNote::default()),
+                    "resource" => resource = Some(EnexResource::default()),
+                    _ => {}
+                }
+                element = name;
+            }
+            Token::Close(name) => {
+                match name.as_str() {
+                    "note" => notes.extend(note.take()),
+                    "resource" => {
+                        if let (Some(note), Some(resource)) = (&mut note, resource.take()) {
+                            note.resources.push(resource);
+                        }
+                    }
+                    _ => {}
+                }
+                element.clear();
+            }
+            Token::Text(text) => {
+                if let Some(resource) = &mut resource {
+                    match element.as_str() {
+                        "data" => resource.data.push_str(&text),
+                        "mime" => resource.mime.push_str(text.trim()),
+                        "file-name" => resource.file_name = Some(text.trim().to_string()),
+                        _ => {}
+                    }
+                } else if let Some(note) = &mut note {
+                    match element.as_str() {
+                        "title" => note.title.push_str(&text),
+                        "content" => note.content.push_str(&text),
+                        "created" => note.created.push_str(text.trim()),
+                        "updated" => note.updated.push_str(text.trim()),
+                        "tag" => note.tags.push(text.trim().to_string()),
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+    notes
+}
+
+fn convert_note(note: EnexNote, notebook: &str, source: String) -> ImportedNote {
+    let mut warnings = Vec::new();
+
+    let mut resources: HashMap<String, Resource> = HashMap::new();
+    for resource in note.resources {
+        let encoded: String = resource.data.split_whitespace().collect();
+        let data = match STANDARD.decode(encoded) {
+            Ok(data) => data,
+            Err(e) => {
+                warnings.push(format!("could not decode attachment: {}", e));
+                continue;
+            }
+        };
+        let hash = format!("{:x}", Md5::digest(&data));
+        let file_name = resource
+            .file_name
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| format!("attachment.{}", extension_for(&resource.mime)));
+        // Evernote reuses names like `image.png`, so keep them apart by hash
+        let name = format!("{}-{}", &hash[..8], file_name.replace(['/', '\\'], "_"));
+        resources.insert(
+            hash,
+            Resource {
+                name,
+                is_image: resource.mime.starts_with("image/"),
+                data,
+                used: false,
+            },
+        );
+    }
+
+    let conversion = html_to_markdown(&note.content, &mut |tag, attrs| {
+        if tag != "en-media" {
+            return None;
+        }
+        let resource = resources.get_mut(attribute(attrs, "hash")?)?;
+        resource.used = true;
+        Some(media_link(resource))
+    });
+
+    let mut content = conversion.markdown;
+    warnings.extend(conversion.warnings);
+
+    let mut attachments = Vec::new();
+    let mut resources: Vec<Resource> = resources.into_values().collect();
+    resources.sort_by(|a, b| a.name.cmp(&b.name));
+    for resource in resources {
+        if !resource.used {
+            warnings.push(format!(
+                "attachment {} was not referenced and is linked at the end",
+                resource.name
+            ));
+            content.push_str("\n\n");
+            content.push_str(&media_link(&resource));
+        }
+        attachments.push(Attachment {
+            name: resource.name,
+            source: AttachmentSource::Bytes(resource.data),
+        });
+    }
+
+    let updated_at = parse_enex_timestamp(&note.updated).unwrap_or_else(Local::now);
+    let created_at = parse_enex_timestamp(&note.created).unwrap_or(updated_at);
+
+    ImportedNote {
+        source,
+        note: Note {
+            title: note.title.trim().to_string(),
+            content,
+            created_at,
+            updated_at,
+            folder: notebook.to_string(),
+            tags: note.tags,
+            ..Default::default()
+        },
+        attachments,
+        warnings,
+    }
+}
+
+fn media_link(resource: &Resource) -> String {
+    let bang = if resource.is_image { "!" } else { "" };
+    format!(
+        "{}[{}]({})",
+        bang,
+        resource.name,
+        attachment_link(&resource.name)
+    )
+}
+
+fn extension_for(mime: &str) -> &'static str {
+    match mime {
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "image/svg+xml" => "svg",
+        "application/pdf" => "pdf",
+        "text/plain" => "txt",
+        _ => "bin",
+    }
+}
+
+/// ENEX timestamps look like `20240131T083000Z` and are always UTC
+fn parse_enex_timestamp(value: &str) -> Option<DateTime<Local>> {
+    NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%SZ")
+        .ok()
+        .map(|naive| naive.and_utc().with_timezone(&Local))
+        .or_else(|| {
+            DateTime::parse_from_rfc3339(value)
+                .ok()
+                .map(|timestamp| timestamp.with_timezone(&Utc).with_timezone(&Local))
+        })
+}
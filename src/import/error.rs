@@ -0,0 +1,24 @@
+use std::path::PathBuf;
+use thiserror::Error;
+
+use crate::storage::error::StorageError;
+
+#[derive(Error, Debug)]
+pub enum ImportError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Storage error: {0}")]
+    Storage(#[from] StorageError),
+
+    #[error("Import source not found at {0}")]
+    SourceNotFound(PathBuf),
+
+    #[error("Unknown import format: {0}")]
+    UnknownFormat(String),
+
+    #[error("Failed to parse {0}: {1}")]
+    Parse(String, String),
+}
+
+pub type ImportResult<T> = Result<T, ImportError>;
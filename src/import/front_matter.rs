@@ -0,0 +1,118 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+
+/// Key/value pairs from a YAML front matter block.
+///
+/// Only the subset of YAML found in note front matter is understood: scalar
+/// values, inline lists (`[a, b]`) and block lists (`- a`).
+#[derive(Debug, Default, Clone)]
+pub struct FrontMatter {
+    fields: Vec<(String, Vec<String>)>,
+}
+
+impl FrontMatter {
+    /// First value of a field, if present
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values(key).first().map(String::as_str)
+    }
+
+    /// All values of a field
+    pub fn values(&self, key: &str) -> &[String] {
+        self.fields
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, values)| values.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// First timestamp found among the given keys
+    pub fn timestamp(&self, keys: &[&str]) -> Option<DateTime<Local>> {
+        keys.iter()
+            .filter_map(|key| self.get(key))
+            .find_map(parse_timestamp)
+    }
+}
+
+/// Split a front matter block from the start of a note.
+///
+/// Returns the parsed front matter (if any) and the remaining content.
+pub fn split(content: &str) -> (Option<FrontMatter>, &str) {
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return (None, content);
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            let body = &rest[offset + line.len()..];
+            return (
+                Some(parse(&rest[..offset])),
+                body.trim_start_matches(['\r', '\n']),
+            );
+        }
+        offset += line.len();
+    }
+
+    // Unterminated block, treat it as regular content
+    (None, content)
+}
+
+fn parse(block: &str) -> FrontMatter {
+    let mut fields: Vec<(String, Vec<String>)> = Vec::new();
+    for line in block.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        if let Some(item) = trimmed.strip_prefix("- ") {
+            if let Some((_, values)) = fields.last_mut() {
+                values.push(unquote(item));
+            }
+            continue;
+        }
+
+        if let Some((key, value)) = trimmed.split_once(':') {
+            let value = value.trim();
+            let values =
+                if let Some(list) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+                    list.split(',')
+                        .map(unquote)
+                        .filter(|item| !item.is_empty())
+                        .collect()
+                } else if value.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![unquote(value)]
+                };
+            fields.push((key.trim().to_string(), values));
+        }
+    }
+    FrontMatter { fields }
+}
+
+fn unquote(value: &str) -> String {
+    value
+        .trim()
+        .trim_matches(|c| c == '"' || c == '\'')
+        .to_string()
+}
+
+/// Parse the timestamp formats commonly found in exported notes
+pub fn parse_timestamp(value: &str) -> Option<DateTime<Local>> {
+    let value = value.trim();
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Some(timestamp.with_timezone(&Local));
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(value, format) {
+            return Local.from_local_datetime(&naive).earliest();
+        }
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .and_then(|naive| Local.from_local_datetime(&naive).earliest())
+}
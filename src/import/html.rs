@@ -0,0 +1,639 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::import::error::ImportResult;
+use crate::import::markdown::{file_times, walk};
+use crate::import::{add_attachment, attachment_link, file_inside, ImportedNote, Importer};
+use crate::models::note::Note;
+use crate::tokenizer::{tokenize, Token};
+
+/// Imports HTML files as notes, converting their content to Markdown
+pub struct HtmlImporter;
+
+impl Importer for HtmlImporter {
+    fn name(&self) -> &'static str {
+        "html"
+    }
+
+    fn scan(&self, path: &Path) -> ImportResult<Vec<ImportedNote>> {
+        let (root, files) = if path.is_file() {
+            let root = path.parent().unwrap_or(Path::new("")).to_path_buf();
+            (root, vec![path.to_path_buf()])
+        } else {
+            let mut files = Vec::new();
+            walk(path, &mut files)?;
+            files.retain(|file| is_html(file));
+            (path.to_path_buf(), files)
+        };
+
+        let mut notes = Vec::new();
+        for file in files {
+            let html = fs::read_to_string(&file)?;
+            let base = file.parent().unwrap_or(Path::new("")).to_path_buf();
+
+            let mut attachments = Vec::new();
+            let mut warnings = Vec::new();
+            let mut conversion = html_to_markdown(&html, &mut |tag, attrs| {
+                if tag != "img" {
+                    return None;
+                }
+                let src = attribute(attrs, "src")?;
+                let local: PathBuf = base.join(src.replace("%20", " "));
+                if src.contains("://") || !local.is_file() {
+                    return None;
+                }
+                let Some(local) = file_inside(&root, &local) else {
+                    warnings.push(format!(
+                        "image {} is outside the imported folder and was not copied",
+                        src
+                    ));
+                    return None;
+                };
+                let name = local.file_name()?.to_string_lossy().to_string();
+                let alt = attribute(attrs, "alt").unwrap_or(&name).to_string();
+                let name = add_attachment(&mut attachments, &name, &local);
+                Some(format!("![{}]({})", alt, attachment_link(&name)))
+            });
+            conversion.warnings.extend(warnings);
+
+            let title = conversion
+                .title
+                .clone()
+                .or_else(|| Some(file.file_stem()?.to_string_lossy().to_string()))
+                .unwrap_or_default();
+            let (created_at, updated_at) = file_times(&file);
+
+            notes.push(ImportedNote {
+                source: file.display().to_string(),
+                note: Note {
+                    title,
+                    content: conversion.markdown,
+                    created_at,
+                    updated_at,
+                    ..Default::default()
+                },
+                attachments,
+                warnings: conversion.warnings,
+            });
+        }
+        Ok(notes)
+    }
+}
+
+fn is_html(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm"))
+}
+
+/// Attributes of a tag as `(name, value)` pairs
+pub type Attributes = [(String, String)];
+
+/// Renders an `img` or `en-media` element (tag name and attributes) as Markdown
+pub type MediaResolver<'a> = dyn FnMut(&str, &Attributes) -> Option<String> + 'a;
+
+/// Get the value of an attribute by name
+pub(crate) fn attribute<'a>(attrs: &'a Attributes, name: &str) -> Option<&'a str> {
+    attrs
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+/// Result of converting an HTML document to Markdown
+#[derive(Debug, Default)]
+pub struct Conversion {
+    pub markdown: String,
+    /// Text of the `<title>` element, or the first `<h1>` if there is none
+    pub title: Option<String>,
+    /// Content that could not be represented in Markdown
+    pub warnings: Vec<String>,
+}
+
+/// Convert HTML (or Evernote ENML) to Markdown.
+///
+/// `media` is asked to render `img` and `en-media` elements; returning `None`
+/// for an `img` keeps its original source, while unresolved `en-media`
+/// elements are reported as losses.
+pub fn html_to_markdown(html: &str, media: &mut MediaResolver) -> Conversion {
+    let mut converter = Converter::default();
+    for token in tokenize(html) {
+        match token {
+            Token::Open {
+                name,
+                attrs,
+                self_closing,
+            } => {
+                converter.open(&name, &attrs, media);
+                if self_closing {
+                    converter.close(&name);
+                }
+            }
+            Token::Close(name) => converter.close(&name),
+            Token::Text(text) => converter.text(&text),
+        }
+    }
+    converter.finish()
+}
+
+#[derive(Debug)]
+struct ListLevel {
+    ordered: bool,
+    next: u64,
+    /// Evernote checklists mark every item as a task
+    todo: bool,
+    /// Width of the last item marker, which items nested in it are indented by
+    indent: usize,
+}
+
+#[derive(Debug, Default)]
+struct Table {
+    rows: Vec<Vec<String>>,
+    cell: Option<String>,
+    /// Nested tables are flattened into the enclosing cell
+    nested: usize,
+}
+
+#[derive(Default)]
+struct Converter {
+    out: String,
+    lists: Vec<ListLevel>,
+    quote_depth: usize,
+    /// Depth of `pre` (or Evernote code block) elements
+    pre: usize,
+    /// Tags that opened a code block with the number of same-named elements
+    /// open inside it, so only the matching close ends the block
+    pre_tags: Vec<(String, usize)>,
+    links: Vec<Option<String>>,
+    table: Option<Table>,
+    /// Depth of elements whose content is dropped
+    skip: usize,
+    in_title: bool,
+    title: Option<String>,
+    heading_text: Option<String>,
+    warnings: Vec<String>,
+}
+
+impl Converter {
+    fn open(&mut self, name: &str, attrs: &Attributes, media: &mut MediaResolver) {
+        if self.skip > 0 {
+            if is_dropped(name) {
+                self.skip += 1;
+            }
+            return;
+        }
+
+        if let Some((tag, nested)) = self.pre_tags.last_mut() {
+            if tag == name {
+                *nested += 1;
+            }
+        }
+
+        let style = attribute(attrs, "style")
+            .unwrap_or_default()
+            .replace(' ', "");
+        match name {
+            "title" => self.in_title = true,
+            "p" | "section" | "article" | "header" | "footer" => self.block_start(),
+            "div" if style.contains("-en-codeblock:true") => self.start_pre(name),
+            "div" => self.block_start(),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.ensure_blank();
+                let level = name[1..].parse().unwrap_or(1);
+                self.write(&format!("{} ", "#".repeat(level)));
+                if name == "h1" && self.title.is_none() {
+                    self.heading_text = Some(String::new());
+                }
+            }
+            "br" => self.line_break(),
+            "hr" => {
+                self.ensure_blank();
+                self.write("---");
+                self.ensure_blank();
+            }
+            "pre" => self.start_pre(name),
+            "blockquote" => {
+                self.ensure_blank();
+                self.quote_depth += 1;
+            }
+            "ul" | "ol" => {
+                if self.lists.is_empty() {
+                    self.ensure_blank();
+                }
+                let start = attribute(attrs, "start").and_then(|s| s.parse().ok());
+                self.lists.push(ListLevel {
+                    ordered: name == "ol",
+                    next: start.unwrap_or(1),
+                    todo: style.contains("--en-todo:true"),
+                    indent: 2,
+                });
+            }
+            "li" => {
+                self.ensure_newline();
+                // Nested items line up with the text of their parent item
+                let outer = self.lists.len().saturating_sub(1);
+                let indent: usize = self.lists[..outer].iter().map(|level| level.indent).sum();
+                let checked = style.contains("--en-checked:true");
+                let marker = match self.lists.last_mut() {
+                    Some(level) if level.ordered => {
+                        level.next += 1;
+                        let marker = format!("{}. ", level.next - 1);
+                        level.indent = marker.len();
+                        marker
+                    }
+                    Some(level) if level.todo => {
+                        format!("- [{}] ", if checked { "x" } else { " " })
+                    }
+                    _ => "- ".to_string(),
+                };
+                self.write(&format!("{}{}", " ".repeat(indent), marker));
+            }
+            "table" => match &mut self.table {
+                Some(table) => {
+                    table.nested += 1;
+                    self.warnings
+                        .push("nested table flattened into a cell".to_string());
+                }
+                None => {
+                    self.ensure_blank();
+                    self.table = Some(Table::default());
+                }
+            },
+            "tr" => {
+                if let Some(table) = self.table.as_mut().filter(|table| table.nested == 0) {
+                    table.rows.push(Vec::new());
+                }
+            }
+            "td" | "th" => {
+                if let Some(table) = self.table.as_mut().filter(|table| table.nested == 0) {
+                    if table.rows.is_empty() {
+                        table.rows.push(Vec::new());
+                    }
+                    table.cell = Some(String::new());
+                }
+            }
+            "strong" | "b" => self.write("**"),
+            "em" | "i" => self.write("*"),
+            "s" | "del" | "strike" => self.write("~~"),
+            "code" if self.pre == 0 => self.write("`"),
+            "a" => {
+                let href = attribute(attrs, "href")
+                    .filter(|href| !href.is_empty())
+                    .map(str::to_string);
+                if href.is_some() {
+                    self.write("[");
+                }
+                self.links.push(href);
+            }
+            "img" => {
+                let markdown = media(name, attrs).unwrap_or_else(|| {
+                    let src = attribute(attrs, "src").unwrap_or_default();
+                    let alt = attribute(attrs, "alt").unwrap_or_default();
+                    format!("![{}]({})", alt, src)
+                });
+                self.write(&markdown);
+            }
+            "en-media" => match media(name, attrs) {
+                Some(markdown) => self.write(&markdown),
+                None => self.warnings.push(format!(
+                    "attachment {} not found",
+                    attribute(attrs, "hash").unwrap_or("without hash")
+                )),
+            },
+            "en-todo" => self.checkbox(attribute(attrs, "checked") == Some("true")),
+            "input" if attribute(attrs, "type") == Some("checkbox") => {
+                self.checkbox(attribute(attrs, "checked").is_some())
+            }
+            "u" => self.warn_once("underline formatting dropped"),
+            _ if is_dropped(name) => {
+                self.skip += 1;
+                self.warnings.push(format!("<{}> content dropped", name));
+            }
+            _ => {}
+        }
+    }
+
+    fn close(&mut self, name: &str) {
+        if self.skip > 0 {
+            if is_dropped(name) {
+                self.skip -= 1;
+            }
+            return;
+        }
+
+        if let Some((tag, nested)) = self.pre_tags.last_mut() {
+            if tag == name {
+                if *nested > 0 {
+                    *nested -= 1;
+                } else {
+                    self.pre_tags.pop();
+                    self.pre -= 1;
+                    self.ensure_newline();
+                    self.write("```");
+                    self.ensure_blank();
+                    return;
+                }
+            }
+        }
+
+        match name {
+            "title" => self.in_title = false,
+            "p" | "div" | "section" | "article" | "header" | "footer" => self.block_end(),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                if let Some(heading) = self.heading_text.take() {
+                    let heading = heading.trim().to_string();
+                    if !heading.is_empty() {
+                        self.title = Some(heading);
+                    }
+                }
+                self.ensure_blank();
+            }
+            "blockquote" => {
+                self.ensure_newline();
+                // Drop the quoted blank line left by the last block
+                if self.ends_with_blank_line() {
+                    let end = self.out[..self.out.len() - 1]
+                        .rfind('\n')
+                        .map_or(0, |i| i + 1);
+                    self.out.truncate(end);
+                }
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+                self.ensure_blank();
+            }
+            "ul" | "ol" => {
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.ensure_blank();
+                }
+            }
+            "li" => {
+                self.drop_trailing_break();
+                self.ensure_newline();
+            }
+            "table" => match &mut self.table {
+                Some(table) if table.nested > 0 => table.nested -= 1,
+                Some(_) => {
+                    if let Some(table) = self.table.take() {
+                        self.write_table(table);
+                    }
+                }
+                None => {}
+            },
+            "td" | "th" => {
+                if let Some(table) = self.table.as_mut().filter(|table| table.nested == 0) {
+                    if let Some(cell) = table.cell.take() {
+                        let cell = cell.split_whitespace().collect::<Vec<_>>().join(" ");
+                        if let Some(row) = table.rows.last_mut() {
+                            row.push(cell.replace('|', "\\|"));
+                        }
+                    }
+                }
+            }
+            "strong" | "b" => self.write("**"),
+            "em" | "i" => self.write("*"),
+            "s" | "del" | "strike" => self.write("~~"),
+            "code" if self.pre == 0 => self.write("`"),
+            "a" => {
+                if let Some(Some(href)) = self.links.pop() {
+                    self.write(&format!("]({})", href));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.skip > 0 {
+            return;
+        }
+        if self.in_title {
+            let title = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            if !title.is_empty() {
+                self.title = Some(title);
+            }
+            return;
+        }
+        if self.pre > 0 {
+            self.write(text);
+            return;
+        }
+
+        // Collapse whitespace like a browser would
+        let mut collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if text.starts_with(char::is_whitespace) && !collapsed.is_empty() {
+            collapsed.insert(0, ' ');
+        }
+        if text.ends_with(char::is_whitespace) && !collapsed.is_empty() {
+            collapsed.push(' ');
+        }
+        if collapsed.is_empty() && !text.is_empty() {
+            collapsed.push(' ');
+        }
+        if self.at_line_start() {
+            collapsed = collapsed.trim_start().to_string();
+        }
+        if let Some(heading) = &mut self.heading_text {
+            heading.push_str(&collapsed);
+        }
+        self.write(&collapsed);
+    }
+
+    fn finish(mut self) -> Conversion {
+        if let Some(table) = self.table.take() {
+            self.write_table(table);
+        }
+        self.drop_trailing_break();
+        let markdown = self
+            .out
+            .lines()
+            .map(str::trim_end)
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim()
+            .to_string();
+        Conversion {
+            markdown,
+            title: self.title,
+            warnings: self.warnings,
+        }
+    }
+
+    fn warn_once(&mut self, warning: &str) {
+        if !self.warnings.iter().any(|existing| existing == warning) {
+            self.warnings.push(warning.to_string());
+        }
+    }
+
+    fn start_pre(&mut self, tag: &str) {
+        self.ensure_blank();
+        self.write("```\n");
+        self.pre += 1;
+        self.pre_tags.push((tag.to_string(), 0));
+    }
+
+    /// Evernote writes checklists as `<div><en-todo/>text</div>`, so a checkbox
+    /// outside a list starts a task item of its own
+    fn checkbox(&mut self, checked: bool) {
+        let in_cell = self
+            .table
+            .as_ref()
+            .is_some_and(|table| table.cell.is_some());
+        if self.lists.is_empty() && !in_cell && self.pre == 0 {
+            self.ensure_newline();
+            self.write("- ");
+        }
+        self.write(if checked { "[x] " } else { "[ ] " });
+    }
+
+    /// Start of a paragraph-like block; inside list items and cells it only breaks the line
+    fn block_start(&mut self) {
+        if self.pre > 0 {
+            return;
+        }
+        if self
+            .table
+            .as_ref()
+            .is_some_and(|table| table.cell.is_some())
+        {
+            self.write(" ");
+        } else if self.lists.is_empty() {
+            self.ensure_blank();
+        } else if !self.at_item_start() {
+            self.ensure_newline();
+        }
+    }
+
+    fn block_end(&mut self) {
+        if self.pre > 0 {
+            self.write("\n");
+        } else if self
+            .table
+            .as_ref()
+            .is_some_and(|table| table.cell.is_some())
+        {
+            self.write(" ");
+        } else if self.lists.is_empty() {
+            self.ensure_blank();
+        }
+    }
+
+    fn line_break(&mut self) {
+        if self.pre > 0 {
+            self.write("\n");
+        } else if let Some(cell) = self.table.as_mut().and_then(|table| table.cell.as_mut()) {
+            cell.push_str("<br>");
+        } else if !self.at_line_start() {
+            // Trailing spaces are trimmed from every line, so use a backslash
+            self.write("\\\n");
+        }
+    }
+
+    fn write(&mut self, text: &str) {
+        if let Some(table) = &mut self.table {
+            if let Some(cell) = &mut table.cell {
+                cell.push_str(text);
+            }
+            // Text between cells is dropped
+            return;
+        }
+
+        for (i, part) in text.split('\n').enumerate() {
+            if i > 0 {
+                self.out.push('\n');
+            }
+            if part.is_empty() {
+                continue;
+            }
+            if self.quote_depth > 0 && (self.out.is_empty() || self.out.ends_with('\n')) {
+                self.out.push_str(&"> ".repeat(self.quote_depth));
+            }
+            self.out.push_str(part);
+        }
+    }
+
+    fn current_line(&self) -> &str {
+        self.out.rsplit('\n').next().unwrap_or_default()
+    }
+
+    fn at_line_start(&self) -> bool {
+        let line = self.current_line().trim_start_matches(['>', ' ']);
+        line.is_empty() || self.at_item_start() || matches!(line, "- [ ] " | "- [x] ")
+    }
+
+    /// Whether the current line holds only a list marker
+    fn at_item_start(&self) -> bool {
+        let line = self.current_line().trim_start();
+        let marker = line
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .trim_start_matches(['-', '.', ' '])
+            .trim_start_matches("[ ]")
+            .trim_start_matches("[x]")
+            .trim();
+        !self.lists.is_empty() && !line.is_empty() && marker.is_empty()
+    }
+
+    /// Whether the output ends with an empty (or only quoted) line
+    fn ends_with_blank_line(&self) -> bool {
+        self.out
+            .strip_suffix('\n')
+            .and_then(|out| out.rsplit('\n').next())
+            .is_some_and(|line| line.trim_matches(['>', ' ']).is_empty())
+    }
+
+    fn ensure_newline(&mut self) {
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+    }
+
+    /// A line break at the end of a block would show as a backslash
+    fn drop_trailing_break(&mut self) {
+        if self.pre == 0 && self.out.ends_with("\\\n") {
+            self.out.truncate(self.out.len() - 2);
+            self.out.push('\n');
+        }
+    }
+
+    fn ensure_blank(&mut self) {
+        self.drop_trailing_break();
+        self.ensure_newline();
+        if self.out.is_empty() || self.ends_with_blank_line() {
+            return;
+        }
+        if self.quote_depth > 0 {
+            self.out.push_str(&">".repeat(self.quote_depth));
+        }
+        self.out.push('\n');
+    }
+
+    fn write_table(&mut self, table: Table) {
+        let rows: Vec<Vec<String>> = table
+            .rows
+            .into_iter()
+            .filter(|row| !row.is_empty())
+            .collect();
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        if columns == 0 {
+            return;
+        }
+
+        self.ensure_blank();
+        for (i, row) in rows.iter().enumerate() {
+            let cells = (0..columns)
+                .map(|column| row.get(column).map(String::as_str).unwrap_or_default())
+                .collect::<Vec<_>>();
+            self.write(&format!("| {} |\n", cells.join(" | ")));
+            if i == 0 {
+                self.write(&format!("|{}\n", " --- |".repeat(columns)));
+            }
+        }
+        self.ensure_blank();
+    }
+}
+
+/// Elements whose content cannot be represented in Markdown
+fn is_dropped(name: &str) -> bool {
+    matches!(
+        name,
+        "iframe" | "object" | "embed" | "video" | "audio" | "svg" | "form" | "en-crypt" | "canvas"
+    )
+}
@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local, TimeZone};
+use serde_json::Value;
+
+use crate::import::error::{ImportError, ImportResult};
+use crate::import::front_matter::parse_timestamp;
+use crate::import::{add_attachment, attachment_link, Attachment, ImportedNote, Importer};
+use crate::models::note::Note;
+
+/// Joplin item types, as stored in the `type_` field
+const TYPE_NOTE: u32 = 1;
+const TYPE_FOLDER: u32 = 2;
+const TYPE_RESOURCE: u32 = 4;
+const TYPE_TAG: u32 = 5;
+const TYPE_NOTE_TAG: u32 = 6;
+
+/// Imports a Joplin "RAW" (Markdown + metadata) or JSON export directory
+pub struct JoplinImporter;
+
+/// A single item of a Joplin export
+#[derive(Debug, Default)]
+struct JoplinItem {
+    title: String,
+    body: String,
+    fields: HashMap<String, String>,
+}
+
+impl JoplinItem {
+    fn field(&self, key: &str) -> &str {
+        self.fields.get(key).map(String::as_str).unwrap_or_default()
+    }
+
+    fn item_type(&self) -> u32 {
+        self.field("type_").parse().unwrap_or(0)
+    }
+
+    fn timestamp(&self, key: &str) -> Option<DateTime<Local>> {
+        let value = self.field(key);
+        // JSON exports use milliseconds since the epoch, RAW exports ISO 8601
+        match value.parse::<i64>() {
+            Ok(millis) => Local.timestamp_millis_opt(millis).single(),
+            Err(_) => parse_timestamp(value),
+        }
+    }
+}
+
+impl Importer for JoplinImporter {
+    fn name(&self) -> &'static str {
+        "joplin"
+    }
+
+    fn scan(&self, path: &Path) -> ImportResult<Vec<ImportedNote>> {
+        let mut items = Vec::new();
+        let mut entries = fs::read_dir(path)?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .collect::<Vec<_>>();
+        entries.sort();
+
+        for entry in entries {
+            let item = match entry.extension().and_then(|ext| ext.to_str()) {
+                Some("md") => parse_raw(&fs::read_to_string(&entry)?),
+                Some("json") => parse_json(&entry)?,
+                _ => continue,
+            };
+            items.push(item);
+        }
+
+        let by_type = |kind: u32| items.iter().filter(move |item| item.item_type() == kind);
+
+        let folders: HashMap<&str, &JoplinItem> = by_type(TYPE_FOLDER)
+            .map(|item| (item.field("id"), item))
+            .collect();
+        let tags: HashMap<&str, &str> = by_type(TYPE_TAG)
+            .map(|item| (item.field("id"), item.title.as_str()))
+            .collect();
+        let resources: HashMap<&str, &JoplinItem> = by_type(TYPE_RESOURCE)
+            .map(|item| (item.field("id"), item))
+            .collect();
+        let mut note_tags: HashMap<&str, Vec<String>> = HashMap::new();
+        for link in by_type(TYPE_NOTE_TAG) {
+            if let Some(tag) = tags.get(link.field("tag_id")) {
+                note_tags
+                    .entry(link.field("note_id"))
+                    .or_default()
+                    .push(tag.to_string());
+            }
+        }
+
+        let mut notes = Vec::new();
+        for item in by_type(TYPE_NOTE) {
+            let mut warnings = Vec::new();
+            let mut attachments = Vec::new();
+            let content = convert_resource_links(
+                &item.body,
+                path,
+                &resources,
+                &mut attachments,
+                &mut warnings,
+            );
+
+            let updated_at = item.timestamp("updated_time").unwrap_or_else(Local::now);
+            let note = Note {
+                title: item.title.clone(),
+                content,
+                created_at: item.timestamp("created_time").unwrap_or(updated_at),
+                updated_at,
+                folder: folder_path(item.field("parent_id"), &folders),
+                tags: note_tags.remove(item.field("id")).unwrap_or_default(),
+                ..Default::default()
+            };
+
+            notes.push(ImportedNote {
+                source: format!("joplin:{}", item.field("id")),
+                note,
+                attachments,
+                warnings,
+            });
+        }
+        Ok(notes)
+    }
+}
+
+/// Parse a RAW export item: title, blank line, body, blank line, `key: value` metadata
+fn parse_raw(raw: &str) -> JoplinItem {
+    let lines: Vec<&str> = raw.lines().collect();
+
+    // Metadata is the trailing run of `key: value` lines
+    let mut metadata_start = lines.len();
+    while metadata_start > 0 {
+        let line = lines[metadata_start - 1];
+        let is_field = line
+            .split_once(": ")
+            .or_else(|| line.strip_suffix(':').map(|key| (key, "")))
+            .is_some_and(|(key, _)| {
+                !key.is_empty() && key.chars().all(|c| c.is_ascii_lowercase() || c == '_')
+            });
+        if !is_field {
+            break;
+        }
+        metadata_start -= 1;
+    }
+
+    let fields = lines[metadata_start..]
+        .iter()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            Some((key.to_string(), value.trim().to_string()))
+        })
+        .collect();
+
+    let content = &lines[..metadata_start];
+    let title = content.first().copied().unwrap_or_default().to_string();
+    let body = content
+        .iter()
+        .skip(1)
+        .skip_while(|line| line.trim().is_empty())
+        .copied()
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_end()
+        .to_string();
+
+    JoplinItem {
+        title,
+        body,
+        fields,
+    }
+}
+
+/// Parse a JSON export item
+fn parse_json(path: &Path) -> ImportResult<JoplinItem> {
+    let raw = fs::read_to_string(path)?;
+    let value: Value = serde_json::from_str(&raw)
+        .map_err(|e| ImportError::Parse(path.display().to_string(), e.to_string()))?;
+
+    let mut item = JoplinItem::default();
+    if let Value::Object(map) = value {
+        for (key, value) in map {
+            let value = match value {
+                Value::String(value) => value,
+                Value::Null => String::new(),
+                other => other.to_string(),
+            };
+            match key.as_str() {
+                "title" => item.title = value,
+                "body" => item.body = value,
+                _ => {
+                    item.fields.insert(key, value);
+                }
+            }
+        }
+    }
+    Ok(item)
+}
+
+/// Build a `/` separated folder path by following `parent_id` links
+fn folder_path(parent_id: &str, folders: &HashMap<&str, &JoplinItem>) -> String {
+    let mut parts = Vec::new();
+    let mut current = parent_id;
+    while let Some(folder) = folders.get(current) {
+        // Guard against cycles in damaged exports
+        if parts.len() > folders.len() {
+            break;
+        }
+        parts.push(folder.title.clone());
+        current = folder.field("parent_id");
+    }
+    parts.reverse();
+    parts.join("/")
+}
+
+/// Point `:/<resource id>` links at attachments copied from the `resources` directory
+fn convert_resource_links(
+    body: &str,
+    export_dir: &Path,
+    resources: &HashMap<&str, &JoplinItem>,
+    attachments: &mut Vec<Attachment>,
+    warnings: &mut Vec<String>,
+) -> String {
+    let mut output = String::with_capacity(body.len());
+    let mut rest = body;
+
+    while let Some(start) = rest.find("(:/") {
+        output.push_str(&rest[..start + 1]);
+        let id: String = rest[start + 3..]
+            .chars()
+            .take_while(|c| c.is_ascii_hexdigit())
+            .collect();
+
+        match resources.get(id.as_str()) {
+            Some(resource) => {
+                let extension = resource.field("file_extension");
+                let file_name = if extension.is_empty() {
+                    id.clone()
+                } else {
+                    format!("{}.{}", id, extension)
+                };
+                let path: PathBuf = export_dir.join("resources").join(&file_name);
+                if path.is_file() {
+                    // Prefer the original file name when the title still has its extension
+                    let name = if Path::new(&resource.title).extension().is_some() {
+                        resource.title.clone()
+                    } else {
+                        file_name
+                    };
+                    let name = add_attachment(attachments, &name, &path);
+                    output.push_str(&attachment_link(&name));
+                } else {
+                    warnings.push(format!("resource file {} is missing", file_name));
+                    output.push_str(&format!(":/{}", id));
+                }
+            }
+            None => {
+                warnings.push(format!("unknown resource {}", id));
+                output.push_str(&format!(":/{}", id));
+            }
+        }
+
+        rest = &rest[start + 3 + id.len()..];
+    }
+
+    output.push_str(rest);
+    output
+}
@@ -0,0 +1,316 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local};
+
+use crate::import::error::ImportResult;
+use crate::import::front_matter::{self, FrontMatter};
+use crate::import::{
+    add_attachment, attachment_link, file_inside, Attachment, ImportedNote, Importer,
+};
+use crate::models::note::Note;
+use crate::models::text::inline_tags;
+
+/// Imports a folder of Markdown files, optionally understanding Obsidian syntax
+pub struct MarkdownImporter {
+    obsidian: bool,
+}
+
+impl MarkdownImporter {
+    /// Importer for plain Markdown folders
+    pub fn plain() -> Self {
+        Self { obsidian: false }
+    }
+
+    /// Importer for Obsidian vaults, converting `[[links]]`, embeds and inline tags
+    pub fn obsidian() -> Self {
+        Self { obsidian: true }
+    }
+}
+
+impl Importer for MarkdownImporter {
+    fn name(&self) -> &'static str {
+        if self.obsidian {
+            "obsidian"
+        } else {
+            "markdown"
+        }
+    }
+
+    fn scan(&self, path: &Path) -> ImportResult<Vec<ImportedNote>> {
+        let (root, files) = if path.is_file() {
+            let root = path.parent().unwrap_or(Path::new("")).to_path_buf();
+            (root, vec![path.to_path_buf()])
+        } else {
+            let mut files = Vec::new();
+            walk(path, &mut files)?;
+            (path.to_path_buf(), files)
+        };
+
+        // Non-Markdown files can be referenced from notes by their file name
+        let assets: HashMap<String, PathBuf> = files
+            .iter()
+            .filter(|file| !is_markdown(file))
+            .filter_map(|file| {
+                Some((
+                    file.file_name()?.to_string_lossy().to_string(),
+                    file.clone(),
+                ))
+            })
+            .collect();
+
+        let mut notes = Vec::new();
+        for file in files.iter().filter(|file| is_markdown(file)) {
+            notes.push(self.read_file(&root, file, &assets)?);
+        }
+        Ok(notes)
+    }
+}
+
+impl MarkdownImporter {
+    fn read_file(
+        &self,
+        root: &Path,
+        file: &Path,
+        assets: &HashMap<String, PathBuf>,
+    ) -> ImportResult<ImportedNote> {
+        let raw = fs::read_to_string(file)?;
+        let (front_matter, body) = front_matter::split(&raw);
+        let front_matter = front_matter.unwrap_or_default();
+
+        let mut attachments = Vec::new();
+        let mut warnings = Vec::new();
+
+        let mut content = body.to_string();
+        if self.obsidian {
+            content = convert_wiki_links(&content, assets, &mut attachments, &mut warnings);
+        }
+        content = convert_local_links(&content, root, file, &mut attachments, &mut warnings);
+
+        let title = front_matter
+            .get("title")
+            .map(str::to_string)
+            .or_else(|| Some(file.file_stem()?.to_string_lossy().to_string()))
+            .unwrap_or_default();
+
+        let folder = file
+            .parent()
+            .and_then(|parent| parent.strip_prefix(root).ok())
+            .map(|relative| {
+                relative
+                    .components()
+                    .map(|part| part.as_os_str().to_string_lossy().to_string())
+                    .collect::<Vec<_>>()
+                    .join("/")
+            })
+            .unwrap_or_default();
+
+        let mut tags = front_matter_tags(&front_matter);
+        if self.obsidian {
+            for tag in inline_tags(&content) {
+                if !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            }
+        }
+
+        let (created_at, updated_at) = file_times(file);
+        let note = Note {
+            title,
+            content,
+            created_at: front_matter
+                .timestamp(&["created", "created_at", "date"])
+                .unwrap_or(created_at),
+            updated_at: front_matter
+                .timestamp(&["updated", "updated_at", "modified"])
+                .unwrap_or(updated_at),
+            folder,
+            tags,
+            ..Default::default()
+        };
+
+        Ok(ImportedNote {
+            source: file.display().to_string(),
+            note,
+            attachments,
+            warnings,
+        })
+    }
+}
+
+/// Recursively collect files, skipping hidden entries such as `.obsidian` and `.trash`
+pub(crate) fn walk(dir: &Path, files: &mut Vec<PathBuf>) -> ImportResult<()> {
+    let mut entries = fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .collect::<Vec<_>>();
+    entries.sort();
+
+    for path in entries {
+        let hidden = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if hidden {
+            continue;
+        }
+        // Symlinked folders are skipped, as they can loop back to a parent
+        if fs::symlink_metadata(&path)?.is_dir() {
+            walk(&path, files)?;
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("md") || ext.eq_ignore_ascii_case("markdown"))
+}
+
+/// Creation and modification time of a file, falling back to now
+pub(crate) fn file_times(path: &Path) -> (DateTime<Local>, DateTime<Local>) {
+    let now = Local::now();
+    let Ok(metadata) = fs::metadata(path) else {
+        return (now, now);
+    };
+    let updated_at = metadata.modified().map(DateTime::from).unwrap_or(now);
+    let created_at = metadata.created().map(DateTime::from).unwrap_or(updated_at);
+    (created_at, updated_at)
+}
+
+fn front_matter_tags(front_matter: &FrontMatter) -> Vec<String> {
+    let mut tags = Vec::new();
+    for key in ["tags", "tag"] {
+        for value in front_matter.values(key) {
+            // Tags may also be written as a single space separated string
+            for tag in value.split_whitespace() {
+                let tag = tag.trim_start_matches('#').to_string();
+                if !tag.is_empty() && !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            }
+        }
+    }
+    tags
+}
+
+/// Rewrite Obsidian `[[links]]` and `![[embeds]]`.
+///
+/// Links to notes keep the wiki-link form but drop folder paths and the `.md`
+/// extension. Links and embeds pointing to other files become regular Markdown
+/// links to the copied attachment.
+fn convert_wiki_links(
+    content: &str,
+    assets: &HashMap<String, PathBuf>,
+    attachments: &mut Vec<Attachment>,
+    warnings: &mut Vec<String>,
+) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(start) = rest.find("[[") {
+        let Some(length) = rest[start + 2..].find("]]") else {
+            break;
+        };
+        let embed = start > 0 && rest[..start].ends_with('!');
+        let prefix_end = if embed { start - 1 } else { start };
+        output.push_str(&rest[..prefix_end]);
+
+        let inner = &rest[start + 2..start + 2 + length];
+        let (target, alias) = match inner.split_once('|') {
+            Some((target, alias)) => (target.trim(), Some(alias.trim())),
+            None => (inner.trim(), None),
+        };
+        let (path, anchor) = match target.split_once('#') {
+            Some((path, anchor)) => (path, Some(anchor)),
+            None => (target, None),
+        };
+        let file_name = path.rsplit('/').next().unwrap_or(path);
+
+        if let Some(asset) = assets.get(file_name) {
+            let name = add_attachment(attachments, file_name, asset);
+            // Image embeds may carry a size instead of an alias
+            let label = alias
+                .filter(|alias| !alias.chars().all(|c| c.is_ascii_digit() || c == 'x'))
+                .unwrap_or(file_name);
+            let bang = if embed { "!" } else { "" };
+            output.push_str(&format!("{}[{}]({})", bang, label, attachment_link(&name)));
+        } else {
+            let title = file_name.strip_suffix(".md").unwrap_or(file_name);
+            if embed {
+                warnings.push(format!("embedded note '{}' converted to a link", title));
+            }
+            output.push_str("[[");
+            output.push_str(title);
+            if let Some(anchor) = anchor {
+                output.push('#');
+                output.push_str(anchor);
+            }
+            if let Some(alias) = alias {
+                output.push('|');
+                output.push_str(alias);
+            }
+            output.push_str("]]");
+        }
+
+        rest = &rest[start + 2 + length + 2..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Copy files referenced by relative Markdown links and point the links at the attachments.
+///
+/// Only files inside `root`, the imported folder, are copied.
+fn convert_local_links(
+    content: &str,
+    root: &Path,
+    file: &Path,
+    attachments: &mut Vec<Attachment>,
+    warnings: &mut Vec<String>,
+) -> String {
+    let base = file.parent().unwrap_or(Path::new(""));
+    let mut output = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(start) = rest.find("](") {
+        let Some(length) = rest[start + 2..].find(')') else {
+            break;
+        };
+        output.push_str(&rest[..start + 2]);
+
+        let target = &rest[start + 2..start + 2 + length];
+        let decoded = target.replace("%20", " ");
+        let candidate = base.join(&decoded);
+        let is_local = !target.contains("://")
+            && !target.starts_with('#')
+            && !target.starts_with("attachments/")
+            && !is_markdown(&candidate)
+            && candidate.is_file();
+
+        match candidate.file_name() {
+            Some(name) if is_local => match file_inside(root, &candidate) {
+                Some(path) => {
+                    let name = add_attachment(attachments, &name.to_string_lossy(), &path);
+                    output.push_str(&attachment_link(&name));
+                }
+                None => {
+                    warnings.push(format!(
+                        "{} is outside the imported folder and was not copied",
+                        target
+                    ));
+                    output.push_str(target);
+                }
+            },
+            _ => output.push_str(target),
+        }
+
+        rest = &rest[start + 2 + length..];
+    }
+
+    output.push_str(rest);
+    output
+}
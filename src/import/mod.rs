@@ -0,0 +1,306 @@
+pub mod enex;
+pub mod error;
+pub mod front_matter;
+pub mod html;
+pub mod joplin;
+pub mod markdown;
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::models::note::Note;
+use crate::storage::Storage;
+use error::{ImportError, ImportResult};
+
+pub use enex::EnexImporter;
+pub use html::HtmlImporter;
+pub use joplin::JoplinImporter;
+pub use markdown::MarkdownImporter;
+
+/// Importer trait defines the interface for reading notes from another application
+pub trait Importer {
+    /// Short name used to select the importer (e.g. `obsidian`, `joplin`)
+    fn name(&self) -> &'static str;
+
+    /// Read every note found at the given path without writing anything
+    fn scan(&self, path: &Path) -> ImportResult<Vec<ImportedNote>>;
+}
+
+/// Where the bytes of an attachment come from
+#[derive(Debug, Clone)]
+pub enum AttachmentSource {
+    File(PathBuf),
+    Bytes(Vec<u8>),
+}
+
+/// A file that belongs to an imported note
+#[derive(Debug, Clone)]
+pub struct Attachment {
+    /// File name the attachment is stored under
+    pub name: String,
+    pub source: AttachmentSource,
+}
+
+impl Attachment {
+    fn read(&self) -> ImportResult<Vec<u8>> {
+        match &self.source {
+            AttachmentSource::File(path) => Ok(fs::read(path)?),
+            AttachmentSource::Bytes(bytes) => Ok(bytes.clone()),
+        }
+    }
+}
+
+/// A note read from an import source, ready to be written to storage
+#[derive(Debug, Clone)]
+pub struct ImportedNote {
+    /// Human readable location of the note in the source (usually a path)
+    pub source: String,
+    pub note: Note,
+    pub attachments: Vec<Attachment>,
+    /// Anything that could not be converted faithfully
+    pub warnings: Vec<String>,
+}
+
+/// Outcome of an import, also produced for dry runs
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    /// Source location and final title of every imported note
+    pub imported: Vec<(String, String)>,
+    /// Original and assigned title of every note whose title was taken
+    pub collisions: Vec<(String, String)>,
+    pub attachments: usize,
+    /// Warnings prefixed with the final title of the note they belong to
+    pub warnings: Vec<String>,
+}
+
+impl ImportReport {
+    /// Render the report as human readable lines
+    pub fn summary(&self) -> String {
+        let verb = if self.dry_run {
+            "Would import"
+        } else {
+            "Imported"
+        };
+        let mut lines = Vec::new();
+        for (source, title) in &self.imported {
+            lines.push(format!("{} '{}' from {}", verb, title, source));
+        }
+        for (original, renamed) in &self.collisions {
+            lines.push(format!(
+                "Title collision: '{}' imported as '{}'",
+                original, renamed
+            ));
+        }
+        for warning in &self.warnings {
+            lines.push(format!("Warning: {}", warning));
+        }
+        lines.push(format!(
+            "{} {} notes and {} attachments",
+            verb,
+            self.imported.len(),
+            self.attachments
+        ));
+        lines.join("\n")
+    }
+}
+
+/// All available importers
+pub fn importers() -> Vec<Box<dyn Importer>> {
+    vec![
+        Box::new(MarkdownImporter::plain()),
+        Box::new(MarkdownImporter::obsidian()),
+        Box::new(JoplinImporter),
+        Box::new(EnexImporter),
+        Box::new(HtmlImporter),
+    ]
+}
+
+/// Find an importer by its name
+pub fn find_importer(name: &str) -> ImportResult<Box<dyn Importer>> {
+    importers()
+        .into_iter()
+        .find(|importer| importer.name() == name)
+        .ok_or_else(|| ImportError::UnknownFormat(name.to_string()))
+}
+
+/// Scan a source and write its notes to storage.
+///
+/// Titles that already exist (in storage or earlier in the same import) get a
+/// numeric suffix. Nothing is written when `dry_run` is set.
+pub fn import(
+    importer: &dyn Importer,
+    path: &Path,
+    storage: &dyn Storage,
+    dry_run: bool,
+) -> ImportResult<ImportReport> {
+    if !path.exists() {
+        return Err(ImportError::SourceNotFound(path.to_path_buf()));
+    }
+
+    let existing = storage.list_notes()?;
+    let mut titles: HashSet<String> = existing.iter().map(|note| note.title.clone()).collect();
+    let first_order = existing
+        .iter()
+        .map(|note| note.order + 1)
+        .max()
+        .unwrap_or(0);
+
+    let mut report = ImportReport {
+        dry_run,
+        ..Default::default()
+    };
+    for (index, imported) in importer.scan(path)?.into_iter().enumerate() {
+        let mut note = imported.note;
+
+        let title = unique_title(&note.title, &titles);
+        if title != note.title {
+            report.collisions.push((note.title.clone(), title.clone()));
+            note.title = title;
+        }
+        titles.insert(note.title.clone());
+
+        note.order = first_order + index;
+
+        let mut warnings = imported.warnings;
+        let attachments = imported.attachments;
+
+        if !dry_run {
+            let mut renamed = HashMap::new();
+            for attachment in &attachments {
+                let stored = storage.write_attachment(&attachment.name, &attachment.read()?)?;
+                let stored_name = stored.rsplit('/').next().unwrap_or(&stored);
+                if stored_name != attachment.name {
+                    warnings.push(format!(
+                        "attachment '{}' saved as '{}', the name was taken",
+                        attachment.name, stored_name
+                    ));
+                    renamed.insert(
+                        attachment_link(&attachment.name),
+                        attachment_link(stored_name),
+                    );
+                }
+            }
+            // Another file already had the name, point the links at the new one
+            note.content = relink(&note.content, &renamed);
+            storage.write_note(&note)?;
+        }
+
+        report.attachments += attachments.len();
+        report.warnings.extend(
+            warnings
+                .into_iter()
+                .map(|warning| format!("'{}': {}", note.title, warning)),
+        );
+        report.imported.push((imported.source, note.title));
+    }
+
+    Ok(report)
+}
+
+/// The canonical path of `path` if it is a file inside `root`.
+///
+/// Links in imported documents may point anywhere through `..`, absolute paths
+/// or symlinks; only files under the imported folder are copied, so an import
+/// never pulls unrelated local files into the vault.
+pub(crate) fn file_inside(root: &Path, path: &Path) -> Option<PathBuf> {
+    let root = if root.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        root
+    };
+    let root = root.canonicalize().ok()?;
+    let path = path.canonicalize().ok()?;
+    (path.starts_with(&root) && path.is_file()).then_some(path)
+}
+
+/// Add a file to the attachments of a note and return the name to link it by.
+///
+/// A file linked more than once is added once, while different files that
+/// share a name get ` (2)`, ` (3)`, ... before the extension.
+pub(crate) fn add_attachment(attachments: &mut Vec<Attachment>, name: &str, path: &Path) -> String {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let existing = attachments.iter().find(
+        |attachment| matches!(&attachment.source, AttachmentSource::File(source) if *source == path),
+    );
+    if let Some(existing) = existing {
+        return existing.name.clone();
+    }
+
+    let stem = Path::new(name)
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let extension = Path::new(name)
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    let name = (1..)
+        .map(|n| match n {
+            1 => name.to_string(),
+            n => format!("{} ({}){}", stem, n, extension),
+        })
+        .find(|candidate| {
+            !attachments
+                .iter()
+                .any(|attachment| attachment.name == *candidate)
+        })
+        .unwrap_or_else(|| name.to_string());
+    attachments.push(Attachment {
+        name: name.clone(),
+        source: AttachmentSource::File(path),
+    });
+    name
+}
+
+/// Replace the targets of Markdown links found in `renamed`, in a single pass
+/// so a new target is never renamed again
+fn relink(content: &str, renamed: &HashMap<String, String>) -> String {
+    if renamed.is_empty() {
+        return content.to_string();
+    }
+    let mut output = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(start) = rest.find("](") {
+        output.push_str(&rest[..start + 2]);
+        rest = &rest[start + 2..];
+        // Attachment names such as `photo (2).png` put balanced parentheses in the target
+        let mut depth = 0;
+        let end = rest.find(|c| {
+            match c {
+                '(' => depth += 1,
+                ')' if depth == 0 => return true,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            false
+        });
+        let Some(end) = end else {
+            break;
+        };
+        match renamed.get(&rest[..end]) {
+            Some(target) => output.push_str(target),
+            None => output.push_str(&rest[..end]),
+        }
+        rest = &rest[end..];
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Link target of an attachment, with spaces encoded so the link stays valid
+pub(crate) fn attachment_link(name: &str) -> String {
+    format!("attachments/{}", name.replace(' ', "%20"))
+}
+
+/// Append ` (2)`, ` (3)`, ... to a title until it is not taken
+fn unique_title(title: &str, taken: &HashSet<String>) -> String {
+    if !taken.contains(title) {
+        return title.to_string();
+    }
+    (2..)
+        .map(|n| format!("{} ({})", title, n))
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap_or_else(|| title.to_string())
+}
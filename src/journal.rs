@@ -0,0 +1,31 @@
+use chrono::{Local, NaiveDate};
+
+use crate::models::note::Note;
+
+/// Folder holding the daily notes
+pub const JOURNAL_FOLDER: &str = "Journal";
+
+/// Title of the daily note for a date, such as `2026-10-16`
+pub fn entry_title(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+/// Content a new daily note starts with
+pub fn entry_content(date: NaiveDate) -> String {
+    format!("# {}\n\n", date.format("%A, %-d %B %Y"))
+}
+
+/// Date of a daily note.
+///
+/// Notes titled with a date count wherever they are, so daily notes named by
+/// hand show up too. Other notes in the journal folder fall back to the day
+/// they were created.
+pub fn entry_date(note: &Note) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(&note.title, "%Y-%m-%d")
+        .ok()
+        .or_else(|| (note.folder == JOURNAL_FOLDER).then(|| note.created_at.date_naive()))
+}
+
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
@@ -0,0 +1,301 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use edtui::actions::{Execute, Redo, SwitchMode, Undo};
+use edtui::events::{KeyEvent, KeyEventHandler, KeyEventRegister};
+use edtui::{EditorEventHandler, EditorMode, EditorState};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::commands::Command;
+use crate::sorting::{Grouping, SortKey};
+use crate::ui::CardLayout;
+
+/// File in the notes directory holding the editing mode and leader mappings
+pub const KEYMAP_FILE: &str = "keymap.json";
+
+/// How keys typed in the editor are handled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EditingMode {
+    /// Modal editing, with a leader key and `:` commands in normal mode
+    #[default]
+    Vim,
+    /// Always inserting, with `Ctrl+Z`/`Ctrl+Y` to undo and redo
+    Standard,
+}
+
+impl EditingMode {
+    pub fn toggle(self) -> Self {
+        match self {
+            EditingMode::Vim => EditingMode::Standard,
+            EditingMode::Standard => EditingMode::Vim,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            EditingMode::Vim => "vim",
+            EditingMode::Standard => "standard",
+        }
+    }
+
+    /// Editor key handling for the mode
+    pub fn event_handler(self) -> EditorEventHandler {
+        let mut key_handler = KeyEventHandler::default();
+        if self == EditingMode::Standard {
+            key_handler.insert(KeyEventRegister::i(vec![KeyEvent::Ctrl('z')]), Undo);
+            key_handler.insert(KeyEventRegister::i(vec![KeyEvent::Ctrl('y')]), Redo);
+        }
+        EditorEventHandler::new(key_handler)
+    }
+}
+
+/// Record an undo step, which the editor itself only does when insert mode
+/// is entered or text is deleted
+pub fn capture_undo(editor: &mut EditorState) {
+    let (cursor, mode) = (editor.cursor, editor.mode);
+    editor.mode = EditorMode::Normal;
+    SwitchMode(EditorMode::Insert).execute(editor);
+    // Entering insert mode from normal mode pulls the cursor back from the end of the line
+    editor.cursor = cursor;
+    editor.mode = mode;
+}
+
+/// Outcome of the keys typed after the leader
+pub enum LeaderMatch {
+    Command(Command),
+    /// Some mapping starts with the keys, wait for more
+    Pending,
+    None,
+}
+
+/// Editing mode and leader mappings, kept in [`KEYMAP_FILE`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeymapSettings {
+    pub mode: EditingMode,
+    /// Key starting a mapping in vim normal mode
+    pub leader: char,
+    /// Keys typed after the leader, and the name of the command they run
+    pub leader_mappings: BTreeMap<String, String>,
+}
+
+impl KeymapSettings {
+    /// Keymap saved in the notes directory, or the default one
+    pub fn load(root_dir: &Path) -> Self {
+        let path = root_dir.join(KEYMAP_FILE);
+        let Ok(contents) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        match serde_json::from_str::<Self>(&contents) {
+            Ok(settings) => {
+                for (keys, name) in &settings.leader_mappings {
+                    if Command::from_name(name).is_none() {
+                        warn!(
+                            "Ignoring mapping of '{}' to unknown command '{}'",
+                            keys, name
+                        );
+                    }
+                }
+                settings
+            }
+            Err(e) => {
+                warn!("Ignoring invalid keymap in {}: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self, root_dir: &Path) -> io::Result<()> {
+        let path = root_dir.join(KEYMAP_FILE);
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, json)?;
+        fs::rename(temp_path, path)
+    }
+
+    /// Command mapped to the keys typed after the leader
+    pub fn leader_command(&self, keys: &str) -> LeaderMatch {
+        if let Some(command) = self
+            .leader_mappings
+            .get(keys)
+            .and_then(|name| Command::from_name(name))
+        {
+            return LeaderMatch::Command(command);
+        }
+        if self
+            .leader_mappings
+            .keys()
+            .any(|mapped| mapped.len() > keys.len() && mapped.starts_with(keys))
+        {
+            LeaderMatch::Pending
+        } else {
+            LeaderMatch::None
+        }
+    }
+}
+
+impl Default for KeymapSettings {
+    fn default() -> Self {
+        let leader_mappings = [
+            ("n", "new_note"),
+            ("f", "find_note"),
+            ("r", "rename_note"),
+            ("d", "delete_note"),
+            ("u", "unlock"),
+            ("o", "outline"),
+            ("i", "insert_toc"),
+            ("h", "history"),
+            ("j", "journal"),
+            ("c", "calendar"),
+            ("s", "sync"),
+            ("l", "live_preview"),
+            ("e", "editor"),
+            ("E", "edit_externally"),
+            ("I", "note_info"),
+            ("v", "dashboard"),
+            ("p", "preview"),
+            ("b", "toggle_note_list"),
+            ("x", "toggle_split"),
+            ("w", "focus_other_pane"),
+            ("Ss", "cycle_sort"),
+            ("Sr", "reverse_sort"),
+            ("Sg", "cycle_grouping"),
+            ("Sc", "cycle_cards"),
+            ("P", "toggle_pin"),
+            ("a", "archive_note"),
+            ("A", "archive"),
+            ("Bm", "toggle_mark"),
+            ("Ba", "mark_all"),
+            ("Bf", "move_to_folder"),
+            ("Bt", "add_tag"),
+            ("BT", "remove_tag"),
+            ("Be", "export_notes"),
+            ("Bj", "merge_notes"),
+            ("tn", "next_tab"),
+            ("tp", "previous_tab"),
+            ("tk", "keep_tab"),
+            ("tc", "close_tab"),
+            ("mb", "bold"),
+            ("mi", "italic"),
+            ("mc", "inline_code"),
+            ("mk", "link"),
+            ("mt", "format_table"),
+            ("ya", "copy_note"),
+            ("yr", "copy_note_text"),
+            ("z=", "spelling_suggestions"),
+            ("zg", "add_to_dictionary"),
+            ("zt", "toggle_spellcheck"),
+        ]
+        .into_iter()
+        .map(|(keys, name)| (keys.to_string(), name.to_string()))
+        .collect();
+        Self {
+            mode: EditingMode::default(),
+            leader: ' ',
+            leader_mappings,
+        }
+    }
+}
+
+/// Command typed after `:` in vim normal mode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExCommand {
+    Write,
+    Quit,
+    WriteQuit,
+    /// Open the note with this title
+    Edit(String),
+    /// Rename the note, asking for the title when there is none
+    Rename(Option<String>),
+    New,
+    /// Go to a line, from 1
+    Line(usize),
+    /// Sort the note list by a key, descending when set
+    Sort(SortKey, bool),
+    /// Split the note list into sections
+    Group(Grouping),
+    /// Show the notes of the list with a card layout
+    Cards(CardLayout),
+    /// Move the marked notes into a folder, the root when empty
+    Folder(String),
+    /// Add a tag to the marked notes
+    Tag(String),
+    /// Remove a tag from the marked notes
+    Untag(String),
+    /// Export the marked notes in a format, into a directory or the current one
+    Export(String, Option<String>),
+    /// Merge the marked notes into a new note with this title
+    Merge(String),
+}
+
+impl ExCommand {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let line = line.trim();
+        let (name, argument) = match line.split_once(char::is_whitespace) {
+            Some((name, argument)) => (name, Some(argument.trim()).filter(|a| !a.is_empty())),
+            None => (line, None),
+        };
+
+        if let Ok(number) = name.parse::<usize>() {
+            return Ok(ExCommand::Line(number));
+        }
+        match (name, argument) {
+            ("w" | "write", None) => Ok(ExCommand::Write),
+            ("q" | "quit", None) => Ok(ExCommand::Quit),
+            ("wq" | "x" | "xit", None) => Ok(ExCommand::WriteQuit),
+            ("e" | "edit", Some(title)) => Ok(ExCommand::Edit(title.to_string())),
+            ("e" | "edit", None) => Err("Give the title of the note to open".to_string()),
+            ("rename", title) => Ok(ExCommand::Rename(title.map(str::to_string))),
+            ("new", None) => Ok(ExCommand::New),
+            ("sort", Some(argument)) => {
+                let mut words = argument.split_whitespace();
+                let name = words.next().unwrap_or_default();
+                let key =
+                    SortKey::from_name(name).ok_or_else(|| format!("Cannot sort by {}", name))?;
+                let descending = match words.next() {
+                    None | Some("asc") => false,
+                    Some("desc") => true,
+                    Some(order) => return Err(format!("Not an order: {}", order)),
+                };
+                Ok(ExCommand::Sort(key, descending))
+            }
+            ("group", Some(name)) => Grouping::from_name(name)
+                .map(ExCommand::Group)
+                .ok_or_else(|| format!("Cannot group by {}", name)),
+            ("sort" | "group", None) => Err(format!("Give what to {} the notes by", name)),
+            ("cards", Some(name)) => CardLayout::from_name(name)
+                .map(ExCommand::Cards)
+                .ok_or_else(|| format!("Not a card layout: {}", name)),
+            ("cards", None) => Err("Give the card layout: compact, normal or expanded".to_string()),
+            ("folder", folder) => Ok(ExCommand::Folder(
+                folder.unwrap_or_default().trim_matches('/').to_string(),
+            )),
+            ("tag", Some(tag)) => Ok(ExCommand::Tag(tag.trim_start_matches('#').to_string())),
+            ("untag", Some(tag)) => Ok(ExCommand::Untag(tag.trim_start_matches('#').to_string())),
+            ("tag" | "untag", None) => Err("Give the tag".to_string()),
+            ("export", Some(argument)) => {
+                let (format, directory) = match argument.split_once(char::is_whitespace) {
+                    Some((format, directory)) => (format, Some(directory.trim().to_string())),
+                    None => (argument, None),
+                };
+                Ok(ExCommand::Export(format.to_string(), directory))
+            }
+            ("export", None) => Err("Give the format to export to, such as plain".to_string()),
+            ("merge", Some(title)) => Ok(ExCommand::Merge(title.to_string())),
+            ("merge", None) => Err("Give the title of the merged note".to_string()),
+            _ => Err(format!("Not an editor command: {}", line)),
+        }
+    }
+
+    /// Whether the command writes notes, which has to wait while a sync is running
+    pub fn modifies_notes(&self) -> bool {
+        matches!(
+            self,
+            ExCommand::Folder(_) | ExCommand::Tag(_) | ExCommand::Untag(_) | ExCommand::Merge(_)
+        )
+    }
+}
@@ -0,0 +1,24 @@
+pub mod app;
+pub mod cli;
+pub mod clipboard;
+pub mod commands;
+pub mod completion;
+pub mod export;
+pub mod external_editor;
+pub mod import;
+pub mod journal;
+pub mod keymap;
+pub mod links;
+pub mod markdown_edit;
+pub mod models;
+pub mod outline;
+pub mod sorting;
+pub mod spellcheck;
+pub mod stats;
+pub mod storage;
+pub mod sync;
+pub mod templates;
+pub mod theme;
+pub mod tokenizer;
+pub mod ui;
+pub use app::App;
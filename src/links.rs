@@ -0,0 +1,81 @@
+use std::io;
+use std::process::{Command, Stdio};
+
+/// Where a link of the preview leads
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkTarget {
+    /// A heading of the same note, by anchor such as `#getting-started`
+    Heading(String),
+    /// Another note, by title
+    Note(String),
+    /// A web page or e-mail address, opened outside Thoth
+    External(String),
+}
+
+impl LinkTarget {
+    pub fn parse(target: &str) -> Self {
+        let target = target.trim().trim_matches(['<', '>']);
+        if let Some(anchor) = target.strip_prefix('#') {
+            return LinkTarget::Heading(percent_decode(anchor));
+        }
+        if target.contains("://") || target.starts_with("mailto:") {
+            return LinkTarget::External(target.to_string());
+        }
+        // E-mail autolinks show the bare address
+        if target.contains('@') && !target.contains(['/', ' ']) {
+            return LinkTarget::External(format!("mailto:{}", target));
+        }
+
+        let path = target.split('#').next().unwrap_or_default();
+        let path = path.trim_start_matches("./");
+        let file = path.rsplit('/').next().unwrap_or(path);
+        let title = file.strip_suffix(".md").unwrap_or(file);
+        LinkTarget::Note(percent_decode(title))
+    }
+}
+
+/// Open a URL with the program the desktop uses for it
+pub fn open_external(url: &str) -> io::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(target_os = "windows") {
+        // Unlike `cmd /C start`, this passes the URL on without a shell parsing it
+        let mut command = Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler");
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    // Keep the opener from writing over the interface
+    command
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_| ())
+}
+
+/// Decode `%20` and the like, leaving malformed escapes as they are
+pub fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = (bytes[index] == b'%')
+            .then(|| text.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
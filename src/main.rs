@@ -1,15 +1,23 @@
-pub mod app;
-pub mod commands;
-pub mod models;
-pub mod storage;
-pub mod theme;
-pub mod ui;
-pub use app::App;
+use std::io::stdout;
+
+use crossterm::event::{
+    DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+};
+use crossterm::execute;
+use thoth_note::{cli, App};
 
 fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(command) = cli::parse(&args)? {
+        return cli::run(command);
+    }
+
     let terminal = ratatui::init();
+    execute!(stdout(), EnableMouseCapture, EnableBracketedPaste)?;
     let result = App::new().run(terminal);
+    execute!(stdout(), DisableMouseCapture, DisableBracketedPaste)?;
     ratatui::restore();
     result
 }
@@ -0,0 +1,483 @@
+use std::collections::BTreeMap;
+
+use edtui::actions::{Execute, SwitchMode};
+use edtui::{EditorMode, EditorState, Index2, RowIndex};
+use ratatui::text::Span;
+
+use crate::keymap::capture_undo;
+
+/// Inline formatting put around the selection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Bold,
+    Italic,
+    Code,
+    Link,
+}
+
+impl Format {
+    fn delimiters(self) -> (&'static str, &'static str) {
+        match self {
+            Format::Bold => ("**", "**"),
+            Format::Italic => ("_", "_"),
+            Format::Code => ("`", "`"),
+            Format::Link => ("[", "]()"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Marker {
+    Bullet(char),
+    /// Number and the `.` or `)` after it
+    Ordered(usize, char),
+}
+
+/// A list item at the start of a line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ListItem {
+    indent: usize,
+    marker: Marker,
+    /// Columns taken by the marker and the spaces after it
+    marker_width: usize,
+    task: bool,
+    /// Column the text of the item starts at
+    content_start: usize,
+}
+
+impl ListItem {
+    fn parse(line: &[char]) -> Option<Self> {
+        let indent = line.iter().take_while(|&&c| c == ' ').count();
+        let rest = &line[indent..];
+        let (marker, length) = match rest.first()? {
+            &c @ ('-' | '*' | '+') => (Marker::Bullet(c), 1),
+            _ => {
+                let digits = rest.iter().take_while(|c| c.is_ascii_digit()).count();
+                // CommonMark allows up to nine digits
+                if digits == 0 || digits > 9 {
+                    return None;
+                }
+                let delimiter = *rest.get(digits).filter(|&&c| c == '.' || c == ')')?;
+                let number = rest[..digits].iter().collect::<String>().parse().ok()?;
+                (Marker::Ordered(number, delimiter), digits + 1)
+            }
+        };
+
+        // The marker ends the line or is followed by a space, unlike `**bold**` or `---`
+        let after = &rest[length..];
+        if after.first().is_some_and(|&c| c != ' ') {
+            return None;
+        }
+        let spaces = after.iter().take_while(|&&c| c == ' ').count();
+        let marker_width = length + spaces.max(1);
+        let mut content_start = indent + length + spaces;
+
+        let text = &line[content_start..];
+        let task = text.len() >= 3
+            && text[0] == '['
+            && matches!(text[1], ' ' | 'x' | 'X')
+            && text[2] == ']'
+            && text.get(3).is_none_or(|&c| c == ' ');
+        if task {
+            content_start += 3 + usize::from(text.get(3).is_some());
+        }
+        Some(Self {
+            indent,
+            marker,
+            marker_width,
+            task,
+            content_start,
+        })
+    }
+
+    /// Start of the item following this one
+    fn next_prefix(&self) -> String {
+        let marker = match self.marker {
+            Marker::Bullet(c) => c.to_string(),
+            Marker::Ordered(number, delimiter) => format!("{}{}", number + 1, delimiter),
+        };
+        let task = if self.task { "[ ] " } else { "" };
+        format!("{}{} {}", " ".repeat(self.indent), marker, task)
+    }
+}
+
+fn row(editor: &EditorState, index: usize) -> Vec<char> {
+    editor
+        .lines
+        .get(RowIndex::new(index))
+        .cloned()
+        .unwrap_or_default()
+}
+
+fn set_row(editor: &mut EditorState, index: usize, chars: Vec<char>) {
+    if let Some(row) = editor.lines.get_mut(RowIndex::new(index)) {
+        *row = chars;
+    }
+}
+
+/// Continue the list item under the cursor on a new line, or end the list
+/// when the item is empty. Returns whether the line was a list item.
+pub fn continue_list(editor: &mut EditorState) -> bool {
+    let cursor = editor.cursor;
+    let line = row(editor, cursor.row);
+    let Some(item) = ListItem::parse(&line) else {
+        return false;
+    };
+    // Enter within the marker breaks the line as usual
+    if cursor.col < item.content_start.min(line.len()) {
+        return false;
+    }
+
+    capture_undo(editor);
+    if line[item.content_start.min(line.len())..]
+        .iter()
+        .all(|c| c.is_whitespace())
+    {
+        set_row(editor, cursor.row, Vec::new());
+        editor.cursor = Index2::new(cursor.row, 0);
+        return true;
+    }
+
+    let mut line = line;
+    let tail = line.split_off(cursor.col.min(line.len()));
+    let prefix: Vec<char> = item.next_prefix().chars().collect();
+    let mut next = prefix.clone();
+    next.extend(tail.into_iter().skip_while(|&c| c == ' '));
+    set_row(editor, cursor.row, line);
+    editor.lines.insert(RowIndex::new(cursor.row + 1), next);
+    editor.cursor = Index2::new(cursor.row + 1, prefix.len());
+
+    if let Marker::Ordered(..) = item.marker {
+        renumber(editor, cursor.row + 1);
+    }
+    true
+}
+
+/// Indent the list item under the cursor below the item before it, or
+/// outdent it to the level of its parent. Returns whether the line was a list item.
+pub fn indent_item(editor: &mut EditorState, outdent: bool) -> bool {
+    let cursor = editor.cursor;
+    let line = row(editor, cursor.row);
+    let Some(item) = ListItem::parse(&line) else {
+        return false;
+    };
+
+    let previous_item = |accept: &dyn Fn(&ListItem) -> bool| {
+        (0..cursor.row)
+            .rev()
+            .map(|index| row(editor, index))
+            .take_while(|line| !line.iter().all(|c| c.is_whitespace()))
+            .filter_map(|line| ListItem::parse(&line))
+            .find(|other| accept(other))
+    };
+    let indent = if outdent {
+        if item.indent == 0 {
+            return true;
+        }
+        previous_item(&|other| other.indent < item.indent)
+            .map_or(item.indent.saturating_sub(2), |parent| parent.indent)
+    } else {
+        // Nested items line up with the text of the item they belong to
+        previous_item(&|other| other.indent <= item.indent)
+            .filter(|sibling| sibling.indent == item.indent)
+            .map_or(item.indent + 2, |sibling| {
+                item.indent + sibling.marker_width
+            })
+    };
+
+    capture_undo(editor);
+    let mut chars = vec![' '; indent];
+    chars.extend_from_slice(&line[item.indent..]);
+    set_row(editor, cursor.row, chars);
+    editor.cursor.col = (cursor.col + indent).saturating_sub(item.indent);
+    renumber(editor, cursor.row);
+    true
+}
+
+/// Number the ordered items of the list around a line in sequence, each
+/// nested list from 1 and the outermost one from its first number
+fn renumber(editor: &mut EditorState, index: usize) {
+    let is_blank = |line: &[char]| line.iter().all(|c| c.is_whitespace());
+    let mut start = index;
+    while start > 0 && !is_blank(&row(editor, start - 1)) {
+        start -= 1;
+    }
+    let mut end = index;
+    while end + 1 < editor.lines.len() && !is_blank(&row(editor, end + 1)) {
+        end += 1;
+    }
+
+    let items: Vec<(usize, ListItem)> = (start..=end)
+        .filter_map(|index| ListItem::parse(&row(editor, index)).map(|item| (index, item)))
+        .collect();
+    let Some(top) = items.iter().map(|(_, item)| item.indent).min() else {
+        return;
+    };
+
+    // Next number at each indent
+    let mut counters: BTreeMap<usize, usize> = BTreeMap::new();
+    for (index, item) in items {
+        counters.split_off(&(item.indent + 1));
+        let Marker::Ordered(number, delimiter) = item.marker else {
+            counters.remove(&item.indent);
+            continue;
+        };
+        let first = if item.indent == top { number } else { 1 };
+        let expected = *counters.entry(item.indent).or_insert(first);
+        counters.insert(item.indent, expected + 1);
+        if expected == number {
+            continue;
+        }
+
+        let line = row(editor, index);
+        let old_width = number.to_string().len();
+        let mut chars: Vec<char> = line[..item.indent].to_vec();
+        chars.extend(expected.to_string().chars());
+        chars.push(delimiter);
+        chars.extend_from_slice(&line[item.indent + old_width + 1..]);
+        set_row(editor, index, chars);
+
+        if editor.cursor.row == index && editor.cursor.col > item.indent {
+            let new_width = expected.to_string().len();
+            editor.cursor.col = (editor.cursor.col + new_width).saturating_sub(old_width);
+        }
+    }
+}
+
+/// Put Markdown syntax around the selection, or the word under the cursor,
+/// or take it away when it is already there
+pub fn toggle_format(editor: &mut EditorState, format: Format) {
+    let (open, close) = format.delimiters();
+    let open: Vec<char> = open.chars().collect();
+    let close: Vec<char> = close.chars().collect();
+    // An empty note has no line to type on
+    if editor.lines.is_empty() {
+        editor.lines.insert(RowIndex::new(0), Vec::new());
+    }
+
+    // Start and end of the text, the end being exclusive
+    let (start, end) = match editor.selection.take() {
+        Some(selection) if selection.line_mode => {
+            let end_row = selection.end().row;
+            (
+                Index2::new(selection.start().row, 0),
+                Index2::new(end_row, row(editor, end_row).len()),
+            )
+        }
+        Some(selection) => {
+            let end = selection.end();
+            let length = row(editor, end.row).len();
+            (
+                selection.start(),
+                Index2::new(end.row, (end.col + 1).min(length)),
+            )
+        }
+        None => word_at(&row(editor, editor.cursor.row), editor.cursor),
+    };
+    capture_undo(editor);
+
+    let mut first = row(editor, start.row);
+    let mut last = row(editor, end.row);
+    let wrapped = format != Format::Link
+        && start.col >= open.len()
+        && first[start.col - open.len()..start.col] == open[..]
+        && last.get(end.col..end.col + close.len()) == Some(&close[..]);
+
+    if wrapped {
+        last.drain(end.col..end.col + close.len());
+        if start.row == end.row {
+            last.drain(start.col - open.len()..start.col);
+            set_row(editor, start.row, last);
+        } else {
+            first.drain(start.col - open.len()..start.col);
+            set_row(editor, start.row, first);
+            set_row(editor, end.row, last);
+        }
+        editor.cursor = Index2::new(start.row, start.col - open.len());
+    } else {
+        last.splice(end.col..end.col, close.iter().copied());
+        if start.row == end.row {
+            last.splice(start.col..start.col, open.iter().copied());
+            set_row(editor, start.row, last);
+        } else {
+            first.splice(start.col..start.col, open.iter().copied());
+            set_row(editor, start.row, first);
+            set_row(editor, end.row, last);
+        }
+        let shift = if start.row == end.row { open.len() } else { 0 };
+        editor.cursor = Index2::new(end.row, end.col + shift + close.len());
+    }
+
+    if start == end {
+        // Type the text between the delimiters
+        editor.cursor = Index2::new(start.row, start.col + open.len());
+        SwitchMode(EditorMode::Insert).execute(editor);
+    } else if format == Format::Link && !wrapped {
+        // Type the address between the parentheses
+        editor.cursor.col -= 1;
+        SwitchMode(EditorMode::Insert).execute(editor);
+    } else if editor.mode != EditorMode::Insert {
+        editor.mode = EditorMode::Normal;
+        let length = row(editor, editor.cursor.row).len();
+        editor.cursor.col = editor.cursor.col.min(length.saturating_sub(1));
+    }
+}
+
+/// Word under or just before the cursor, or an empty range at the cursor
+/// when there is none
+fn word_at(line: &[char], cursor: Index2) -> (Index2, Index2) {
+    let is_word = |c: &char| c.is_alphanumeric() || *c == '_' || *c == '-' || *c == '\'';
+    let col = cursor.col.min(line.len());
+    let at = if line.get(col).is_some_and(is_word) {
+        col
+    } else if col > 0 && is_word(&line[col - 1]) {
+        col - 1
+    } else {
+        return (Index2::new(cursor.row, col), Index2::new(cursor.row, col));
+    };
+    let start = at - line[..at].iter().rev().take_while(|c| is_word(c)).count();
+    let end = at + line[at..].iter().take_while(|c| is_word(c)).count();
+    (Index2::new(cursor.row, start), Index2::new(cursor.row, end))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Alignment {
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+/// Cells of a table row, without the outer pipes
+fn table_cells(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = match line.strip_suffix('|') {
+        Some(rest) if !rest.ends_with('\\') => rest,
+        _ => line,
+    };
+
+    let mut cells = vec![String::new()];
+    let mut escaped = false;
+    for c in line.chars() {
+        match c {
+            '|' if !escaped => cells.push(String::new()),
+            _ => cells.last_mut().unwrap().push(c),
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    cells
+        .into_iter()
+        .map(|cell| cell.trim().to_string())
+        .collect()
+}
+
+fn delimiter_alignments(line: &str) -> Option<Vec<Alignment>> {
+    let cells = table_cells(line);
+    cells
+        .iter()
+        .map(|cell| {
+            let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+            if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+                return None;
+            }
+            Some(match (cell.starts_with(':'), cell.ends_with(':')) {
+                (true, true) => Alignment::Center,
+                (true, false) => Alignment::Left,
+                (false, true) => Alignment::Right,
+                (false, false) => Alignment::None,
+            })
+        })
+        .collect()
+}
+
+/// Pad the columns of the table under the cursor to the same width.
+/// Returns whether the cursor was on a table.
+pub fn format_table(editor: &mut EditorState) -> bool {
+    let is_table_row = |line: &[char]| line.contains(&'|');
+    let cursor = editor.cursor;
+    if !is_table_row(&row(editor, cursor.row)) {
+        return false;
+    }
+    let mut start = cursor.row;
+    while start > 0 && is_table_row(&row(editor, start - 1)) {
+        start -= 1;
+    }
+    let mut end = cursor.row;
+    while end + 1 < editor.lines.len() && is_table_row(&row(editor, end + 1)) {
+        end += 1;
+    }
+
+    let lines: Vec<String> = (start..=end)
+        .map(|index| row(editor, index).into_iter().collect())
+        .collect();
+    // The header is followed by the delimiter row
+    let Some(alignments) = lines.get(1).and_then(|line| delimiter_alignments(line)) else {
+        return false;
+    };
+    let indent: String = lines[0].chars().take_while(|c| *c == ' ').collect();
+    let mut rows: Vec<Vec<String>> = lines.iter().map(|line| table_cells(line)).collect();
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    for cells in rows.iter_mut() {
+        cells.resize(columns, String::new());
+    }
+
+    let width = |text: &str| Span::raw(text).width();
+    let widths: Vec<usize> = (0..columns)
+        .map(|column| {
+            rows.iter()
+                .enumerate()
+                .filter(|(index, _)| *index != 1)
+                .map(|(_, cells)| width(&cells[column]))
+                .max()
+                .unwrap_or(0)
+                .max(3)
+        })
+        .collect();
+
+    let formatted: Vec<String> = rows
+        .iter()
+        .enumerate()
+        .map(|(index, cells)| {
+            let cells: Vec<String> = cells
+                .iter()
+                .enumerate()
+                .map(|(column, cell)| {
+                    let alignment = alignments.get(column).copied().unwrap_or(Alignment::None);
+                    let column_width = widths[column];
+                    if index == 1 {
+                        return match alignment {
+                            Alignment::None => "-".repeat(column_width),
+                            Alignment::Left => format!(":{}", "-".repeat(column_width - 1)),
+                            Alignment::Right => format!("{}:", "-".repeat(column_width - 1)),
+                            Alignment::Center => format!(":{}:", "-".repeat(column_width - 2)),
+                        };
+                    }
+                    let padding = column_width - width(cell);
+                    let (left, right) = match alignment {
+                        Alignment::Right => (padding, 0),
+                        Alignment::Center => (padding / 2, padding - padding / 2),
+                        Alignment::None | Alignment::Left => (0, padding),
+                    };
+                    format!("{}{}{}", " ".repeat(left), cell, " ".repeat(right))
+                })
+                .collect();
+            format!("{}| {} |", indent, cells.join(" | "))
+        })
+        .collect();
+
+    // Keep the cursor in the same cell
+    let line = &lines[cursor.row - start];
+    let before: String = line.chars().take(cursor.col).collect();
+    let cell = table_cells(&format!("{}x", before)).len().saturating_sub(1);
+    let cell = cell.min(columns.saturating_sub(1));
+    let col =
+        indent.chars().count() + 2 + widths[..cell].iter().map(|width| width + 3).sum::<usize>();
+
+    capture_undo(editor);
+    for (offset, line) in formatted.into_iter().enumerate() {
+        set_row(editor, start + offset, line.chars().collect());
+    }
+    editor.cursor = Index2::new(cursor.row, col);
+    true
+}
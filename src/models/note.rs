@@ -9,6 +9,8 @@ use ratatui::{
 };
 use serde::{Deserialize, Serialize};
 
+use crate::models::text::{count_words, inline_tags};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Note {
     pub title: String,
@@ -17,6 +19,38 @@ pub struct Note {
     pub updated_at: DateTime<Local>,
     pub selected: bool,
     pub order: usize,
+    /// Folder path of the note, using `/` as separator (empty for the root)
+    #[serde(default)]
+    pub folder: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Whether the note is stored encrypted
+    #[serde(default)]
+    pub encrypted: bool,
+    /// Kept at the top of the list whatever it is sorted by
+    #[serde(default)]
+    pub pinned: bool,
+    /// Left out of the list and searches, until restored from the archive
+    #[serde(default)]
+    pub archived: bool,
+    /// Set when the note is encrypted and its content could not be decrypted
+    #[serde(skip)]
+    pub locked: bool,
+    /// Set when a pull left conflict markers in the note that still need resolving
+    #[serde(skip)]
+    pub conflicted: bool,
+    /// Picked in the note list for a batch action
+    #[serde(skip)]
+    pub marked: bool,
+    /// Edited since it was last written to storage
+    #[serde(skip)]
+    pub dirty: bool,
+    /// Tags written in the text, found by [`Note::update_text_info`]
+    #[serde(skip)]
+    pub text_tags: Vec<String>,
+    /// Words in the text, counted by [`Note::update_text_info`]
+    #[serde(skip)]
+    pub words: usize,
 }
 
 impl Default for Note {
@@ -28,13 +62,132 @@ impl Default for Note {
             updated_at: Local::now(),
             selected: false,
             order: 0,
+            folder: String::new(),
+            tags: Vec::new(),
+            encrypted: false,
+            pinned: false,
+            archived: false,
+            locked: false,
+            conflicted: false,
+            marked: false,
+            dirty: false,
+            text_tags: Vec::new(),
+            words: 0,
         }
     }
 }
 
-fn get_created_string(note: &Note) -> String {
-    let now = Local::now();
-    let duration = now.signed_duration_since(note.created_at);
+impl Note {
+    /// Join notes into a new one, in the order given. The merged note takes
+    /// the earliest creation date, the folder of the first note and the tags
+    /// of all of them, and is encrypted when any of them is.
+    pub fn merged(title: &str, notes: &[&Note], order: usize) -> Note {
+        let mut content = notes
+            .iter()
+            .map(|note| note.content.trim_end())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        content.push('\n');
+        let mut tags: Vec<String> = Vec::new();
+        for tag in notes.iter().flat_map(|note| &note.tags) {
+            if !tags.contains(tag) {
+                tags.push(tag.clone());
+            }
+        }
+        let mut merged = Note {
+            title: title.to_string(),
+            content,
+            created_at: notes
+                .iter()
+                .map(|note| note.created_at)
+                .min()
+                .unwrap_or_else(Local::now),
+            folder: notes
+                .first()
+                .map(|note| note.folder.clone())
+                .unwrap_or_default(),
+            tags,
+            order,
+            // Text from an encrypted note never goes to disk in the clear
+            encrypted: notes.iter().any(|note| note.encrypted),
+            ..Note::default()
+        };
+        merged.update_text_info();
+        merged
+    }
+
+    /// Find the tags and count the words of the text, once it is loaded or
+    /// changed, so that drawing the note list does not go through every text
+    pub fn update_text_info(&mut self) {
+        self.text_tags = inline_tags(&self.content);
+        self.words = count_words(&self.content);
+    }
+
+    /// First line of text under the headings, for the expanded card
+    pub fn snippet(&self) -> Option<&str> {
+        if self.locked {
+            return None;
+        }
+        self.content.lines().map(str::trim).find(|line| {
+            !line.is_empty()
+                && !line.starts_with('#')
+                && !line.starts_with("```")
+                && !line.starts_with("---")
+        })
+    }
+
+    /// Tags of the metadata followed by those written in the text
+    pub fn all_tags(&self) -> Vec<String> {
+        let mut tags = self.tags.clone();
+        for tag in &self.text_tags {
+            if !tags.contains(tag) {
+                tags.push(tag.clone());
+            }
+        }
+        tags
+    }
+
+    pub fn has_tags(&self) -> bool {
+        !self.tags.is_empty() || !self.text_tags.is_empty()
+    }
+
+    /// Title with the icons of the note's state
+    pub(crate) fn card_title(&self) -> String {
+        let mut title = if self.conflicted {
+            format!("⚠ {}", self.title)
+        } else if self.locked {
+            format!("🔒 {}", self.title)
+        } else if self.encrypted {
+            format!("🔓 {}", self.title)
+        } else {
+            self.title.clone()
+        };
+        if self.pinned {
+            title.insert_str(0, "📌 ");
+        }
+        if self.marked {
+            title.insert_str(0, "✓ ");
+        }
+        if self.dirty {
+            title.push_str(" ●");
+        }
+        title
+    }
+
+    pub(crate) fn card_color(&self) -> Color {
+        if self.selected {
+            Color::Green
+        } else if self.marked {
+            Color::Magenta
+        } else {
+            Color::Gray
+        }
+    }
+}
+
+/// How long ago a time was, or its date when it is more than a week old
+pub(crate) fn relative_time(time: DateTime<Local>) -> String {
+    let duration = Local::now().signed_duration_since(time);
     if duration.num_seconds() < 60 {
         "just now".to_string()
     } else if duration.num_minutes() < 60 {
@@ -44,19 +197,17 @@ fn get_created_string(note: &Note) -> String {
     } else if duration.num_days() < 7 {
         format!("{}d ago", duration.num_days())
     } else {
-        note.updated_at.format("%Y-%m-%d").to_string()
+        time.format("%Y-%m-%d").to_string()
     }
 }
 
 impl Widget for Note {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let (fg_color, border_style) = if self.selected {
-            (Color::Green, Style::default().fg(Color::Green))
-        } else {
-            (Color::Gray, Style::default().fg(Color::Gray))
-        };
+        let fg_color = self.card_color();
+        let border_style = Style::default().fg(fg_color);
 
-        let created_string = get_created_string(&self);
+        let created_string = relative_time(self.created_at);
+        let title = self.card_title();
 
         Paragraph::new(vec![
             Line::from(created_string).style(Style::default().fg(fg_color))
@@ -65,10 +216,7 @@ impl Widget for Note {
             Block::bordered()
                 .border_style(border_style)
                 .border_set(symbols::border::ROUNDED)
-                .title(
-                    Span::styled(self.title.as_str(), Style::default().fg(fg_color))
-                        .into_centered_line(),
-                )
+                .title(Span::styled(title, Style::default().fg(fg_color)).into_centered_line())
                 .padding(ratatui::widgets::Padding::left(1)),
         )
         .alignment(Alignment::Left)
@@ -0,0 +1,45 @@
+/// Collect `#tags` from note content, ignoring code and headings
+pub fn inline_tags(content: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    let mut in_fence = false;
+    for line in content.lines() {
+        if line.trim_start().starts_with("```") || line.trim_start().starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let mut in_code = false;
+        let mut previous = ' ';
+        let chars: Vec<char> = line.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            if c == '`' {
+                in_code = !in_code;
+            } else if c == '#' && !in_code && previous.is_whitespace() {
+                let tag: String = chars[i + 1..]
+                    .iter()
+                    .take_while(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '/'))
+                    .collect();
+                // Pure numbers such as `#1` are issue references, not tags
+                if tag.chars().any(|c| !c.is_ascii_digit()) && !tags.contains(&tag) {
+                    tags.push(tag.clone());
+                }
+                i += tag.chars().count();
+            }
+            previous = chars[i];
+            i += 1;
+        }
+    }
+    tags
+}
+
+/// Words of a text, leaving out runs of symbols such as list markers
+pub fn count_words(text: &str) -> usize {
+    text.split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .count()
+}
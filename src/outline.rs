@@ -0,0 +1,156 @@
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+
+use crate::ui::markdown_options;
+
+/// Line starting a generated table of contents
+pub const TOC_START: &str = "<!-- toc -->";
+/// Line ending a generated table of contents
+pub const TOC_END: &str = "<!-- /toc -->";
+
+/// A heading of a note, as the preview renders it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    pub level: u8,
+    pub title: String,
+    /// Line of the note the heading starts on
+    pub line: usize,
+}
+
+/// Headings of a note in order.
+///
+/// Parsed with the options of the preview, so lines starting with `#` inside
+/// code blocks are not taken for headings.
+pub fn headings(content: &str) -> Vec<Heading> {
+    let mut headings = Vec::new();
+    let mut current: Option<Heading> = None;
+
+    for (event, range) in Parser::new_ext(content, markdown_options()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                current = Some(Heading {
+                    level: level as u8,
+                    title: String::new(),
+                    line: content[..range.start].matches('\n').count(),
+                });
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some(heading) = current.as_mut() {
+                    heading.title.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::Heading(_)) => headings.extend(current.take()),
+            _ => {}
+        }
+    }
+    headings
+}
+
+/// Index of the heading whose section contains a line of the note
+pub fn section_at(headings: &[Heading], line: usize) -> Option<usize> {
+    headings.iter().rposition(|heading| heading.line <= line)
+}
+
+/// Index after the last heading of the section started by a heading,
+/// which ends at the next heading of the same or a higher level
+pub fn section_end(headings: &[Heading], index: usize) -> usize {
+    let level = headings[index].level;
+    headings[index + 1..]
+        .iter()
+        .position(|heading| heading.level <= level)
+        .map_or(headings.len(), |offset| index + 1 + offset)
+}
+
+/// Anchor of a heading, following the GitHub rules
+pub fn slug(title: &str) -> String {
+    title
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Insert a table of contents of the headings below it, or refresh the existing one.
+///
+/// A new table goes after the title when the note starts with a level 1
+/// heading, otherwise at the top.
+pub fn insert_toc(content: &str) -> String {
+    let mut lines: Vec<&str> = content.lines().collect();
+    let all_headings = headings(content);
+
+    let existing = lines
+        .iter()
+        .position(|line| line.trim() == TOC_START)
+        .and_then(|start| {
+            let end = lines[start..]
+                .iter()
+                .position(|line| line.trim() == TOC_END)?;
+            Some((start, start + end))
+        });
+    let (start, end) = match existing {
+        Some((start, end)) => (start, end + 1),
+        None => {
+            let title = all_headings
+                .first()
+                .filter(|heading| heading.level == 1 && heading.line == 0)
+                .map_or(0, |_| 1);
+            (title, title)
+        }
+    };
+
+    let listed: Vec<&Heading> = all_headings
+        .iter()
+        .filter(|heading| heading.line >= end)
+        .collect();
+    let top_level = listed
+        .iter()
+        .map(|heading| heading.level)
+        .min()
+        .unwrap_or(1);
+
+    let mut toc = vec![TOC_START.to_string()];
+    let mut used_slugs: Vec<String> = Vec::new();
+    for heading in listed {
+        // Repeated titles get numbered anchors
+        let base = slug(&heading.title);
+        let count = used_slugs.iter().filter(|used| **used == base).count();
+        used_slugs.push(base.clone());
+        let anchor = match count {
+            0 => base,
+            count => format!("{}-{}", base, count),
+        };
+        toc.push(format!(
+            "{}- [{}](#{})",
+            "  ".repeat((heading.level - top_level) as usize),
+            heading.title.trim(),
+            anchor
+        ));
+    }
+    toc.push(TOC_END.to_string());
+
+    let toc: Vec<&str> = toc.iter().map(String::as_str).collect();
+    if existing.is_none() {
+        // Keep the table apart from the text around it
+        let mut block = Vec::new();
+        if start > 0 {
+            block.push("");
+        }
+        block.extend(toc);
+        if lines.get(start).is_some_and(|line| !line.trim().is_empty()) {
+            block.push("");
+        }
+        lines.splice(start..end, block);
+    } else {
+        lines.splice(start..end, toc);
+    }
+
+    let mut result = lines.join("\n");
+    if content.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
@@ -0,0 +1,286 @@
+use std::cmp::Ordering;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::models::note::Note;
+use crate::models::text::inline_tags;
+
+/// File in the notes directory keeping the order of the note list
+pub const SORTING_FILE: &str = "sorting.json";
+
+/// What the note list is sorted by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    /// The order notes are moved into with `Alt+Up/Down`
+    #[default]
+    Manual,
+    /// Title, with numbers compared by value
+    Title,
+    Created,
+    Updated,
+    /// Length of the content
+    Size,
+}
+
+impl SortKey {
+    pub fn next(self) -> Self {
+        match self {
+            SortKey::Manual => SortKey::Title,
+            SortKey::Title => SortKey::Created,
+            SortKey::Created => SortKey::Updated,
+            SortKey::Updated => SortKey::Size,
+            SortKey::Size => SortKey::Manual,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortKey::Manual => "manual",
+            SortKey::Title => "title",
+            SortKey::Created => "created",
+            SortKey::Updated => "updated",
+            SortKey::Size => "size",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            SortKey::Manual,
+            SortKey::Title,
+            SortKey::Created,
+            SortKey::Updated,
+            SortKey::Size,
+        ]
+        .into_iter()
+        .find(|key| key.label() == name)
+    }
+}
+
+/// Sections the note list is split into
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Grouping {
+    #[default]
+    None,
+    Folder,
+    /// First tag of each note
+    Tag,
+    /// Month the note was created or updated in, depending on the sort
+    Month,
+}
+
+impl Grouping {
+    pub fn next(self) -> Self {
+        match self {
+            Grouping::None => Grouping::Folder,
+            Grouping::Folder => Grouping::Tag,
+            Grouping::Tag => Grouping::Month,
+            Grouping::Month => Grouping::None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Grouping::None => "none",
+            Grouping::Folder => "folder",
+            Grouping::Tag => "tag",
+            Grouping::Month => "month",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            Grouping::None,
+            Grouping::Folder,
+            Grouping::Tag,
+            Grouping::Month,
+        ]
+        .into_iter()
+        .find(|grouping| grouping.label() == name)
+    }
+}
+
+/// Order and sections of the note list, kept in [`SORTING_FILE`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SortSettings {
+    pub key: SortKey,
+    pub descending: bool,
+    pub grouping: Grouping,
+}
+
+impl SortSettings {
+    /// Order saved in the notes directory, or the manual one
+    pub fn load(root_dir: &Path) -> Self {
+        let path = root_dir.join(SORTING_FILE);
+        let Ok(contents) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        serde_json::from_str(&contents).unwrap_or_else(|e| {
+            warn!("Ignoring invalid sorting in {}: {}", path.display(), e);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, root_dir: &Path) -> io::Result<()> {
+        let path = root_dir.join(SORTING_FILE);
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, json)?;
+        fs::rename(temp_path, path)
+    }
+
+    /// Description for the status line, such as `updated, descending, by folder`
+    pub fn describe(&self) -> String {
+        let mut description = match self.key {
+            SortKey::Manual => "manual order".to_string(),
+            key => key.label().to_string(),
+        };
+        if self.descending {
+            description.push_str(", descending");
+        }
+        if self.grouping != Grouping::None {
+            description.push_str(", by ");
+            description.push_str(self.grouping.label());
+        }
+        description
+    }
+
+    /// Sort notes into groups, and by the key within each group, with the
+    /// pinned notes above all of them.
+    ///
+    /// The `order` of the notes is left as it is, so that the manual order
+    /// comes back when it is picked again.
+    pub fn sort(&self, notes: &mut [Note]) {
+        notes.sort_by(|a, b| {
+            let group = b.pinned.cmp(&a.pinned).then_with(|| self.group_order(a, b));
+            let ordering = match self.key {
+                SortKey::Manual => a.order.cmp(&b.order),
+                SortKey::Title => natural_cmp(&a.title, &b.title),
+                SortKey::Created => a.created_at.cmp(&b.created_at),
+                SortKey::Updated => a.updated_at.cmp(&b.updated_at),
+                SortKey::Size => a.content.len().cmp(&b.content.len()),
+            };
+            let ordering = if self.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            };
+            group
+                .then(ordering)
+                .then_with(|| natural_cmp(&a.title, &b.title))
+        });
+    }
+
+    /// Heading of the section a note is listed in, `None` without grouping
+    pub fn group(&self, note: &Note) -> Option<String> {
+        match self.grouping {
+            Grouping::None => None,
+            // Pinned notes have a section of their own
+            _ if note.pinned => Some("Pinned".to_string()),
+            Grouping::Folder if note.folder.is_empty() => Some("No folder".to_string()),
+            Grouping::Folder => Some(note.folder.clone()),
+            Grouping::Tag => Some(
+                first_tag(note)
+                    .map(|tag| format!("#{}", tag))
+                    .unwrap_or_else(|| "No tag".to_string()),
+            ),
+            Grouping::Month => Some(self.month_date(note).format("%B %Y").to_string()),
+        }
+    }
+
+    /// Heading shown above a note of the sorted list when it is the first
+    /// of its section
+    pub fn header(&self, notes: &[Note], index: usize) -> Option<String> {
+        let group = self.group(notes.get(index)?)?;
+        let previous = index
+            .checked_sub(1)
+            .and_then(|previous| notes.get(previous));
+        match previous {
+            Some(previous) if self.group(previous).as_ref() == Some(&group) => None,
+            _ => Some(group),
+        }
+    }
+
+    /// Order of the sections: folders and tags by name with the notes outside
+    /// of them first, months newest first
+    fn group_order(&self, a: &Note, b: &Note) -> Ordering {
+        match self.grouping {
+            Grouping::None => Ordering::Equal,
+            Grouping::Folder => natural_cmp(&a.folder, &b.folder),
+            Grouping::Tag => match (first_tag(a), first_tag(b)) {
+                (Some(a), Some(b)) => natural_cmp(&a, &b),
+                (a, b) => a.is_some().cmp(&b.is_some()),
+            },
+            Grouping::Month => {
+                let month = |note| self.month_date(note).format("%Y-%m").to_string();
+                month(b).cmp(&month(a))
+            }
+        }
+    }
+
+    fn month_date(&self, note: &Note) -> DateTime<Local> {
+        match self.key {
+            SortKey::Created => note.created_at,
+            _ => note.updated_at,
+        }
+    }
+}
+
+/// Tag of a note's metadata, or the first one written in its text
+fn first_tag(note: &Note) -> Option<String> {
+    note.tags
+        .first()
+        .cloned()
+        .or_else(|| inline_tags(&note.content).into_iter().next())
+}
+
+/// Compare text ignoring case, with runs of digits compared by value so that
+/// `Note 2` comes before `Note 10`
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a = a.chars().peekable();
+    let mut b = b.chars().peekable();
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let x = take_number(&mut a);
+                let y = take_number(&mut b);
+                // Longer runs without leading zeros are larger numbers
+                let ordering = x
+                    .trim_start_matches('0')
+                    .len()
+                    .cmp(&y.trim_start_matches('0').len())
+                    .then_with(|| x.trim_start_matches('0').cmp(y.trim_start_matches('0')))
+                    .then_with(|| x.len().cmp(&y.len()));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(x), Some(y)) => {
+                let ordering = x.to_lowercase().cmp(y.to_lowercase());
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
+
+fn take_number(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut number = String::new();
+    while let Some(c) = chars.next_if(char::is_ascii_digit) {
+        number.push(c);
+    }
+    number
+}
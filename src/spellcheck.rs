@@ -0,0 +1,782 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::hash::{Hash, Hasher};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, RwLock};
+use std::{env, thread};
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// File in the notes directory holding the spell checking settings
+pub const SPELLING_FILE: &str = "spelling.json";
+
+/// Words added to the dictionary, one per line, kept with the notes
+pub const PERSONAL_DICTIONARY_FILE: &str = "dictionary.txt";
+
+/// Most suggestions offered for a word
+const MAX_SUGGESTIONS: usize = 8;
+
+/// Where dictionaries are usually installed
+const DICTIONARY_DIRS: &[&str] = &[
+    "/usr/share/hunspell",
+    "/usr/share/myspell",
+    "/usr/share/myspell/dicts",
+    "/usr/local/share/hunspell",
+    "/Library/Spelling",
+];
+
+/// Language and dictionary used to check spelling, kept in [`SPELLING_FILE`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpellSettings {
+    pub enabled: bool,
+    /// Name of the Hunspell dictionary, such as `en_US` for `en_US.aff` and `en_US.dic`
+    pub language: String,
+    /// Directory searched for the dictionary before the usual ones
+    pub dictionary_dir: Option<PathBuf>,
+}
+
+impl Default for SpellSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            language: "en_US".to_string(),
+            dictionary_dir: None,
+        }
+    }
+}
+
+impl SpellSettings {
+    /// Settings saved in the notes directory, or the default ones
+    pub fn load(root_dir: &Path) -> Self {
+        let path = root_dir.join(SPELLING_FILE);
+        let Ok(contents) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        serde_json::from_str(&contents).unwrap_or_else(|e| {
+            warn!(
+                "Ignoring invalid spelling settings in {}: {}",
+                path.display(),
+                e
+            );
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, root_dir: &Path) -> io::Result<()> {
+        let path = root_dir.join(SPELLING_FILE);
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, json)?;
+        fs::rename(temp_path, path)
+    }
+
+    /// Directories searched for the dictionary, in order
+    fn search_dirs(&self) -> Vec<PathBuf> {
+        let mut dirs: Vec<PathBuf> = self.dictionary_dir.iter().cloned().collect();
+        // Hunspell's own variable for dictionary paths
+        if let Some(paths) = env::var_os("DICPATH") {
+            dirs.extend(env::split_paths(&paths));
+        }
+        if let Some(data) = dirs::data_dir() {
+            dirs.push(data.join("hunspell"));
+        }
+        if let Some(home) = dirs::home_dir() {
+            dirs.push(home.join("Library/Spelling"));
+        }
+        dirs.extend(DICTIONARY_DIRS.iter().map(PathBuf::from));
+        dirs
+    }
+}
+
+/// How flags are written in a dictionary
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FlagFormat {
+    /// One character per flag
+    Char,
+    /// Two characters per flag
+    Long,
+    /// Numbers separated by commas
+    Number,
+}
+
+type Flag = u32;
+
+/// Part of the condition an affix puts on the word it is added to
+#[derive(Debug, Clone)]
+enum Condition {
+    Any,
+    /// One of the characters, or any other than them when negated
+    Set(Vec<char>, bool),
+}
+
+impl Condition {
+    fn parse(pattern: &str) -> Vec<Condition> {
+        if pattern == "." {
+            return Vec::new();
+        }
+        let mut conditions = Vec::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            conditions.push(match c {
+                '.' => Condition::Any,
+                '[' => {
+                    let set: Vec<char> = chars.by_ref().take_while(|&c| c != ']').collect();
+                    match set.split_first() {
+                        Some(('^', rest)) => Condition::Set(rest.to_vec(), true),
+                        _ => Condition::Set(set, false),
+                    }
+                }
+                c => Condition::Set(vec![c], false),
+            });
+        }
+        conditions
+    }
+
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Condition::Any => true,
+            Condition::Set(set, negated) => set.contains(&c) != *negated,
+        }
+    }
+}
+
+/// Prefix or suffix rule of a dictionary
+#[derive(Debug, Clone)]
+struct Affix {
+    flag: Flag,
+    /// Whether the rule combines with affixes of the other kind
+    cross: bool,
+    strip: String,
+    add: String,
+    condition: Vec<Condition>,
+}
+
+impl Affix {
+    /// Stem the suffix was added to
+    fn strip_suffix(&self, word: &str) -> Option<String> {
+        let stem = format!("{}{}", word.strip_suffix(self.add.as_str())?, self.strip);
+        let chars: Vec<char> = stem.chars().collect();
+        let start = chars.len().checked_sub(self.condition.len())?;
+        (!chars.is_empty()
+            && self
+                .condition
+                .iter()
+                .zip(&chars[start..])
+                .all(|(condition, &c)| condition.matches(c)))
+        .then_some(stem)
+    }
+
+    /// Stem the prefix was added to
+    fn strip_prefix(&self, word: &str) -> Option<String> {
+        let stem = format!("{}{}", self.strip, word.strip_prefix(self.add.as_str())?);
+        let chars: Vec<char> = stem.chars().collect();
+        (chars.len() >= self.condition.len()
+            && !chars.is_empty()
+            && self
+                .condition
+                .iter()
+                .zip(&chars)
+                .all(|(condition, &c)| condition.matches(c)))
+        .then_some(stem)
+    }
+}
+
+/// Words of a Hunspell dictionary and the affixes they take
+#[derive(Debug, Default)]
+pub struct Dictionary {
+    words: HashMap<String, Vec<Flag>>,
+    prefixes: Vec<Affix>,
+    suffixes: Vec<Affix>,
+    /// Characters tried when looking for suggestions, most common first
+    try_chars: Vec<char>,
+    /// Common mistakes and their corrections
+    replacements: Vec<(String, String)>,
+    forbidden: Option<Flag>,
+    no_suggest: Option<Flag>,
+    /// Marks stems that are only words with an affix
+    need_affix: Option<Flag>,
+}
+
+impl Dictionary {
+    /// Find and load the dictionary for a language
+    fn find(language: &str, dirs: &[PathBuf]) -> Result<Self, String> {
+        let dir = dirs
+            .iter()
+            .find(|dir| dir.join(format!("{}.dic", language)).is_file())
+            .ok_or_else(|| format!("No dictionary found for {}", language))?;
+        let aff = fs::read(dir.join(format!("{}.aff", language)))
+            .map_err(|e| format!("Failed to read {}.aff: {}", language, e))?;
+        let dic = fs::read(dir.join(format!("{}.dic", language)))
+            .map_err(|e| format!("Failed to read {}.dic: {}", language, e))?;
+        let dictionary = Self::parse(&aff, &dic);
+        info!(
+            "Loaded {} words of the {} dictionary from {}",
+            dictionary.words.len(),
+            language,
+            dir.display()
+        );
+        Ok(dictionary)
+    }
+
+    /// Read the affix and word files, in the encoding the affix file names
+    pub fn parse(aff: &[u8], dic: &[u8]) -> Self {
+        // The encoding is named before any text that depends on it
+        let utf8 = String::from_utf8_lossy(aff)
+            .lines()
+            .find_map(|line| line.strip_prefix("SET "))
+            .is_none_or(|encoding| encoding.trim().eq_ignore_ascii_case("UTF-8"));
+        let decode = |bytes: &[u8]| -> String {
+            if utf8 {
+                String::from_utf8_lossy(bytes).into_owned()
+            } else {
+                // ISO 8859 encodings map their first 256 code points to the same bytes
+                bytes.iter().map(|&b| char::from(b)).collect()
+            }
+        };
+
+        let mut dictionary = Dictionary::default();
+        let mut format = FlagFormat::Char;
+        let mut aliases: Vec<Vec<Flag>> = Vec::new();
+        // Whether the affixes of a flag combine, from the line before their rules
+        let mut cross_products: HashMap<(bool, Flag), bool> = HashMap::new();
+        let aff = decode(aff);
+        for line in aff.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                ["FLAG", "long", ..] => format = FlagFormat::Long,
+                ["FLAG", "num", ..] => format = FlagFormat::Number,
+                ["AF", flags, ..] if flags.parse::<usize>().is_err() => {
+                    aliases.push(parse_flags(flags, format));
+                }
+                ["TRY", chars, ..] => dictionary.try_chars = chars.chars().collect(),
+                ["REP", from, to, ..] => {
+                    let (from, to) = (from.replace('_', " "), to.replace('_', " "));
+                    dictionary.replacements.push((from, to));
+                }
+                ["FORBIDDENWORD", flag, ..] => dictionary.forbidden = first_flag(flag, format),
+                ["NOSUGGEST", flag, ..] => dictionary.no_suggest = first_flag(flag, format),
+                ["NEEDAFFIX", flag, ..] => dictionary.need_affix = first_flag(flag, format),
+                [kind @ ("PFX" | "SFX"), flag, cross @ ("Y" | "N"), count]
+                    if count.parse::<usize>().is_ok() =>
+                {
+                    if let Some(flag) = first_flag(flag, format) {
+                        cross_products.insert((*kind == "PFX", flag), *cross == "Y");
+                    }
+                }
+                [kind @ ("PFX" | "SFX"), flag, strip, add, condition, ..] => {
+                    let Some(flag) = first_flag(flag, format) else {
+                        continue;
+                    };
+                    let cross = cross_products
+                        .get(&(*kind == "PFX", flag))
+                        .copied()
+                        .unwrap_or(false);
+                    let strip = if *strip == "0" { "" } else { strip };
+                    // Continuation classes of two-level affixes are not followed
+                    let add = add.split('/').next().unwrap_or_default();
+                    let add = if add == "0" { "" } else { add };
+                    let affix = Affix {
+                        flag,
+                        cross,
+                        strip: strip.to_string(),
+                        add: add.to_string(),
+                        condition: Condition::parse(condition),
+                    };
+                    if *kind == "PFX" {
+                        dictionary.prefixes.push(affix);
+                    } else {
+                        dictionary.suffixes.push(affix);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // The first line counts the words
+        for line in decode(dic).lines().skip(1) {
+            let entry = line.split(['\t', ' ']).next().unwrap_or_default();
+            let (word, flags) = match entry.split_once('/') {
+                Some((word, flags)) => {
+                    let flags = match flags.parse::<usize>() {
+                        Ok(alias) if !aliases.is_empty() => aliases
+                            .get(alias.wrapping_sub(1))
+                            .cloned()
+                            .unwrap_or_default(),
+                        _ => parse_flags(flags, format),
+                    };
+                    (word, flags)
+                }
+                None => (entry, Vec::new()),
+            };
+            if !word.is_empty() {
+                dictionary
+                    .words
+                    .entry(word.to_string())
+                    .or_default()
+                    .extend(flags);
+            }
+        }
+        if dictionary.try_chars.is_empty() {
+            dictionary.try_chars = ('a'..='z').collect();
+        }
+        dictionary
+    }
+
+    /// Whether a word is spelled correctly, allowing capitals at the start of
+    /// sentences and in headings
+    pub fn check(&self, word: &str) -> bool {
+        let word = word.replace('’', "'");
+        if self.check_form(&word) {
+            return true;
+        }
+        let lower = word.to_lowercase();
+        if lower != word && self.check_form(&lower) {
+            return true;
+        }
+        // Words written in capitals, such as `PARIS` for `Paris`
+        let capitalized = capitalize(&lower);
+        capitalized != word
+            && word.chars().all(|c| !c.is_lowercase())
+            && self.check_form(&capitalized)
+    }
+
+    fn check_form(&self, word: &str) -> bool {
+        if let Some(flags) = self.words.get(word) {
+            if !self.has(flags, self.forbidden) && !self.has(flags, self.need_affix) {
+                return true;
+            }
+        }
+        self.suffixes.iter().any(|suffix| {
+            let Some(stem) = suffix.strip_suffix(word) else {
+                return false;
+            };
+            if self.stem_has(&stem, suffix.flag, None) {
+                return true;
+            }
+            suffix.cross
+                && self
+                    .prefixes
+                    .iter()
+                    .filter(|prefix| prefix.cross)
+                    .any(|prefix| {
+                        prefix.strip_prefix(&stem).is_some_and(|root| {
+                            self.stem_has(&root, suffix.flag, Some(prefix.flag))
+                        })
+                    })
+        }) || self.prefixes.iter().any(|prefix| {
+            prefix
+                .strip_prefix(word)
+                .is_some_and(|stem| self.stem_has(&stem, prefix.flag, None))
+        })
+    }
+
+    /// Whether the stem is a word taking the affixes
+    fn stem_has(&self, stem: &str, flag: Flag, other: Option<Flag>) -> bool {
+        self.words.get(stem).is_some_and(|flags| {
+            flags.contains(&flag)
+                && other.is_none_or(|other| flags.contains(&other))
+                && !self.has(flags, self.forbidden)
+        })
+    }
+
+    fn has(&self, flags: &[Flag], flag: Option<Flag>) -> bool {
+        flag.is_some_and(|flag| flags.contains(&flag))
+    }
+
+    /// Correctly spelled words close to a misspelled one, best first
+    pub fn suggest(&self, word: &str) -> Vec<String> {
+        let word = word.replace('’', "'");
+        let lower = word.to_lowercase();
+        let chars: Vec<char> = lower.chars().collect();
+        let mut candidates: Vec<String> = Vec::new();
+
+        for (from, to) in &self.replacements {
+            for (index, _) in lower.match_indices(from.as_str()) {
+                let mut candidate = lower.clone();
+                candidate.replace_range(index..index + from.len(), to);
+                candidates.push(candidate);
+            }
+        }
+        // Swapped neighbours
+        for i in 1..chars.len() {
+            let mut swapped = chars.clone();
+            swapped.swap(i - 1, i);
+            candidates.push(swapped.iter().collect());
+        }
+        // A wrong, extra or missing character
+        for i in 0..=chars.len() {
+            for &c in &self.try_chars {
+                if i < chars.len() && chars[i] != c {
+                    let mut replaced = chars.clone();
+                    replaced[i] = c;
+                    candidates.push(replaced.iter().collect());
+                }
+                let mut inserted = chars.clone();
+                inserted.insert(i, c);
+                candidates.push(inserted.iter().collect());
+            }
+            if i < chars.len() {
+                let mut removed = chars.clone();
+                removed.remove(i);
+                candidates.push(removed.iter().collect());
+            }
+        }
+        // Two words run together
+        for i in 1..chars.len() {
+            let (first, second): (String, String) =
+                (chars[..i].iter().collect(), chars[i..].iter().collect());
+            if self.check(&first) && self.check(&second) {
+                candidates.push(format!("{} {}", first, second));
+            }
+        }
+
+        let capitalized = word.chars().next().is_some_and(char::is_uppercase);
+        let mut seen = HashSet::new();
+        candidates
+            .into_iter()
+            .filter(|candidate| !candidate.is_empty() && *candidate != lower)
+            .filter(|candidate| candidate.contains(' ') || self.check(candidate))
+            .filter(|candidate| {
+                self.words
+                    .get(candidate.as_str())
+                    .is_none_or(|flags| !self.has(flags, self.no_suggest))
+            })
+            .map(|candidate| {
+                if capitalized {
+                    capitalize(&candidate)
+                } else {
+                    candidate
+                }
+            })
+            .filter(|candidate| seen.insert(candidate.clone()))
+            .take(MAX_SUGGESTIONS)
+            .collect()
+    }
+}
+
+fn parse_flags(flags: &str, format: FlagFormat) -> Vec<Flag> {
+    match format {
+        FlagFormat::Char => flags.chars().map(Flag::from).collect(),
+        FlagFormat::Long => flags
+            .chars()
+            .collect::<Vec<_>>()
+            .chunks(2)
+            .map(|pair| pair.iter().fold(0, |flag, &c| flag << 16 | Flag::from(c)))
+            .collect(),
+        FlagFormat::Number => flags
+            .split(',')
+            .filter_map(|number| number.trim().parse().ok())
+            .collect(),
+    }
+}
+
+fn first_flag(flags: &str, format: FlagFormat) -> Option<Flag> {
+    parse_flags(flags, format).first().copied()
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// A misspelled word of a note, by line and character
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Misspelling {
+    pub row: usize,
+    pub col: usize,
+    pub word: String,
+}
+
+/// Words of a note that are not spelled correctly, leaving out code, links,
+/// tags and other text that is not prose
+pub fn misspellings(text: &str, is_correct: impl Fn(&str) -> bool) -> Vec<Misspelling> {
+    let mut found = Vec::new();
+    let mut in_fence = false;
+    for (row, line) in text.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        let chars: Vec<char> = line.chars().collect();
+        for (col, word) in prose_words(&chars) {
+            if !is_correct(&word) {
+                found.push(Misspelling { row, col, word });
+            }
+        }
+    }
+    found
+}
+
+/// Characters that can be part of a URL, an address or an identifier
+fn is_token_char(c: char) -> bool {
+    c.is_alphanumeric() || "'’-_./:@%?=&#+~".contains(c)
+}
+
+/// Words of a line to check, with the column they start at
+fn prose_words(line: &[char]) -> Vec<(usize, String)> {
+    let mut words = Vec::new();
+    let mut i = 0;
+    while i < line.len() {
+        let rest = &line[i..];
+        let skip_to = |close: &[char], from: usize| {
+            rest[from..]
+                .windows(close.len())
+                .position(|window| window == close)
+                .map(|end| i + from + end + close.len())
+        };
+        let skipped = match rest {
+            // Inline code, up to backticks as many as the opening ones
+            ['`', ..] => {
+                let ticks = rest.iter().take_while(|&&c| c == '`').count();
+                Some(skip_to(&['`'].repeat(ticks), ticks).unwrap_or(i + ticks))
+            }
+            // Link targets, wiki links and autolinks or HTML tags
+            [']', '(', ..] => skip_to(&[')'], 2),
+            ['[', '[', ..] => skip_to(&[']', ']'], 2),
+            ['<', next, ..] if !next.is_whitespace() => skip_to(&['>'], 1),
+            _ => None,
+        };
+        if let Some(next) = skipped {
+            i = next;
+            continue;
+        }
+        if !is_token_char(line[i]) {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        let length = rest.iter().take_while(|&&c| is_token_char(c)).count();
+        i += length;
+        let token: String = rest[..length].iter().collect();
+        let trimmed = token.trim_matches(|c: char| !c.is_alphanumeric());
+        let not_prose = token.contains("://")
+            || token.starts_with("www.")
+            || token.contains('@')
+            || token.starts_with('#')
+            // Emoji shortcodes such as `:tada:`
+            || token.len() > 2 && token.starts_with(':') && token.ends_with(':')
+            // Identifiers such as `file_name` or `Cargo.toml`
+            || trimmed.contains('_')
+            || trimmed.contains('.');
+        if not_prose {
+            continue;
+        }
+
+        // Split on hyphens and slashes, keeping apostrophes within words
+        let mut col = start;
+        for part in rest[..length].split(|&c| !(c.is_alphanumeric() || c == '\'' || c == '’')) {
+            let leading = part.iter().take_while(|&&c| c == '\'' || c == '’').count();
+            let word: String = part[leading..].iter().collect();
+            let word = word.trim_end_matches(['\'', '’']).to_string();
+            let letters = word.chars().filter(|c| c.is_alphabetic()).count();
+            let acronym = word.chars().all(|c| !c.is_lowercase());
+            let mixed_case = word.chars().skip(1).any(char::is_uppercase);
+            if letters > 1
+                && letters == word.chars().filter(|&c| c != '\'' && c != '’').count()
+                && !acronym
+                && !mixed_case
+            {
+                words.push((col + leading, word));
+            }
+            col += part.len() + 1;
+        }
+    }
+    words
+}
+
+/// Word under the cursor, as its first and last column
+pub fn word_at(line: &[char], col: usize) -> Option<(usize, usize)> {
+    let is_word = |c: &char| c.is_alphabetic() || *c == '\'' || *c == '’';
+    let col = if line.get(col).is_some_and(is_word) {
+        col
+    } else {
+        col.checked_sub(1)
+            .filter(|&col| line.get(col).is_some_and(is_word))?
+    };
+    let start = col - line[..col].iter().rev().take_while(|c| is_word(c)).count();
+    let end = col + line[col..].iter().take_while(|c| is_word(c)).count();
+    // Quotes around the word are not part of it
+    let start = start
+        + line[start..end]
+            .iter()
+            .take_while(|c| !c.is_alphabetic())
+            .count();
+    let end = end
+        - line[start..end]
+            .iter()
+            .rev()
+            .take_while(|c| !c.is_alphabetic())
+            .count();
+    (start < end).then_some((start, end))
+}
+
+/// Dictionary and personal words, shared with the checking thread
+#[derive(Default)]
+struct Shared {
+    dictionary: RwLock<Option<Dictionary>>,
+    personal: RwLock<HashSet<String>>,
+    /// Why there is no dictionary
+    error: Mutex<Option<String>>,
+}
+
+impl Shared {
+    fn check(&self, dictionary: &Dictionary, word: &str) -> bool {
+        let personal = self.personal.read().unwrap();
+        personal.contains(word) || personal.contains(&word.to_lowercase()) || dictionary.check(word)
+    }
+}
+
+/// Checks the spelling of the note being edited on a thread of its own, so
+/// that typing does not wait for it
+pub struct SpellChecker {
+    pub settings: SpellSettings,
+    personal_path: PathBuf,
+    shared: Arc<Shared>,
+    jobs: Sender<(u64, String)>,
+    results: Receiver<(u64, Vec<Misspelling>)>,
+    /// Text last sent for checking, and the last one checked
+    requested: Option<u64>,
+    received: Option<u64>,
+}
+
+impl SpellChecker {
+    /// Start loading the dictionary and checking notes
+    pub fn start(settings: SpellSettings, root_dir: &Path) -> Self {
+        let personal_path = root_dir.join(PERSONAL_DICTIONARY_FILE);
+        let shared = Arc::new(Shared::default());
+        if let Ok(contents) = fs::read_to_string(&personal_path) {
+            *shared.personal.write().unwrap() = contents
+                .lines()
+                .map(str::trim)
+                .filter(|word| !word.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+
+        let (jobs, receiver) = mpsc::channel::<(u64, String)>();
+        let (sender, results) = mpsc::channel();
+        let thread_shared = Arc::clone(&shared);
+        let (language, dirs) = (settings.language.clone(), settings.search_dirs());
+        thread::spawn(move || {
+            match Dictionary::find(&language, &dirs) {
+                Ok(dictionary) => *thread_shared.dictionary.write().unwrap() = Some(dictionary),
+                Err(e) => {
+                    warn!("{}", e);
+                    *thread_shared.error.lock().unwrap() = Some(e);
+                }
+            }
+            while let Ok(mut job) = receiver.recv() {
+                // Only the latest text is worth checking
+                while let Ok(newer) = receiver.try_recv() {
+                    job = newer;
+                }
+                let (key, text) = job;
+                let found = match thread_shared.dictionary.read().unwrap().as_ref() {
+                    Some(dictionary) => {
+                        misspellings(&text, |word| thread_shared.check(dictionary, word))
+                    }
+                    None => Vec::new(),
+                };
+                if sender.send((key, found)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            settings,
+            personal_path,
+            shared,
+            jobs,
+            results,
+            requested: None,
+            received: None,
+        }
+    }
+
+    /// Check the text unless it was already
+    pub fn request(&mut self, text: &str) {
+        let mut hasher = DefaultHasher::new();
+        text.hash(&mut hasher);
+        let key = hasher.finish();
+        if self.requested != Some(key) && self.jobs.send((key, text.to_string())).is_ok() {
+            self.requested = Some(key);
+        }
+    }
+
+    /// Whether the text has to be sent again even if it is unchanged
+    pub fn wants_text(&self) -> bool {
+        self.requested.is_none()
+    }
+
+    /// Misspellings of the last text checked, once they are ready
+    pub fn poll(&mut self) -> Option<Vec<Misspelling>> {
+        let (key, found) = self.results.try_iter().last()?;
+        self.received = Some(key);
+        Some(found)
+    }
+
+    /// Whether the checking thread is still on a text
+    pub fn is_waiting(&self) -> bool {
+        self.requested.is_some() && self.requested != self.received
+    }
+
+    /// Forget the text last checked, so that it is checked again
+    pub fn recheck(&mut self) {
+        self.requested = None;
+    }
+
+    /// Whether a word is spelled correctly, `Err` with the reason when there
+    /// is no dictionary to tell
+    pub fn check(&self, word: &str) -> Result<bool, String> {
+        match self.shared.dictionary.read().unwrap().as_ref() {
+            Some(dictionary) => Ok(self.shared.check(dictionary, word)),
+            None => Err(self.unavailable()),
+        }
+    }
+
+    /// Suggestions for a misspelled word
+    pub fn suggest(&self, word: &str) -> Result<Vec<String>, String> {
+        match self.shared.dictionary.read().unwrap().as_ref() {
+            Some(dictionary) => Ok(dictionary.suggest(word)),
+            None => Err(self.unavailable()),
+        }
+    }
+
+    fn unavailable(&self) -> String {
+        self.shared
+            .error
+            .lock()
+            .unwrap()
+            .clone()
+            .unwrap_or_else(|| format!("Loading the {} dictionary", self.settings.language))
+    }
+
+    /// Accept a word from now on, saving it to the personal dictionary
+    pub fn add_word(&mut self, word: &str) -> io::Result<()> {
+        let word = word.replace('’', "'");
+        if !self.shared.personal.write().unwrap().insert(word.clone()) {
+            return Ok(());
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.personal_path)?;
+        writeln!(file, "{}", word)?;
+        self.recheck();
+        Ok(())
+    }
+}
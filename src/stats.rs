@@ -0,0 +1,207 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use chrono::{DateTime, Local, NaiveDate};
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::models::note::Note;
+use crate::models::text::{count_words, inline_tags};
+use crate::ui::markdown_options;
+
+/// File in the notes directory counting the edits of each day
+pub const ACTIVITY_FILE: &str = "activity.json";
+
+/// Reading speed the reading time is estimated with
+const WORDS_PER_MINUTE: usize = 200;
+
+/// Notes listed in each ranking of the dashboard
+const RANKED_NOTES: usize = 10;
+
+/// Counts of what a note is made of
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoteStats {
+    pub words: usize,
+    pub characters: usize,
+    pub lines: usize,
+    pub headings: usize,
+    /// Markdown links and `[[links]]` to other notes
+    pub links: usize,
+    pub open_tasks: usize,
+    pub done_tasks: usize,
+}
+
+impl NoteStats {
+    /// Statistics of a note's Markdown.
+    ///
+    /// Words are counted in the text the preview shows, so markup and link
+    /// targets are left out while code is counted.
+    pub fn of(content: &str) -> Self {
+        let mut stats = NoteStats {
+            characters: content.chars().count(),
+            lines: content.lines().count(),
+            ..NoteStats::default()
+        };
+
+        let mut options = markdown_options();
+        options.insert(Options::ENABLE_TASKLISTS);
+        // Inline markup splits words into several events, so the text of a
+        // block is gathered before it is counted
+        let mut text = String::new();
+        for event in Parser::new_ext(content, options) {
+            let block_end = matches!(
+                event,
+                Event::End(TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::CodeBlock)
+            );
+            match event {
+                Event::Start(Tag::Heading { .. }) => {
+                    stats.headings += 1;
+                    text.push(' ');
+                }
+                Event::Start(Tag::Link { .. }) => stats.links += 1,
+                Event::Start(
+                    Tag::Emphasis | Tag::Strong | Tag::Strikethrough | Tag::Image { .. },
+                ) => {}
+                // Tight list items hold no paragraph, so their text would run
+                // into the list or code block nested in them
+                Event::Start(_) => text.push(' '),
+                Event::TaskListMarker(true) => stats.done_tasks += 1,
+                Event::TaskListMarker(false) => stats.open_tasks += 1,
+                Event::Text(fragment) | Event::Code(fragment) => text.push_str(&fragment),
+                Event::SoftBreak | Event::HardBreak | Event::End(_) => text.push(' '),
+                _ => {}
+            }
+            if block_end {
+                stats.words += count_words(&text);
+                stats.links += text.matches("[[").count();
+                text.clear();
+            }
+        }
+        stats.words += count_words(&text);
+        stats.links += text.matches("[[").count();
+        stats
+    }
+
+    /// Minutes it takes to read the note, rounded up
+    pub fn reading_minutes(&self) -> usize {
+        self.words.div_ceil(WORDS_PER_MINUTE)
+    }
+}
+
+/// Statistics of the whole vault, shown on the dashboard
+#[derive(Debug, Clone, Default)]
+pub struct VaultStats {
+    pub notes: usize,
+    pub words: usize,
+    pub folders: usize,
+    pub tags: usize,
+    pub open_tasks: usize,
+    pub done_tasks: usize,
+    /// Notes edited last, with when they were
+    pub recent: Vec<(String, DateTime<Local>)>,
+    /// Notes with the most words, with their word counts
+    pub largest: Vec<(String, usize)>,
+}
+
+impl VaultStats {
+    pub fn collect(notes: &[Note]) -> Self {
+        let mut vault = VaultStats {
+            notes: notes.len(),
+            ..VaultStats::default()
+        };
+        let mut folders = HashSet::new();
+        let mut tags = HashSet::new();
+        let mut sizes = Vec::with_capacity(notes.len());
+        for note in notes {
+            let stats = NoteStats::of(&note.content);
+            vault.words += stats.words;
+            vault.open_tasks += stats.open_tasks;
+            vault.done_tasks += stats.done_tasks;
+            if !note.folder.is_empty() {
+                folders.insert(note.folder.as_str());
+            }
+            tags.extend(note.tags.iter().cloned().chain(inline_tags(&note.content)));
+            // Locked notes have no content to measure
+            if !note.locked {
+                sizes.push((note.title.clone(), stats.words));
+            }
+        }
+        vault.folders = folders.len();
+        vault.tags = tags.len();
+
+        let mut recent: Vec<&Note> = notes.iter().collect();
+        recent.sort_by_key(|note| std::cmp::Reverse(note.updated_at));
+        vault.recent = recent
+            .into_iter()
+            .take(RANKED_NOTES)
+            .map(|note| (note.title.clone(), note.updated_at))
+            .collect();
+
+        sizes.sort_by_key(|(_, words)| std::cmp::Reverse(*words));
+        sizes.truncate(RANKED_NOTES);
+        vault.largest = sizes;
+        vault
+    }
+}
+
+/// Number of times notes were edited on each day
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Activity {
+    days: BTreeMap<NaiveDate, usize>,
+}
+
+impl Activity {
+    /// Activity saved in the notes directory.
+    ///
+    /// Before anything is recorded, it starts from the days the notes were
+    /// created and last updated on.
+    pub fn load(root_dir: &Path, notes: &[Note]) -> Self {
+        let path = root_dir.join(ACTIVITY_FILE);
+        let Ok(contents) = fs::read_to_string(&path) else {
+            return Self::from_notes(notes);
+        };
+        serde_json::from_str(&contents).unwrap_or_else(|e| {
+            warn!("Ignoring invalid activity in {}: {}", path.display(), e);
+            Self::from_notes(notes)
+        })
+    }
+
+    fn from_notes(notes: &[Note]) -> Self {
+        let mut activity = Self::default();
+        for note in notes {
+            activity.record(note.created_at.date_naive());
+            if note.updated_at.date_naive() != note.created_at.date_naive() {
+                activity.record(note.updated_at.date_naive());
+            }
+        }
+        activity
+    }
+
+    pub fn save(&self, root_dir: &Path) -> io::Result<()> {
+        let path = root_dir.join(ACTIVITY_FILE);
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, json)?;
+        fs::rename(temp_path, path)
+    }
+
+    pub fn record(&mut self, date: NaiveDate) {
+        *self.days.entry(date).or_default() += 1;
+    }
+
+    pub fn edits(&self, date: NaiveDate) -> usize {
+        self.days.get(&date).copied().unwrap_or(0)
+    }
+
+    /// Most edits made on a single day since a date
+    pub fn busiest_since(&self, date: NaiveDate) -> usize {
+        self.days
+            .range(date..)
+            .map(|(_, &edits)| edits)
+            .max()
+            .unwrap_or(0)
+    }
+}
@@ -0,0 +1,112 @@
+use argon2::Argon2;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+    Key, XChaCha20Poly1305, XNonce,
+};
+
+use crate::storage::error::{StorageError, StorageResult};
+
+/// First line of an encrypted note file
+pub const HEADER: &str = "thoth-encrypted:v1";
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+/// Plaintext encrypted into the vault config to check a passphrase
+const VERIFIER: &[u8] = b"thoth";
+
+/// Key derived from the vault passphrase
+pub struct VaultKey {
+    cipher: XChaCha20Poly1305,
+}
+
+impl VaultKey {
+    /// Derive a key from a passphrase with Argon2id
+    pub fn derive(passphrase: &str, salt: &[u8]) -> StorageResult<Self> {
+        let mut key = Key::default();
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| StorageError::Crypto(e.to_string()))?;
+        Ok(Self {
+            cipher: XChaCha20Poly1305::new(&key),
+        })
+    }
+
+    /// Encrypt and authenticate data, returning the nonce followed by the ciphertext
+    pub fn encrypt(&self, plaintext: &[u8]) -> StorageResult<Vec<u8>> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|e| StorageError::Crypto(e.to_string()))?;
+
+        let mut output = nonce.to_vec();
+        output.extend(ciphertext);
+        Ok(output)
+    }
+
+    /// Decrypt data produced by [`VaultKey::encrypt`], failing if it was tampered with
+    pub fn decrypt(&self, data: &[u8]) -> StorageResult<Vec<u8>> {
+        if data.len() < NONCE_LEN {
+            return Err(StorageError::Crypto("ciphertext too short".to_string()));
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        self.cipher
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| StorageError::WrongPassphrase)
+    }
+
+    /// Encrypt note content into the text stored on disk
+    pub fn seal(&self, content: &str) -> StorageResult<String> {
+        let sealed = self.encrypt(content.as_bytes())?;
+        Ok(format!("{}\n{}\n", HEADER, STANDARD.encode(sealed)))
+    }
+
+    /// Decrypt the text stored on disk back into note content
+    pub fn open(&self, stored: &str) -> StorageResult<String> {
+        let encoded = stored
+            .strip_prefix(HEADER)
+            .ok_or_else(|| StorageError::Crypto("missing encryption header".to_string()))?
+            .trim();
+        let sealed = STANDARD
+            .decode(encoded)
+            .map_err(|e| StorageError::Crypto(e.to_string()))?;
+        String::from_utf8(self.decrypt(&sealed)?).map_err(|e| StorageError::Crypto(e.to_string()))
+    }
+
+    /// Encrypted marker stored in the vault config
+    pub fn verifier(&self) -> StorageResult<String> {
+        Ok(STANDARD.encode(self.encrypt(VERIFIER)?))
+    }
+
+    /// Check the marker written by [`VaultKey::verifier`]
+    pub fn verify(&self, verifier: &str) -> StorageResult<()> {
+        let sealed = STANDARD
+            .decode(verifier)
+            .map_err(|e| StorageError::Crypto(e.to_string()))?;
+        match self.decrypt(&sealed)? {
+            plaintext if plaintext == VERIFIER => Ok(()),
+            _ => Err(StorageError::WrongPassphrase),
+        }
+    }
+}
+
+/// Whether stored note content is encrypted
+pub fn is_sealed(stored: &str) -> bool {
+    stored.starts_with(HEADER)
+}
+
+/// Generate a random salt for key derivation, base64 encoded
+pub fn random_salt() -> String {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    STANDARD.encode(salt)
+}
+
+/// Decode a salt produced by [`random_salt`]
+pub fn decode_salt(salt: &str) -> StorageResult<Vec<u8>> {
+    STANDARD
+        .decode(salt)
+        .map_err(|e| StorageError::Crypto(e.to_string()))
+}
@@ -14,6 +14,21 @@ pub enum StorageError {
 
     #[error("Failed to parse note metadata: {0}")]
     MetadataParse(String),
+
+    #[error("Note {0} is encrypted and the vault is locked")]
+    Locked(String),
+
+    #[error("Wrong passphrase")]
+    WrongPassphrase,
+
+    #[error("Encryption error: {0}")]
+    Crypto(String),
+
+    #[error("Git error: {0}")]
+    Git(String),
+
+    #[error("Operation not supported by this storage")]
+    Unsupported,
 }
 
 pub type StorageResult<T> = Result<T, StorageError>;
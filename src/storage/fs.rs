@@ -1,13 +1,31 @@
 use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 use std::fs::{self, File};
-use std::io::{Read, Write};
+use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
 
 use crate::models::note::Note;
+use crate::storage::crypto::{self, VaultKey};
 use crate::storage::error::{StorageError, StorageResult};
 use crate::storage::Storage;
 
+/// Directory inside the root directory holding note attachments
+const ATTACHMENTS_DIR: &str = "attachments";
+
+/// File inside the root directory holding the encryption settings
+const VAULT_CONFIG_FILE: &str = "vault.json";
+
+/// Extension of re-encrypted note files waiting to replace the originals
+const REKEY_EXTENSION: &str = "md.rekey";
+
+/// Name of the file holding a note's content
+pub(crate) fn note_file_name(title: &str) -> String {
+    // Sanitize the title to be a valid filename
+    let sanitized = title.replace("/", "_").replace("\\", "_");
+    format!("{}.md", sanitized)
+}
+
 /// Metadata for a note stored in the file system
 #[derive(Debug, Serialize, Deserialize)]
 struct NoteMetadata {
@@ -15,11 +33,38 @@ struct NoteMetadata {
     created_at: DateTime<Local>,
     updated_at: DateTime<Local>,
     order: usize,
+    #[serde(default)]
+    folder: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    encrypted: bool,
+    #[serde(default)]
+    pinned: bool,
+    #[serde(default)]
+    archived: bool,
+}
+
+/// Encryption settings of a vault, written once a passphrase is set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct VaultConfig {
+    /// Salt for deriving the key from the passphrase
+    salt: String,
+    /// Known plaintext encrypted with the key, used to check passphrases
+    verifier: String,
+    /// Encrypt every note, not only the ones marked as encrypted
+    #[serde(default)]
+    encrypt_all: bool,
 }
 
 /// File system implementation of the Storage trait
 pub struct FSStorage {
     root_dir: PathBuf,
+    /// Key for encrypted notes, set once the vault is unlocked
+    key: RefCell<Option<VaultKey>>,
+    /// Vault config as last read or written, so writing a note needs no extra
+    /// read. Loaded when first needed and again when the vault is unlocked.
+    vault: RefCell<Option<Option<VaultConfig>>>,
 }
 
 impl Default for FSStorage {
@@ -33,21 +78,26 @@ impl FSStorage {
     pub fn new() -> Self {
         let home_dir = dirs::home_dir().expect("Failed to get home directory");
         let root_dir = home_dir.join(".rnote");
-        Self { root_dir }
+        Self::with_root_dir(root_dir)
     }
 
     /// Create a new FSStorage with a custom root directory
     pub fn with_root_dir<P: AsRef<Path>>(root_dir: P) -> Self {
         Self {
             root_dir: root_dir.as_ref().to_path_buf(),
+            key: RefCell::new(None),
+            vault: RefCell::new(None),
         }
     }
 
+    /// Directory holding the notes
+    pub fn root_dir(&self) -> &Path {
+        &self.root_dir
+    }
+
     /// Get the path to a note file
     fn get_note_path(&self, title: &str) -> PathBuf {
-        // Sanitize the title to be a valid filename
-        let sanitized = title.replace("/", "_").replace("\\", "_");
-        self.root_dir.join(format!("{}.md", sanitized))
+        self.root_dir.join(note_file_name(title))
     }
 
     /// Get the path to a note's metadata file
@@ -88,6 +138,140 @@ impl FSStorage {
 
         Ok(())
     }
+
+    /// Write a file through a temporary file so it is never left half written
+    fn write_atomic(path: &Path, contents: &[u8]) -> StorageResult<()> {
+        let mut extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_string())
+            .unwrap_or_default();
+        extension.push_str(".tmp");
+        let temp_path = path.with_extension(extension);
+
+        let mut file = File::create(&temp_path)?;
+        file.write_all(contents)?;
+        file.flush()?;
+        fs::rename(temp_path, path)?;
+        Ok(())
+    }
+
+    fn read_vault_config(&self) -> StorageResult<Option<VaultConfig>> {
+        if let Some(config) = self.vault.borrow().as_ref() {
+            return Ok(config.clone());
+        }
+        let config = self.load_vault_config()?;
+        *self.vault.borrow_mut() = Some(config.clone());
+        Ok(config)
+    }
+
+    fn load_vault_config(&self) -> StorageResult<Option<VaultConfig>> {
+        let path = self.root_dir.join(VAULT_CONFIG_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(path)?;
+        serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|e| StorageError::MetadataParse(e.to_string()))
+    }
+
+    fn write_vault_config(&self, config: &VaultConfig) -> StorageResult<()> {
+        let json = serde_json::to_string_pretty(config)
+            .map_err(|e| StorageError::MetadataParse(e.to_string()))?;
+        Self::write_atomic(&self.root_dir.join(VAULT_CONFIG_FILE), json.as_bytes())?;
+        *self.vault.borrow_mut() = Some(Some(config.clone()));
+        Ok(())
+    }
+
+    /// Paths of all note content files
+    fn note_paths(&self) -> StorageResult<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(&self.root_dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "md") {
+                paths.push(path);
+            }
+        }
+        Ok(paths)
+    }
+
+    /// Paths of re-encrypted files left behind by an interrupted passphrase change
+    fn rekey_paths(&self) -> StorageResult<Vec<PathBuf>> {
+        let suffix = format!(".{}", REKEY_EXTENSION);
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(&self.root_dir)? {
+            let path = entry?.path();
+            if path.to_string_lossy().ends_with(&suffix) {
+                paths.push(path);
+            }
+        }
+        Ok(paths)
+    }
+
+    /// Finish or roll back an interrupted passphrase change.
+    ///
+    /// Re-encrypted files only replace the originals after the new vault
+    /// config is written, so files the current key can open belong to a
+    /// committed change and the rest are leftovers of an aborted one.
+    fn recover_rekey(&self, key: &VaultKey) -> StorageResult<()> {
+        for path in self.rekey_paths()? {
+            let stored = fs::read_to_string(&path)?;
+            if key.open(&stored).is_ok() {
+                fs::rename(&path, path.with_extension(""))?;
+            } else {
+                fs::remove_file(&path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a note's content should be encrypted on disk
+    fn should_encrypt(&self, note: &Note) -> StorageResult<bool> {
+        if note.encrypted {
+            return Ok(true);
+        }
+        Ok(self
+            .read_vault_config()?
+            .is_some_and(|config| config.encrypt_all))
+    }
+
+    /// Build a placeholder for a note that cannot be decrypted
+    fn locked_note(&self, title: &str) -> StorageResult<Note> {
+        let metadata = self.read_metadata(title)?;
+        Ok(Note {
+            title: metadata.title,
+            content: String::new(),
+            created_at: metadata.created_at,
+            updated_at: metadata.updated_at,
+            selected: false,
+            order: metadata.order,
+            folder: metadata.folder,
+            tags: metadata.tags,
+            encrypted: true,
+            pinned: metadata.pinned,
+            archived: metadata.archived,
+            locked: true,
+            conflicted: false,
+            marked: false,
+            dirty: false,
+            text_tags: Vec::new(),
+            words: 0,
+        })
+    }
+
+    fn metadata_for(note: &Note) -> NoteMetadata {
+        NoteMetadata {
+            title: note.title.clone(),
+            created_at: note.created_at,
+            updated_at: note.updated_at,
+            order: note.order,
+            folder: note.folder.clone(),
+            tags: note.tags.clone(),
+            encrypted: note.encrypted,
+            pinned: note.pinned,
+            archived: note.archived,
+        }
+    }
 }
 
 impl Storage for FSStorage {
@@ -113,9 +297,15 @@ impl Storage for FSStorage {
                 if let Some(filename) = path.file_stem() {
                     let title = filename.to_string_lossy().to_string();
 
-                    // Try to read the note
-                    if let Ok(note) = self.read_note(&title) {
-                        notes.push(note);
+                    // Try to read the note, keeping encrypted notes as locked placeholders
+                    match self.read_note(&title) {
+                        Ok(note) => notes.push(note),
+                        Err(StorageError::Locked(_)) => {
+                            if let Ok(note) = self.locked_note(&title) {
+                                notes.push(note);
+                            }
+                        }
+                        Err(_) => {}
                     }
                 }
             }
@@ -128,6 +318,20 @@ impl Storage for FSStorage {
     }
 
     fn read_note(&self, title: &str) -> StorageResult<Note> {
+        let mut note = self.read_stored_note(title)?;
+
+        if crypto::is_sealed(&note.content) {
+            note.content = match self.key.borrow().as_ref() {
+                Some(key) => key.open(&note.content)?,
+                None => return Err(StorageError::Locked(title.to_string())),
+            };
+        }
+        note.update_text_info();
+
+        Ok(note)
+    }
+
+    fn read_stored_note(&self, title: &str) -> StorageResult<Note> {
         let path = self.get_note_path(title);
         let metadata_path = self.get_metadata_path(title);
 
@@ -151,38 +355,76 @@ impl Storage for FSStorage {
             updated_at: metadata.updated_at,
             selected: false,
             order: metadata.order,
+            folder: metadata.folder,
+            tags: metadata.tags,
+            encrypted: metadata.encrypted,
+            pinned: metadata.pinned,
+            archived: metadata.archived,
+            locked: false,
+            conflicted: false,
+            marked: false,
+            dirty: false,
+            text_tags: Vec::new(),
+            words: 0,
         })
     }
 
     fn write_note(&self, note: &Note) -> StorageResult<()> {
         self.init()?;
 
-        let path = self.get_note_path(&note.title);
+        // A locked note has no content in memory, only its metadata can change
+        if note.locked {
+            if !self.get_note_path(&note.title).exists() {
+                return Err(StorageError::Locked(note.title.clone()));
+            }
+            return self.write_metadata(&Self::metadata_for(note));
+        }
 
-        // Create a temporary file for atomic write
-        let temp_path = path.with_extension("md.tmp");
-        let mut file = File::create(&temp_path)?;
+        let path = self.get_note_path(&note.title);
 
-        // Write content
-        file.write_all(note.content.as_bytes())?;
-        file.flush()?;
+        let content = if self.should_encrypt(note)? {
+            let key = self.key.borrow();
+            let Some(key) = key.as_ref() else {
+                return Err(StorageError::Locked(note.title.clone()));
+            };
+            // Sealing takes a fresh nonce, which would make an unchanged note
+            // look edited to git and sync
+            match fs::read_to_string(&path) {
+                Ok(stored)
+                    if crypto::is_sealed(&stored)
+                        && key.open(&stored).is_ok_and(|old| old == note.content) =>
+                {
+                    None
+                }
+                _ => Some(key.seal(&note.content)?),
+            }
+        } else {
+            Some(note.content.clone())
+        };
 
-        // Rename for atomic write
-        fs::rename(&temp_path, &path)?;
+        // Write content through a temporary file for atomic write
+        if let Some(content) = content {
+            Self::write_atomic(&path, content.as_bytes())?;
+        }
 
         // Write metadata
-        let metadata = NoteMetadata {
-            title: note.title.clone(),
-            created_at: note.created_at,
-            updated_at: note.updated_at,
-            order: note.order,
-        };
-
-        self.write_metadata(&metadata)?;
+        self.write_metadata(&Self::metadata_for(note))?;
 
         Ok(())
     }
 
+    fn write_stored_note(&self, note: &Note) -> StorageResult<()> {
+        self.init()?;
+        Self::write_atomic(&self.get_note_path(&note.title), note.content.as_bytes())?;
+        self.write_metadata(&Self::metadata_for(note))
+    }
+
+    fn plain_note_path(&self, note: &Note) -> Option<PathBuf> {
+        // Encrypted files have to go through a decrypted copy
+        let encrypted = self.should_encrypt(note).unwrap_or(true);
+        (!encrypted).then(|| self.get_note_path(&note.title))
+    }
+
     fn delete_note(&self, title: &str) -> StorageResult<()> {
         let path = self.get_note_path(title);
         let metadata_path = self.get_metadata_path(title);
@@ -203,4 +445,157 @@ impl Storage for FSStorage {
 
         Ok(())
     }
+
+    fn write_attachment(&self, name: &str, data: &[u8]) -> StorageResult<String> {
+        let dir = self.root_dir.join(ATTACHMENTS_DIR);
+        if !dir.exists() {
+            fs::create_dir_all(&dir).map_err(|_| StorageError::DirectoryCreation(dir.clone()))?;
+        }
+
+        let sanitized = name.replace("/", "_").replace("\\", "_");
+        let path = Path::new(&sanitized);
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let extension = path
+            .extension()
+            .map(|ext| format!(".{}", ext.to_string_lossy()))
+            .unwrap_or_default();
+
+        for counter in 1.. {
+            let candidate = if counter == 1 {
+                sanitized.clone()
+            } else {
+                format!("{} ({}){}", stem, counter, extension)
+            };
+            let target = dir.join(&candidate);
+            match fs::read(&target) {
+                // The same file imported again
+                Ok(existing) if existing == data => {}
+                Ok(_) => continue,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => fs::write(&target, data)?,
+                Err(e) => return Err(e.into()),
+            }
+            return Ok(format!("{}/{}", ATTACHMENTS_DIR, candidate));
+        }
+        unreachable!("attachment names ran out")
+    }
+
+    fn write_notes_metadata(&self, notes: &[&Note]) -> StorageResult<()> {
+        self.init()?;
+        // Metadata without a note file would show up as an empty note
+        if let Some(missing) = notes
+            .iter()
+            .find(|note| !self.get_note_path(&note.title).exists())
+        {
+            return Err(StorageError::NoteNotFound(missing.title.clone()));
+        }
+        notes
+            .iter()
+            .try_for_each(|note| self.write_metadata(&Self::metadata_for(note)))
+    }
+
+    fn rename_note(&self, old_title: &str, note: &Note) -> StorageResult<()> {
+        if !note.locked {
+            self.delete_note(old_title)?;
+            return self.write_note(note);
+        }
+
+        // Locked content cannot be rewritten, so move the encrypted file as is
+        let old_path = self.get_note_path(old_title);
+        if !old_path.exists() {
+            return Err(StorageError::NoteNotFound(old_title.to_string()));
+        }
+        fs::rename(old_path, self.get_note_path(&note.title))?;
+        fs::remove_file(self.get_metadata_path(old_title))?;
+        self.write_metadata(&Self::metadata_for(note))
+    }
+
+    fn has_passphrase(&self) -> bool {
+        self.read_vault_config()
+            .is_ok_and(|config| config.is_some())
+    }
+
+    fn is_unlocked(&self) -> bool {
+        self.key.borrow().is_some()
+    }
+
+    fn unlock(&self, passphrase: &str) -> StorageResult<()> {
+        // Another device may have changed the passphrase or settings since
+        self.vault.replace(None);
+        let config = self
+            .read_vault_config()?
+            .ok_or_else(|| StorageError::Crypto("no passphrase has been set".to_string()))?;
+
+        let key = VaultKey::derive(passphrase, &crypto::decode_salt(&config.salt)?)?;
+        key.verify(&config.verifier)?;
+        self.recover_rekey(&key)?;
+
+        *self.key.borrow_mut() = Some(key);
+        Ok(())
+    }
+
+    fn set_passphrase(&self, passphrase: &str) -> StorageResult<()> {
+        self.init()?;
+
+        let salt = crypto::random_salt();
+        let new_key = VaultKey::derive(passphrase, &crypto::decode_salt(&salt)?)?;
+        let old_config = self.read_vault_config()?;
+
+        // Re-encrypt every encrypted note next to the original
+        let mut rekeyed = Vec::new();
+        if old_config.is_some() {
+            let key = self.key.borrow();
+            let old_key = key
+                .as_ref()
+                .ok_or_else(|| StorageError::Locked("vault".to_string()))?;
+
+            for path in self.note_paths()? {
+                let stored = fs::read_to_string(&path)?;
+                if crypto::is_sealed(&stored) {
+                    let content = old_key.open(&stored)?;
+                    let rekey_path = path.with_extension(REKEY_EXTENSION);
+                    Self::write_atomic(&rekey_path, new_key.seal(&content)?.as_bytes())?;
+                    rekeyed.push(rekey_path);
+                }
+            }
+        }
+
+        // Writing the config commits the change, see `recover_rekey`
+        let config = VaultConfig {
+            salt,
+            verifier: new_key.verifier()?,
+            encrypt_all: old_config.is_some_and(|config| config.encrypt_all),
+        };
+        self.write_vault_config(&config)?;
+
+        for path in rekeyed {
+            fs::rename(&path, path.with_extension(""))?;
+        }
+
+        *self.key.borrow_mut() = Some(new_key);
+        Ok(())
+    }
+
+    fn encrypts_all(&self) -> bool {
+        self.read_vault_config()
+            .is_ok_and(|config| config.is_some_and(|config| config.encrypt_all))
+    }
+
+    fn set_encrypt_all(&self, enabled: bool) -> StorageResult<()> {
+        let mut config = self
+            .read_vault_config()?
+            .ok_or_else(|| StorageError::Crypto("no passphrase has been set".to_string()))?;
+        if !self.is_unlocked() {
+            return Err(StorageError::Locked("vault".to_string()));
+        }
+
+        let notes = self.list_notes()?;
+        config.encrypt_all = enabled;
+        self.write_vault_config(&config)?;
+
+        // Rewrite every note so its content matches the new setting
+        for note in notes {
+            self.write_note(&note)?;
+        }
+        Ok(())
+    }
 }
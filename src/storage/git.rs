@@ -0,0 +1,459 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use chrono::{DateTime, Local};
+
+use crate::storage::crypto;
+use crate::storage::error::{StorageError, StorageResult};
+use crate::storage::fs::note_file_name;
+
+/// Name of the remote used for pulling and pushing
+const REMOTE: &str = "origin";
+
+/// Files which are never committed: temporary files and the machine specific
+/// sync state and layout
+const GITIGNORE: &str =
+    "*.tmp\n*.rekey\nsync.json\nlayout.json\nkeymap.json\nspelling.json\nactivity.json\nsorting.json\n";
+
+/// Identity used for commits when git has none configured
+const FALLBACK_NAME: &str = "Thoth";
+const FALLBACK_EMAIL: &str = "thoth@localhost";
+
+/// A commit touching a note
+#[derive(Debug, Clone)]
+pub struct Revision {
+    pub id: String,
+    pub date: DateTime<Local>,
+    pub summary: String,
+    /// Path of the note's content file at this revision, relative to the vault
+    pub path: String,
+}
+
+impl Revision {
+    pub fn short_id(&self) -> &str {
+        &self.id[..self.id.len().min(8)]
+    }
+}
+
+/// Result of pulling from the remote
+#[derive(Debug, Default)]
+pub struct PullOutcome {
+    /// Whether any remote changes were merged
+    pub updated: bool,
+    /// Titles of notes left with conflict markers to resolve by hand
+    pub conflicts: Vec<String>,
+    /// Notes whose conflicts could not be merged line by line and kept the local version
+    pub kept_local: Vec<String>,
+}
+
+/// Notes directory versioned with git, driving the `git` command line tool
+pub struct GitRepo {
+    root_dir: PathBuf,
+    /// `-c` options giving git a fallback identity, empty when one is configured
+    identity: Vec<String>,
+}
+
+impl GitRepo {
+    /// Open the repository if the notes directory is one
+    pub fn open<P: AsRef<Path>>(root_dir: P) -> Option<Self> {
+        let root_dir = root_dir.as_ref();
+        root_dir
+            .join(".git")
+            .exists()
+            .then(|| Self::with_identity(root_dir))
+    }
+
+    /// Turn the notes directory into a repository and commit the existing notes
+    pub fn init<P: AsRef<Path>>(root_dir: P, remote: Option<&str>) -> StorageResult<Self> {
+        let root_dir = root_dir.as_ref();
+        let repo = Self::with_identity(root_dir);
+
+        if !root_dir.join(".git").exists() {
+            repo.git(&["init", "--quiet"])?;
+        }
+        if let Some(url) = remote {
+            repo.set_remote(url)?;
+            if !repo.has_commits() {
+                repo.follow_remote_branch()?;
+            }
+        }
+
+        repo.commit_all("Start versioning notes")?;
+        Ok(repo)
+    }
+
+    /// Look up once whether git knows who commits, rather than before every command
+    fn with_identity(root_dir: &Path) -> Self {
+        let mut repo = Self {
+            root_dir: root_dir.to_path_buf(),
+            identity: Vec::new(),
+        };
+        let configured = repo
+            .run(&["config", "user.email"])
+            .is_ok_and(|output| output.status.success());
+        if !configured {
+            repo.identity = vec![
+                "-c".to_string(),
+                format!("user.name={}", FALLBACK_NAME),
+                "-c".to_string(),
+                format!("user.email={}", FALLBACK_EMAIL),
+            ];
+        }
+        repo
+    }
+
+    /// Point the remote at a new URL
+    pub fn set_remote(&self, url: &str) -> StorageResult<()> {
+        if self.has_remote() {
+            self.git(&["remote", "set-url", REMOTE, url])?;
+        } else {
+            self.git(&["remote", "add", REMOTE, url])?;
+        }
+        Ok(())
+    }
+
+    /// Name a new repository's branch like the remote's default branch, so they match
+    fn follow_remote_branch(&self) -> StorageResult<()> {
+        let output = self.run(&["ls-remote", "--symref", REMOTE, "HEAD"])?;
+        let listing = String::from_utf8_lossy(&output.stdout);
+        let branch = listing.lines().find_map(|line| {
+            line.strip_prefix("ref: refs/heads/")?
+                .split_whitespace()
+                .next()
+        });
+        if let Some(branch) = branch {
+            self.git(&["symbolic-ref", "HEAD", &format!("refs/heads/{}", branch)])?;
+        }
+        Ok(())
+    }
+
+    pub fn has_remote(&self) -> bool {
+        self.run(&["remote", "get-url", REMOTE])
+            .is_ok_and(|output| output.status.success())
+    }
+
+    /// Commit every change in the notes directory, returning whether a commit was made
+    ///
+    /// While a merge is in progress, notes still containing conflict markers are left
+    /// out and the merge is only concluded once all of them are resolved.
+    pub fn commit_all(&self, message: &str) -> StorageResult<bool> {
+        self.update_gitignore()?;
+        if self.is_merging() {
+            let mut pending = Vec::new();
+            for path in self.unmerged_paths()? {
+                let file = self.root_dir.join(&path);
+                if fs::read_to_string(&file).is_ok_and(|content| has_conflict_markers(&content)) {
+                    pending.push(path);
+                }
+            }
+
+            let mut args = vec!["add".to_string(), "-A".to_string(), "--".to_string()];
+            args.push(".".to_string());
+            args.extend(pending.iter().map(|path| format!(":!{}", path)));
+            self.git(&args.iter().map(String::as_str).collect::<Vec<_>>())?;
+
+            if !pending.is_empty() {
+                return Ok(false);
+            }
+            self.git(&["commit", "--quiet", "--no-edit"])?;
+            return Ok(true);
+        }
+
+        self.git(&["add", "-A"])?;
+        let staged = self.run(&["diff", "--cached", "--quiet"])?;
+        if staged.status.success() && self.has_commits() {
+            return Ok(false);
+        }
+        self.git(&["commit", "--quiet", "--allow-empty", "-m", message])?;
+        Ok(true)
+    }
+
+    /// Add the files which are never committed to `.gitignore`, keeping the
+    /// lines already there, and stop tracking any that were committed before
+    /// they were ignored
+    fn update_gitignore(&self) -> StorageResult<()> {
+        let path = self.root_dir.join(".gitignore");
+        let mut content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let missing: Vec<&str> = GITIGNORE
+            .lines()
+            .filter(|pattern| !content.lines().any(|line| line.trim() == *pattern))
+            .collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        for pattern in &missing {
+            content.push_str(pattern);
+            content.push('\n');
+        }
+        fs::write(&path, content)?;
+
+        if self.has_commits() {
+            let mut args = vec!["rm", "-r", "--cached", "--quiet", "--ignore-unmatch", "--"];
+            args.extend(&missing);
+            self.git(&args)?;
+        }
+        Ok(())
+    }
+
+    /// Commits that touched a note, newest first
+    pub fn history(&self, title: &str) -> StorageResult<Vec<Revision>> {
+        if !self.has_commits() {
+            return Ok(Vec::new());
+        }
+        let file_name = note_file_name(title);
+
+        // Records start with 0x1e and fields are separated by 0x1f, the header and the
+        // file name of each record are terminated by NUL
+        let output = self.git(&[
+            "log",
+            "-z",
+            "--follow",
+            "--name-only",
+            "--format=%x1e%H%x1f%aI%x1f%s",
+            "--",
+            &file_name,
+        ])?;
+
+        let mut revisions = Vec::new();
+        for record in output
+            .split('\x1e')
+            .filter(|record| !record.trim().is_empty())
+        {
+            let mut parts = record.split('\0');
+            let mut fields = parts.next().unwrap_or_default().split('\x1f');
+            let (Some(id), Some(date), Some(summary)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            let Ok(date) = DateTime::parse_from_rfc3339(date) else {
+                continue;
+            };
+            let path = parts
+                .map(|part| part.trim_start_matches('\n'))
+                .find(|part| !part.is_empty())
+                .unwrap_or(&file_name);
+
+            revisions.push(Revision {
+                id: id.to_string(),
+                date: date.with_timezone(&Local),
+                summary: summary.to_string(),
+                path: path.to_string(),
+            });
+        }
+        Ok(revisions)
+    }
+
+    /// Changes a revision made to a note, as a unified diff
+    pub fn diff(&self, revision: &Revision) -> StorageResult<String> {
+        let metadata = revision.path.trim_end_matches(".md").to_string() + ".meta.json";
+        self.git(&[
+            "show",
+            "--format=",
+            "--find-renames",
+            &revision.id,
+            "--",
+            &revision.path,
+            &metadata,
+        ])
+    }
+
+    /// Merge the remote branch into the local one
+    ///
+    /// Metadata and encrypted notes cannot be merged line by line, so conflicts in them
+    /// keep the local version. Other conflicts are left in the notes as markers.
+    pub fn pull(&self) -> StorageResult<PullOutcome> {
+        self.require_remote()?;
+        let branch = self.branch()?;
+
+        // A remote without our branch, such as a freshly created one, has nothing to pull
+        let heads = self.run(&["ls-remote", "--exit-code", "--heads", REMOTE, &branch])?;
+        match heads.status.code() {
+            Some(0) => {}
+            Some(2) => return Ok(PullOutcome::default()),
+            _ => return Err(StorageError::Git(stderr(&heads))),
+        }
+
+        let before = self.head();
+        let output = self.run_with_identity(&[
+            "pull",
+            "--no-rebase",
+            "--no-edit",
+            // Vaults started separately on each machine share no history at first
+            "--allow-unrelated-histories",
+            REMOTE,
+            &branch,
+        ])?;
+
+        let mut outcome = PullOutcome::default();
+        if !output.status.success() && !self.is_merging() {
+            return Err(StorageError::Git(stderr(&output)));
+        }
+
+        for path in self.unmerged_paths()? {
+            let file = self.root_dir.join(&path);
+            let content = fs::read_to_string(&file).unwrap_or_default();
+            let title = note_title(&path);
+
+            if path.ends_with(".md") && !content.contains(crypto::HEADER) && file.exists() {
+                outcome.conflicts.extend(title);
+                continue;
+            }
+
+            self.keep_local(&path)?;
+            if path.ends_with(".md") {
+                outcome.kept_local.extend(title);
+            }
+        }
+
+        if self.is_merging() && outcome.conflicts.is_empty() {
+            self.git(&["commit", "--quiet", "--no-edit"])?;
+        }
+        outcome.updated = self.head() != before || self.is_merging();
+        Ok(outcome)
+    }
+
+    /// Send local commits to the remote
+    pub fn push(&self) -> StorageResult<()> {
+        self.require_remote()?;
+        if self.is_merging() {
+            return Err(StorageError::Git(
+                "resolve the conflicts before pushing".to_string(),
+            ));
+        }
+
+        let branch = self.branch()?;
+        let output = self.run(&["push", "--quiet", "--set-upstream", REMOTE, &branch])?;
+        if !output.status.success() {
+            let message = stderr(&output);
+            if message.contains("rejected") {
+                return Err(StorageError::Git(
+                    "the remote has changes, pull before pushing".to_string(),
+                ));
+            }
+            return Err(StorageError::Git(message));
+        }
+        Ok(())
+    }
+
+    /// Titles of notes that still have unresolved conflicts
+    pub fn conflicted_notes(&self) -> StorageResult<Vec<String>> {
+        Ok(self
+            .unmerged_paths()?
+            .iter()
+            .filter(|path| path.ends_with(".md"))
+            .filter_map(|path| note_title(path))
+            .collect())
+    }
+
+    fn keep_local(&self, path: &str) -> StorageResult<()> {
+        let checkout = self.run(&["checkout", "--ours", "--", path])?;
+        if checkout.status.success() {
+            self.git(&["add", "--", path])?;
+        } else {
+            // Deleted locally
+            self.git(&["rm", "--quiet", "--cached", "--ignore-unmatch", "--", path])?;
+            let file = self.root_dir.join(path);
+            if file.exists() {
+                fs::remove_file(file)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn require_remote(&self) -> StorageResult<()> {
+        if self.has_remote() {
+            Ok(())
+        } else {
+            Err(StorageError::Git(
+                "no remote configured, run `thoth git remote <url>`".to_string(),
+            ))
+        }
+    }
+
+    fn unmerged_paths(&self) -> StorageResult<Vec<String>> {
+        let output = self.git(&["diff", "--name-only", "--diff-filter=U", "-z"])?;
+        Ok(output
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    fn is_merging(&self) -> bool {
+        self.run(&["rev-parse", "-q", "--verify", "MERGE_HEAD"])
+            .is_ok_and(|output| output.status.success())
+    }
+
+    fn has_commits(&self) -> bool {
+        self.head().is_some()
+    }
+
+    fn head(&self) -> Option<String> {
+        self.git(&["rev-parse", "-q", "--verify", "HEAD"])
+            .ok()
+            .map(|id| id.trim().to_string())
+    }
+
+    fn branch(&self) -> StorageResult<String> {
+        Ok(self
+            .git(&["symbolic-ref", "--short", "HEAD"])?
+            .trim()
+            .to_string())
+    }
+
+    /// Run git and return its standard output, failing if it exits with an error
+    fn git(&self, args: &[&str]) -> StorageResult<String> {
+        let output = self.run_with_identity(args)?;
+        if !output.status.success() {
+            return Err(StorageError::Git(stderr(&output)));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    fn run(&self, args: &[&str]) -> StorageResult<Output> {
+        Ok(Command::new("git")
+            .arg("-C")
+            .arg(&self.root_dir)
+            .args(args)
+            .output()?)
+    }
+
+    /// Run git with the fallback identity, if any, so commits work on machines without one
+    fn run_with_identity(&self, args: &[&str]) -> StorageResult<Output> {
+        Ok(Command::new("git")
+            .arg("-C")
+            .arg(&self.root_dir)
+            .args(&self.identity)
+            .args(args)
+            .output()?)
+    }
+}
+
+/// Whether a file still contains the markers git leaves in conflicting regions
+fn has_conflict_markers(content: &str) -> bool {
+    content.lines().any(|line| line.starts_with("<<<<<<< "))
+        && content.lines().any(|line| line.starts_with(">>>>>>> "))
+}
+
+/// Title of the note stored in a content file, as written by `FSStorage`
+fn note_title(path: &str) -> Option<String> {
+    path.strip_suffix(".md")
+        .or_else(|| path.strip_suffix(".meta.json"))
+        .filter(|title| !title.contains('/'))
+        .map(str::to_string)
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).trim().to_string()
+}
@@ -1,8 +1,12 @@
+pub mod crypto;
 pub mod error;
 pub mod fs;
+pub mod git;
+
+use std::path::PathBuf;
 
 use crate::models::note::Note;
-use error::StorageResult;
+use error::{StorageError, StorageResult};
 
 /// Storage trait defines the interface for note persistence
 pub trait Storage {
@@ -18,12 +22,70 @@ pub trait Storage {
     /// Write a note to storage
     fn write_note(&self, note: &Note) -> StorageResult<()>;
 
+    /// Write the metadata of several notes at once, such as their order,
+    /// folder or tags, leaving their content as it is
+    fn write_notes_metadata(&self, notes: &[&Note]) -> StorageResult<()> {
+        notes.iter().try_for_each(|note| self.write_note(note))
+    }
+
     /// Delete a note from storage
     fn delete_note(&self, title: &str) -> StorageResult<()>;
 
+    /// Store an attachment and return the relative path notes use to link to it.
+    ///
+    /// A different file already stored under the name is kept, and the
+    /// attachment gets a name of its own.
+    fn write_attachment(&self, name: &str, data: &[u8]) -> StorageResult<String>;
+
+    /// Read a note with its content exactly as stored, still encrypted if the note is
+    fn read_stored_note(&self, title: &str) -> StorageResult<Note> {
+        self.read_note(title)
+    }
+
+    /// Write a note read with [`Storage::read_stored_note`], keeping its content as is
+    fn write_stored_note(&self, note: &Note) -> StorageResult<()> {
+        self.write_note(note)
+    }
+
+    /// File holding the note's content as plain text, which an external
+    /// editor can change in place
+    fn plain_note_path(&self, _note: &Note) -> Option<PathBuf> {
+        None
+    }
+
     /// Rename a note in storage
     fn rename_note(&self, old_title: &str, note: &Note) -> StorageResult<()> {
         self.delete_note(old_title)?;
         self.write_note(note)
     }
+
+    /// Whether a passphrase has been set for encrypted notes
+    fn has_passphrase(&self) -> bool {
+        false
+    }
+
+    /// Whether encrypted notes can currently be read and written
+    fn is_unlocked(&self) -> bool {
+        false
+    }
+
+    /// Unlock encrypted notes for the rest of the session
+    fn unlock(&self, _passphrase: &str) -> StorageResult<()> {
+        Err(StorageError::Unsupported)
+    }
+
+    /// Set the passphrase, re-encrypting every encrypted note if one was set before
+    fn set_passphrase(&self, _passphrase: &str) -> StorageResult<()> {
+        Err(StorageError::Unsupported)
+    }
+
+    /// Whether every note is encrypted regardless of its own flag
+    fn encrypts_all(&self) -> bool {
+        false
+    }
+
+    /// Turn encryption of the whole vault on or off
+    fn set_encrypt_all(&self, _enabled: bool) -> StorageResult<()> {
+        Err(StorageError::Unsupported)
+    }
 }
@@ -0,0 +1,72 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::sync::error::SyncResult;
+use crate::sync::{Remote, RemoteEntry};
+
+/// A local directory as remote, such as a mounted network share or a synced folder
+pub struct DirRemote {
+    dir: PathBuf,
+}
+
+impl DirRemote {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Modification time and size stand in for an ETag
+    fn etag(path: &Path) -> Option<String> {
+        let metadata = fs::metadata(path).ok()?;
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+        Some(format!("{}-{}", modified.as_nanos(), metadata.len()))
+    }
+}
+
+impl Remote for DirRemote {
+    fn list(&self) -> SyncResult<Vec<RemoteEntry>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.is_file() {
+                entries.push(RemoteEntry {
+                    name: path
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .to_string(),
+                    etag: Self::etag(&path),
+                });
+            }
+        }
+        Ok(entries)
+    }
+
+    fn get(&self, name: &str) -> SyncResult<(Vec<u8>, Option<String>)> {
+        let path = self.dir.join(name);
+        Ok((fs::read(&path)?, Self::etag(&path)))
+    }
+
+    fn put(&self, name: &str, data: &[u8]) -> SyncResult<Option<String>> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(name);
+        let temp_path = self.dir.join(format!(".{}.tmp", name));
+        fs::write(&temp_path, data)?;
+        fs::rename(temp_path, &path)?;
+        Ok(Self::etag(&path))
+    }
+
+    fn delete(&self, name: &str) -> SyncResult<()> {
+        let path = self.dir.join(name);
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
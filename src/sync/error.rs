@@ -0,0 +1,23 @@
+use thiserror::Error;
+
+use crate::storage::error::StorageError;
+
+#[derive(Error, Debug)]
+pub enum SyncError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Storage error: {0}")]
+    Storage(#[from] StorageError),
+
+    #[error("Sync is not set up, run `thoth sync --remote <url>` first")]
+    NotConfigured,
+
+    #[error("Request to {0} failed: {1}")]
+    Http(String, String),
+
+    #[error("Failed to parse {0}: {1}")]
+    Parse(String, String),
+}
+
+pub type SyncResult<T> = Result<T, SyncError>;
@@ -0,0 +1,531 @@
+pub mod dir;
+pub mod error;
+pub mod webdav;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use chrono::{DateTime, Local};
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+
+use crate::models::note::Note;
+use crate::storage::fs::note_file_name;
+use crate::storage::Storage;
+use dir::DirRemote;
+use error::{SyncError, SyncResult};
+use webdav::WebDavRemote;
+
+/// File inside the notes directory holding the remote and the state of the last sync
+pub const SYNC_FILE: &str = "sync.json";
+
+/// Where notes are synced to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteConfig {
+    /// WebDAV collection URL, or a local directory path
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// Password for the WebDAV server, `THOTH_SYNC_PASSWORD` takes precedence
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+}
+
+/// A note as it was when both sides last agreed on it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SyncedNote {
+    hash: String,
+    order: usize,
+    /// ETag of the remote document, if the remote reports one
+    #[serde(default)]
+    etag: Option<String>,
+}
+
+/// Remote configuration and the state of the last sync, stored in [`SYNC_FILE`]
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SyncSettings {
+    pub remote: Option<RemoteConfig>,
+    /// Synced notes by remote document name
+    #[serde(default)]
+    synced: BTreeMap<String, SyncedNote>,
+}
+
+impl SyncSettings {
+    pub fn load(root_dir: &Path) -> SyncResult<Self> {
+        let path = root_dir.join(SYNC_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&path)?;
+        serde_json::from_str(&contents)
+            .map_err(|e| SyncError::Parse(path.display().to_string(), e.to_string()))
+    }
+
+    pub fn save(&self, root_dir: &Path) -> SyncResult<()> {
+        let path = root_dir.join(SYNC_FILE);
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| SyncError::Parse(path.display().to_string(), e.to_string()))?;
+        let temp_path = path.with_extension("json.tmp");
+        // The file may hold the WebDAV password, so only the user can read it
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&temp_path)?;
+        #[cfg(unix)]
+        {
+            // A file left behind by an earlier save keeps its mode when opened
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        }
+        file.write_all(json.as_bytes())?;
+        fs::rename(temp_path, path)?;
+        Ok(())
+    }
+
+    /// Point sync at a new remote, forgetting the state of the previous one
+    pub fn set_remote(&mut self, remote: RemoteConfig) {
+        self.remote = Some(remote);
+        self.synced.clear();
+    }
+}
+
+/// A document in the remote collection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteEntry {
+    pub name: String,
+    pub etag: Option<String>,
+}
+
+/// A collection of note documents that notes are synced with
+pub trait Remote {
+    /// List the documents in the collection
+    fn list(&self) -> SyncResult<Vec<RemoteEntry>>;
+
+    /// Fetch a document and its ETag
+    fn get(&self, name: &str) -> SyncResult<(Vec<u8>, Option<String>)>;
+
+    /// Store a document, returning its new ETag if the remote reports it
+    fn put(&self, name: &str, data: &[u8]) -> SyncResult<Option<String>>;
+
+    /// Remove a document
+    fn delete(&self, name: &str) -> SyncResult<()>;
+}
+
+/// Open the remote described by a configuration
+pub fn open_remote(config: &RemoteConfig) -> Box<dyn Remote + Send> {
+    if config.url.starts_with("http://") || config.url.starts_with("https://") {
+        let password = std::env::var("THOTH_SYNC_PASSWORD")
+            .ok()
+            .or_else(|| config.password.clone());
+        Box::new(WebDavRemote::new(
+            &config.url,
+            config.username.clone(),
+            password,
+        ))
+    } else {
+        let path = config.url.strip_prefix("file://").unwrap_or(&config.url);
+        Box::new(DirRemote::new(path))
+    }
+}
+
+/// A note as stored in the remote collection.
+///
+/// Encrypted notes keep their encrypted content, so the remote never sees
+/// it in plain text.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct NoteDocument {
+    title: String,
+    content: String,
+    created_at: DateTime<Local>,
+    updated_at: DateTime<Local>,
+    #[serde(default)]
+    order: usize,
+    #[serde(default)]
+    folder: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    encrypted: bool,
+    #[serde(default)]
+    pinned: bool,
+    #[serde(default)]
+    archived: bool,
+}
+
+impl NoteDocument {
+    fn from_note(note: Note) -> Self {
+        Self {
+            title: note.title,
+            content: note.content,
+            created_at: note.created_at,
+            updated_at: note.updated_at,
+            order: note.order,
+            folder: note.folder,
+            tags: note.tags,
+            encrypted: note.encrypted,
+            pinned: note.pinned,
+            archived: note.archived,
+        }
+    }
+
+    fn into_note(self) -> Note {
+        Note {
+            title: self.title,
+            content: self.content,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            order: self.order,
+            folder: self.folder,
+            tags: self.tags,
+            encrypted: self.encrypted,
+            pinned: self.pinned,
+            archived: self.archived,
+            ..Default::default()
+        }
+    }
+
+    fn parse(name: &str, data: &[u8]) -> SyncResult<Self> {
+        serde_json::from_slice(data).map_err(|e| SyncError::Parse(name.to_string(), e.to_string()))
+    }
+
+    fn to_json(&self) -> SyncResult<Vec<u8>> {
+        serde_json::to_vec_pretty(self)
+            .map_err(|e| SyncError::Parse(self.title.clone(), e.to_string()))
+    }
+
+    /// Hash of everything that makes two versions of a note differ.
+    ///
+    /// Timestamps and the position in the list are left out, so saving without
+    /// changes or reordering notes on both sides is not a conflict.
+    fn hash(&self) -> String {
+        let mut hasher = Md5::new();
+        for part in [&self.title, &self.content, &self.folder] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        for tag in &self.tags {
+            hasher.update(tag.as_bytes());
+            hasher.update([0]);
+        }
+        hasher.update([self.encrypted as u8]);
+        // Flags added later only count when set, so notes synced before keep their hashes
+        for (flag, set) in [("pinned", self.pinned), ("archived", self.archived)] {
+            if set {
+                hasher.update(flag.as_bytes());
+            }
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Whether the note changed since it was last synced
+    fn changed_since(&self, synced: &SyncedNote) -> bool {
+        self.hash() != synced.hash || self.order != synced.order
+    }
+}
+
+/// Name of the remote document holding a note
+fn document_name(title: &str) -> String {
+    let file_name = note_file_name(title);
+    format!("{}.json", file_name.trim_end_matches(".md"))
+}
+
+/// Position in the sync, reported before each note is handled
+#[derive(Debug, Clone)]
+pub struct SyncProgress {
+    pub done: usize,
+    pub total: usize,
+    pub name: String,
+}
+
+/// What a sync changed
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    pub uploaded: usize,
+    pub downloaded: usize,
+    pub deleted_local: usize,
+    pub deleted_remote: usize,
+    /// Titles of the copies saved for notes changed on both sides
+    pub conflicts: Vec<String>,
+}
+
+impl SyncReport {
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "Uploaded {}, downloaded {}, deleted {} local and {} remote notes",
+            self.uploaded, self.downloaded, self.deleted_local, self.deleted_remote
+        );
+        if !self.conflicts.is_empty() {
+            summary.push_str(&format!(
+                ", {} changed on both sides and saved as {}",
+                self.conflicts.len(),
+                self.conflicts
+                    .iter()
+                    .map(|title| format!("'{}'", title))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        summary
+    }
+}
+
+/// Sync the notes in a directory with its configured remote
+pub fn sync_notes(
+    root_dir: &Path,
+    storage: &dyn Storage,
+    progress: &mut dyn FnMut(&SyncProgress),
+) -> SyncResult<SyncReport> {
+    let mut settings = SyncSettings::load(root_dir)?;
+    let remote = open_remote(settings.remote.as_ref().ok_or(SyncError::NotConfigured)?);
+
+    // Keep the progress made so far even when a request fails
+    let result = sync(storage, remote.as_ref(), &mut settings, progress);
+    settings.save(root_dir)?;
+    result
+}
+
+/// Reconcile local notes with a remote collection.
+///
+/// Each note is compared with the state both sides agreed on at the last
+/// sync: a side that changed wins over one that did not, a note deleted on
+/// one side is deleted on the other unless it changed there, and a note
+/// changed on both sides keeps the newer version under its title and the
+/// older one as a conflict copy.
+pub fn sync(
+    storage: &dyn Storage,
+    remote: &dyn Remote,
+    settings: &mut SyncSettings,
+    progress: &mut dyn FnMut(&SyncProgress),
+) -> SyncResult<SyncReport> {
+    let mut local = BTreeMap::new();
+    for note in storage.list_notes()? {
+        let note = storage.read_stored_note(&note.title)?;
+        local.insert(document_name(&note.title), NoteDocument::from_note(note));
+    }
+
+    let remote_entries: BTreeMap<String, Option<String>> = remote
+        .list()?
+        .into_iter()
+        .filter(|entry| entry.name.ends_with(".json"))
+        .map(|entry| (entry.name, entry.etag))
+        .collect();
+
+    let names: BTreeSet<String> = local
+        .keys()
+        .chain(remote_entries.keys())
+        .chain(settings.synced.keys())
+        .cloned()
+        .collect();
+
+    let mut syncer = Syncer {
+        storage,
+        remote,
+        synced: &mut settings.synced,
+        report: SyncReport::default(),
+        taken: names.clone(),
+    };
+
+    let total = names.len();
+    for (done, name) in names.into_iter().enumerate() {
+        progress(&SyncProgress {
+            done,
+            total,
+            name: name.trim_end_matches(".json").to_string(),
+        });
+        syncer.sync_note(&name, local.remove(&name), remote_entries.get(&name))?;
+    }
+    progress(&SyncProgress {
+        done: total,
+        total,
+        name: String::new(),
+    });
+
+    Ok(syncer.report)
+}
+
+struct Syncer<'a> {
+    storage: &'a dyn Storage,
+    remote: &'a dyn Remote,
+    synced: &'a mut BTreeMap<String, SyncedNote>,
+    report: SyncReport,
+    /// Document names in use, to keep conflict copies apart from existing notes
+    taken: BTreeSet<String>,
+}
+
+impl Syncer<'_> {
+    fn sync_note(
+        &mut self,
+        name: &str,
+        local: Option<NoteDocument>,
+        remote_etag: Option<&Option<String>>,
+    ) -> SyncResult<()> {
+        let synced = self.synced.get(name).cloned();
+
+        match (local, remote_etag, synced) {
+            (None, None, _) => {
+                self.synced.remove(name);
+            }
+            (Some(local), None, None) => self.upload(name, &local)?,
+            (Some(local), None, Some(synced)) => {
+                if local.changed_since(&synced) {
+                    // Edited here after it was deleted there, keep the edit
+                    self.upload(name, &local)?;
+                } else {
+                    self.storage.delete_note(&local.title)?;
+                    self.synced.remove(name);
+                    self.report.deleted_local += 1;
+                }
+            }
+            (None, Some(_), None) => {
+                let (remote, etag) = self.fetch(name)?;
+                self.download(name, remote, etag)?;
+            }
+            (None, Some(etag), Some(synced)) => {
+                match self.fetch_if_changed(name, etag, &synced)? {
+                    // Edited there after it was deleted here, keep the edit
+                    Some((remote, etag)) => self.download(name, remote, etag)?,
+                    None => {
+                        self.remote.delete(name)?;
+                        self.synced.remove(name);
+                        self.report.deleted_remote += 1;
+                    }
+                }
+            }
+            (Some(local), Some(etag), synced) => {
+                let local_changed = synced
+                    .as_ref()
+                    .is_none_or(|synced| local.changed_since(synced));
+                let remote = match &synced {
+                    Some(synced) => self.fetch_if_changed(name, etag, synced)?,
+                    None => Some(self.fetch(name)?),
+                };
+
+                match (local_changed, remote) {
+                    (false, None) => {}
+                    (true, None) => self.upload(name, &local)?,
+                    (false, Some((remote, etag))) => self.download(name, remote, etag)?,
+                    (true, Some((remote, etag))) => {
+                        if remote.hash() == local.hash() {
+                            // Same change on both sides
+                            if remote.order == local.order {
+                                self.record(name, &local, etag);
+                            } else {
+                                self.upload(name, &local)?;
+                            }
+                        } else {
+                            self.resolve_conflict(name, local, remote, etag)?;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Fetch the remote document if it changed since the last sync
+    fn fetch_if_changed(
+        &self,
+        name: &str,
+        etag: &Option<String>,
+        synced: &SyncedNote,
+    ) -> SyncResult<Option<(NoteDocument, Option<String>)>> {
+        if etag.is_some() && *etag == synced.etag {
+            return Ok(None);
+        }
+        // Without ETags the content has to be compared
+        let (remote, etag) = self.fetch(name)?;
+        if remote.changed_since(synced) {
+            Ok(Some((remote, etag)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn fetch(&self, name: &str) -> SyncResult<(NoteDocument, Option<String>)> {
+        let (data, etag) = self.remote.get(name)?;
+        Ok((NoteDocument::parse(name, &data)?, etag))
+    }
+
+    fn upload(&mut self, name: &str, document: &NoteDocument) -> SyncResult<()> {
+        let etag = self.remote.put(name, &document.to_json()?)?;
+        self.record(name, document, etag);
+        self.report.uploaded += 1;
+        Ok(())
+    }
+
+    fn download(
+        &mut self,
+        name: &str,
+        document: NoteDocument,
+        etag: Option<String>,
+    ) -> SyncResult<()> {
+        self.record(name, &document, etag);
+        self.storage.write_stored_note(&document.into_note())?;
+        self.report.downloaded += 1;
+        Ok(())
+    }
+
+    fn record(&mut self, name: &str, document: &NoteDocument, etag: Option<String>) {
+        self.synced.insert(
+            name.to_string(),
+            SyncedNote {
+                hash: document.hash(),
+                order: document.order,
+                etag,
+            },
+        );
+    }
+
+    /// Keep both versions of a note changed on both sides.
+    ///
+    /// The newer version keeps the title and the older one is saved as a
+    /// copy, on both sides.
+    fn resolve_conflict(
+        &mut self,
+        name: &str,
+        local: NoteDocument,
+        remote: NoteDocument,
+        remote_etag: Option<String>,
+    ) -> SyncResult<()> {
+        let local_is_newer = local.updated_at >= remote.updated_at;
+        let (newer, mut older) = if local_is_newer {
+            (local, remote)
+        } else {
+            (remote, local)
+        };
+
+        older.title = self.conflict_title(&older);
+        let copy_name = document_name(&older.title);
+        self.taken.insert(copy_name.clone());
+        self.storage.write_stored_note(&older.clone().into_note())?;
+        self.upload(&copy_name, &older)?;
+        self.report.conflicts.push(older.title);
+
+        if local_is_newer {
+            self.upload(name, &newer)
+        } else {
+            self.download(name, newer, remote_etag)
+        }
+    }
+
+    fn conflict_title(&self, document: &NoteDocument) -> String {
+        let base = format!(
+            "{} (conflict {})",
+            document.title,
+            document.updated_at.format("%Y-%m-%d %H-%M")
+        );
+        let mut title = base.clone();
+        let mut counter = 2;
+        while self.taken.contains(&document_name(&title)) {
+            title = format!("{} {}", base, counter);
+            counter += 1;
+        }
+        title
+    }
+}
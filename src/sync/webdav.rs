@@ -0,0 +1,287 @@
+use std::io::Read;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::links::percent_decode;
+use crate::sync::error::{SyncError, SyncResult};
+use crate::sync::{Remote, RemoteEntry};
+use crate::tokenizer::{tokenize, Token};
+
+/// Properties requested when listing the collection
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:"><d:prop><d:getetag/><d:resourcetype/></d:prop></d:propfind>"#;
+
+/// A WebDAV collection holding one document per note
+pub struct WebDavRemote {
+    /// Collection URL, ending with `/`
+    base_url: String,
+    agent: ureq::Agent,
+    authorization: Option<String>,
+}
+
+impl WebDavRemote {
+    pub fn new(url: &str, username: Option<String>, password: Option<String>) -> Self {
+        let mut base_url = url.to_string();
+        if !base_url.ends_with('/') {
+            base_url.push('/');
+        }
+        let authorization = username.map(|username| {
+            let credentials = format!("{}:{}", username, password.unwrap_or_default());
+            format!("Basic {}", STANDARD.encode(credentials))
+        });
+
+        Self {
+            base_url,
+            agent: ureq::AgentBuilder::new().build(),
+            authorization,
+        }
+    }
+
+    fn url(&self, name: &str) -> String {
+        format!("{}{}", self.base_url, percent_encode(name))
+    }
+
+    fn request(&self, method: &str, url: &str) -> ureq::Request {
+        let request = self.agent.request(method, url);
+        match &self.authorization {
+            Some(authorization) => request.set("Authorization", authorization),
+            None => request,
+        }
+    }
+
+    /// Create the collection, which servers require before storing documents in it
+    fn create_collection(&self) -> SyncResult<()> {
+        match self.request("MKCOL", &self.base_url).call() {
+            // Already exists
+            Ok(_) | Err(ureq::Error::Status(405, _)) => Ok(()),
+            Err(e) => Err(http_error(&self.base_url, e)),
+        }
+    }
+}
+
+impl Remote for WebDavRemote {
+    fn list(&self) -> SyncResult<Vec<RemoteEntry>> {
+        let response = match self
+            .request("PROPFIND", &self.base_url)
+            .set("Depth", "1")
+            .set("Content-Type", "application/xml; charset=utf-8")
+            .send_string(PROPFIND_BODY)
+        {
+            Ok(response) => response,
+            // Nothing has been synced yet
+            Err(ureq::Error::Status(404, _)) => return Ok(Vec::new()),
+            Err(e) => return Err(http_error(&self.base_url, e)),
+        };
+
+        let body = response
+            .into_string()
+            .map_err(|e| SyncError::Http(self.base_url.clone(), e.to_string()))?;
+        Ok(parse_multistatus(&body))
+    }
+
+    fn get(&self, name: &str) -> SyncResult<(Vec<u8>, Option<String>)> {
+        let url = self.url(name);
+        let response = self
+            .request("GET", &url)
+            .call()
+            .map_err(|e| http_error(&url, e))?;
+        let etag = response.header("ETag").map(normalize_etag);
+
+        let mut data = Vec::new();
+        response
+            .into_reader()
+            .read_to_end(&mut data)
+            .map_err(|e| SyncError::Http(url, e.to_string()))?;
+        Ok((data, etag))
+    }
+
+    fn put(&self, name: &str, data: &[u8]) -> SyncResult<Option<String>> {
+        let url = self.url(name);
+        let request = || {
+            self.request("PUT", &url)
+                .set("Content-Type", "application/json")
+        };
+
+        let response = match request().send_bytes(data) {
+            Ok(response) => response,
+            // The collection does not exist yet
+            Err(ureq::Error::Status(404 | 409, _)) => {
+                self.create_collection()?;
+                request()
+                    .send_bytes(data)
+                    .map_err(|e| http_error(&url, e))?
+            }
+            Err(e) => return Err(http_error(&url, e)),
+        };
+        Ok(response.header("ETag").map(normalize_etag))
+    }
+
+    fn delete(&self, name: &str) -> SyncResult<()> {
+        let url = self.url(name);
+        match self.request("DELETE", &url).call() {
+            Ok(_) | Err(ureq::Error::Status(404, _)) => Ok(()),
+            Err(e) => Err(http_error(&url, e)),
+        }
+    }
+}
+
+fn http_error(url: &str, error: ureq::Error) -> SyncError {
+    let message = match error {
+        ureq::Error::Status(code, response) => format!("{} {}", code, response.status_text()),
+        ureq::Error::Transport(transport) => transport.to_string(),
+    };
+    SyncError::Http(url.to_string(), message)
+}
+
+/// Collect the documents of a `207 Multi-Status` response, skipping collections
+fn parse_multistatus(xml: &str) -> Vec<RemoteEntry> {
+    let mut entries = Vec::new();
+    let mut element = String::new();
+    let mut href = String::new();
+    let mut etag = String::new();
+    let mut is_collection = false;
+
+    for token in tokenize(xml) {
+        match token {
+            Token::Open { name, .. } => {
+                // Element names carry whatever namespace prefix the server picked
+                let name = local_name(&name).to_string();
+                match name.as_str() {
+                    "response" => {
+                        href.clear();
+                        etag.clear();
+                        is_collection = false;
+                    }
+                    "collection" => is_collection = true,
+                    _ => {}
+                }
+                element = name;
+            }
+            Token::Close(name) => {
+                if local_name(&name) == "response" && !is_collection {
+                    let name = href
+                        .trim()
+                        .trim_end_matches('/')
+                        .rsplit('/')
+                        .next()
+                        .map(percent_decode)
+                        .unwrap_or_default();
+                    if !name.is_empty() {
+                        entries.push(RemoteEntry {
+                            name,
+                            etag: (!etag.trim().is_empty()).then(|| normalize_etag(&etag)),
+                        });
+                    }
+                }
+                element.clear();
+            }
+            Token::Text(text) => match element.as_str() {
+                "href" => href.push_str(&text),
+                "getetag" => etag.push_str(&text),
+                _ => {}
+            },
+        }
+    }
+    entries
+}
+
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+/// Servers quote ETags and may mark them weak, which does not matter for comparing them
+fn normalize_etag(etag: &str) -> String {
+    etag.trim()
+        .trim_start_matches("W/")
+        .trim_matches('"')
+        .to_string()
+}
+
+fn percent_encode(name: &str) -> String {
+    let mut encoded = String::with_capacity(name.len());
+    for byte in name.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, etag: Option<&str>) -> RemoteEntry {
+        RemoteEntry {
+            name: name.to_string(),
+            etag: etag.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn multistatus_lists_documents_but_not_collections() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:multistatus xmlns:d="DAV:">
+  <d:response>
+    <d:href>/dav/notes/</d:href>
+    <d:propstat><d:prop>
+      <d:getetag>"dir"</d:getetag>
+      <d:resourcetype><d:collection/></d:resourcetype>
+    </d:prop></d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>/dav/notes/Shopping%20List.json</d:href>
+    <d:propstat><d:prop>
+      <d:getetag>W/"5f-abc"</d:getetag>
+      <d:resourcetype/>
+    </d:prop></d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>https://example.com/dav/notes/Caf%C3%A9.json</d:href>
+    <d:propstat><d:prop><d:resourcetype/></d:prop></d:propstat>
+  </d:response>
+</d:multistatus>"#;
+        assert_eq!(
+            parse_multistatus(xml),
+            [
+                entry("Shopping List.json", Some("5f-abc")),
+                entry("Café.json", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn multistatus_accepts_any_namespace_prefix() {
+        let xml = r#"<D:multistatus xmlns:D="DAV:"><D:response>
+<D:href>/notes/Ideas.json</D:href>
+<D:propstat><D:prop><D:getetag>"1"</D:getetag><D:resourcetype></D:resourcetype></D:prop>
+<D:status>HTTP/1.1 200 OK</D:status></D:propstat>
+</D:response><response xmlns="DAV:"><href>/notes/Todo.json</href>
+<propstat><prop><getetag>"2"</getetag></prop></propstat></response></D:multistatus>"#;
+        assert_eq!(
+            parse_multistatus(xml),
+            [
+                entry("Ideas.json", Some("1")),
+                entry("Todo.json", Some("2"))
+            ]
+        );
+    }
+
+    #[test]
+    fn etags_lose_quotes_and_weakness() {
+        assert_eq!(normalize_etag(" \"abc\" "), "abc");
+        assert_eq!(normalize_etag("W/\"abc\""), "abc");
+        assert_eq!(normalize_etag("abc"), "abc");
+    }
+
+    #[test]
+    fn document_names_are_percent_encoded() {
+        assert_eq!(percent_encode("Ideas.json"), "Ideas.json");
+        assert_eq!(percent_encode("My Note.json"), "My%20Note.json");
+        assert_eq!(percent_encode("a/b?c#d"), "a%2Fb%3Fc%23d");
+        assert_eq!(percent_encode("Café"), "Caf%C3%A9");
+        assert_eq!(percent_decode(&percent_encode("Café & co")), "Café & co");
+    }
+}
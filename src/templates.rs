@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::NaiveDateTime;
+
+/// Directory under the notes directory holding the templates
+pub const TEMPLATES_DIR: &str = "templates";
+
+/// Template used for daily notes when it exists
+pub const JOURNAL_TEMPLATE: &str = "journal";
+
+/// Content of the welcome note, shipped as the first built-in template
+pub const WELCOME: &str = include_str!("welcome.md");
+
+/// Placeholders filled in without asking
+const BUILT_IN_VARIABLES: [&str; 4] = ["date", "time", "title", "cursor"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    pub name: String,
+    pub content: String,
+}
+
+/// A template with its placeholders replaced
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expanded {
+    pub content: String,
+    /// Row and column of the `{{cursor}}` placeholder
+    pub cursor: Option<(usize, usize)>,
+}
+
+/// Built-in templates followed by the Markdown files in the templates directory.
+///
+/// A file named like a built-in template replaces it.
+pub fn load_templates(root_dir: &Path) -> Vec<Template> {
+    let mut templates = vec![Template {
+        name: "welcome".to_string(),
+        content: WELCOME.to_string(),
+    }];
+
+    let mut paths: Vec<_> = fs::read_dir(root_dir.join(TEMPLATES_DIR))
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "md"))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let (Some(stem), Ok(content)) = (path.file_stem(), fs::read_to_string(&path)) else {
+            continue;
+        };
+        let template = Template {
+            name: stem.to_string_lossy().to_string(),
+            content,
+        };
+        match templates.iter_mut().find(|t| t.name == template.name) {
+            Some(existing) => *existing = template,
+            None => templates.push(template),
+        }
+    }
+    templates
+}
+
+/// Load one template from the templates directory
+pub fn load_template(root_dir: &Path, name: &str) -> Option<Template> {
+    let path = root_dir.join(TEMPLATES_DIR).join(format!("{}.md", name));
+    let content = fs::read_to_string(path).ok()?;
+    Some(Template {
+        name: name.to_string(),
+        content,
+    })
+}
+
+/// Custom placeholders the user is asked to fill in, in order of appearance
+pub fn variables(content: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for Placeholder { name, .. } in placeholders(content) {
+        if !BUILT_IN_VARIABLES.contains(&name) && !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// Replace the placeholders of a template.
+///
+/// `{{date}}` and `{{time}}` come from `now`, and take a `chrono` format after
+/// a colon, like `{{date:%A %d %B}}`. Custom variables come from `values` and
+/// are left empty without one.
+pub fn expand(
+    content: &str,
+    title: &str,
+    now: NaiveDateTime,
+    values: &HashMap<String, String>,
+) -> Expanded {
+    let mut expanded = String::with_capacity(content.len());
+    let mut cursor = None;
+    let mut rest = 0;
+
+    for Placeholder {
+        range,
+        name,
+        format,
+    } in placeholders(content)
+    {
+        expanded.push_str(&content[rest..range.start]);
+        rest = range.end;
+        match name {
+            "date" | "time" => {
+                let default = if name == "date" { "%Y-%m-%d" } else { "%H:%M" };
+                // Formats such as `%z` need a time zone and fail, so stay as written
+                let written = expanded.len();
+                if write!(expanded, "{}", now.format(format.unwrap_or(default))).is_err() {
+                    expanded.truncate(written);
+                    expanded.push_str(&content[range]);
+                }
+            }
+            "title" => expanded.push_str(title),
+            "cursor" => {
+                if cursor.is_none() {
+                    let line_start = expanded.rfind('\n').map_or(0, |i| i + 1);
+                    cursor = Some((
+                        expanded.matches('\n').count(),
+                        expanded[line_start..].chars().count(),
+                    ));
+                }
+            }
+            name => expanded.push_str(values.get(name).map_or("", String::as_str)),
+        }
+    }
+    expanded.push_str(&content[rest..]);
+
+    Expanded {
+        content: expanded,
+        cursor,
+    }
+}
+
+/// A `{{name}}` or `{{name:format}}` placeholder in a template
+struct Placeholder<'a> {
+    /// Byte range including the braces
+    range: Range<usize>,
+    name: &'a str,
+    format: Option<&'a str>,
+}
+
+/// The placeholders of a template.
+///
+/// Only `date` and `time` take a format, and only a valid one; anything else
+/// is left in the note as written.
+fn placeholders(content: &str) -> Vec<Placeholder<'_>> {
+    let mut found = Vec::new();
+    let mut offset = 0;
+    while let Some(start) = content[offset..].find("{{").map(|i| offset + i) {
+        let Some(end) = content[start + 2..].find("}}").map(|i| start + 2 + i) else {
+            break;
+        };
+        let inner = &content[start + 2..end];
+        let (name, format) = match inner.split_once(':') {
+            Some((name, format)) => (name.trim(), Some(format.trim())),
+            None => (inner.trim(), None),
+        };
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == ' ')
+            && format.is_none_or(|format| {
+                matches!(name, "date" | "time")
+                    && !format.is_empty()
+                    && !StrftimeItems::new(format).any(|item| item == Item::Error)
+            });
+        if valid {
+            found.push(Placeholder {
+                range: start..end + 2,
+                name,
+                format,
+            });
+            offset = end + 2;
+        } else {
+            offset = start + 2;
+        }
+    }
+    found
+}
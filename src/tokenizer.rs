@@ -0,0 +1,212 @@
+/// A piece of an HTML or XML document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Token {
+    Open {
+        name: String,
+        attrs: Vec<(String, String)>,
+        self_closing: bool,
+    },
+    Close(String),
+    Text(String),
+}
+
+/// Split HTML or XML into tokens.
+///
+/// This is deliberately forgiving: unknown constructs become text and
+/// unclosed tags are left for the consumer to deal with. Comments,
+/// declarations and processing instructions are dropped, CDATA sections
+/// become raw text and the contents of `script` and `style` are skipped.
+pub(crate) fn tokenize(input: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = input;
+
+    while !rest.is_empty() {
+        let Some(start) = rest.find('<') else {
+            tokens.push(Token::Text(decode_entities(rest)));
+            break;
+        };
+        if start > 0 {
+            tokens.push(Token::Text(decode_entities(&rest[..start])));
+        }
+        rest = &rest[start..];
+
+        if let Some(body) = rest.strip_prefix("<![CDATA[") {
+            let end = body.find("]]>").unwrap_or(body.len());
+            tokens.push(Token::Text(body[..end].to_string()));
+            rest = body.get(end + 3..).unwrap_or_default();
+        } else if let Some(body) = rest.strip_prefix("<!--") {
+            rest = body.find("-->").map_or("", |end| &body[end + 3..]);
+        } else if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+        } else if let Some(body) = rest.strip_prefix("</") {
+            let end = body.find('>').unwrap_or(body.len());
+            tokens.push(Token::Close(body[..end].trim().to_ascii_lowercase()));
+            rest = body.get(end + 1..).unwrap_or_default();
+        } else {
+            let Some(end) = tag_end(rest) else {
+                // A stray `<` in text
+                tokens.push(Token::Text("<".to_string()));
+                rest = &rest[1..];
+                continue;
+            };
+            let inner = &rest[1..end];
+            rest = &rest[end + 1..];
+            let Some(token) = parse_open_tag(inner) else {
+                tokens.push(Token::Text(format!("<{}>", decode_entities(inner))));
+                continue;
+            };
+
+            // Raw text elements are skipped entirely
+            if let Token::Open {
+                name,
+                self_closing: false,
+                ..
+            } = &token
+            {
+                if name == "script" || name == "style" {
+                    let close = format!("</{}", name);
+                    let lower = rest.to_ascii_lowercase();
+                    let skip = lower.find(&close).unwrap_or(rest.len());
+                    rest = &rest[skip..];
+                    rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+                    continue;
+                }
+            }
+            tokens.push(token);
+        }
+    }
+    tokens
+}
+
+/// Find the closing `>` of a tag, ignoring any inside quoted attribute values
+fn tag_end(input: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in input.char_indices().skip(1) {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '>') => return Some(i),
+            (None, '<') => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_open_tag(inner: &str) -> Option<Token> {
+    let self_closing = inner.ends_with('/');
+    let inner = inner.trim_end_matches('/');
+    let name_end = inner
+        .find(|c: char| c.is_whitespace())
+        .unwrap_or(inner.len());
+    let name = inner[..name_end].to_ascii_lowercase();
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == ':')
+    {
+        return None;
+    }
+
+    let mut attrs = Vec::new();
+    let mut rest = inner[name_end..].trim_start();
+    while !rest.is_empty() {
+        let key_end = rest
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let key = rest[..key_end].to_ascii_lowercase();
+        rest = rest[key_end..].trim_start();
+
+        let mut value = String::new();
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            let (raw, remaining) = match after.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let body = &after[1..];
+                    let end = body.find(quote).unwrap_or(body.len());
+                    (&body[..end], body.get(end + 1..).unwrap_or_default())
+                }
+                _ => {
+                    let end = after
+                        .find(|c: char| c.is_whitespace())
+                        .unwrap_or(after.len());
+                    (&after[..end], &after[end..])
+                }
+            };
+            value = decode_entities(raw);
+            rest = remaining.trim_start();
+        }
+        if !key.is_empty() {
+            attrs.push((key, value));
+        }
+    }
+
+    Some(Token::Open {
+        name,
+        attrs,
+        self_closing,
+    })
+}
+
+/// Decode the character references that show up in note HTML
+pub(crate) fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest[1..]
+            .find(|c: char| !(c.is_alphanumeric() || c == '#'))
+            .map(|end| end + 1);
+        let decoded = end
+            .filter(|end| rest[*end..].starts_with(';'))
+            .and_then(|end| decode_entity(&rest[1..end]).map(|c| (c, end + 1)));
+        match decoded {
+            Some((c, consumed)) => {
+                output.push(c);
+                rest = &rest[consumed..];
+            }
+            None => {
+                output.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+fn decode_entity(name: &str) -> Option<char> {
+    if let Some(number) = name.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    let c = match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "ndash" => '–',
+        "mdash" => '—',
+        "hellip" => '…',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "bull" => '•',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        _ => return None,
+    };
+    Some(c)
+}
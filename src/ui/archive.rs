@@ -0,0 +1,69 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    symbols,
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+use super::from_str;
+use crate::app::AppState;
+use crate::theme::palette;
+
+/// Renders the archived notes next to the content of the selected one
+pub fn render_archive(frame: &mut Frame, state: &mut AppState, area: Rect) {
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
+        .split(area);
+
+    let items: Vec<ListItem> = state
+        .archive
+        .iter()
+        .map(|note| {
+            ListItem::new(vec![
+                Line::from(note.title.as_str()),
+                Line::styled(
+                    note.updated_at.format("%Y-%m-%d %H:%M").to_string(),
+                    Style::default().fg(palette::OVERLAY1),
+                ),
+            ])
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_set(symbols::border::ROUNDED)
+                .border_style(state.theme.selected_style)
+                .title(Span::styled("Archive", state.theme.title_style))
+                .title_bottom("<↑/↓> Select <Enter> Restore <Esc> Close")
+                .title_alignment(Alignment::Center),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(palette::SURFACE0)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut list_state = ListState::default().with_selected(Some(state.archive_selected));
+    frame.render_stateful_widget(list, columns[0], &mut list_state);
+
+    let Some(note) = state.archive.get(state.archive_selected) else {
+        return;
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(symbols::border::ROUNDED)
+        .border_style(Style::default().fg(palette::TEAL))
+        .title(note.title.as_str())
+        .title_alignment(Alignment::Center);
+    let content = if note.locked {
+        Paragraph::new("Locked note, restore it and unlock it to read it")
+            .style(Style::default().fg(palette::OVERLAY1))
+    } else {
+        Paragraph::new(from_str(&note.content, columns[1].width.saturating_sub(2)))
+    };
+    frame.render_widget(content.wrap(Wrap { trim: false }).block(block), columns[1]);
+}
@@ -0,0 +1,74 @@
+use std::collections::HashSet;
+
+use chrono::{Datelike, Days, NaiveDate};
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use super::centered_rect;
+use crate::app::AppState;
+use crate::journal;
+use crate::theme::palette;
+
+/// Renders a month view around the selected day, marking days that have a daily note
+pub fn render_calendar(frame: &mut Frame, state: &mut AppState, area: Rect) {
+    let selected = state.calendar_date;
+    let today = journal::today();
+    let entries: HashSet<NaiveDate> = state.notes.iter().filter_map(journal::entry_date).collect();
+
+    let block = Block::default()
+        .title(format!(" {} ", selected.format("%B %Y")))
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(Style::default().fg(palette::YELLOW));
+
+    let mut lines = vec![Line::styled(
+        " Mo Tu We Th Fr Sa Su",
+        Style::default().fg(palette::OVERLAY1),
+    )];
+
+    // Start the grid on the Monday before the first of the month
+    let first = selected.with_day(1).unwrap_or(selected);
+    let mut day = first
+        .checked_sub_days(Days::new(first.weekday().num_days_from_monday() as u64))
+        .unwrap_or(first);
+    while day.month() == selected.month() || day < first {
+        let mut spans = Vec::with_capacity(7);
+        for _ in 0..7 {
+            if day.month() != selected.month() {
+                spans.push(Span::raw("   "));
+            } else {
+                let mut style = Style::default().fg(palette::SUBTEXT0);
+                if entries.contains(&day) {
+                    style = style.fg(palette::GREEN).add_modifier(Modifier::BOLD);
+                }
+                if day == today {
+                    style = style.add_modifier(Modifier::UNDERLINED);
+                }
+                if day == selected {
+                    style = style.fg(palette::CRUST).bg(palette::YELLOW);
+                }
+                spans.push(Span::raw(" "));
+                spans.push(Span::styled(format!("{:>2}", day.day()), style));
+            }
+            day = day.succ_opt().unwrap_or(day);
+        }
+        lines.push(Line::from(spans));
+    }
+
+    lines.push(Line::default());
+    lines.push(Line::styled(
+        " Enter open  t today",
+        Style::default().fg(palette::OVERLAY0),
+    ));
+
+    let popup_area = centered_rect(area, 24, lines.len() as u16 + 2);
+
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(Paragraph::new(Text::from(lines)).block(block), popup_area);
+}
@@ -0,0 +1,81 @@
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::app::AppState;
+use crate::completion::CompletionKind;
+use crate::theme::palette;
+
+use super::editor::cursor_cell;
+
+/// Most suggestions listed at once
+const MAX_ROWS: usize = 8;
+const MAX_WIDTH: u16 = 40;
+
+/// Renders the suggestions for the text before the cursor, below the cursor
+/// or above it near the bottom of the editor
+pub fn render_completion(frame: &mut Frame, state: &mut AppState, editor_area: Rect) {
+    let Some(completion) = state.completion.as_ref().filter(|c| !c.dismissed) else {
+        return;
+    };
+    let Some(cursor) = cursor_cell(frame, editor_area) else {
+        return;
+    };
+
+    // Scroll the list to keep the selected suggestion in view
+    let rows = completion.items.len().min(MAX_ROWS);
+    let first = (completion.selected + 1).saturating_sub(rows);
+    let lines: Vec<Line> = completion
+        .items
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(rows)
+        .map(|(index, item)| {
+            let style = if index == completion.selected {
+                Style::default()
+                    .fg(palette::CRUST)
+                    .bg(palette::YELLOW)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(palette::TEXT)
+            };
+            Line::from(Span::styled(format!(" {} ", item.label), style))
+        })
+        .collect();
+
+    let widest = lines.iter().map(Line::width).max().unwrap_or(0) as u16;
+    let title = format!(" {} ", completion.kind.label());
+    let width = (widest.max(title.len() as u16) + 2)
+        .min(MAX_WIDTH)
+        .min(frame.area().width);
+    let height = rows as u16 + 2;
+    let below = editor_area.bottom().saturating_sub(cursor.y + 1);
+    let y = if below >= height || cursor.y < editor_area.y + height {
+        cursor.y + 1
+    } else {
+        cursor.y - height
+    };
+    // Line the suggestions up with the start of the typed text
+    let marker = u16::from(completion.kind == CompletionKind::Emoji);
+    let typed = Span::raw(completion.query.as_str()).width() as u16 + marker;
+    let x = cursor
+        .x
+        .saturating_sub(typed + 2)
+        .max(editor_area.x)
+        .min(frame.area().right().saturating_sub(width));
+    let area = Rect::new(x, y, width, height).intersection(frame.area());
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .title(title)
+        .style(Style::default().fg(palette::YELLOW).bg(palette::MANTLE));
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
@@ -0,0 +1,48 @@
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::app::{AppState, CONTEXT_MENU_ITEMS};
+use crate::theme::palette;
+
+/// Renders the note actions next to where the note list was right-clicked
+pub fn render_context_menu(frame: &mut Frame, state: &mut AppState, area: Rect) {
+    let Some(menu) = state.context_menu.as_mut() else {
+        return;
+    };
+
+    let lines: Vec<Line> = CONTEXT_MENU_ITEMS
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let style = if index == menu.selected {
+                Style::default()
+                    .fg(palette::CRUST)
+                    .bg(palette::YELLOW)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(palette::TEXT)
+            };
+            Line::styled(format!(" {:<11}", item), style)
+        })
+        .collect();
+
+    // Open at the click, moved back inside the screen near the edges
+    let width = 14.min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let x = menu.x.min(area.right().saturating_sub(width));
+    let y = menu.y.min(area.bottom().saturating_sub(height));
+    menu.area = Rect::new(x, y, width, height);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(Style::default().fg(palette::YELLOW));
+
+    frame.render_widget(Clear, menu.area);
+    frame.render_widget(Paragraph::new(lines).block(block), menu.area);
+}
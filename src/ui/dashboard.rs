@@ -0,0 +1,208 @@
+use chrono::{Datelike, Days};
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    symbols,
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::app::AppState;
+use crate::journal;
+use crate::stats::Activity;
+use crate::theme::palette;
+
+/// Columns taken by the names of the days left of the heatmap
+const DAY_LABEL_WIDTH: u16 = 4;
+
+/// Renders the totals of the vault, a heatmap of the edits made on each day
+/// and the notes edited last and the largest ones
+pub fn render_dashboard(frame: &mut Frame, state: &mut AppState, area: Rect) {
+    let Some(dashboard) = &state.dashboard else {
+        return;
+    };
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(10),
+            Constraint::Fill(1),
+        ])
+        .split(area);
+
+    let mut totals = vec![
+        count(dashboard.notes, "note"),
+        count(dashboard.words, "word"),
+        count(dashboard.folders, "folder"),
+        count(dashboard.tags, "tag"),
+    ];
+    if dashboard.open_tasks + dashboard.done_tasks > 0 {
+        totals.push(format!(
+            "{} of {} tasks done",
+            dashboard.done_tasks,
+            dashboard.open_tasks + dashboard.done_tasks
+        ));
+    }
+    let summary = Paragraph::new(Line::from(totals.join(" · ")))
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(palette::TEXT))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_set(symbols::border::ROUNDED)
+                .border_style(state.theme.selected_style)
+                .title(Span::styled("Dashboard", state.theme.title_style))
+                .title_alignment(Alignment::Center),
+        );
+    frame.render_widget(summary, rows[0]);
+
+    render_heatmap(frame, &state.activity, rows[1]);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(rows[2]);
+
+    let recent: Vec<ListItem> = dashboard
+        .recent
+        .iter()
+        .map(|(title, updated_at)| {
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    updated_at.format("%Y-%m-%d %H:%M").to_string(),
+                    Style::default().fg(palette::OVERLAY1),
+                ),
+                Span::raw(" "),
+                Span::raw(title.as_str()),
+            ]))
+        })
+        .collect();
+    let recent = List::new(recent)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_set(symbols::border::ROUNDED)
+                .border_style(Style::default().fg(palette::TEAL))
+                .title("Recently edited")
+                .title_bottom("<↑/↓> Select <Enter> Open <Esc> Close")
+                .title_alignment(Alignment::Center),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(palette::SURFACE0)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut list_state = ListState::default().with_selected(Some(state.dashboard_selected));
+    frame.render_stateful_widget(recent, columns[0], &mut list_state);
+
+    let largest: Vec<ListItem> = dashboard
+        .largest
+        .iter()
+        .map(|(title, words)| {
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{:>7}", words),
+                    Style::default().fg(palette::OVERLAY1),
+                ),
+                Span::raw(" "),
+                Span::raw(title.as_str()),
+            ]))
+        })
+        .collect();
+    let largest = List::new(largest).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_set(symbols::border::ROUNDED)
+            .border_style(Style::default().fg(palette::TEAL))
+            .title("Largest notes, in words")
+            .title_alignment(Alignment::Center),
+    );
+    frame.render_widget(largest, columns[1]);
+}
+
+/// Renders a week per column up to the current one, with a square per day
+/// colored by the number of edits made on it
+fn render_heatmap(frame: &mut Frame, activity: &Activity, area: Rect) {
+    let weeks = (area.width.saturating_sub(DAY_LABEL_WIDTH + 2) / 2).max(1) as u64;
+    let today = journal::today();
+    let monday = today
+        .checked_sub_days(Days::new(today.weekday().num_days_from_monday() as u64))
+        .unwrap_or(today);
+    let first = monday
+        .checked_sub_days(Days::new((weeks - 1) * 7))
+        .unwrap_or(monday);
+    let busiest = activity.busiest_since(first);
+
+    // Name the month above the first week starting in it
+    let mut months = " ".repeat(DAY_LABEL_WIDTH as usize);
+    for week in 0..weeks {
+        let start = first + Days::new(week * 7);
+        let column = DAY_LABEL_WIDTH as usize + 2 * week as usize;
+        let new_month = week == 0 || start.month() != (start - Days::new(7)).month();
+        if new_month && months.len() <= column {
+            months.push_str(&" ".repeat(column - months.len()));
+            months.push_str(&start.format("%b").to_string());
+        }
+    }
+    let mut lines = vec![Line::styled(months, Style::default().fg(palette::OVERLAY1))];
+
+    let mut total = 0;
+    for (weekday, label) in ["Mon", "", "Wed", "", "Fri", "", "Sun"]
+        .into_iter()
+        .enumerate()
+    {
+        let mut spans = vec![Span::styled(
+            format!("{:<width$}", label, width = DAY_LABEL_WIDTH as usize),
+            Style::default().fg(palette::OVERLAY1),
+        )];
+        let mut day = first + Days::new(weekday as u64);
+        while day <= today {
+            let edits = activity.edits(day);
+            total += edits;
+            spans.push(Span::styled(
+                "■ ",
+                Style::default().fg(shade(edits, busiest)),
+            ));
+            day = day + Days::new(7);
+        }
+        lines.push(Line::from(spans));
+    }
+
+    let heatmap = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_set(symbols::border::ROUNDED)
+            .border_style(Style::default().fg(palette::TEAL))
+            .title("Activity")
+            .title_bottom(format!(
+                "{} in the last {} weeks",
+                count(total, "edit"),
+                weeks
+            ))
+            .title_alignment(Alignment::Center),
+    );
+    frame.render_widget(heatmap, area);
+}
+
+/// Color of a day, from the surface for none to green for the busiest day
+fn shade(edits: usize, busiest: usize) -> Color {
+    if edits == 0 || busiest == 0 {
+        return palette::SURFACE0;
+    }
+    // Four levels, so that a single busy day does not wash out the others
+    let level = (edits * 4).div_ceil(busiest).min(4) as i32;
+    let (Color::Rgb(r0, g0, b0), Color::Rgb(r1, g1, b1)) = (palette::SURFACE1, palette::GREEN)
+    else {
+        return palette::GREEN;
+    };
+    let mix = |from: u8, to: u8| (from as i32 + (to as i32 - from as i32) * level / 4) as u8;
+    Color::Rgb(mix(r0, r1), mix(g0, g1), mix(b0, b1))
+}
+
+fn count(number: usize, noun: &str) -> String {
+    match number {
+        1 => format!("1 {}", noun),
+        _ => format!("{} {}s", number, noun),
+    }
+}
@@ -1,35 +1,61 @@
 use ratatui::{
     layout::{Alignment, Rect},
     style::{Color, Style},
+    text::Line,
     widgets::{Block, Borders, Clear, Paragraph},
     Frame,
 };
 
+use super::centered_rect;
 use crate::app::AppState;
 
-pub fn render_delete_confirm(frame: &mut Frame, state: &mut AppState, area: Rect) {
-    // Calculate dialog dimensions
-    let width = 50;
-    let height = 3;
-    let x = (area.width.saturating_sub(width)) / 2;
-    let y = (area.height.saturating_sub(height)) / 2;
-    let dialog_area = Rect::new(x, y, width, height);
+/// Titles listed when several notes are deleted, before the rest are only counted
+const LISTED_TITLES: usize = 10;
 
-    // Get current note title
-    let title = state
-        .list_state
-        .selected
-        .and_then(|i| state.notes.get(i))
-        .map(|note| note.title.as_str())
-        .unwrap_or("this note");
+pub fn render_delete_confirm(frame: &mut Frame, state: &mut AppState, area: Rect) {
+    // Titles of the marked notes, or of the current one
+    let titles: Vec<&str> = state
+        .batch_indices()
+        .into_iter()
+        .map(|index| state.notes[index].title.as_str())
+        .collect();
 
     // Create confirmation message
-    let message = format!("Delete '{}'? (Enter to confirm, Esc to cancel)", title);
+    let lines = match titles.as_slice() {
+        [] | [_] => vec![Line::from(format!(
+            "Delete '{}'? (Enter to confirm, Esc to cancel)",
+            titles.first().unwrap_or(&"this note")
+        ))],
+        titles => {
+            let mut lines = vec![
+                Line::from(format!(
+                    "Delete {} notes? (Enter to confirm, Esc to cancel)",
+                    titles.len()
+                )),
+                Line::default(),
+            ];
+            lines.extend(
+                titles
+                    .iter()
+                    .take(LISTED_TITLES)
+                    .map(|&title| Line::from(title)),
+            );
+            if titles.len() > LISTED_TITLES {
+                lines.push(Line::from(format!(
+                    "and {} more",
+                    titles.len() - LISTED_TITLES
+                )));
+            }
+            lines
+        }
+    };
+    let width = lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 4;
+    let dialog_area = centered_rect(area, width.max(50), lines.len() as u16 + 2);
 
     // Render dialog box
     frame.render_widget(Clear, dialog_area); // Clear the background
     frame.render_widget(
-        Paragraph::new(message)
+        Paragraph::new(lines)
             .block(Block::default().borders(Borders::ALL))
             .alignment(Alignment::Center)
             .style(Style::default().fg(Color::Red)),
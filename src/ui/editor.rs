@@ -1,36 +1,212 @@
-use edtui::{EditorStatusLine, EditorView, SyntaxHighlighter};
+use std::collections::HashMap;
+
+use edtui::{
+    EditorMode, EditorState, EditorStatusLine, EditorTheme, EditorView, RowIndex, SyntaxHighlighter,
+};
 use ratatui::{
-    layout::{Alignment, Rect},
-    style::Style,
+    layout::{Alignment, Margin, Position, Rect},
+    style::{Modifier, Style},
     symbols,
     text::Span,
     widgets::{Block, Borders},
     Frame,
 };
 
-use crate::{app::AppState, theme::palette};
+use crate::{
+    app::{AppState, View},
+    theme::palette,
+};
 
 pub fn render_editor(frame: &mut Frame, state: &mut AppState, area: Rect) {
+    // Name the notes when two are edited side by side
+    let title = match state.current_view {
+        View::Split => state.active_title().unwrap_or("Editor").to_string(),
+        _ => "Editor".to_string(),
+    };
+    let theme = &state.theme;
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme.selected_style)
+        .border_set(symbols::border::ROUNDED)
+        .title(Span::styled(title, theme.title_style))
+        .title_alignment(Alignment::Center);
+    render_pane(frame, &mut state.editor_state, block, true, area);
+    underline_misspellings(frame, state, area);
+}
+
+/// Renders the note of the split view which does not take input
+pub fn render_other_editor(frame: &mut Frame, state: &mut AppState, area: Rect) {
+    let Some(tab) = state
+        .split
+        .as_ref()
+        .and_then(|split| state.tabs.iter_mut().find(|tab| tab.title == split.other))
+    else {
+        return;
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(palette::SURFACE2))
+        .border_set(symbols::border::ROUNDED)
+        .title(Span::styled(
+            tab.title.clone(),
+            Style::default().fg(palette::OVERLAY1),
+        ))
+        .title_alignment(Alignment::Center);
+    render_pane(frame, &mut tab.editor, block, false, area);
+}
+
+fn render_pane(
+    frame: &mut Frame,
+    editor_state: &mut EditorState,
+    block: Block,
+    focused: bool,
+    area: Rect,
+) {
     let syntax_highlighter = SyntaxHighlighter::new("ayu-dark", "markdown");
-    let editor = EditorView::new(&mut state.editor_state)
+    let mut theme = edtui::EditorTheme::default()
+        .block(block)
+        .base(Style::default().bg(palette::BASE).fg(palette::OVERLAY0))
+        .status_line(
+            EditorStatusLine::default()
+                .style_text(Style::default().fg(palette::ROSEWATER))
+                .style_line(Style::default().fg(palette::ROSEWATER)),
+        );
+    if !focused {
+        theme = theme.hide_cursor();
+    }
+    // The status bar shows the mode, the editor only the text being searched for
+    if !focused || editor_state.mode != EditorMode::Search {
+        theme = theme.hide_status_line();
+    }
+    let editor = EditorView::new(editor_state)
         .syntax_highlighter(Some(syntax_highlighter))
         .wrap(true)
-        .theme(
-            edtui::EditorTheme::default()
-                .block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .border_style(state.theme.selected_style)
-                        .border_set(symbols::border::ROUNDED)
-                        .title(Span::styled("Editor", state.theme.title_style))
-                        .title_alignment(Alignment::Center),
-                )
-                .base(Style::default().bg(palette::BASE).fg(palette::OVERLAY0))
-                .status_line(
-                    EditorStatusLine::default()
-                        .style_text(Style::default().fg(palette::ROSEWATER))
-                        .style_line(Style::default().fg(palette::ROSEWATER)),
-                ),
-        );
+        .theme(theme);
     frame.render_widget(editor, area);
 }
+
+/// Cell the editor drew its cursor on, which edtui does not report
+pub(super) fn cursor_cell(frame: &mut Frame, area: Rect) -> Option<Position> {
+    let style = EditorTheme::default().cursor_style;
+    let (fg, bg) = (style.fg?, style.bg?);
+    let buffer = frame.buffer_mut();
+    area.rows()
+        .flat_map(|row| row.columns())
+        .map(|cell| Position::new(cell.x, cell.y))
+        .find(|&position| {
+            buffer
+                .cell(position)
+                .is_some_and(|cell| cell.fg == fg && cell.bg == bg)
+        })
+}
+
+/// Underline the misspelled words on screen, placing them from the cursor
+/// the way the editor wraps lines
+fn underline_misspellings(frame: &mut Frame, state: &AppState, area: Rect) {
+    let editor = &state.editor_state;
+    if state.misspellings.is_empty() {
+        return;
+    }
+    let Some(cursor) = cursor_cell(frame, area) else {
+        return;
+    };
+    let mut text_area = area.inner(Margin::new(1, 1));
+    if editor.mode == EditorMode::Search {
+        text_area.height = text_area.height.saturating_sub(1);
+    }
+    let width = text_area.width as usize;
+    let line = |row: usize| editor.lines.get(RowIndex::new(row)).map(Vec::as_slice);
+
+    // Screen row of the first part of each line shown, going both ways from the cursor
+    let mut tops = HashMap::new();
+    let cursor_line = line(editor.cursor.row).unwrap_or_default();
+    let top = i32::from(cursor.y) - wrapped_row(cursor_line, editor.cursor.col, width) as i32;
+    tops.insert(editor.cursor.row, top);
+    let (mut row, mut above) = (editor.cursor.row, top);
+    while row > 0 && above > i32::from(text_area.top()) {
+        row -= 1;
+        above -= line(row).map_or(1, |line| line_rows(line, width)) as i32;
+        tops.insert(row, above);
+    }
+    let (mut row, mut below) = (editor.cursor.row, top);
+    while below < i32::from(text_area.bottom()) {
+        let Some(current) = line(row) else {
+            break;
+        };
+        below += line_rows(current, width) as i32;
+        row += 1;
+        tops.insert(row, below);
+    }
+
+    let style = Style::default()
+        .add_modifier(Modifier::UNDERLINED)
+        .underline_color(palette::RED);
+    let buffer = frame.buffer_mut();
+    for misspelling in &state.misspellings {
+        let (Some(&top), Some(line)) = (tops.get(&misspelling.row), line(misspelling.row)) else {
+            continue;
+        };
+        // Results for an older version of the text may not fit anymore
+        let end = misspelling.col + misspelling.word.chars().count();
+        let still_there = line
+            .get(misspelling.col..end)
+            .is_some_and(|chars| chars.iter().copied().eq(misspelling.word.chars()));
+        if !still_there {
+            continue;
+        }
+        for col in misspelling.col..end {
+            let (sub_row, x) = wrapped_position(line, col, width);
+            let y = top + sub_row as i32;
+            if y < i32::from(text_area.top()) || y >= i32::from(text_area.bottom()) {
+                continue;
+            }
+            let position = Position::new(text_area.x + x as u16, y as u16);
+            if let Some(cell) = buffer.cell_mut(position) {
+                cell.set_style(style);
+            }
+        }
+    }
+}
+
+fn char_width(c: char) -> usize {
+    // The editor shows tabs two columns wide
+    if c == '\t' {
+        2
+    } else {
+        Span::raw(c.to_string()).width()
+    }
+}
+
+/// Wrapped row and screen column of a character of a line
+fn wrapped_position(line: &[char], col: usize, width: usize) -> (usize, usize) {
+    let (mut row, mut x) = (0, 0);
+    for &c in &line[..col.min(line.len())] {
+        let c_width = char_width(c);
+        if x + c_width > width {
+            row += 1;
+            x = 0;
+        }
+        x += c_width;
+    }
+    match line.get(col) {
+        Some(&c) if x + char_width(c) > width => (row + 1, 0),
+        _ => (row, x),
+    }
+}
+
+/// Wrapped row of a column, which for the end of the line is its last row
+fn wrapped_row(line: &[char], col: usize, width: usize) -> usize {
+    if col >= line.len() {
+        line_rows(line, width) - 1
+    } else {
+        wrapped_position(line, col, width).0
+    }
+}
+
+/// Screen rows a line takes up
+fn line_rows(line: &[char], width: usize) -> usize {
+    match line.len() {
+        0 => 1,
+        len => wrapped_position(line, len - 1, width).0 + 1,
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::thread;
+
+use ansi_to_tui::IntoText;
+use ratatui::text::Line;
+use syntect::{
+    easy::HighlightLines,
+    highlighting::ThemeSet,
+    parsing::SyntaxSet,
+    util::{as_24_bit_terminal_escaped, LinesWithEndings},
+};
+use tracing::{debug, warn};
+
+static SYNTAX_SET: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+static THEME_SET: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+
+static WORKER: LazyLock<Worker> = LazyLock::new(Worker::start);
+
+/// Highlighter for the language of a code block, if syntect knows it
+pub fn highlighter(lang: &str) -> Option<HighlightLines<'static>> {
+    match SYNTAX_SET.find_syntax_by_token(lang) {
+        Some(syntax) => {
+            debug!("Starting code block with syntax: {:?}", lang);
+            let theme = &THEME_SET.themes["base16-ocean.dark"];
+            Some(HighlightLines::new(syntax, theme))
+        }
+        None => {
+            warn!("Could not find syntax for code block: {:?}", lang);
+            None
+        }
+    }
+}
+
+/// Highlight the next part of a code block
+pub fn highlight_lines(highlighter: &mut HighlightLines, code: &str) -> Vec<Line<'static>> {
+    LinesWithEndings::from(code)
+        .filter_map(|line| highlighter.highlight_line(line, &SYNTAX_SET).ok())
+        .filter_map(|part| as_24_bit_terminal_escaped(&part, false).into_text().ok())
+        .flat_map(|text| text.lines)
+        .collect()
+}
+
+/// Whether syntect knows the language of a code block
+pub fn supports(lang: &str) -> bool {
+    SYNTAX_SET.find_syntax_by_token(lang).is_some()
+}
+
+/// Names and file extensions syntect finds the languages of code blocks by
+pub fn languages() -> Vec<String> {
+    static LANGUAGES: LazyLock<Vec<String>> = LazyLock::new(|| {
+        let mut languages: Vec<String> = SYNTAX_SET
+            .syntaxes()
+            .iter()
+            .flat_map(|syntax| {
+                std::iter::once(syntax.name.to_lowercase()).chain(syntax.file_extensions.clone())
+            })
+            .filter(|token| {
+                !token.is_empty()
+                    && token
+                        .chars()
+                        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "+#-".contains(c))
+            })
+            .collect();
+        languages.sort_unstable();
+        languages.dedup();
+        languages
+    });
+    LANGUAGES.clone()
+}
+
+/// Key of a code block and its highlighted lines, which are `None` while the
+/// background worker is still on it
+pub fn request(lang: &str, code: &str) -> (u64, Option<Vec<Line<'static>>>) {
+    let key = key(lang, code);
+    if let Some(lines) = WORKER.done.lock().unwrap().get(&key) {
+        return (key, Some(lines.clone()));
+    }
+    if WORKER.queued.lock().unwrap().insert(key) {
+        let job = (key, lang.to_string(), code.to_string());
+        if WORKER.jobs.lock().unwrap().send(job).is_err() {
+            warn!("Highlighting worker stopped");
+        }
+    }
+    (key, None)
+}
+
+/// Whether the worker has finished a code block
+pub fn is_done(key: u64) -> bool {
+    WORKER.done.lock().unwrap().contains_key(&key)
+}
+
+/// Forget the highlighted code blocks that are not shown anymore
+pub fn retain(keys: &HashSet<u64>) {
+    WORKER
+        .done
+        .lock()
+        .unwrap()
+        .retain(|key, _| keys.contains(key));
+}
+
+fn key(lang: &str, code: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (lang, code).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Highlights code blocks on a thread of its own, so that syntect does not
+/// hold up drawing
+struct Worker {
+    jobs: Mutex<Sender<(u64, String, String)>>,
+    done: Arc<Mutex<HashMap<u64, Vec<Line<'static>>>>>,
+    queued: Arc<Mutex<HashSet<u64>>>,
+}
+
+impl Worker {
+    fn start() -> Self {
+        let (jobs, receiver) = mpsc::channel::<(u64, String, String)>();
+        let done = Arc::new(Mutex::new(HashMap::new()));
+        let queued = Arc::new(Mutex::new(HashSet::new()));
+
+        let (thread_done, thread_queued) = (Arc::clone(&done), Arc::clone(&queued));
+        thread::spawn(move || {
+            for (key, lang, code) in receiver {
+                let lines = highlighter(&lang)
+                    .map(|mut highlighter| highlight_lines(&mut highlighter, &code))
+                    .unwrap_or_default();
+                thread_done.lock().unwrap().insert(key, lines);
+                thread_queued.lock().unwrap().remove(&key);
+            }
+        });
+
+        Self {
+            jobs: Mutex::new(jobs),
+            done,
+            queued,
+        }
+    }
+}
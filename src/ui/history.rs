@@ -0,0 +1,88 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    symbols,
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::app::AppState;
+use crate::theme::palette;
+
+/// Renders the commit log of the selected note next to the diff of the selected commit
+pub fn render_history(frame: &mut Frame, state: &mut AppState, area: Rect) {
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
+        .split(area);
+
+    let items: Vec<ListItem> = state
+        .history
+        .iter()
+        .map(|revision| {
+            ListItem::new(vec![
+                Line::from(vec![
+                    Span::styled(revision.short_id(), Style::default().fg(palette::YELLOW)),
+                    Span::raw(" "),
+                    Span::styled(
+                        revision.date.format("%Y-%m-%d %H:%M").to_string(),
+                        Style::default().fg(palette::OVERLAY1),
+                    ),
+                ]),
+                Line::from(revision.summary.as_str()),
+            ])
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_set(symbols::border::ROUNDED)
+                .border_style(state.theme.selected_style)
+                .title(Span::styled("History", state.theme.title_style))
+                .title_bottom("<↑/↓> Select <PgUp/PgDn> Scroll <Esc> Close")
+                .title_alignment(Alignment::Center),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(palette::SURFACE0)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut list_state = ListState::default().with_selected(Some(state.history_selected));
+    frame.render_stateful_widget(list, columns[0], &mut list_state);
+
+    let diff = Paragraph::new(diff_text(&state.history_diff))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_set(symbols::border::ROUNDED)
+                .border_style(Style::default().fg(palette::TEAL))
+                .title("Changes")
+                .title_alignment(Alignment::Center),
+        )
+        .scroll((state.history_scroll as u16, 0));
+    frame.render_widget(diff, columns[1]);
+}
+
+fn diff_text(diff: &str) -> Text<'_> {
+    diff.lines()
+        .map(|line| {
+            let color = if line.starts_with("+++") || line.starts_with("---") {
+                palette::SUBTEXT0
+            } else if line.starts_with('+') {
+                palette::GREEN
+            } else if line.starts_with('-') {
+                palette::RED
+            } else if line.starts_with("@@") {
+                palette::SAPPHIRE
+            } else if line.starts_with("diff ") || line.starts_with("index ") {
+                palette::OVERLAY1
+            } else {
+                palette::TEXT
+            };
+            Line::styled(line, Style::default().fg(color))
+        })
+        .collect()
+}
@@ -1,53 +1,277 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
 
 use crate::app::View;
+use crate::models::note::Note;
+
+/// File in the notes directory keeping the layout between sessions
+pub const LAYOUT_FILE: &str = "layout.json";
+
+/// Below this width the editor and preview are stacked in the `Auto` arrangement
+const STACK_BELOW_WIDTH: u16 = 100;
 
+/// Where the panes were drawn on the last frame
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct Areas {
-    pub note_list: Rect,
+    /// `None` while the note list is hidden
+    pub note_list: Option<Rect>,
+    /// Tab bar above the panes, when more than one note is open
+    pub tabs: Option<Rect>,
     pub preview: Option<Rect>,
+    /// Editor taking input
     pub editor: Option<Rect>,
+    /// Second editor of the split view
+    pub other_editor: Option<Rect>,
+}
+
+/// How the editor and the preview share the screen when both are shown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Arrangement {
+    /// Side by side, or stacked on narrow terminals
+    #[default]
+    Auto,
+    SideBySide,
+    /// Editor above the preview
+    Stacked,
+}
+
+impl Arrangement {
+    pub fn next(self) -> Self {
+        match self {
+            Arrangement::Auto => Arrangement::SideBySide,
+            Arrangement::SideBySide => Arrangement::Stacked,
+            Arrangement::Stacked => Arrangement::Auto,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Arrangement::Auto => "automatic",
+            Arrangement::SideBySide => "side by side",
+            Arrangement::Stacked => "stacked",
+        }
+    }
+}
+
+/// How much of each note the cards of the note list show
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CardLayout {
+    /// A line per note
+    Compact,
+    /// Title and age in a box
+    #[default]
+    Normal,
+    /// A snippet of the text, the last edit, the length and the tags too
+    Expanded,
+}
+
+impl CardLayout {
+    pub fn next(self) -> Self {
+        match self {
+            CardLayout::Compact => CardLayout::Normal,
+            CardLayout::Normal => CardLayout::Expanded,
+            CardLayout::Expanded => CardLayout::Compact,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CardLayout::Compact => "compact",
+            CardLayout::Normal => "normal",
+            CardLayout::Expanded => "expanded",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            CardLayout::Compact,
+            CardLayout::Normal,
+            CardLayout::Expanded,
+        ]
+        .into_iter()
+        .find(|cards| cards.label() == name)
+    }
+
+    /// Rows taken by the card of a note
+    pub fn height(self, note: &Note) -> u16 {
+        match self {
+            CardLayout::Compact => 1,
+            CardLayout::Normal => 3,
+            // The row of tags is left out when there are none
+            CardLayout::Expanded if !note.has_tags() => 4,
+            CardLayout::Expanded => 5,
+        }
+    }
+}
+
+/// Sizes and arrangement of the panes, kept in [`LAYOUT_FILE`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutSettings {
+    /// Share of the width taken by the note list, in percent
+    pub list: u16,
+    /// Share of the space next to the note list taken by the editor when the
+    /// preview is shown too, in percent
+    pub editor: u16,
+    pub list_hidden: bool,
+    pub arrangement: Arrangement,
+    pub cards: CardLayout,
 }
 
-pub fn create_layout(area: Rect, view: View) -> Areas {
-    if let View::LivePreview = view {
+impl LayoutSettings {
+    pub const MIN_LIST: u16 = 10;
+    pub const MAX_LIST: u16 = 60;
+    pub const MIN_EDITOR: u16 = 20;
+    pub const MAX_EDITOR: u16 = 80;
+
+    /// Layout saved in the notes directory, or the default one
+    pub fn load(root_dir: &Path) -> Self {
+        let path = root_dir.join(LAYOUT_FILE);
+        let Ok(contents) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        match serde_json::from_str::<Self>(&contents) {
+            Ok(settings) => settings.clamped(),
+            Err(e) => {
+                warn!("Ignoring invalid layout in {}: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self, root_dir: &Path) -> io::Result<()> {
+        let path = root_dir.join(LAYOUT_FILE);
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, json)?;
+        fs::rename(temp_path, path)
+    }
+
+    /// Widen or narrow the note list by some percent of the width
+    pub fn resize_list(&mut self, delta: i16) {
+        self.list = self.list.saturating_add_signed(delta);
+        *self = self.clamped();
+    }
+
+    /// Grow or shrink the editor against the preview
+    pub fn resize_editor(&mut self, delta: i16) {
+        self.editor = self.editor.saturating_add_signed(delta);
+        *self = self.clamped();
+    }
+
+    /// Whether the editor goes above the preview on a screen of this width
+    pub fn stacked(&self, width: u16) -> bool {
+        match self.arrangement {
+            Arrangement::Auto => width < STACK_BELOW_WIDTH,
+            Arrangement::SideBySide => false,
+            Arrangement::Stacked => true,
+        }
+    }
+
+    fn clamped(mut self) -> Self {
+        self.list = self.list.clamp(Self::MIN_LIST, Self::MAX_LIST);
+        self.editor = self.editor.clamp(Self::MIN_EDITOR, Self::MAX_EDITOR);
+        self
+    }
+}
+
+impl Default for LayoutSettings {
+    fn default() -> Self {
+        Self {
+            list: 20,
+            editor: 50,
+            list_hidden: false,
+            arrangement: Arrangement::Auto,
+            cards: CardLayout::Normal,
+        }
+    }
+}
+
+/// Split the screen into panes for a view.
+///
+/// In the split view the editor taking input goes on the left (or top) when
+/// `focus_left` is set, and on the other side otherwise.
+pub fn create_layout(
+    area: Rect,
+    view: View,
+    settings: &LayoutSettings,
+    tab_bar: bool,
+    focus_left: bool,
+) -> Areas {
+    // The outline takes the place of the note list, so it is shown even when the list is hidden
+    let (note_list, main) = if settings.list_hidden && view != View::Outline {
+        (None, area)
+    } else {
         let columns = Layout::default()
             .direction(Direction::Horizontal)
-            .constraints(
-                [
-                    Constraint::Percentage(20),
-                    Constraint::Percentage(40),
-                    Constraint::Percentage(40),
-                ]
-                .as_ref(),
-            )
+            .constraints([Constraint::Percentage(settings.list), Constraint::Fill(1)])
             .split(area);
+        (Some(columns[0]), columns[1])
+    };
+
+    let (tabs, main) = if tab_bar {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Fill(1)])
+            .split(main);
+        (Some(rows[0]), rows[1])
+    } else {
+        (None, main)
+    };
 
-        return Areas {
-            note_list: columns[0],
-            preview: Some(columns[2]),
-            editor: Some(columns[1]),
+    let mut areas = Areas {
+        note_list,
+        tabs,
+        ..Areas::default()
+    };
+    if let View::LivePreview | View::Outline | View::ContextMenu | View::Split = view {
+        let direction = if settings.stacked(area.width) {
+            Direction::Vertical
+        } else {
+            Direction::Horizontal
         };
+        let panes = Layout::default()
+            .direction(direction)
+            .constraints([Constraint::Percentage(settings.editor), Constraint::Fill(1)])
+            .split(main);
+
+        if view == View::Split {
+            let (focused, other) = if focus_left {
+                (panes[0], panes[1])
+            } else {
+                (panes[1], panes[0])
+            };
+            areas.editor = Some(focused);
+            areas.other_editor = Some(other);
+        } else {
+            areas.editor = Some(panes[0]);
+            areas.preview = Some(panes[1]);
+        }
+        return areas;
     }
-    let columns = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(20), Constraint::Percentage(80)].as_ref())
-        .split(area);
 
     match view {
-        View::Editor => Areas {
-            note_list: columns[0],
-            preview: None,
-            editor: Some(columns[1]),
-        },
-        View::Preview => Areas {
-            note_list: columns[0],
-            preview: Some(columns[1]),
-            editor: None,
-        },
-        _ => Areas {
-            note_list: columns[0],
-            preview: Some(columns[1]),
-            editor: None,
-        },
+        View::Editor => areas.editor = Some(main),
+        _ => areas.preview = Some(main),
     }
+    areas
+}
+
+/// Area of a dialog centered in `area`, shrunk to fit on small screens
+pub fn centered_rect(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
 }
@@ -1,37 +1,141 @@
+mod archive;
+mod calendar;
+mod completion;
+mod context_menu;
+mod dashboard;
 mod delete_confirm;
 mod editor;
+mod highlight;
+mod history;
 mod layout;
+mod note_info;
 mod note_list;
+mod outline;
+mod passphrase;
 mod preview;
 mod rename;
+mod render_cache;
+mod status;
+mod tabs;
 
-use ratatui::Frame;
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::Style,
+    widgets::{Paragraph, Wrap},
+    Frame,
+};
 
 use crate::app::{AppState, View};
+use crate::theme::palette;
 
+pub use archive::render_archive;
+pub use calendar::render_calendar;
+pub use completion::render_completion;
+pub use context_menu::render_context_menu;
+pub use dashboard::render_dashboard;
 pub use delete_confirm::render_delete_confirm;
-pub use editor::render_editor;
-use layout::create_layout;
+pub use editor::{render_editor, render_other_editor};
+pub use highlight::languages;
+pub use history::render_history;
+use layout::{centered_rect, create_layout};
+pub use layout::{Areas, Arrangement, CardLayout, LayoutSettings, LAYOUT_FILE};
+pub use note_info::render_note_info;
 pub use note_list::render_note_list;
-pub use preview::render_preview;
+pub use outline::render_outline;
+pub use passphrase::render_passphrase;
+pub use preview::{from_str, markdown_options, render_preview};
 pub use rename::render_rename;
+pub use render_cache::{PreviewCache, SourceMap};
+pub use status::{render_status, status_shown};
+pub use tabs::render_tabs;
+
+/// Smallest screen the panes are drawn on
+const MIN_WIDTH: u16 = 30;
+const MIN_HEIGHT: u16 = 8;
 
 pub fn render(frame: &mut Frame, state: &mut AppState) {
-    let areas = create_layout(frame.area(), state.current_view);
+    let mut area = frame.area();
+    if area.width < MIN_WIDTH || area.height < MIN_HEIGHT {
+        state.areas = Areas::default();
+        let message = Paragraph::new("Terminal too small, resize it to use Thoth")
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(palette::YELLOW))
+            .wrap(Wrap { trim: true });
+        frame.render_widget(message, centered_rect(area, area.width, 3));
+        return;
+    }
+
+    // Reserve the bottom line for the status message while there is one
+    if status_shown(state) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)])
+            .split(area);
+        area = rows[0];
+        render_status(frame, state, rows[1]);
+    }
+
+    let focus_left = state.split.as_ref().is_none_or(|split| split.focus_left);
+    let areas = create_layout(
+        area,
+        state.current_view,
+        &state.layout,
+        state.tabs.len() > 1,
+        focus_left,
+    );
+    state.areas = areas;
+    if let Some(tabs_area) = areas.tabs {
+        render_tabs(frame, state, tabs_area);
+    }
 
     // Render the different components
-    render_note_list(frame, state, areas.note_list);
+    if let Some(list_area) = areas.note_list {
+        if state.current_view == View::Outline {
+            render_outline(frame, state, list_area);
+        } else {
+            render_note_list(frame, state, list_area);
+        }
+    }
 
     match state.current_view {
         View::Editor => render_editor(frame, state, areas.editor.unwrap()),
         View::Preview => render_preview(frame, state, areas.preview.unwrap()),
         View::List => {} // List is always shown
-        View::Rename => render_rename(frame, state, frame.area()),
-        View::LivePreview => {
+        View::Rename | View::TemplateVariable => render_rename(frame, state, frame.area()),
+        View::LivePreview | View::Outline => {
             render_editor(frame, state, areas.editor.unwrap());
             render_preview(frame, state, areas.preview.unwrap())
         }
         View::DeleteConfirm => render_delete_confirm(frame, state, frame.area()),
+        View::Passphrase => render_passphrase(frame, state, frame.area()),
+        View::History => render_history(frame, state, areas.preview.unwrap()),
+        View::Archive => render_archive(frame, state, areas.preview.unwrap()),
+        View::Dashboard => render_dashboard(frame, state, areas.preview.unwrap()),
+        View::NoteInfo => {
+            render_preview(frame, state, areas.preview.unwrap());
+            render_note_info(frame, state, frame.area())
+        }
+        View::Calendar => {
+            render_preview(frame, state, areas.preview.unwrap());
+            render_calendar(frame, state, frame.area())
+        }
+        View::Split => {
+            render_editor(frame, state, areas.editor.unwrap());
+            render_other_editor(frame, state, areas.other_editor.unwrap());
+        }
+        View::ContextMenu => {
+            render_editor(frame, state, areas.editor.unwrap());
+            render_preview(frame, state, areas.preview.unwrap());
+            render_context_menu(frame, state, frame.area())
+        }
+    }
+
+    // Suggestions may reach over the panes next to the editor
+    if matches!(
+        state.current_view,
+        View::Editor | View::LivePreview | View::Split
+    ) {
+        render_completion(frame, state, areas.editor.unwrap());
     }
 
     // Add help/status bar if needed
@@ -0,0 +1,89 @@
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::Style,
+    text::{Line, Span, Text},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use super::centered_rect;
+use crate::app::AppState;
+use crate::theme::palette;
+
+/// Width of the names column
+const LABEL_WIDTH: usize = 14;
+
+/// Renders the statistics of the selected note with its exact dates
+pub fn render_note_info(frame: &mut Frame, state: &mut AppState, area: Rect) {
+    let Some(note) = state
+        .list_state
+        .selected
+        .and_then(|selected| state.notes.get(selected))
+    else {
+        return;
+    };
+    let stats = &state.note_stats;
+    let minutes = match stats.reading_minutes() {
+        1 => "1 minute".to_string(),
+        minutes => format!("{} minutes", minutes),
+    };
+    let mut rows = vec![
+        ("Words", stats.words.to_string()),
+        ("Characters", stats.characters.to_string()),
+        ("Lines", stats.lines.to_string()),
+        ("Headings", stats.headings.to_string()),
+        ("Links", stats.links.to_string()),
+        (
+            "Tasks",
+            format!("{} open, {} done", stats.open_tasks, stats.done_tasks),
+        ),
+        ("Reading time", minutes),
+        ("", String::new()),
+        (
+            "Created",
+            note.created_at.format("%Y-%m-%d %H:%M:%S %:z").to_string(),
+        ),
+        (
+            "Updated",
+            note.updated_at.format("%Y-%m-%d %H:%M:%S %:z").to_string(),
+        ),
+    ];
+    if !note.folder.is_empty() {
+        rows.push(("Folder", note.folder.clone()));
+    }
+    if !note.tags.is_empty() {
+        rows.push(("Tags", note.tags.join(", ")));
+    }
+
+    let mut lines: Vec<Line> = rows
+        .into_iter()
+        .map(|(label, value)| {
+            Line::from(vec![
+                Span::styled(
+                    format!(" {:<width$}", label, width = LABEL_WIDTH),
+                    Style::default().fg(palette::OVERLAY1),
+                ),
+                Span::styled(value, Style::default().fg(palette::TEXT)),
+            ])
+        })
+        .collect();
+    lines.push(Line::default());
+    lines.push(Line::styled(
+        " Esc close",
+        Style::default().fg(palette::OVERLAY0),
+    ));
+
+    let block = Block::default()
+        .title(format!(" {} ", note.title))
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(Style::default().fg(palette::YELLOW));
+
+    let width = lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 3;
+    let width = width.max(note.title.chars().count() as u16 + 6);
+    let popup_area = centered_rect(area, width, lines.len() as u16 + 2);
+
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(Paragraph::new(Text::from(lines)).block(block), popup_area);
+}
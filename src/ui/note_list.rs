@@ -1,23 +1,148 @@
 use ratatui::{
-    layout::Rect,
-    widgets::{Block, BorderType, Borders},
+    buffer::Buffer,
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    symbols,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Paragraph, Widget},
     Frame,
 };
 use tui_widget_list::{ListBuilder, ListView};
 
+use super::CardLayout;
 use crate::app::AppState;
+use crate::models::note::{relative_time, Note};
+use crate::sorting::SortKey;
+use crate::theme::palette;
+
+/// A note in the list, under the heading of its section when it starts one
+struct NoteCard {
+    note: Note,
+    header: Option<String>,
+    layout: CardLayout,
+}
+
+impl Widget for NoteCard {
+    fn render(self, mut area: Rect, buf: &mut Buffer) {
+        if let Some(header) = self.header {
+            Line::styled(
+                format!(" {}", header),
+                Style::default()
+                    .fg(palette::MAUVE)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .render(Rect { height: 1, ..area }, buf);
+            area.y += 1;
+            area.height = area.height.saturating_sub(1);
+        }
+        match self.layout {
+            CardLayout::Compact => render_compact(&self.note, area, buf),
+            CardLayout::Normal => self.note.render(area, buf),
+            CardLayout::Expanded => render_expanded(&self.note, area, buf),
+        }
+    }
+}
+
+/// Draw the note on a single line, with its age on the right
+fn render_compact(note: &Note, area: Rect, buf: &mut Buffer) {
+    let color = note.card_color();
+    let mut style = Style::default().fg(color);
+    if note.selected {
+        style = style.add_modifier(Modifier::BOLD);
+    }
+    let marker = if note.selected { "▌" } else { " " };
+    let age = Line::styled(format!(" {} ", relative_time(note.created_at)), style)
+        .alignment(Alignment::Right);
+    // Long titles are cut short of the age
+    let title_area = Rect {
+        width: area.width.saturating_sub(age.width() as u16),
+        ..area
+    };
+    Line::from(vec![
+        Span::styled(marker, style),
+        Span::styled(note.card_title(), style),
+    ])
+    .render(title_area, buf);
+    age.render(area, buf);
+}
+
+/// Draw the note with a snippet of its text, when it was last edited,
+/// its length and its tags
+fn render_expanded(note: &Note, area: Rect, buf: &mut Buffer) {
+    let color = note.card_color();
+    let muted = Style::default().fg(palette::OVERLAY1);
+    let mut lines = vec![match note.snippet() {
+        Some(snippet) => Line::styled(
+            snippet.to_string(),
+            Style::default().fg(color).add_modifier(Modifier::ITALIC),
+        ),
+        None => Line::styled("No text", muted),
+    }];
+
+    let mut details = format!("edited {}", relative_time(note.updated_at));
+    if !note.locked {
+        details.push_str(&format!(" · {} words", note.words));
+    }
+    lines.push(Line::styled(details, muted));
+
+    let tags = note.all_tags();
+    if !tags.is_empty() {
+        let chip = Style::default().fg(palette::CRUST).bg(palette::LAVENDER);
+        let mut spans = Vec::new();
+        for tag in tags {
+            spans.push(Span::styled(format!(" #{} ", tag), chip));
+            spans.push(Span::raw(" "));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    let title = note.card_title();
+    Paragraph::new(lines)
+        .block(
+            Block::bordered()
+                .border_style(Style::default().fg(color))
+                .border_set(symbols::border::ROUNDED)
+                .title(Span::styled(title, Style::default().fg(color)).into_centered_line())
+                .padding(ratatui::widgets::Padding::left(1)),
+        )
+        .render(area, buf);
+}
 
 pub fn render_note_list(frame: &mut Frame, state: &mut AppState, area: Rect) {
+    let sorting = state.sorting;
+    let layout = state.layout.cards;
+    let notes = &state.notes;
     let builder = ListBuilder::new(|context| {
-        let mut note = state.notes[context.index].clone();
+        let mut note = notes[context.index].clone();
         note.selected = context.is_selected;
-        (note, 3)
+        let header = sorting.header(notes, context.index);
+        let height = layout.height(&note) + u16::from(header.is_some());
+        (
+            NoteCard {
+                note,
+                header,
+                layout,
+            },
+            height,
+        )
     });
 
     let note_count = state.notes.len();
+    // Notes are only moved by hand in the manual order
+    let hint = if sorting.key == SortKey::Manual {
+        "<Ctrl-↑/↓> Navigate <Alt-↑/↓> Move".to_string()
+    } else {
+        format!("<Ctrl-↑/↓> Navigate · by {}", sorting.describe())
+    };
+    let marked = notes.iter().filter(|note| note.marked).count();
+    let footer = if marked > 0 {
+        format!("{} marked", marked)
+    } else {
+        "<Ctrl-N/R/D/S>".to_string()
+    };
     let block = Block::default()
-        .title_top("<Ctrl-↑/↓> Navigate <Alt-↑/↓> Move")
-        .title_bottom("<Ctrl-N/R/D/S>")
+        .title_top(hint)
+        .title_bottom(footer)
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded);
 
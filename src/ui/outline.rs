@@ -0,0 +1,64 @@
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    symbols,
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState},
+    Frame,
+};
+
+use crate::app::AppState;
+use crate::theme::palette;
+
+/// Renders the headings of the selected note in place of the note list
+pub fn render_outline(frame: &mut Frame, state: &mut AppState, area: Rect) {
+    let items: Vec<ListItem> = state
+        .outline
+        .iter()
+        .enumerate()
+        .map(|(index, heading)| {
+            let marker = if state.folded_headings.contains(&index) {
+                "▸ "
+            } else {
+                "  "
+            };
+            let mut style = Style::default().fg(match heading.level {
+                1 => palette::MAUVE,
+                2 => palette::BLUE,
+                3 => palette::TEAL,
+                _ => palette::SUBTEXT0,
+            });
+            if state.outline_current == Some(index) {
+                style = style.add_modifier(Modifier::UNDERLINED);
+            }
+            ListItem::new(Line::from(vec![
+                Span::raw("  ".repeat(heading.level.saturating_sub(1) as usize)),
+                Span::styled(marker, Style::default().fg(palette::OVERLAY1)),
+                Span::styled(heading.title.as_str(), style),
+            ]))
+        })
+        .collect();
+
+    let title = if state.outline.is_empty() {
+        "Outline (no headings)"
+    } else {
+        "Outline"
+    };
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_set(symbols::border::ROUNDED)
+                .border_style(state.theme.selected_style)
+                .title(Span::styled(title, state.theme.title_style))
+                .title_bottom("<Enter> Jump <Space> Fold <u> Unfold all")
+                .title_alignment(Alignment::Center),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(palette::SURFACE0)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut list_state = ListState::default().with_selected(Some(state.outline_selected));
+    frame.render_stateful_widget(list, area, &mut list_state);
+}
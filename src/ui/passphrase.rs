@@ -0,0 +1,46 @@
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Text},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use super::centered_rect;
+use crate::app::{AppState, PassphrasePrompt};
+
+/// Renders the passphrase dialog, masking the typed characters
+pub fn render_passphrase(frame: &mut Frame, state: &mut AppState, area: Rect) {
+    let title = match state.passphrase_prompt {
+        PassphrasePrompt::Unlock => "Enter Passphrase (Esc to skip)",
+        PassphrasePrompt::New(_) => "New Passphrase",
+        PassphrasePrompt::Confirm(..) => "Repeat Passphrase",
+    };
+
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(Style::default().fg(Color::Yellow));
+
+    let mut lines = vec![Line::styled(
+        format!("> {}", "*".repeat(state.passphrase_buffer.chars().count())),
+        Style::default().add_modifier(Modifier::BOLD),
+    )];
+    if let Some(error) = &state.passphrase_error {
+        lines.push(Line::styled(
+            error.as_str(),
+            Style::default().fg(Color::Red),
+        ));
+    }
+
+    let popup_area = centered_rect(area, 50, lines.len() as u16 + 2);
+
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(
+        Paragraph::new(Text::from(lines))
+            .block(block)
+            .alignment(Alignment::Left),
+        popup_area,
+    );
+}
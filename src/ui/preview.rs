@@ -7,38 +7,66 @@ use ratatui::{
     widgets::{Block, Borders, Paragraph, Scrollbar, ScrollbarState, Wrap},
     Frame,
 };
-use std::{sync::LazyLock, vec};
+use std::{collections::HashMap, ops::Range, vec};
 
-use crate::app::AppState;
-use ansi_to_tui::IntoText;
+use crate::app::{AppState, View};
 use itertools::{Itertools, Position};
 use pulldown_cmark::{
-    BlockQuoteKind, CodeBlockKind, CowStr, Event, HeadingLevel, LinkType, Options, Parser, Tag,
-    TagEnd,
-};
-use syntect::{
-    easy::HighlightLines,
-    highlighting::ThemeSet,
-    parsing::SyntaxSet,
-    util::{as_24_bit_terminal_escaped, LinesWithEndings},
+    BlockQuoteKind, BrokenLink, CodeBlockKind, CowStr, Event, HeadingLevel, LinkType, Options,
+    Parser, Tag, TagEnd,
 };
+use syntect::easy::HighlightLines;
+
+use super::highlight;
+use super::render_cache::SourceMap;
 use tracing::{debug, instrument, warn};
 
-pub fn render_preview(frame: &mut Frame, state: &mut AppState, area: Rect) {
-    let content = state
-        .editor_state
-        .lines
-        .flatten(&Some('\n'))
-        .iter()
-        .map(|row| row.to_string())
-        .collect::<Vec<String>>()
-        .join("");
+/// Rows kept visible below the line the preview follows
+const FOLLOW_MARGIN: usize = 3;
 
+pub fn render_preview(frame: &mut Frame, state: &mut AppState, area: Rect) {
     let selected = state.list_state.selected.unwrap_or(0);
 
-    if let Some(note) = state.notes.get(selected) {
-        let area_width = area.width;
-        let text = from_str(&content, area_width);
+    let note = state.notes.get(selected);
+    if let Some((locked, title)) = note.map(|note| (note.locked, note.title.clone())) {
+        let height = area.height.saturating_sub(2) as usize;
+        let (text, scroll, total_rows) = if locked {
+            state.preview_heading_rows.clear();
+            state.preview_source_map = SourceMap::default();
+            let text = Text::from(vec![
+                Line::default(),
+                Line::styled("🔒 This note is encrypted", styles::H2),
+                Line::from("Press <Ctrl-U> to unlock the vault"),
+            ]);
+            (text, state.preview_scroll_offset, 3)
+        } else {
+            let cache = &mut state.preview_cache;
+            if cache.update(
+                &state.editor_state.lines,
+                area.width,
+                &state.folded_headings,
+            ) {
+                state.preview_heading_rows = cache.heading_rows().to_vec();
+                state.preview_source_map = cache.source_map().clone();
+            }
+
+            if let Some(index) = state.preview_heading_jump.take() {
+                if let Some(&row) = state.preview_heading_rows.get(index) {
+                    state.preview_scroll_offset = row;
+                }
+            }
+            if state.current_view == View::LivePreview {
+                follow_cursor(state, height);
+            }
+            let total_rows = state.preview_cache.total_rows();
+            state.preview_scroll_offset = state.preview_scroll_offset.min(total_rows);
+
+            // Only the lines in view are wrapped and drawn
+            let (text, scroll) = state
+                .preview_cache
+                .visible(state.preview_scroll_offset, height);
+            (text, scroll, total_rows)
+        };
         let paragraph = Paragraph::new(text)
             .alignment(Alignment::Left)
             .block(
@@ -46,7 +74,7 @@ pub fn render_preview(frame: &mut Frame, state: &mut AppState, area: Rect) {
                     .borders(Borders::ALL)
                     .border_set(symbols::border::ROUNDED)
                     .border_style(Style::default().fg(palette::TEAL))
-                    .title(note.title.as_str())
+                    .title(title)
                     .title_style(
                         Style::default()
                             .fg(palette::MAROON)
@@ -54,19 +82,15 @@ pub fn render_preview(frame: &mut Frame, state: &mut AppState, area: Rect) {
                     )
                     .title_alignment(Alignment::Center),
             )
-            .scroll((state.preview_scroll_offset as u16, 0))
+            .scroll((scroll as u16, 0))
             .wrap(Wrap { trim: false });
-        let line_count = paragraph.line_count(area.width);
-        if state.preview_scroll_offset > line_count {
-            state.preview_scroll_offset = line_count;
-        }
 
         let scrollbar = Scrollbar::new(ratatui::widgets::ScrollbarOrientation::VerticalRight)
             .begin_symbol(Some("↑"))
             .end_symbol(Some("↓"));
 
-        let mut scrollbar_state = ScrollbarState::new(paragraph.line_count(area.width))
-            .position(state.preview_scroll_offset);
+        let mut scrollbar_state =
+            ScrollbarState::new(total_rows + 2).position(state.preview_scroll_offset);
 
         frame.render_widget(paragraph, area);
         frame.render_stateful_widget(
@@ -80,15 +104,138 @@ pub fn render_preview(frame: &mut Frame, state: &mut AppState, area: Rect) {
     }
 }
 
-pub fn from_str(input: &str, area_width: u16) -> Text {
+/// Scroll the preview to the block at the editor cursor when the cursor moved
+/// to another line or lines were added or removed.
+///
+/// Otherwise the preview keeps the position it was scrolled to by hand.
+fn follow_cursor(state: &mut AppState, height: usize) {
+    let anchor = (
+        state.editor_state.cursor.row,
+        state.editor_state.lines.len(),
+    );
+    if state.preview_followed == Some(anchor) {
+        return;
+    }
+    state.preview_followed = Some(anchor);
+
+    let row = state.preview_source_map.row_for_line(anchor.0);
+    let offset = &mut state.preview_scroll_offset;
+    if row < *offset {
+        *offset = row;
+    } else if row + FOLLOW_MARGIN >= *offset + height {
+        *offset = (row + FOLLOW_MARGIN + 1).saturating_sub(height);
+    }
+}
+
+/// Whether a span of the preview shows the target of a link.
+///
+/// Links are shown as their text followed by the target, autolinks as the
+/// target alone, both in the link style.
+pub(super) fn is_link(style: Style) -> bool {
+    style.fg == styles::LINK.fg && style.add_modifier.contains(Modifier::UNDERLINED)
+}
+
+/// Markdown extensions the preview understands
+pub fn markdown_options() -> Options {
     let mut options = Options::empty();
     options.insert(Options::ENABLE_STRIKETHROUGH);
-    let parser = Parser::new_ext(input, options);
+    options
+}
+
+pub fn from_str(input: &str, area_width: u16) -> Text<'_> {
+    let parser = Parser::new_ext(input, markdown_options()).into_offset_iter();
     let mut writer = TextWriter::new(parser, area_width);
     writer.run();
     writer.text
 }
 
+/// How the text before a block ends, which decides the spacing in front of it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub(super) struct BlockContext {
+    needs_newline: bool,
+    /// Whether earlier blocks wrote any lines
+    preceded: bool,
+}
+
+/// A top-level block of a note rendered on its own
+pub(super) struct RenderedBlock {
+    pub lines: Vec<Line<'static>>,
+    /// Line index and level of each heading
+    pub headings: Vec<(usize, u8)>,
+    /// Line of the block and line index each block or line of text starts on
+    pub source_lines: Vec<(usize, usize)>,
+    /// Context for the block after this one
+    pub context: BlockContext,
+    /// Code blocks, by highlighting key
+    pub code_blocks: Vec<u64>,
+    /// Code blocks shown without highlighting until the worker is done with them
+    pub missing_highlights: Vec<u64>,
+}
+
+/// Render one top-level block, leaving the highlighting of its code blocks
+/// to the background worker.
+///
+/// Reference links are resolved with the definitions of the whole note.
+pub(super) fn render_block(
+    input: &str,
+    area_width: u16,
+    context: BlockContext,
+    references: &HashMap<String, (String, String)>,
+) -> RenderedBlock {
+    let callback = |link: BrokenLink<'_>| {
+        references
+            .get(&reference_label(&link.reference))
+            .map(|(url, title)| (url.clone().into(), title.clone().into()))
+    };
+    let parser = Parser::new_with_broken_link_callback(input, markdown_options(), Some(callback))
+        .into_offset_iter();
+    let mut writer = TextWriter::new(parser, area_width);
+    writer.needs_newline = context.needs_newline;
+    writer.preceded = context.preceded;
+    writer.deferred_highlighting = true;
+    writer.run();
+
+    let newlines: Vec<usize> = input.match_indices('\n').map(|(i, _)| i).collect();
+    let source_lines = writer
+        .source_offsets
+        .iter()
+        .map(|&(offset, line)| (newlines.partition_point(|&i| i < offset), line))
+        .collect();
+    RenderedBlock {
+        context: BlockContext {
+            needs_newline: writer.needs_newline,
+            preceded: context.preceded || !writer.text.lines.is_empty(),
+        },
+        lines: writer.text.lines.into_iter().map(into_owned).collect(),
+        headings: writer.headings,
+        source_lines,
+        code_blocks: writer.code_blocks,
+        missing_highlights: writer.missing_highlights,
+    }
+}
+
+/// Reference links match their definitions case-insensitively and ignoring
+/// repeated whitespace
+pub(super) fn reference_label(label: &str) -> String {
+    label
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn into_owned(line: Line<'_>) -> Line<'static> {
+    Line {
+        spans: line
+            .spans
+            .into_iter()
+            .map(|span| Span::styled(span.content.into_owned(), span.style))
+            .collect(),
+        style: line.style,
+        alignment: line.alignment,
+    }
+}
+
 struct TextWriter<'a, I> {
     /// Iterator supplying events.
     iter: I,
@@ -119,14 +266,32 @@ struct TextWriter<'a, I> {
     needs_newline: bool,
 
     area_width: u16,
-}
 
-static SYNTAX_SET: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
-static THEME_SET: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+    /// Line index and level of each heading.
+    headings: Vec<(usize, u8)>,
+
+    /// Source offset and line index of each block and line of text.
+    source_offsets: Vec<(usize, usize)>,
+
+    /// Whether earlier blocks, rendered separately, wrote any lines.
+    preceded: bool,
+
+    /// Leave code block highlighting to the background worker.
+    deferred_highlighting: bool,
+
+    /// Language and content of the code block being collected for the worker.
+    deferred_code: Option<(String, String)>,
+
+    /// Highlighting keys of the code blocks handed to the worker.
+    code_blocks: Vec<u64>,
+
+    /// Code blocks the worker has not highlighted yet.
+    missing_highlights: Vec<u64>,
+}
 
 impl<'a, I> TextWriter<'a, I>
 where
-    I: Iterator<Item = Event<'a>>,
+    I: Iterator<Item = (Event<'a>, Range<usize>)>,
 {
     fn new(iter: I, area_width: u16) -> Self {
         Self {
@@ -140,13 +305,31 @@ where
             code_highlighter: None,
             link: None,
             area_width,
+            headings: vec![],
+            source_offsets: vec![],
+            preceded: false,
+            deferred_highlighting: false,
+            deferred_code: None,
+            code_blocks: vec![],
+            missing_highlights: vec![],
         }
     }
 
     fn run(&mut self) {
         debug!("Running text writer");
-        while let Some(event) = self.iter.next() {
+        while let Some((event, range)) = self.iter.next() {
+            let records = !matches!(event, Event::End(_));
             self.handle_event(event);
+
+            // Remember where lines started by the event come from
+            let line = self.text.lines.len().saturating_sub(1);
+            let is_new = self
+                .source_offsets
+                .last()
+                .is_none_or(|&(_, last)| last < line);
+            if records && is_new && !self.text.lines.is_empty() {
+                self.source_offsets.push((range.start, line));
+            }
         }
     }
 
@@ -256,6 +439,7 @@ where
             HeadingLevel::H6 => styles::H6,
         };
         let content = format!("{} ", "▌".repeat(level as usize));
+        self.headings.push((self.text.lines.len(), level as u8));
         self.push_line(Line::styled(content, style));
         self.needs_newline = false;
     }
@@ -305,14 +489,12 @@ where
     }
 
     fn text(&mut self, text: CowStr<'a>) {
+        if let Some((_, code)) = &mut self.deferred_code {
+            code.push_str(&text);
+            return;
+        }
         if let Some(highlighter) = &mut self.code_highlighter {
-            let text: Text = LinesWithEndings::from(&text)
-                .filter_map(|line| highlighter.highlight_line(line, &SYNTAX_SET).ok())
-                .filter_map(|part| as_24_bit_terminal_escaped(&part, false).into_text().ok())
-                .flatten()
-                .collect();
-
-            for line in text.lines {
+            for line in highlight::highlight_lines(highlighter, &text) {
                 let mut prefixed_line = line;
                 if let Some(prefix) = self.line_prefixes.last() {
                     prefixed_line.spans.insert(0, prefix.clone());
@@ -348,7 +530,9 @@ where
     }
 
     fn rule(&mut self) {
-        self.push_line(Line::from("─".repeat(self.area_width as usize - 2)));
+        self.push_line(Line::from(
+            "─".repeat((self.area_width as usize).saturating_sub(2)),
+        ));
     }
 
     fn hard_break(&mut self) {
@@ -395,7 +579,7 @@ where
     }
 
     fn start_codeblock(&mut self, kind: CodeBlockKind<'_>) {
-        if !self.text.lines.is_empty() {
+        if !self.text.lines.is_empty() || self.preceded {
             self.push_line(Line::default());
         }
         let lang = match kind {
@@ -405,7 +589,11 @@ where
 
         self.line_styles.push(styles::CODE);
 
-        self.set_code_highlighter(lang);
+        if self.deferred_highlighting && highlight::supports(lang) {
+            self.deferred_code = Some((lang.to_string(), String::new()));
+        } else {
+            self.set_code_highlighter(lang);
+        }
 
         let mut breaker = String::from("╒══");
         if !lang.is_empty() {
@@ -413,7 +601,7 @@ where
         } else {
             breaker.push_str("══");
         }
-        let filler = "═".repeat(self.area_width as usize - 2 - 5 - lang.len());
+        let filler = "═".repeat((self.area_width as usize).saturating_sub(2 + 5 + lang.len()));
         breaker.push_str(&filler);
         self.push_line(Span::from(breaker).into());
 
@@ -422,9 +610,26 @@ where
     }
 
     fn end_codeblock(&mut self) {
+        if let Some((lang, code)) = self.deferred_code.take() {
+            let (key, lines) = highlight::request(&lang, &code);
+            self.code_blocks.push(key);
+            // Shown plain until the worker is done, with the same layout
+            let lines = lines.unwrap_or_else(|| {
+                self.missing_highlights.push(key);
+                code.lines()
+                    .map(|line| Line::styled(line.to_string(), styles::CODE))
+                    .collect()
+            });
+            for mut line in lines {
+                if let Some(prefix) = self.line_prefixes.last() {
+                    line.spans.insert(0, prefix.clone());
+                }
+                self.text.push_line(line);
+            }
+        }
         self.line_prefixes.pop();
         let mut bottom_breaker = String::from("└");
-        bottom_breaker.push_str(&"─".repeat((self.area_width - 3) as usize));
+        bottom_breaker.push_str(&"─".repeat(self.area_width.saturating_sub(3) as usize));
         self.push_line(Span::from(bottom_breaker).into());
 
         self.needs_newline = true;
@@ -435,14 +640,7 @@ where
 
     #[instrument(level = "trace", skip(self))]
     fn set_code_highlighter(&mut self, lang: &str) {
-        if let Some(syntax) = SYNTAX_SET.find_syntax_by_token(lang) {
-            debug!("Starting code block with syntax: {:?}", lang);
-            let theme = &THEME_SET.themes["base16-ocean.dark"];
-            let highlighter = HighlightLines::new(syntax, theme);
-            self.code_highlighter = Some(highlighter);
-        } else {
-            warn!("Could not find syntax for code block: {:?}", lang);
-        }
+        self.code_highlighter = highlight::highlighter(lang);
     }
 
     #[instrument(level = "trace", skip(self))]
@@ -496,7 +694,7 @@ where
         match link_type {
             LinkType::Autolink => {
                 self.link = None;
-                self.push_inline_style(Style::default().underlined().fg(palette::BLUE));
+                self.push_inline_style(styles::LINK);
             }
             _ => {
                 self.link = Some(dest_url);
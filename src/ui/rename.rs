@@ -1,38 +1,44 @@
 use ratatui::{
     layout::{Alignment, Margin, Rect},
     style::{Color, Modifier, Style},
-    text::Text,
+    text::{Line, Text},
     widgets::{Block, BorderType, Borders, Clear, Paragraph},
     Frame,
 };
 
+use super::centered_rect;
 use crate::app::AppState;
 
-/// Renders the rename dialog
+/// Renders the rename dialog, which also names new notes and fills in template variables
 pub fn render_rename(frame: &mut Frame, state: &mut AppState, area: Rect) {
+    let title = match &state.template_prompt {
+        Some(prompt) => format!("Enter {}", prompt.current_variable().unwrap_or_default()),
+        None => "Enter Note Name".to_string(),
+    };
     let block = Block::default()
-        .title("Enter Note Name")
+        .title(title)
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
         .style(Style::default().fg(Color::Yellow));
 
-    let width = 50;
-    let height = 3;
-    let x = (area.width - width) / 2;
-    let y = (area.height - height) / 2;
-
-    let popup_area = Rect::new(
-        area.x + x,
-        area.y + y,
-        width.min(area.width),
-        height.min(area.height),
-    );
+    let height = if state.creating_new_note { 4 } else { 3 };
+    let popup_area = centered_rect(area, 50, height);
 
     // Clear the background
     frame.render_widget(Clear, popup_area);
     frame.render_widget(block.clone(), popup_area);
 
-    let text = Text::from(format!("> {}", state.rename_buffer));
+    let mut text = Text::from(format!("> {}", state.rename_buffer));
+    if state.creating_new_note {
+        let template = state
+            .template_selected
+            .and_then(|index| state.templates.get(index))
+            .map_or("none", |template| template.name.as_str());
+        text.push_line(Line::styled(
+            format!("Template: {} (Tab to change)", template),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
     let input = Paragraph::new(text)
         .style(Style::default().add_modifier(Modifier::BOLD))
         .block(block)
@@ -0,0 +1,650 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::rc::Rc;
+
+use edtui::Lines;
+use ratatui::{
+    style::Style,
+    text::{Line, Span, Text},
+    widgets::{Paragraph, Wrap},
+};
+
+use super::highlight;
+use super::preview::{is_link, reference_label, render_block, BlockContext, RenderedBlock};
+use crate::theme::palette;
+
+/// Rendered preview of the note in the editor, kept between frames.
+///
+/// The note is split into top-level blocks and only blocks that changed are
+/// rendered again. Folding and wrapping are redone when the rendered text,
+/// the width or the folded sections change, and each frame only draws the
+/// lines in view.
+#[derive(Default)]
+pub struct PreviewCache {
+    /// Editor lines and width the text was rendered from
+    lines: Option<Lines>,
+    width: u16,
+    /// Rendered blocks by content, width and context
+    blocks: HashMap<u64, Rc<CachedBlock>>,
+    /// Code blocks shown without highlighting
+    missing_highlights: Vec<u64>,
+    /// Blocks of the note in order, with the line of the text each starts on
+    order: Vec<(usize, Rc<CachedBlock>)>,
+    /// Rows each line of the text takes once wrapped
+    heights: Vec<usize>,
+    headings: Vec<(usize, u8)>,
+    source_lines: Vec<(usize, usize)>,
+    /// Sections folded in `view`
+    folded: HashSet<usize>,
+    view: View,
+}
+
+struct CachedBlock {
+    block: RenderedBlock,
+    /// Rows each line takes once wrapped
+    heights: Vec<usize>,
+}
+
+/// Where things are on screen once folded sections are hidden
+#[derive(Default)]
+struct View {
+    /// The text without the folded sections, if any are folded
+    folded_text: Option<Text<'static>>,
+    /// Row each line starts on
+    rows: Vec<usize>,
+    total_rows: usize,
+    heading_rows: Vec<usize>,
+    source_map: SourceMap,
+}
+
+impl PreviewCache {
+    /// Bring the preview up to date with the editor.
+    ///
+    /// Returns whether the positions of the headings or lines changed.
+    pub fn update(&mut self, lines: &Lines, width: u16, folded: &HashSet<usize>) -> bool {
+        let highlighted = self
+            .missing_highlights
+            .iter()
+            .any(|&key| highlight::is_done(key));
+        let changed = self.lines.as_ref() != Some(lines) || self.width != width;
+
+        if changed || highlighted {
+            self.render(lines, width);
+        } else if self.folded == *folded {
+            return false;
+        }
+
+        self.folded = folded.clone();
+        self.view = View::new(
+            self.text_lines(),
+            &self.heights,
+            &self.headings,
+            &self.source_lines,
+            folded,
+            width.saturating_sub(2),
+        );
+        true
+    }
+
+    /// Whether code blocks are waiting for the highlighting worker
+    pub fn is_waiting(&self) -> bool {
+        !self.missing_highlights.is_empty()
+    }
+
+    pub fn total_rows(&self) -> usize {
+        self.view.total_rows
+    }
+
+    /// Preview row of each heading
+    pub fn heading_rows(&self) -> &[usize] {
+        &self.view.heading_rows
+    }
+
+    pub fn source_map(&self) -> &SourceMap {
+        &self.view.source_map
+    }
+
+    /// Lines showing the rows from `offset` on, with the rows to scroll past
+    /// within the first line
+    pub fn visible(&self, offset: usize, height: usize) -> (Text<'static>, usize) {
+        let rows = &self.view.rows;
+        let first = rows.partition_point(|&row| row <= offset).saturating_sub(1);
+        let end = rows.partition_point(|&row| row < offset + height.max(1));
+        let count = end.saturating_sub(first);
+        let lines: Vec<Line<'static>> = match &self.view.folded_text {
+            Some(text) => text.lines.iter().skip(first).take(count).cloned().collect(),
+            None => {
+                // Start from the block holding the first line
+                let block = self
+                    .order
+                    .partition_point(|&(start, _)| start <= first)
+                    .saturating_sub(1);
+                let skip = first - self.order.get(block).map_or(0, |&(start, _)| start);
+                self.order[block.min(self.order.len())..]
+                    .iter()
+                    .flat_map(|(_, cached)| cached.block.lines.iter())
+                    .skip(skip)
+                    .take(count)
+                    .cloned()
+                    .collect()
+            }
+        };
+        let scroll = offset.saturating_sub(rows.get(first).copied().unwrap_or(0));
+        (Text::from(lines), scroll)
+    }
+
+    /// Target of the link shown at a column of a preview row.
+    ///
+    /// The column within a wrapped line is estimated from the width, so a
+    /// line with a single link follows it from anywhere on the line.
+    pub fn link_at(&self, row: usize, column: usize) -> Option<String> {
+        let rows = &self.view.rows;
+        let index = rows.partition_point(|&start| start <= row).checked_sub(1)?;
+        let line = self.line(index)?;
+        let width = self.width.saturating_sub(2).max(1) as usize;
+        let position = (row - rows[index]) * width + column;
+
+        let mut links = Vec::new();
+        let mut start = 0;
+        for span in &line.spans {
+            let end = start + span.content.chars().count();
+            if is_link(span.style) && !span.content.trim().is_empty() {
+                links.push((start..end, span.content.trim()));
+            }
+            start = end;
+        }
+        let target = match links.as_slice() {
+            [(_, target)] => Some(*target),
+            links => links
+                .iter()
+                .find(|(range, _)| range.contains(&position))
+                .map(|(_, target)| *target),
+        };
+        target.map(str::to_string)
+    }
+
+    /// Line of the text as shown, with folded sections hidden
+    fn line(&self, index: usize) -> Option<&Line<'static>> {
+        if let Some(text) = &self.view.folded_text {
+            return text.lines.get(index);
+        }
+        let block = self
+            .order
+            .partition_point(|&(start, _)| start <= index)
+            .checked_sub(1)?;
+        let (start, cached) = self.order.get(block)?;
+        cached.block.lines.get(index - start)
+    }
+
+    /// Lines of the rendered text, folded sections included
+    fn text_lines(&self) -> impl Iterator<Item = &Line<'static>> {
+        self.order
+            .iter()
+            .flat_map(|(_, cached)| cached.block.lines.iter())
+    }
+
+    fn render(&mut self, lines: &Lines, width: u16) {
+        let mut content = String::new();
+        for (index, row) in lines.iter_row().enumerate() {
+            if index > 0 {
+                content.push('\n');
+            }
+            content.extend(row);
+        }
+        let references = reference_definitions(&content);
+        let references_key = {
+            let mut entries: Vec<_> = references.iter().collect();
+            entries.sort();
+            hash(&entries)
+        };
+
+        let mut blocks: HashMap<u64, Rc<CachedBlock>> = HashMap::with_capacity(self.blocks.len());
+        let mut line_count = 0;
+        self.order.clear();
+        self.heights.clear();
+        self.headings.clear();
+        self.source_lines.clear();
+        self.missing_highlights.clear();
+        let mut context = BlockContext::default();
+
+        for (first_line, source) in split_blocks(&content) {
+            let key = hash(&(source, width, context, references_key));
+            // A block repeated in the note is rendered once
+            let cached = match blocks
+                .get(&key)
+                .cloned()
+                .or_else(|| self.blocks.remove(&key))
+            {
+                Some(cached)
+                    if !cached
+                        .block
+                        .missing_highlights
+                        .iter()
+                        .any(|&key| highlight::is_done(key)) =>
+                {
+                    cached
+                }
+                _ => {
+                    let block = render_block(source, width, context, &references);
+                    let heights = block
+                        .lines
+                        .iter()
+                        .map(|line| line_height(line, width.saturating_sub(2)))
+                        .collect();
+                    Rc::new(CachedBlock { block, heights })
+                }
+            };
+            let block = &cached.block;
+
+            let offset = line_count;
+            line_count += block.lines.len();
+            self.heights.extend(cached.heights.iter().copied());
+            self.headings.extend(
+                block
+                    .headings
+                    .iter()
+                    .map(|&(line, level)| (line + offset, level)),
+            );
+            self.source_lines.extend(
+                block
+                    .source_lines
+                    .iter()
+                    .map(|&(source_line, line)| (source_line + first_line, line + offset)),
+            );
+            self.missing_highlights
+                .extend(block.missing_highlights.iter().copied());
+            context = block.context;
+            blocks.entry(key).or_insert_with(|| Rc::clone(&cached));
+            self.order.push((offset, cached));
+        }
+
+        // Blocks no longer in the note are dropped along with their highlighting
+        let code_blocks: HashSet<u64> = blocks
+            .values()
+            .flat_map(|cached| cached.block.code_blocks.iter().copied())
+            .collect();
+        highlight::retain(&code_blocks);
+
+        self.blocks = blocks;
+        self.lines = Some(lines.clone());
+        self.width = width;
+    }
+}
+
+impl View {
+    fn new<'a>(
+        text_lines: impl Iterator<Item = &'a Line<'static>>,
+        heights: &[usize],
+        headings: &[(usize, u8)],
+        source_lines: &[(usize, usize)],
+        folded: &HashSet<usize>,
+        width: u16,
+    ) -> Self {
+        let (folded_text, line_map, folded_heights) = if folded.is_empty() {
+            (None, None, None)
+        } else {
+            let (text, line_map, heights) =
+                fold_sections(text_lines, heights, headings, folded, width);
+            (Some(text), Some(line_map), Some(heights))
+        };
+
+        let heights = folded_heights.as_deref().unwrap_or(heights);
+        let mut rows = Vec::with_capacity(heights.len());
+        let mut total_rows = 0;
+        for height in heights {
+            rows.push(total_rows);
+            total_rows += height;
+        }
+
+        let row_of = |line: usize| {
+            let line = match &line_map {
+                Some(line_map) => *line_map.get(line)?,
+                None => line,
+            };
+            rows.get(line).copied()
+        };
+        Self {
+            heading_rows: headings
+                .iter()
+                .map(|&(line, _)| row_of(line).unwrap_or(0))
+                .collect(),
+            source_map: SourceMap(
+                source_lines
+                    .iter()
+                    .filter_map(|&(source_line, line)| Some((source_line, row_of(line)?)))
+                    .collect(),
+            ),
+            folded_text,
+            rows,
+            total_rows,
+        }
+    }
+}
+
+/// Preview rows of the lines of a note, recorded at the start of each block
+/// and each line of text
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap(Vec<(usize, usize)>);
+
+impl SourceMap {
+    /// Preview row showing a line of the note
+    pub fn row_for_line(&self, line: usize) -> usize {
+        let index = self
+            .0
+            .partition_point(|&(source_line, _)| source_line <= line);
+        let Some(&(source_line, row)) = index.checked_sub(1).and_then(|i| self.0.get(i)) else {
+            return 0;
+        };
+        // Lines inside a block, such as a code block, map one to one
+        let mut row = row + (line - source_line);
+        if let Some(&(_, next_row)) = self.0.get(index) {
+            row = row.min(next_row.saturating_sub(1).max(self.0[index - 1].1));
+        }
+        row
+    }
+
+    /// Line of the note shown on a preview row
+    pub fn line_for_row(&self, row: usize) -> usize {
+        let index = self.0.partition_point(|&(_, start)| start <= row);
+        let Some(&(source_line, start)) = index.checked_sub(1).and_then(|i| self.0.get(i)) else {
+            return 0;
+        };
+        let mut line = source_line + (row - start);
+        if let Some(&(next_line, _)) = self.0.get(index) {
+            line = line.min(next_line.saturating_sub(1).max(source_line));
+        }
+        line
+    }
+}
+/// Rows a line takes once wrapped to a width
+fn line_height(line: &Line, width: u16) -> usize {
+    Paragraph::new(line.clone())
+        .wrap(Wrap { trim: false })
+        .line_count(width)
+}
+
+/// Hide the content of folded sections, leaving their headings marked with `…`.
+///
+/// Returns the line each line of the text is shown on, where lines inside a
+/// folded section map to the heading of that section, and the heights of the
+/// remaining lines.
+fn fold_sections<'a>(
+    text_lines: impl Iterator<Item = &'a Line<'static>>,
+    heights: &[usize],
+    headings: &[(usize, u8)],
+    folded: &HashSet<usize>,
+    width: u16,
+) -> (Text<'static>, Vec<usize>, Vec<usize>) {
+    let mut lines: Vec<Line> = Vec::with_capacity(heights.len());
+    let mut folded_heights = Vec::with_capacity(heights.len());
+    let mut line_map = Vec::with_capacity(heights.len());
+    let mut next_heading = 0;
+    // Level of the folded section being skipped
+    let mut skipping: Option<u8> = None;
+
+    for (index, line) in text_lines.enumerate() {
+        let mut line = line.clone();
+        let mut folds = false;
+        if let Some(&(heading_line, level)) = headings.get(next_heading) {
+            if heading_line == index {
+                if skipping.is_some_and(|folded_level| level <= folded_level) {
+                    skipping = None;
+                }
+                if skipping.is_none() && folded.contains(&next_heading) {
+                    line.push_span(Span::styled(" …", Style::new().fg(palette::OVERLAY1)));
+                    folds = true;
+                }
+                next_heading += 1;
+            }
+        }
+        if skipping.is_some() {
+            line_map.push(lines.len().saturating_sub(1));
+        } else {
+            line_map.push(lines.len());
+            folded_heights.push(if folds {
+                line_height(&line, width)
+            } else {
+                heights.get(index).copied().unwrap_or(1)
+            });
+            lines.push(line);
+        }
+        if folds {
+            skipping = headings.get(next_heading - 1).map(|&(_, level)| level);
+        }
+    }
+    (Text::from(lines), line_map, folded_heights)
+}
+
+/// Split a note into top-level blocks that render the same on their own.
+///
+/// Blocks end at blank lines, except inside fenced code blocks and HTML
+/// blocks that run on over them, and where the next line is indented or
+/// continues a list. Returns the first line of each block with its text.
+fn split_blocks(content: &str) -> Vec<(usize, &str)> {
+    let mut blocks = Vec::new();
+    // First line and byte offset of the current block
+    let mut start: Option<(usize, usize)> = None;
+    let mut fence: Option<&str> = None;
+    let mut html_end: Option<&[&str]> = None;
+    let mut in_list = false;
+    let mut after_blank = false;
+    let mut offset = 0;
+
+    for (index, line) in content.split_inclusive('\n').enumerate() {
+        let text = line.trim_end_matches(['\n', '\r']);
+        if let Some(marker) = fence {
+            let closing = text.trim();
+            if closing.starts_with(marker) && closing.trim_start_matches(&marker[..1]).is_empty() {
+                fence = None;
+            }
+        } else if let Some(ends) = html_end {
+            let lower = text.to_ascii_lowercase();
+            if ends.iter().any(|end| lower.contains(end)) {
+                html_end = None;
+            }
+        } else if text.trim().is_empty() {
+            after_blank = start.is_some();
+        } else {
+            let continues = start.is_some()
+                && (!after_blank
+                    || text.starts_with([' ', '\t'])
+                    || (in_list && is_list_item(text)));
+            if !continues {
+                if let Some((first_line, block_start)) = start {
+                    blocks.push((first_line, &content[block_start..offset]));
+                }
+                start = Some((index, offset));
+                in_list = is_list_item(text);
+            }
+            after_blank = false;
+            fence = opening_fence(text);
+            html_end = html_block_end(text);
+        }
+        offset += line.len();
+    }
+    if let Some((first_line, block_start)) = start {
+        blocks.push((first_line, &content[block_start..]));
+    }
+    blocks
+}
+
+/// Marker of a fenced code block opened by a line
+fn opening_fence(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    ["```", "~~~"].into_iter().find_map(|marker| {
+        let length = trimmed.len() - trimmed.trim_start_matches(&marker[..1]).len();
+        (line.len() - trimmed.len() <= 3 && length >= 3).then(|| &trimmed[..length])
+    })
+}
+
+/// What ends an HTML block opened by a line, for the kinds of HTML block that
+/// run on over blank lines, such as comments and `<pre>`. `None` when the line
+/// opens no such block or closes it again.
+fn html_block_end(line: &str) -> Option<&'static [&'static str]> {
+    let trimmed = line.trim_start();
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let lower = trimmed.to_ascii_lowercase();
+    let (opening, ends): (usize, &'static [&'static str]) = if lower.starts_with("<!--") {
+        (4, &["-->"])
+    } else if lower.starts_with("<?") {
+        (2, &["?>"])
+    } else if lower.starts_with("<![cdata[") {
+        (9, &["]]>"])
+    } else if lower.starts_with("<!") && lower[2..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+        (2, &[">"])
+    } else {
+        let tag = ["pre", "script", "style", "textarea"]
+            .into_iter()
+            .find(|tag| {
+                lower
+                    .strip_prefix('<')
+                    .and_then(|rest| rest.strip_prefix(tag))
+                    .is_some_and(|rest| {
+                        rest.is_empty() || rest.starts_with(|c: char| c == '>' || c.is_whitespace())
+                    })
+            })?;
+        (
+            tag.len() + 1,
+            &["</pre>", "</script>", "</style>", "</textarea>"],
+        )
+    };
+    (!ends.iter().any(|end| lower[opening..].contains(end))).then_some(ends)
+}
+
+fn is_list_item(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with(['-', '*', '+']) {
+        return trimmed[1..].starts_with([' ', '\t']) || trimmed.len() == 1;
+    }
+    let digits = trimmed.len()
+        - trimmed
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .len();
+    digits > 0 && trimmed[digits..].starts_with(['.', ')'])
+}
+
+/// Reference link definitions such as `[label]: https://example.com "Title"`
+fn reference_definitions(content: &str) -> HashMap<String, (String, String)> {
+    let mut references = HashMap::new();
+    if !content.contains("]:") {
+        return references;
+    }
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if line.len() - trimmed.len() > 3 {
+            continue;
+        }
+        let Some((label, rest)) = trimmed
+            .strip_prefix('[')
+            .and_then(|rest| rest.split_once("]:"))
+        else {
+            continue;
+        };
+        let rest = rest.trim();
+        let (url, title) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let title = title.trim().trim_matches(['"', '\'', '(', ')']);
+        if !label.is_empty() && !url.is_empty() {
+            references
+                .entry(reference_label(label))
+                .or_insert_with(|| (url.trim_matches(['<', '>']).to_string(), title.to_string()));
+        }
+    }
+    references
+}
+
+fn hash<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::preview::from_str;
+
+    /// Render a note block by block, the way the cache does
+    fn render_by_blocks(content: &str, width: u16) -> Vec<Line<'static>> {
+        let references = reference_definitions(content);
+        let mut context = BlockContext::default();
+        let mut lines = Vec::new();
+        for (_, source) in split_blocks(content) {
+            let block = render_block(source, width, context, &references);
+            context = block.context;
+            lines.extend(block.lines);
+        }
+        lines
+    }
+
+    fn assert_renders_like_whole(content: &str) {
+        for width in [24, 80] {
+            assert_eq!(
+                render_by_blocks(content, width),
+                from_str(content, width).lines,
+                "{:?} at width {}",
+                content,
+                width
+            );
+        }
+    }
+
+    #[test]
+    fn paragraphs_headings_and_quotes() {
+        assert_renders_like_whole(
+            "# Title\n\nFirst paragraph\nwrapping on.\n\n\n## Section\n\n> quoted\n\n> another quote\n\nSetext\n===\n\n---\n\nEnd",
+        );
+    }
+
+    #[test]
+    fn lists_with_blank_lines_between_items() {
+        assert_renders_like_whole(
+            "- one\n\n- two\n\n  continued\n\n- three\n\n1. first\n\n2. second\n\nAfter the list",
+        );
+        assert_renders_like_whole("- [ ] task\n\n- [x] done\n    - nested\n\n      deeper\n\nText");
+    }
+
+    #[test]
+    fn blank_lines_inside_fenced_code() {
+        assert_renders_like_whole("Before\n\n```\nfirst\n\n\nafter blanks\n```\n\nAfter");
+        assert_renders_like_whole("~~~\n# not a heading\n\n- not a list\n~~~\n\nText");
+        // A shorter fence inside a longer one does not close it
+        assert_renders_like_whole("````\n```\n\ninner\n```\n````\n\nText");
+        // An unclosed fence runs to the end of the note
+        assert_renders_like_whole("Text\n\n```\ncode\n\nstill code");
+    }
+
+    #[test]
+    fn blank_lines_inside_indented_code() {
+        assert_renders_like_whole("Paragraph\n\n    code\n\n    more code\n\n\n    last\n\nText");
+        assert_renders_like_whole("- item\n\n      code in the item\n\n      more\n\nText");
+    }
+
+    #[test]
+    fn html_blocks() {
+        assert_renders_like_whole("<div>\nInside\n</div>\n\nText");
+        assert_renders_like_whole("Text\n\n<!-- a comment\n\nspanning a blank line -->\n\nAfter");
+        assert_renders_like_whole("<pre>\nkeep\n\nspacing\n</pre>\n\nAfter");
+        assert_renders_like_whole(
+            "<details>\n<summary>More</summary>\n\nHidden *text*\n\n</details>",
+        );
+    }
+
+    #[test]
+    fn tables_and_references() {
+        assert_renders_like_whole(
+            "| a | b |\n|---|---|\n| 1 | 2 |\n\nSee [the docs][docs].\n\n[docs]: https://example.com \"Docs\"",
+        );
+    }
+
+    #[test]
+    fn blocks_start_on_their_lines() {
+        let content = "# Title\n\n```\na\n\nb\n```\n\n- one\n\n- two\n\nEnd";
+        let starts: Vec<usize> = split_blocks(content)
+            .into_iter()
+            .map(|(line, _)| line)
+            .collect();
+        assert_eq!(starts, [0, 2, 8, 12]);
+    }
+}
@@ -0,0 +1,108 @@
+use edtui::EditorMode;
+use ratatui::{
+    layout::{Alignment, Position, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+
+use crate::app::{AppState, View};
+use crate::keymap::EditingMode;
+use crate::theme::palette;
+
+/// Whether the status line is shown, which it always is in vim mode and
+/// while a note is open
+pub fn status_shown(state: &AppState) -> bool {
+    state.status_message.is_some()
+        || state.command_line.is_some()
+        || state.keymap.mode == EditingMode::Vim
+        || stats_shown(state)
+}
+
+/// Whether the note shown has statistics to put at the end of the line
+fn stats_shown(state: &AppState) -> bool {
+    matches!(
+        state.current_view,
+        View::Editor | View::LivePreview | View::Preview | View::Split | View::Outline
+    ) && state
+        .list_state
+        .selected
+        .and_then(|selected| state.notes.get(selected))
+        .is_some_and(|note| !note.locked)
+}
+
+/// Renders the command line being typed, or the editor mode and the status
+/// message on a single line
+pub fn render_status(frame: &mut Frame, state: &mut AppState, area: Rect) {
+    if let Some(line) = &state.command_line {
+        let text = format!(":{}", line);
+        let cursor = area.x + (text.chars().count() as u16).min(area.width.saturating_sub(1));
+        frame.render_widget(
+            Paragraph::new(text).style(Style::default().fg(palette::TEXT).bg(palette::MANTLE)),
+            area,
+        );
+        frame.set_cursor_position(Position::new(cursor, area.y));
+        return;
+    }
+
+    let mut spans = Vec::new();
+    if state.keymap.mode == EditingMode::Vim {
+        let color = match state.editor_state.mode {
+            EditorMode::Normal => palette::BLUE,
+            EditorMode::Insert => palette::GREEN,
+            EditorMode::Visual => palette::MAUVE,
+            EditorMode::Search => palette::PEACH,
+        };
+        spans.push(Span::styled(
+            format!(" {} ", state.editor_state.mode.name().to_uppercase()),
+            Style::default()
+                .fg(palette::CRUST)
+                .bg(color)
+                .add_modifier(Modifier::BOLD),
+        ));
+        if let Some(keys) = &state.leader_keys {
+            spans.push(Span::styled(
+                format!(" <leader>{}", keys),
+                Style::default().fg(palette::SUBTEXT0),
+            ));
+        }
+        spans.push(Span::raw(" "));
+    }
+    if let Some(message) = &state.status_message {
+        spans.push(Span::styled(
+            format!(" {}", message),
+            Style::default().fg(palette::CRUST).bg(palette::YELLOW),
+        ));
+    }
+
+    // The message fills the rest of the line
+    let (background, foreground) = match state.status_message {
+        Some(_) => (palette::YELLOW, palette::CRUST),
+        None => (palette::MANTLE, palette::SUBTEXT0),
+    };
+    let line = Line::from(spans);
+    let used = line.width();
+    frame.render_widget(
+        Paragraph::new(line).style(Style::default().bg(background)),
+        area,
+    );
+
+    if stats_shown(state) {
+        let stats = &state.note_stats;
+        let text = format!(
+            "{} words · {} min read ",
+            stats.words,
+            stats.reading_minutes()
+        );
+        // Left out when the message needs the room
+        if used + text.chars().count() < area.width as usize {
+            frame.render_widget(
+                Paragraph::new(text)
+                    .alignment(Alignment::Right)
+                    .style(Style::default().fg(foreground).bg(background)),
+                area,
+            );
+        }
+    }
+}
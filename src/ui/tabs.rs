@@ -0,0 +1,53 @@
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+
+use crate::app::{AppState, View};
+use crate::theme::palette;
+
+/// Renders the open notes, marking the active one and recording where each
+/// tab was drawn for mouse clicks
+pub fn render_tabs(frame: &mut Frame, state: &mut AppState, area: Rect) {
+    let other = state
+        .split
+        .as_ref()
+        .filter(|_| state.current_view == View::Split)
+        .map(|split| split.other.as_str());
+
+    let mut spans = Vec::with_capacity(state.tabs.len() * 2);
+    let mut x = area.x;
+    state.tab_areas.clear();
+    for (index, tab) in state.tabs.iter().enumerate() {
+        let label = format!(" {} ", tab.title);
+        let width = (label.chars().count() as u16).min(area.right().saturating_sub(x));
+
+        let mut style = if index == state.active_tab {
+            Style::default()
+                .fg(palette::CRUST)
+                .bg(palette::GREEN)
+                .add_modifier(Modifier::BOLD)
+        } else if Some(tab.title.as_str()) == other {
+            Style::default().fg(palette::TEXT).bg(palette::SURFACE1)
+        } else {
+            Style::default().fg(palette::SUBTEXT0).bg(palette::SURFACE0)
+        };
+        // Tabs replaced by the next note picked in the list
+        if tab.transient {
+            style = style.add_modifier(Modifier::ITALIC);
+        }
+
+        state.tab_areas.push(Rect::new(x, area.y, width, 1));
+        spans.push(Span::styled(label, style));
+        spans.push(Span::raw(" "));
+        x = x.saturating_add(width + 1).min(area.right());
+    }
+
+    frame.render_widget(
+        Paragraph::new(Line::from(spans)).style(Style::default().bg(palette::MANTLE)),
+        area,
+    );
+}
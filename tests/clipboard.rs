@@ -0,0 +1,67 @@
+use edtui::actions::{Execute, Undo};
+use edtui::{EditorState, Index2, Lines};
+use thoth_note::clipboard::paste_text;
+
+fn editor(text: &str, row: usize, col: usize) -> EditorState {
+    let mut editor = EditorState::new(Lines::from(text));
+    editor.cursor = Index2::new(row, col);
+    editor
+}
+
+fn text(editor: &EditorState) -> String {
+    editor.lines.flatten(&Some('\n')).into_iter().collect()
+}
+
+#[test]
+fn text_is_pasted_at_the_cursor() {
+    let mut state = editor("hello world", 0, 6);
+    paste_text(&mut state, "big ");
+    assert_eq!(text(&state), "hello big world");
+    assert_eq!(state.cursor, Index2::new(0, 10));
+}
+
+#[test]
+fn lines_split_the_line_they_are_pasted_in() {
+    let mut state = editor("ab\nnext", 0, 1);
+    paste_text(&mut state, "1\n2\n3");
+    assert_eq!(text(&state), "a1\n2\n3b\nnext");
+    assert_eq!(state.cursor, Index2::new(2, 1));
+
+    let mut state = editor("ab", 0, 2);
+    paste_text(&mut state, "x\n");
+    assert_eq!(text(&state), "abx\n");
+    assert_eq!(state.cursor, Index2::new(1, 0));
+}
+
+#[test]
+fn windows_and_old_mac_line_endings_become_newlines() {
+    let mut state = editor("x", 0, 1);
+    paste_text(&mut state, "a\r\nb\rc");
+    assert_eq!(text(&state), "xa\nb\nc");
+    assert_eq!(state.cursor, Index2::new(2, 1));
+}
+
+#[test]
+fn an_empty_note_takes_the_text() {
+    let mut state = editor("", 0, 0);
+    paste_text(&mut state, "hi\nthere");
+    assert_eq!(text(&state), "hi\nthere");
+    assert_eq!(state.cursor, Index2::new(1, 5));
+}
+
+#[test]
+fn nothing_to_paste_changes_nothing() {
+    let mut state = editor("same", 0, 2);
+    paste_text(&mut state, "");
+    assert_eq!(text(&state), "same");
+    assert_eq!(state.cursor, Index2::new(0, 2));
+}
+
+#[test]
+fn a_paste_is_undone_at_once() {
+    let mut state = editor("one\ntwo", 0, 3);
+    paste_text(&mut state, "\nmid\n");
+    assert_eq!(text(&state), "one\nmid\n\ntwo");
+    Undo.execute(&mut state);
+    assert_eq!(text(&state), "one\ntwo");
+}
@@ -0,0 +1,147 @@
+use edtui::{EditorState, Index2, Lines};
+use thoth_note::completion::{Completion, CompletionKind};
+use thoth_note::models::note::Note;
+
+fn notes() -> Vec<Note> {
+    let note = |title: &str, content: &str, tags: &[&str]| Note {
+        title: title.to_string(),
+        content: content.to_string(),
+        tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        ..Note::default()
+    };
+    vec![
+        note("Project plan", "Due soon #project", &[]),
+        note("Old project", "", &["archive"]),
+        note("Current", "", &["prose"]),
+        note("Prose", "#project again", &[]),
+    ]
+}
+
+/// Editor on a single line with the cursor at its end
+fn typed(line: &str) -> EditorState {
+    let mut editor = EditorState::new(Lines::from(line));
+    editor.cursor = Index2::new(0, line.chars().count());
+    editor
+}
+
+fn complete(editor: &EditorState) -> Option<Completion> {
+    Completion::update(None, editor, &notes(), Some("Current"))
+}
+
+fn labels(completion: &Completion) -> Vec<&str> {
+    completion
+        .items
+        .iter()
+        .map(|item| item.label.as_str())
+        .collect()
+}
+
+fn text(editor: &EditorState) -> String {
+    editor.lines.flatten(&Some('\n')).into_iter().collect()
+}
+
+#[test]
+fn double_brackets_complete_other_notes() {
+    let completion = complete(&typed("See [[pro")).unwrap();
+    assert_eq!(completion.kind, CompletionKind::Note);
+    assert_eq!(completion.start, Index2::new(0, 6));
+    assert_eq!(completion.query, "pro");
+    // Titles starting with the text come first
+    assert_eq!(
+        labels(&completion),
+        ["Project plan", "Prose", "Old project"]
+    );
+
+    // The note being edited is left out
+    let completion = complete(&typed("[[Cur"));
+    assert!(completion.is_none());
+}
+
+#[test]
+fn accepting_a_note_closes_the_link() {
+    let mut editor = typed("See [[pro");
+    complete(&editor).unwrap().accept(&mut editor);
+    assert_eq!(text(&editor), "See [[Project plan]]");
+    assert_eq!(editor.cursor, Index2::new(0, 20));
+
+    // Brackets already closing the link are reused
+    let mut editor = typed("[[Pro]] next");
+    editor.cursor = Index2::new(0, 5);
+    let mut completion = complete(&editor).unwrap();
+    completion.select_next(false);
+    completion.accept(&mut editor);
+    assert_eq!(text(&editor), "[[Prose]] next");
+    assert_eq!(editor.cursor, Index2::new(0, 9));
+}
+
+#[test]
+fn closed_links_and_aliases_are_not_completed() {
+    assert!(complete(&typed("[[Prose]] and")).is_none());
+    assert!(complete(&typed("[[Prose|")).is_none());
+    assert!(complete(&typed("[[Prose#")).is_none());
+}
+
+#[test]
+fn hashes_complete_tags_of_all_notes() {
+    let completion = complete(&typed("Ideas #pro")).unwrap();
+    assert_eq!(completion.kind, CompletionKind::Tag);
+    assert_eq!(completion.start, Index2::new(0, 7));
+    // From metadata and from the text, once each
+    assert_eq!(labels(&completion), ["#project", "#prose"]);
+
+    let mut editor = typed("Ideas #pro");
+    completion.accept(&mut editor);
+    assert_eq!(text(&editor), "Ideas #project");
+}
+
+#[test]
+fn hashes_that_are_not_tags_are_left_alone() {
+    // Issue numbers, anchors inside words and headings
+    assert!(complete(&typed("Fixes #12")).is_none());
+    assert!(complete(&typed("page#pro")).is_none());
+    assert!(complete(&typed("#")).is_none());
+    // Nothing is left to complete
+    assert!(complete(&typed("#archive")).is_none());
+}
+
+#[test]
+fn colons_complete_emoji() {
+    let mut editor = typed("Done :tad");
+    let completion = complete(&editor).unwrap();
+    assert_eq!(completion.kind, CompletionKind::Emoji);
+    assert_eq!(completion.start, Index2::new(0, 5));
+    assert_eq!(labels(&completion), ["🎉 :tada:"]);
+    completion.accept(&mut editor);
+    assert_eq!(text(&editor), "Done 🎉");
+}
+
+#[test]
+fn fences_complete_languages() {
+    let completion = complete(&typed("  ```rus")).unwrap();
+    assert_eq!(completion.kind, CompletionKind::Language);
+    assert_eq!(completion.start, Index2::new(0, 5));
+    assert!(labels(&completion).contains(&"rust"));
+    assert!(complete(&typed("```rust extra")).is_none());
+}
+
+#[test]
+fn the_selection_is_kept_while_typing() {
+    let mut first = complete(&typed("[[pro")).unwrap();
+    first.select_next(true);
+    assert_eq!(first.items[first.selected].label, "Old project");
+    first.dismissed = true;
+
+    let editor = typed("[[proj");
+    let next = Completion::update(Some(&first), &editor, &notes(), Some("Current")).unwrap();
+    assert_eq!(next.items[next.selected].label, "Old project");
+    assert!(next.dismissed);
+}
+
+#[test]
+fn the_last_spelling_item_adds_the_word() {
+    let mut completion = Completion::spelling(Index2::new(0, 0), "teh", vec!["the".to_string()]);
+    assert_eq!(labels(&completion), ["the", "Add \"teh\" to dictionary"]);
+    assert!(!completion.adds_word());
+    completion.select_next(false);
+    assert!(completion.adds_word());
+}
@@ -0,0 +1,97 @@
+use std::path::Path;
+
+use chrono::{TimeZone, Utc};
+use thoth_note::import::{AttachmentSource, EnexImporter, ImportedNote, Importer};
+
+fn notebook() -> Vec<ImportedNote> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/notebook.enex");
+    EnexImporter.scan(&path).unwrap()
+}
+
+fn bytes(source: &AttachmentSource) -> &[u8] {
+    match source {
+        AttachmentSource::Bytes(bytes) => bytes,
+        AttachmentSource::File(path) => panic!("expected bytes, got {}", path.display()),
+    }
+}
+
+#[test]
+fn notes_keep_their_title_notebook_and_tags() {
+    let notes = notebook();
+    assert_eq!(notes.len(), 2);
+    let note = &notes[0].note;
+    assert_eq!(note.title, "Groceries & errands");
+    assert_eq!(note.folder, "notebook");
+    assert_eq!(note.tags, ["home", "lists"]);
+    assert!(notes[0].source.ends_with("notebook.enex#1"));
+}
+
+#[test]
+fn both_timestamp_formats_are_read_as_utc() {
+    let notes = notebook();
+    let note = &notes[0].note;
+    assert_eq!(
+        note.created_at,
+        Utc.with_ymd_and_hms(2024, 1, 31, 8, 30, 0).unwrap()
+    );
+    assert_eq!(
+        note.updated_at,
+        Utc.with_ymd_and_hms(2024, 2, 1, 9, 15, 0).unwrap()
+    );
+
+    // An unreadable creation time falls back to the update time
+    let plain = &notes[1].note;
+    assert_eq!(
+        plain.updated_at,
+        Utc.with_ymd_and_hms(2024, 3, 5, 12, 0, 0).unwrap()
+    );
+    assert_eq!(plain.created_at, plain.updated_at);
+}
+
+#[test]
+fn media_is_found_by_hash_and_unused_resources_are_linked_at_the_end() {
+    let notes = notebook();
+    let imported = &notes[0];
+    assert_eq!(
+        imported.note.content,
+        "- [x] Milk\n\n- [ ] Bread\n\n\
+         Receipt: ![0b75926a-receipt.png](attachments/0b75926a-receipt.png)\n\n\
+         [34400817-attachment.pdf](attachments/34400817-attachment.pdf)"
+    );
+
+    let attachments: Vec<(&str, &[u8])> = imported
+        .attachments
+        .iter()
+        .map(|attachment| (attachment.name.as_str(), bytes(&attachment.source)))
+        .collect();
+    assert_eq!(
+        attachments,
+        [
+            ("0b75926a-receipt.png", b"PNGDATA".as_slice()),
+            ("34400817-attachment.pdf", b"unused pdf".as_slice()),
+        ]
+    );
+}
+
+#[test]
+fn undecodable_and_missing_resources_are_reported() {
+    let notes = notebook();
+    let warnings = &notes[0].warnings;
+    assert_eq!(warnings.len(), 3, "{:?}", warnings);
+    assert!(warnings[0].starts_with("could not decode attachment"));
+    assert_eq!(
+        warnings[1],
+        "attachment ffffffffffffffffffffffffffffffff not found"
+    );
+    assert_eq!(
+        warnings[2],
+        "attachment 34400817-attachment.pdf was not referenced and is linked at the end"
+    );
+}
+
+#[test]
+fn note_content_is_converted_from_enml() {
+    let notes = notebook();
+    assert_eq!(notes[1].note.content, "## Heading\n\nText");
+    assert!(notes[1].warnings.is_empty());
+}
@@ -0,0 +1,259 @@
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span, Text};
+use thoth_note::export::ansi::text_to_ansi;
+use thoth_note::export::error::ExportError;
+use thoth_note::export::json::to_ast;
+use thoth_note::export::plain::wrap_line;
+use thoth_note::export::{exporters, find_exporter};
+use thoth_note::models::note::Note;
+
+const SAMPLE: &str = "# Title\n\n\
+Some *emphasis*, **bold**, `code` and a [link](https://example.com).\n\n\
+## Lists\n\n\
+- One\n  - Nested\n    1. Deep\n- Two\n\n\
+* [ ] Open\n* [x] Done\n\n\
+```rust\nfn main() {}\n```\n\n\
+| Name | Qty |\n| --- | ---: |\n| Apple | 3 |\n| A \\| B | 10 |\n";
+
+fn export(format: &str, content: &str, width: u16) -> String {
+    let note = Note {
+        title: "Export".to_string(),
+        content: content.to_string(),
+        ..Note::default()
+    };
+    find_exporter(format, width).unwrap().export(&note).unwrap()
+}
+
+/// Remove ANSI escape sequences
+fn strip_ansi(text: &str) -> String {
+    let mut plain = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('\x1b') {
+        plain.push_str(&rest[..start]);
+        rest = &rest[start..];
+        rest = &rest[rest.find('m').map_or(rest.len(), |end| end + 1)..];
+    }
+    plain.push_str(rest);
+    plain
+}
+
+#[test]
+fn unknown_formats_are_refused() {
+    assert!(matches!(
+        find_exporter("docx", 80),
+        Err(ExportError::UnknownFormat(name)) if name == "docx"
+    ));
+    for exporter in exporters(80) {
+        assert_eq!(
+            find_exporter(exporter.name(), 80).unwrap().name(),
+            exporter.name()
+        );
+    }
+}
+
+#[test]
+fn rst_output() {
+    assert_eq!(
+        export("rst", SAMPLE, 80),
+        "Title
+=====
+
+Some *emphasis*, **bold**, ``code`` and a `link <https://example.com>`_.
+
+Lists
+-----
+
+- One
+
+  - Nested
+
+    1. Deep
+
+- Two
+
+- [ ] Open
+- [x] Done
+
+.. code-block:: rust
+
+   fn main() {}
+
+.. list-table::
+   :header-rows: 1
+
+   * - Name
+     - Qty
+   * - Apple
+     - 3
+   * - A | B
+     - 10
+"
+    );
+}
+
+#[test]
+fn rst_headings_are_underlined_to_their_display_width() {
+    assert_eq!(
+        export("rst", "### 日本語\n\n#### Note", 80),
+        "日本語\n~~~~~~\n\nNote\n^^^^\n"
+    );
+}
+
+#[test]
+fn asciidoc_output() {
+    assert_eq!(
+        export("asciidoc", SAMPLE, 80),
+        "== Title
+
+Some _emphasis_, *bold*, `+code+` and a link:https://example.com[link].
+
+=== Lists
+
+* One
+** Nested
+... Deep
+* Two
+
+* [ ] Open
+* [x] Done
+
+[source,rust]
+----
+fn main() {}
+----
+
+[cols=\"1,>1\",options=\"header\"]
+|===
+|Name |Qty
+|Apple |3
+|A \\| B |10
+|===
+"
+    );
+}
+
+#[test]
+fn json_output_holds_the_note_and_its_syntax_tree() {
+    let json: serde_json::Value = serde_json::from_str(&export("json", SAMPLE, 80)).unwrap();
+    assert_eq!(json["title"], "Export");
+
+    let blocks = json["ast"]["children"].as_array().unwrap();
+    let kinds: Vec<&str> = blocks
+        .iter()
+        .map(|block| block["type"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            "heading",
+            "paragraph",
+            "heading",
+            "list",
+            "list",
+            "code_block",
+            "table"
+        ]
+    );
+    assert_eq!(blocks[0]["level"], 1);
+    assert_eq!(blocks[5]["language"], "rust");
+
+    let nested = &blocks[3]["children"][0]["children"][1];
+    assert_eq!(nested["type"], "list");
+    assert_eq!(nested["children"][0]["children"][1]["ordered"], true);
+
+    let task = &blocks[4]["children"][1]["children"][0];
+    assert_eq!(task["type"], "task_list_marker");
+    assert_eq!(task["checked"], true);
+
+    let last_row = &blocks[6]["children"][2]["children"];
+    let cell: String = last_row[0]["children"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|text| text["value"].as_str().unwrap())
+        .collect();
+    assert_eq!(cell, "A | B");
+}
+
+#[test]
+fn json_ast_of_an_empty_note_is_an_empty_document() {
+    assert_eq!(to_ast(""), serde_json::json!({ "type": "document" }));
+}
+
+#[test]
+fn plain_output_wraps_paragraphs_to_the_width() {
+    let content = "# Title\n\nA paragraph that is long enough to wrap twice.\n\n\
+                   - One\n  - Nested item\n\n```\nlet x = 1;\n```\n";
+    assert_eq!(
+        export("plain", content, 20),
+        "▌ Title
+
+A paragraph that is
+long enough to wrap
+twice.
+
+■ One
+    ‣  Nested item
+
+╒═══════════════════
+│ let x = 1;
+└───────────────────
+"
+    );
+}
+
+#[test]
+fn ansi_output_is_the_preview_with_escape_codes() {
+    let content = "# Title\n\nPlain text\n\n- One\n  - Nested item\n";
+    let ansi = export("ansi", content, 20);
+    assert!(ansi.starts_with("\x1b[1;4;"));
+    assert_eq!(
+        strip_ansi(&ansi),
+        "▌ Title\n\nPlain text\n\n■ One\n    ‣  Nested item\n"
+    );
+    // Unstyled text carries no codes
+    assert!(ansi.contains("\nPlain text\n"));
+}
+
+#[test]
+fn ansi_codes_combine_modifiers_and_colors() {
+    let text = Text::from(vec![
+        Line::from(vec![
+            Span::raw("plain "),
+            Span::styled("bold red", Style::new().bold().fg(Color::Red)),
+        ]),
+        Line::from(vec![
+            Span::styled("rgb", Style::new().fg(Color::Rgb(1, 2, 3))),
+            Span::styled("indexed", Style::new().bg(Color::Indexed(42))),
+            Span::styled("bright", Style::new().fg(Color::LightBlue)),
+        ]),
+        Line::from("underlined").add_modifier(Modifier::UNDERLINED),
+    ]);
+    assert_eq!(
+        text_to_ansi(&text),
+        "plain \x1b[1;31mbold red\x1b[0m\n\
+         \x1b[38;2;1;2;3mrgb\x1b[0m\x1b[48;5;42mindexed\x1b[0m\x1b[94mbright\x1b[0m\n\
+         \x1b[4munderlined\x1b[0m\n"
+    );
+}
+
+#[test]
+fn lines_wrap_at_spaces() {
+    assert_eq!(wrap_line("short", 10), ["short"]);
+    assert_eq!(
+        wrap_line("one two three four", 9),
+        ["one two", "three", "four"]
+    );
+    assert_eq!(wrap_line("exactly ten", 11), ["exactly ten"]);
+    // Width is counted in characters, not bytes
+    assert_eq!(wrap_line("äöü äöü äöü", 7), ["äöü äöü", "äöü"]);
+    assert_eq!(wrap_line("anything", 0), ["anything"]);
+}
+
+#[test]
+fn words_longer_than_a_row_are_split() {
+    assert_eq!(
+        wrap_line("a supercalifragilistic word", 8),
+        ["a", "supercal", "ifragili", "stic", "word"]
+    );
+}
@@ -0,0 +1,33 @@
+use std::fs;
+
+use thoth_note::external_editor::write_temp_file;
+
+#[test]
+fn temp_copy_is_private_and_removed_on_drop() {
+    let temp = write_temp_file("Secret/plan", "hidden text").unwrap();
+    let path = temp.path().to_path_buf();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "hidden text");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let file_mode = fs::metadata(&path).unwrap().permissions().mode();
+        let dir_mode = fs::metadata(path.parent().unwrap())
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(file_mode & 0o777, 0o600);
+        assert_eq!(dir_mode & 0o777, 0o700);
+    }
+    drop(temp);
+    assert!(!path.exists());
+    assert!(!path.parent().unwrap().exists());
+}
+
+#[test]
+fn temp_copies_of_the_same_note_do_not_collide() {
+    let first = write_temp_file("Note", "one").unwrap();
+    let second = write_temp_file("Note", "two").unwrap();
+    assert_ne!(first.path(), second.path());
+    first.close().unwrap();
+    assert_eq!(fs::read_to_string(second.path()).unwrap(), "two");
+}
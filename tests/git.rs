@@ -0,0 +1,145 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use thoth_note::models::note::Note;
+use thoth_note::storage::fs::FSStorage;
+use thoth_note::storage::git::GitRepo;
+use thoth_note::storage::Storage;
+
+fn note(title: &str, content: &str) -> Note {
+    Note {
+        title: title.to_string(),
+        content: content.to_string(),
+        ..Note::default()
+    }
+}
+
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "git {:?} failed", args);
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+/// A bare repository standing in for the server, and its URL
+fn bare_remote(dir: &Path) -> String {
+    let remote = dir.join("remote.git");
+    fs::create_dir(&remote).unwrap();
+    git(&remote, &["init", "--quiet", "--bare"]);
+    remote.display().to_string()
+}
+
+#[test]
+fn notes_are_pushed_and_pulled_through_a_remote() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = bare_remote(dir.path());
+
+    let laptop = FSStorage::with_root_dir(dir.path().join("laptop"));
+    laptop.write_note(&note("Ideas", "First\n")).unwrap();
+    let laptop_repo = GitRepo::init(laptop.root_dir(), Some(&url)).unwrap();
+    laptop_repo.push().unwrap();
+
+    let desktop = FSStorage::with_root_dir(dir.path().join("desktop"));
+    desktop.init().unwrap();
+    let desktop_repo = GitRepo::init(desktop.root_dir(), Some(&url)).unwrap();
+    let outcome = desktop_repo.pull().unwrap();
+    assert!(outcome.updated);
+    assert!(outcome.conflicts.is_empty());
+    assert_eq!(desktop.read_note("Ideas").unwrap().content, "First\n");
+
+    desktop.write_note(&note("Ideas", "Second\n")).unwrap();
+    assert!(desktop_repo.commit_all("Update 'Ideas'").unwrap());
+    desktop_repo.push().unwrap();
+
+    assert!(laptop_repo.pull().unwrap().updated);
+    assert_eq!(laptop.read_note("Ideas").unwrap().content, "Second\n");
+    assert_eq!(laptop_repo.history("Ideas").unwrap().len(), 2);
+
+    // Nothing new on either side
+    assert!(!laptop_repo.pull().unwrap().updated);
+}
+
+#[test]
+fn a_note_changed_on_both_sides_is_left_with_conflict_markers() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = bare_remote(dir.path());
+
+    let laptop = FSStorage::with_root_dir(dir.path().join("laptop"));
+    laptop.write_note(&note("Plan", "Start\n")).unwrap();
+    let laptop_repo = GitRepo::init(laptop.root_dir(), Some(&url)).unwrap();
+    laptop_repo.push().unwrap();
+    let desktop = FSStorage::with_root_dir(dir.path().join("desktop"));
+    desktop.init().unwrap();
+    let desktop_repo = GitRepo::init(desktop.root_dir(), Some(&url)).unwrap();
+    desktop_repo.pull().unwrap();
+
+    laptop.write_note(&note("Plan", "Laptop edit\n")).unwrap();
+    laptop_repo.commit_all("Update 'Plan'").unwrap();
+    laptop_repo.push().unwrap();
+    desktop.write_note(&note("Plan", "Desktop edit\n")).unwrap();
+    desktop_repo.commit_all("Update 'Plan'").unwrap();
+    assert!(desktop_repo.push().is_err());
+
+    let outcome = desktop_repo.pull().unwrap();
+    assert_eq!(outcome.conflicts, ["Plan"]);
+    assert_eq!(desktop_repo.conflicted_notes().unwrap(), ["Plan"]);
+    let content = fs::read_to_string(desktop.root_dir().join("Plan.md")).unwrap();
+    assert!(content.contains("Laptop edit") && content.contains("Desktop edit"));
+
+    // Resolving the markers concludes the merge
+    desktop.write_note(&note("Plan", "Both edits\n")).unwrap();
+    assert!(desktop_repo.commit_all("Resolve 'Plan'").unwrap());
+    desktop_repo.push().unwrap();
+    laptop_repo.pull().unwrap();
+    assert_eq!(laptop.read_note("Plan").unwrap().content, "Both edits\n");
+}
+
+#[test]
+fn an_existing_gitignore_gains_the_missing_patterns() {
+    let dir = tempfile::tempdir().unwrap();
+    let vault = dir.path().join("vault");
+    fs::create_dir(&vault).unwrap();
+    fs::write(
+        vault.join(".gitignore"),
+        "*.tmp\n*.rekey\nsync.json\ndrafts/",
+    )
+    .unwrap();
+    fs::write(vault.join("layout.json"), "{}").unwrap();
+    git(&vault, &["init", "--quiet"]);
+    git(&vault, &["add", "-A"]);
+    git(
+        &vault,
+        &[
+            "-c",
+            "user.name=Test",
+            "-c",
+            "user.email=test@localhost",
+            "commit",
+            "--quiet",
+            "-m",
+            "Old vault",
+        ],
+    );
+
+    let repo = GitRepo::open(&vault).unwrap();
+    assert!(repo.commit_all("Update").unwrap());
+
+    let gitignore = fs::read_to_string(vault.join(".gitignore")).unwrap();
+    assert_eq!(
+        gitignore,
+        "*.tmp\n*.rekey\nsync.json\ndrafts/\nlayout.json\nkeymap.json\nspelling.json\n\
+         activity.json\nsorting.json\n"
+    );
+    // Still on disk, no longer versioned
+    assert!(vault.join("layout.json").exists());
+    assert!(git(&vault, &["ls-files"])
+        .lines()
+        .all(|file| file != "layout.json"));
+
+    assert!(!repo.commit_all("Update").unwrap());
+}
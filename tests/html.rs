@@ -0,0 +1,123 @@
+use thoth_note::import::html::html_to_markdown;
+use thoth_note::stats::NoteStats;
+
+fn convert(html: &str) -> String {
+    html_to_markdown(html, &mut |_, _| None).markdown
+}
+
+#[test]
+fn checkboxes_outside_lists_become_task_items() {
+    let markdown = convert(
+        "<div><en-todo checked=\"true\"/>Done</div>\
+         <div><en-todo checked=\"false\"/> Open</div>\
+         <p><input type=\"checkbox\"> Also open</p>",
+    );
+    assert_eq!(markdown, "- [x] Done\n\n- [ ] Open\n\n- [ ] Also open");
+
+    let stats = NoteStats::of(&markdown);
+    assert_eq!((stats.open_tasks, stats.done_tasks), (2, 1));
+}
+
+#[test]
+fn evernote_checklists_are_task_lists() {
+    let markdown = convert(
+        "<ul style=\"--en-todo:true;\"><li style=\"--en-checked:true;\">Milk</li>\
+         <li style=\"--en-checked:false;\">Eggs</li></ul>",
+    );
+    assert_eq!(markdown, "- [x] Milk\n- [ ] Eggs");
+}
+
+#[test]
+fn title_comes_from_the_title_element_or_the_first_heading() {
+    let conversion = html_to_markdown(
+        "<html><head><title>Page &amp; Title</title></head><body><h1>Heading</h1></body></html>",
+        &mut |_, _| None,
+    );
+    assert_eq!(conversion.title.as_deref(), Some("Page & Title"));
+    assert_eq!(conversion.markdown, "# Heading");
+
+    let conversion = html_to_markdown("<h2>Second</h2><h1>First <em>one</em></h1>", &mut |_, _| {
+        None
+    });
+    assert_eq!(conversion.title.as_deref(), Some("First one"));
+    assert_eq!(conversion.markdown, "## Second\n\n# First *one*");
+}
+
+#[test]
+fn inline_formatting_and_line_breaks() {
+    assert_eq!(
+        convert(
+            "<p>Some <b>bold</b>, <i>italic</i>, <del>gone</del>, <code>code</code> and \
+             <a href=\"https://example.com\">a link</a>.<br>Next line<br></p><p>After</p>"
+        ),
+        "Some **bold**, *italic*, ~~gone~~, `code` and [a link](https://example.com).\\\n\
+         Next line\n\nAfter"
+    );
+}
+
+#[test]
+fn nested_lists_line_up_with_their_parent_item() {
+    assert_eq!(
+        convert(
+            "<ul><li>One<ul><li>Nested</li><li>Nested two\
+             <ol start=\"9\"><li>Nine</li><li>Ten<ul><li>Deep</li></ul></li></ol>\
+             </li></ul></li><li>Two</li></ul>"
+        ),
+        "- One\n  - Nested\n  - Nested two\n    9. Nine\n    10. Ten\n        - Deep\n- Two"
+    );
+}
+
+#[test]
+fn tables_escape_pipes_and_fill_short_rows() {
+    assert_eq!(
+        convert(
+            "<table><tr><th>Name</th><th>Note</th></tr>\
+             <tr><td>A | B</td><td>multi\nline<br>break</td></tr>\
+             <tr><td>only</td></tr></table>"
+        ),
+        "| Name | Note |\n| --- | --- |\n| A \\| B | multi line<br>break |\n| only |  |"
+    );
+}
+
+#[test]
+fn quotes_and_code_blocks() {
+    assert_eq!(
+        convert(
+            "<blockquote><p>Quoted</p><p>Twice</p></blockquote>\
+             <pre><code>fn main() {\n    1 &lt; 2\n}</code></pre>\
+             <div style=\"-en-codeblock: true\"><div>evernote</div><div>code</div></div>"
+        ),
+        "> Quoted\n>\n> Twice\n\n```\nfn main() {\n    1 < 2\n}\n```\n\n```\nevernote\ncode\n```"
+    );
+}
+
+#[test]
+fn unsupported_content_is_dropped_with_a_warning() {
+    let conversion = html_to_markdown(
+        "<p>Before</p><iframe src=\"x\"><p>inside</p></iframe><script>alert(1)</script>\
+         <p><u>under</u><u>lined</u></p>",
+        &mut |_, _| None,
+    );
+    assert_eq!(conversion.markdown, "Before\n\nunderlined");
+    assert_eq!(
+        conversion.warnings,
+        ["<iframe> content dropped", "underline formatting dropped"]
+    );
+}
+
+#[test]
+fn images_are_rendered_by_the_resolver_or_kept() {
+    let markdown = html_to_markdown(
+        "<img src=\"local.png\" alt=\"Local\"> <img src=\"https://example.com/a.png\">",
+        &mut |tag, _| (tag == "img").then(|| "![Local](attachments/local.png)".to_string()),
+    )
+    .markdown;
+    assert_eq!(
+        markdown,
+        "![Local](attachments/local.png) ![Local](attachments/local.png)"
+    );
+    assert_eq!(
+        convert("<img src=\"https://example.com/a.png\" alt=\"Remote\">"),
+        "![Remote](https://example.com/a.png)"
+    );
+}
@@ -0,0 +1,160 @@
+use std::fs;
+
+use thoth_note::import::{import, HtmlImporter, Importer, MarkdownImporter};
+use thoth_note::storage::fs::FSStorage;
+use thoth_note::storage::Storage;
+
+#[test]
+fn attachment_links_encode_spaces_in_both_link_forms() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("My Photo.png"), b"png").unwrap();
+    fs::write(
+        dir.path().join("Trip.md"),
+        "![[My Photo.png]]\n\n![again](My%20Photo.png)\n",
+    )
+    .unwrap();
+
+    let notes = MarkdownImporter::obsidian().scan(dir.path()).unwrap();
+    assert_eq!(
+        notes[0].note.content,
+        "![My Photo.png](attachments/My%20Photo.png)\n\n![again](attachments/My%20Photo.png)\n"
+    );
+}
+
+#[test]
+fn same_named_attachments_do_not_overwrite_each_other() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("source");
+    for (folder, bytes) in [("trip", "beach"), ("work", "chart")] {
+        fs::create_dir_all(source.join(folder)).unwrap();
+        fs::write(source.join(folder).join("photo 1.png"), bytes).unwrap();
+        fs::write(
+            source.join(folder).join(format!("{}.md", folder)),
+            "![photo](photo%201.png)\n",
+        )
+        .unwrap();
+    }
+    let storage = FSStorage::with_root_dir(dir.path().join("vault"));
+    storage.write_attachment("photo 1.png", b"older").unwrap();
+
+    let report = import(&MarkdownImporter::plain(), &source, &storage, false).unwrap();
+    assert_eq!(report.warnings.len(), 2);
+
+    let attachments = dir.path().join("vault/attachments");
+    assert_eq!(fs::read(attachments.join("photo 1.png")).unwrap(), b"older");
+    assert_eq!(
+        fs::read(attachments.join("photo 1 (2).png")).unwrap(),
+        b"beach"
+    );
+    assert_eq!(
+        fs::read(attachments.join("photo 1 (3).png")).unwrap(),
+        b"chart"
+    );
+    assert_eq!(
+        storage.read_note("trip").unwrap().content,
+        "![photo](attachments/photo%201%20(2).png)\n"
+    );
+    assert_eq!(
+        storage.read_note("work").unwrap().content,
+        "![photo](attachments/photo%201%20(3).png)\n"
+    );
+
+    // Importing the same file again reuses the stored copy
+    let path = storage.write_attachment("photo 1.png", b"chart").unwrap();
+    assert_eq!(path, "attachments/photo 1 (3).png");
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinked_folders_are_not_followed() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("notes")).unwrap();
+    fs::write(dir.path().join("notes/Note.md"), "Text\n").unwrap();
+    std::os::unix::fs::symlink(dir.path(), dir.path().join("notes/loop")).unwrap();
+
+    let notes = MarkdownImporter::plain()
+        .scan(&dir.path().join("notes"))
+        .unwrap();
+    assert_eq!(notes.len(), 1);
+}
+
+#[test]
+fn html_images_outside_the_imported_folder_are_not_copied() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("source");
+    fs::create_dir_all(source.join("pages")).unwrap();
+    fs::write(dir.path().join("secret.txt"), b"secret").unwrap();
+    fs::write(source.join("inside.png"), b"png").unwrap();
+    let secret = dir.path().join("secret.txt");
+    fs::write(
+        source.join("pages/Page.html"),
+        format!(
+            "<img src=\"../inside.png\"><img src=\"../../secret.txt\"><img src=\"{}\">",
+            secret.display()
+        ),
+    )
+    .unwrap();
+
+    let notes = HtmlImporter.scan(&source).unwrap();
+    let note = &notes[0];
+    assert_eq!(note.attachments.len(), 1);
+    assert_eq!(note.attachments[0].name, "inside.png");
+    assert!(note
+        .note
+        .content
+        .starts_with("![inside.png](attachments/inside.png)![](../../secret.txt)"));
+    assert_eq!(note.warnings.len(), 2);
+}
+
+#[test]
+fn markdown_links_outside_the_imported_folder_are_not_copied() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("source");
+    fs::create_dir_all(&source).unwrap();
+    fs::write(dir.path().join("secret.txt"), b"secret").unwrap();
+    let secret = dir.path().join("secret.txt");
+    let content = format!("[a](../secret.txt) [b]({})\n", secret.display());
+    fs::write(source.join("Note.md"), &content).unwrap();
+
+    let notes = MarkdownImporter::plain().scan(&source).unwrap();
+    assert!(notes[0].attachments.is_empty());
+    assert_eq!(notes[0].note.content, content);
+    assert_eq!(notes[0].warnings.len(), 2);
+}
+
+#[test]
+fn different_files_with_one_name_in_a_note_keep_their_own_links() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("source");
+    for (folder, bytes) in [("a", "first"), ("b", "second")] {
+        fs::create_dir_all(source.join(folder)).unwrap();
+        fs::write(source.join(folder).join("image.png"), bytes).unwrap();
+    }
+    fs::write(
+        source.join("Note.md"),
+        "![one](a/image.png) ![two](b/image.png) ![again](a/image.png)\n",
+    )
+    .unwrap();
+    let storage = FSStorage::with_root_dir(dir.path().join("vault"));
+    // Taken in the vault as well, so both files are renamed on import
+    storage.write_attachment("image.png", b"older").unwrap();
+    storage.write_attachment("image (2).png", b"older").unwrap();
+
+    let report = import(&MarkdownImporter::plain(), &source, &storage, false).unwrap();
+    assert_eq!(report.attachments, 2);
+
+    assert_eq!(
+        storage.read_note("Note").unwrap().content,
+        "![one](attachments/image%20(3).png) ![two](attachments/image%20(2)%20(2).png) \
+         ![again](attachments/image%20(3).png)\n"
+    );
+    let attachments = dir.path().join("vault/attachments");
+    assert_eq!(
+        fs::read(attachments.join("image (3).png")).unwrap(),
+        b"first"
+    );
+    assert_eq!(
+        fs::read(attachments.join("image (2) (2).png")).unwrap(),
+        b"second"
+    );
+}
@@ -0,0 +1,45 @@
+use chrono::{Local, NaiveDate, TimeZone};
+use thoth_note::journal::{entry_content, entry_date, entry_title, JOURNAL_FOLDER};
+use thoth_note::models::note::Note;
+
+fn note(title: &str, folder: &str) -> Note {
+    Note {
+        title: title.to_string(),
+        folder: folder.to_string(),
+        created_at: Local.with_ymd_and_hms(2026, 3, 9, 8, 0, 0).unwrap(),
+        ..Note::default()
+    }
+}
+
+#[test]
+fn titles_round_trip_to_their_date() {
+    for (year, month, day) in [(2026, 10, 16), (2024, 2, 29), (1999, 12, 31), (2027, 1, 1)] {
+        let date = NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        let title = entry_title(date);
+        assert_eq!(entry_date(&note(&title, JOURNAL_FOLDER)), Some(date));
+        // Titled by date anywhere else too
+        assert_eq!(entry_date(&note(&title, "Work")), Some(date));
+    }
+    assert_eq!(
+        entry_title(NaiveDate::from_ymd_opt(2026, 1, 5).unwrap()),
+        "2026-01-05"
+    );
+}
+
+#[test]
+fn other_journal_notes_use_their_creation_day() {
+    let created = NaiveDate::from_ymd_opt(2026, 3, 9).unwrap();
+    assert_eq!(entry_date(&note("Trip", JOURNAL_FOLDER)), Some(created));
+    assert_eq!(
+        entry_date(&note("2026-02-30", JOURNAL_FOLDER)),
+        Some(created)
+    );
+    assert_eq!(entry_date(&note("Trip", "")), None);
+    assert_eq!(entry_date(&note("2026-02-30", "")), None);
+}
+
+#[test]
+fn entries_start_with_the_long_date() {
+    let date = NaiveDate::from_ymd_opt(2026, 10, 6).unwrap();
+    assert_eq!(entry_content(date), "# Tuesday, 6 October 2026\n\n");
+}
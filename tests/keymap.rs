@@ -0,0 +1,130 @@
+use std::collections::BTreeMap;
+
+use thoth_note::app::View;
+use thoth_note::commands::Command;
+use thoth_note::keymap::{EditingMode, ExCommand, KeymapSettings, LeaderMatch, KEYMAP_FILE};
+use thoth_note::sorting::{Grouping, SortKey};
+use thoth_note::ui::CardLayout;
+
+#[test]
+fn commands_are_parsed_with_their_arguments() {
+    let parsed = |line: &str| ExCommand::parse(line).unwrap();
+    assert_eq!(parsed("w"), ExCommand::Write);
+    assert_eq!(parsed("  wq  "), ExCommand::WriteQuit);
+    assert_eq!(parsed("42"), ExCommand::Line(42));
+    assert_eq!(
+        parsed("e  Meeting notes "),
+        ExCommand::Edit("Meeting notes".to_string())
+    );
+    assert_eq!(parsed("rename"), ExCommand::Rename(None));
+    assert_eq!(
+        parsed("sort updated desc"),
+        ExCommand::Sort(SortKey::Updated, true)
+    );
+    assert_eq!(parsed("sort title"), ExCommand::Sort(SortKey::Title, false));
+    assert_eq!(parsed("group tag"), ExCommand::Group(Grouping::Tag));
+    assert_eq!(
+        parsed("cards compact"),
+        ExCommand::Cards(CardLayout::Compact)
+    );
+    assert_eq!(
+        parsed("folder /work/2026/"),
+        ExCommand::Folder("work/2026".to_string())
+    );
+    assert_eq!(parsed("folder"), ExCommand::Folder(String::new()));
+    assert_eq!(parsed("tag #idea"), ExCommand::Tag("idea".to_string()));
+    assert_eq!(
+        parsed("export html ~/out dir"),
+        ExCommand::Export("html".to_string(), Some("~/out dir".to_string()))
+    );
+    assert_eq!(parsed("merge All"), ExCommand::Merge("All".to_string()));
+}
+
+#[test]
+fn missing_and_wrong_arguments_are_reported() {
+    let error = |line: &str| ExCommand::parse(line).unwrap_err();
+    assert_eq!(error("e"), "Give the title of the note to open");
+    assert_eq!(error("sort"), "Give what to sort the notes by");
+    assert_eq!(error("group"), "Give what to group the notes by");
+    assert_eq!(error("sort colour"), "Cannot sort by colour");
+    assert_eq!(error("sort title sideways"), "Not an order: sideways");
+    assert_eq!(error("group size"), "Cannot group by size");
+    assert_eq!(error("cards huge"), "Not a card layout: huge");
+    assert_eq!(error("untag"), "Give the tag");
+    assert_eq!(error("merge"), "Give the title of the merged note");
+    // Commands without arguments take none
+    assert_eq!(error("w now"), "Not an editor command: w now");
+    assert_eq!(error("frobnicate"), "Not an editor command: frobnicate");
+}
+
+#[test]
+fn only_batch_commands_wait_for_a_sync() {
+    assert!(ExCommand::Tag("idea".to_string()).modifies_notes());
+    assert!(ExCommand::Merge("All".to_string()).modifies_notes());
+    assert!(!ExCommand::Write.modifies_notes());
+    assert!(!ExCommand::Sort(SortKey::Size, false).modifies_notes());
+}
+
+#[test]
+fn leader_keys_resolve_or_wait_for_more() {
+    let keymap = KeymapSettings::default();
+    assert!(matches!(
+        keymap.leader_command("p"),
+        LeaderMatch::Command(Command::SwitchView(View::Preview))
+    ));
+    assert!(matches!(keymap.leader_command("S"), LeaderMatch::Pending));
+    assert!(matches!(
+        keymap.leader_command("Sr"),
+        LeaderMatch::Command(_)
+    ));
+    assert!(matches!(keymap.leader_command("Sq"), LeaderMatch::None));
+    assert!(matches!(keymap.leader_command("Q"), LeaderMatch::None));
+}
+
+#[test]
+fn default_mappings_run_known_commands_and_none_hides_another() {
+    let keymap = KeymapSettings::default();
+    for (keys, name) in &keymap.leader_mappings {
+        assert!(
+            Command::from_name(name).is_some(),
+            "unknown command {}",
+            name
+        );
+        // A mapping that starts a longer one would run before the longer one is typed
+        assert!(
+            !keymap
+                .leader_mappings
+                .keys()
+                .any(|other| other != keys && other.starts_with(keys.as_str())),
+            "{} hides a longer mapping",
+            keys
+        );
+    }
+}
+
+#[test]
+fn mappings_to_unknown_commands_do_nothing() {
+    let keymap = KeymapSettings {
+        leader_mappings: BTreeMap::from([("q".to_string(), "no_such_command".to_string())]),
+        ..KeymapSettings::default()
+    };
+    assert!(matches!(keymap.leader_command("q"), LeaderMatch::None));
+}
+
+#[test]
+fn the_keymap_is_saved_with_the_notes() {
+    let dir = tempfile::tempdir().unwrap();
+    assert_eq!(KeymapSettings::load(dir.path()), KeymapSettings::default());
+
+    let keymap = KeymapSettings {
+        mode: EditingMode::Standard,
+        leader: ',',
+        leader_mappings: BTreeMap::from([("gg".to_string(), "quit".to_string())]),
+    };
+    keymap.save(dir.path()).unwrap();
+    assert_eq!(KeymapSettings::load(dir.path()), keymap);
+
+    // A broken file falls back to the defaults
+    std::fs::write(dir.path().join(KEYMAP_FILE), "{ not json").unwrap();
+    assert_eq!(KeymapSettings::load(dir.path()), KeymapSettings::default());
+}
@@ -0,0 +1,26 @@
+use thoth_note::links::{percent_decode, LinkTarget};
+
+#[test]
+fn percent_escapes_are_decoded() {
+    assert_eq!(percent_decode("Daily%20notes"), "Daily notes");
+    assert_eq!(percent_decode("caf%C3%A9"), "café");
+    // Malformed escapes stay as they are
+    assert_eq!(percent_decode("100%"), "100%");
+    assert_eq!(percent_decode("%zz%2"), "%zz%2");
+}
+
+#[test]
+fn links_to_notes_and_headings_are_decoded() {
+    assert_eq!(
+        LinkTarget::parse("./Meeting%20notes.md#agenda"),
+        LinkTarget::Note("Meeting notes".to_string())
+    );
+    assert_eq!(
+        LinkTarget::parse("#next%20steps"),
+        LinkTarget::Heading("next steps".to_string())
+    );
+    assert_eq!(
+        LinkTarget::parse("https://example.com/a%20b"),
+        LinkTarget::External("https://example.com/a%20b".to_string())
+    );
+}
@@ -0,0 +1,176 @@
+use edtui::actions::{Execute, MoveForward, SwitchMode};
+use edtui::{EditorMode, EditorState, Index2, Lines};
+use thoth_note::markdown_edit::{continue_list, format_table, indent_item, toggle_format, Format};
+
+fn editor(text: &str, row: usize, col: usize) -> EditorState {
+    let mut editor = EditorState::new(Lines::from(text));
+    editor.cursor = Index2::new(row, col);
+    editor
+}
+
+fn text(editor: &EditorState) -> String {
+    editor.lines.flatten(&Some('\n')).into_iter().collect()
+}
+
+#[test]
+fn enter_continues_a_bullet_list() {
+    let mut state = editor("- one", 0, 5);
+    assert!(continue_list(&mut state));
+    assert_eq!(text(&state), "- one\n- ");
+    assert_eq!(state.cursor, Index2::new(1, 2));
+}
+
+#[test]
+fn enter_numbers_the_next_item_and_the_ones_after_it() {
+    let mut state = editor("1. one\n2. two", 0, 6);
+    assert!(continue_list(&mut state));
+    assert_eq!(text(&state), "1. one\n2. \n3. two");
+    assert_eq!(state.cursor, Index2::new(1, 3));
+
+    // The list keeps its first number and delimiter
+    let mut state = editor("3) three", 0, 8);
+    assert!(continue_list(&mut state));
+    assert_eq!(text(&state), "3) three\n4) ");
+}
+
+#[test]
+fn enter_continues_a_task_list_unchecked() {
+    let mut state = editor("- [x] done", 0, 10);
+    assert!(continue_list(&mut state));
+    assert_eq!(text(&state), "- [x] done\n- [ ] ");
+    assert_eq!(state.cursor, Index2::new(1, 6));
+}
+
+#[test]
+fn enter_on_an_empty_item_ends_the_list() {
+    let mut state = editor("- one\n- ", 1, 2);
+    assert!(continue_list(&mut state));
+    assert_eq!(text(&state), "- one\n");
+    assert_eq!(state.cursor, Index2::new(1, 0));
+
+    let mut state = editor("- [ ] ", 0, 6);
+    assert!(continue_list(&mut state));
+    assert_eq!(text(&state), "");
+}
+
+#[test]
+fn enter_inside_an_item_moves_the_rest_to_a_new_item() {
+    let mut state = editor("- one two", 0, 5);
+    assert!(continue_list(&mut state));
+    assert_eq!(text(&state), "- one\n- two");
+    assert_eq!(state.cursor, Index2::new(1, 2));
+}
+
+#[test]
+fn enter_outside_an_item_is_left_to_the_editor() {
+    assert!(!continue_list(&mut editor("plain text", 0, 5)));
+    // Within the marker
+    assert!(!continue_list(&mut editor("- one", 0, 1)));
+    // Emphasis and rules are not list markers
+    assert!(!continue_list(&mut editor("**bold**", 0, 8)));
+    assert!(!continue_list(&mut editor("---", 0, 3)));
+}
+
+#[test]
+fn tab_nests_an_item_under_the_one_before() {
+    let mut state = editor("- one\n- two", 1, 2);
+    assert!(indent_item(&mut state, false));
+    assert_eq!(text(&state), "- one\n  - two");
+    assert_eq!(state.cursor, Index2::new(1, 4));
+
+    assert!(indent_item(&mut state, true));
+    assert_eq!(text(&state), "- one\n- two");
+    assert_eq!(state.cursor, Index2::new(1, 2));
+}
+
+#[test]
+fn nested_ordered_items_line_up_and_start_from_one() {
+    let mut state = editor("1. one\n2. two", 1, 3);
+    assert!(indent_item(&mut state, false));
+    assert_eq!(text(&state), "1. one\n   1. two");
+    assert_eq!(state.cursor, Index2::new(1, 6));
+
+    assert!(!indent_item(&mut editor("plain", 0, 0), false));
+}
+
+#[test]
+fn formatting_wraps_the_word_under_the_cursor_and_unwraps_it() {
+    let mut state = editor("say hello", 0, 5);
+    toggle_format(&mut state, Format::Bold);
+    assert_eq!(text(&state), "say **hello**");
+    assert_eq!(state.mode, EditorMode::Normal);
+
+    state.cursor = Index2::new(0, 6);
+    toggle_format(&mut state, Format::Bold);
+    assert_eq!(text(&state), "say hello");
+}
+
+#[test]
+fn formatting_wraps_the_selection() {
+    let mut state = editor("one two three", 0, 4);
+    SwitchMode(EditorMode::Visual).execute(&mut state);
+    MoveForward(2).execute(&mut state);
+    toggle_format(&mut state, Format::Code);
+    assert_eq!(text(&state), "one `two` three");
+    assert_eq!(state.selection, None);
+}
+
+#[test]
+fn a_link_leaves_the_cursor_on_its_address() {
+    let mut state = editor("see docs", 0, 5);
+    toggle_format(&mut state, Format::Link);
+    assert_eq!(text(&state), "see [docs]()");
+    assert_eq!(state.cursor, Index2::new(0, 11));
+    assert_eq!(state.mode, EditorMode::Insert);
+}
+
+#[test]
+fn formatting_nothing_types_between_the_delimiters() {
+    let mut state = editor("", 0, 0);
+    toggle_format(&mut state, Format::Italic);
+    assert_eq!(text(&state), "__");
+    assert_eq!(state.cursor, Index2::new(0, 1));
+    assert_eq!(state.mode, EditorMode::Insert);
+}
+
+#[test]
+fn tables_are_padded_to_their_alignment() {
+    let mut state = editor("| a | b |\n|:-|-:|\n| long cell | x |", 0, 6);
+    assert!(format_table(&mut state));
+    assert_eq!(
+        text(&state),
+        "| a         |   b |\n| :-------- | --: |\n| long cell |   x |"
+    );
+    // Still in the second cell
+    assert_eq!(state.cursor, Index2::new(0, 14));
+
+    let mut state = editor("| c |\n|:-:|\n| wide |", 2, 0);
+    assert!(format_table(&mut state));
+    assert_eq!(text(&state), "|  c   |\n| :--: |\n| wide |");
+}
+
+#[test]
+fn wide_characters_take_two_columns() {
+    let mut state = editor("| 名前 | é |\n|---|---|\n| x | y |", 0, 0);
+    assert!(format_table(&mut state));
+    assert_eq!(
+        text(&state),
+        "| 名前 | é   |\n| ---- | --- |\n| x    | y   |"
+    );
+}
+
+#[test]
+fn short_rows_get_empty_cells() {
+    let mut state = editor("| a | b |\n|---|---|\n| x |", 2, 0);
+    assert!(format_table(&mut state));
+    assert_eq!(text(&state), "| a   | b   |\n| --- | --- |\n| x   |     |");
+}
+
+#[test]
+fn only_tables_are_formatted() {
+    assert!(!format_table(&mut editor("no pipes", 0, 0)));
+    // Without a delimiter row it is not a table
+    let mut state = editor("| a |\n| b |", 0, 0);
+    assert!(!format_table(&mut state));
+    assert_eq!(text(&state), "| a |\n| b |");
+}
@@ -0,0 +1,54 @@
+use std::fs;
+
+use thoth_note::models::note::Note;
+use thoth_note::storage::crypto::is_sealed;
+use thoth_note::storage::fs::FSStorage;
+use thoth_note::storage::Storage;
+
+fn note(title: &str, content: &str, encrypted: bool) -> Note {
+    Note {
+        title: title.to_string(),
+        content: content.to_string(),
+        encrypted,
+        ..Note::default()
+    }
+}
+
+#[test]
+fn merging_an_encrypted_note_stores_the_result_sealed() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = FSStorage::with_root_dir(dir.path());
+    storage.set_passphrase("correct horse").unwrap();
+
+    let secret = note("Secret", "The launch code is 1234", true);
+    let plain = note("Plain", "Nothing to hide", false);
+    let merged = Note::merged("Merged", &[&secret, &plain], 2);
+    assert!(merged.encrypted);
+    storage.write_note(&merged).unwrap();
+
+    let stored = fs::read_to_string(dir.path().join("Merged.md")).unwrap();
+    assert!(is_sealed(&stored));
+    assert!(!stored.contains("launch code"));
+    assert!(!stored.contains("Nothing to hide"));
+
+    let read = storage.read_note("Merged").unwrap();
+    assert_eq!(read.content, "The launch code is 1234\n\nNothing to hide\n");
+}
+
+#[test]
+fn merging_plain_notes_keeps_them_plain() {
+    let first = note("First", "One\n\n", false);
+    let second = note("Second", "Two", false);
+    let merged = Note::merged("Both", &[&first, &second], 0);
+    assert!(!merged.encrypted);
+    assert_eq!(merged.content, "One\n\nTwo\n");
+}
+
+#[test]
+fn merged_notes_know_their_tags_and_words() {
+    let first = note("First", "One #idea\n", false);
+    let second = note("Second", "Two words #todo\n", false);
+    let merged = Note::merged("Both", &[&first, &second], 0);
+    assert_eq!(merged.text_tags, ["idea", "todo"]);
+    assert_eq!(merged.words, 5);
+}
@@ -0,0 +1,36 @@
+use thoth_note::models::note::Note;
+use thoth_note::storage::fs::FSStorage;
+use thoth_note::storage::Storage;
+
+#[test]
+fn loaded_notes_know_their_tags_and_words() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = FSStorage::with_root_dir(dir.path());
+    storage
+        .write_note(&Note {
+            title: "Plan".to_string(),
+            content: "# Plan\n\nShip it #work\n\n```\n#not-a-tag\n```\n".to_string(),
+            tags: vec!["q3".to_string()],
+            ..Note::default()
+        })
+        .unwrap();
+
+    let note = storage.read_note("Plan").unwrap();
+    assert_eq!(note.text_tags, ["work"]);
+    assert_eq!(note.all_tags(), ["q3", "work"]);
+    assert_eq!(note.words, 5);
+    assert!(note.has_tags());
+
+    let listed = storage.list_notes().unwrap();
+    assert_eq!(listed[0].words, 5);
+}
+
+#[test]
+fn text_info_follows_the_content_once_updated() {
+    let mut note = Note::default();
+    assert!(!note.has_tags());
+    note.content = "Call #home tonight".to_string();
+    note.update_text_info();
+    assert_eq!(note.text_tags, ["home"]);
+    assert_eq!(note.words, 3);
+}
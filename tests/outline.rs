@@ -0,0 +1,100 @@
+use thoth_note::outline::{headings, insert_toc, section_at, section_end, slug, Heading};
+
+const NOTE: &str = "# Title
+
+## Intro
+
+Text
+
+```
+# not a heading
+```
+
+## Setup `cargo`
+### Steps
+## Intro
+";
+
+fn heading(level: u8, title: &str, line: usize) -> Heading {
+    Heading {
+        level,
+        title: title.to_string(),
+        line,
+    }
+}
+
+#[test]
+fn headings_skip_code_blocks() {
+    assert_eq!(
+        headings(NOTE),
+        [
+            heading(1, "Title", 0),
+            heading(2, "Intro", 2),
+            heading(2, "Setup cargo", 10),
+            heading(3, "Steps", 11),
+            heading(2, "Intro", 12),
+        ]
+    );
+}
+
+#[test]
+fn sections_run_to_the_next_heading_of_their_level() {
+    let outline = headings(NOTE);
+    assert_eq!(section_at(&outline, 0), Some(0));
+    assert_eq!(section_at(&outline, 7), Some(1));
+    assert_eq!(section_at(&outline, 11), Some(3));
+    assert_eq!(section_end(&outline, 2), 4);
+    assert_eq!(section_end(&outline, 0), 5);
+
+    assert_eq!(section_at(&headings("Text\n# Later"), 0), None);
+}
+
+#[test]
+fn slugs_follow_github() {
+    assert_eq!(slug("Hello, World!"), "hello-world");
+    assert_eq!(slug("  Ünïcode  Title "), "ünïcode--title");
+    assert_eq!(slug("snake_case-and-dash"), "snake_case-and-dash");
+    assert_eq!(slug("C++ & Rust"), "c--rust");
+}
+
+#[test]
+fn the_table_goes_below_the_title() {
+    let expected = "# Title
+
+<!-- toc -->
+- [Intro](#intro)
+- [Setup cargo](#setup-cargo)
+  - [Steps](#steps)
+- [Intro](#intro-1)
+<!-- /toc -->
+
+## Intro
+";
+    let with_toc = insert_toc(NOTE);
+    assert!(with_toc.starts_with(expected), "{}", with_toc);
+    assert!(with_toc.ends_with("### Steps\n## Intro\n"));
+}
+
+#[test]
+fn the_table_is_regenerated_in_place() {
+    let with_toc = insert_toc(NOTE);
+    assert_eq!(insert_toc(&with_toc), with_toc);
+
+    let edited = format!("{}## Later\n", with_toc);
+    let refreshed = insert_toc(&edited);
+    assert_eq!(
+        refreshed,
+        with_toc.replace(
+            "- [Intro](#intro-1)\n",
+            "- [Intro](#intro-1)\n- [Later](#later)\n"
+        ) + "## Later\n"
+    );
+}
+
+#[test]
+fn without_a_title_the_table_goes_on_top() {
+    assert_eq!(
+        insert_toc("Intro text\n## A\n"),
+        "<!-- toc -->\n- [A](#a)\n<!-- /toc -->\n\nIntro text\n## A\n"
+    );
+}
@@ -0,0 +1,172 @@
+use std::cmp::Ordering;
+
+use chrono::{Local, TimeZone};
+use thoth_note::models::note::Note;
+use thoth_note::sorting::{natural_cmp, Grouping, SortKey, SortSettings};
+
+fn note(title: &str) -> Note {
+    Note {
+        title: title.to_string(),
+        ..Note::default()
+    }
+}
+
+fn titles(notes: &[Note]) -> Vec<&str> {
+    notes.iter().map(|note| note.title.as_str()).collect()
+}
+
+fn settings(key: SortKey, descending: bool, grouping: Grouping) -> SortSettings {
+    SortSettings {
+        key,
+        descending,
+        grouping,
+    }
+}
+
+#[test]
+fn numbers_are_compared_by_value() {
+    assert_eq!(natural_cmp("note2", "note10"), Ordering::Less);
+    assert_eq!(natural_cmp("Note 10", "Note 9"), Ordering::Greater);
+    assert_eq!(natural_cmp("v1.10", "v1.9"), Ordering::Greater);
+    assert_eq!(natural_cmp("note", "note1"), Ordering::Less);
+    // Equal values are told apart by their leading zeros
+    assert_eq!(natural_cmp("note007", "note7"), Ordering::Greater);
+}
+
+#[test]
+fn case_is_ignored() {
+    assert_eq!(natural_cmp("apple", "Banana"), Ordering::Less);
+    assert_eq!(natural_cmp("Note", "note"), Ordering::Equal);
+    assert_eq!(natural_cmp("ÉCOLE", "école"), Ordering::Equal);
+}
+
+#[test]
+fn titles_sort_naturally() {
+    let mut notes: Vec<Note> = ["note10", "Note2", "apple", "note1"]
+        .into_iter()
+        .map(note)
+        .collect();
+    settings(SortKey::Title, false, Grouping::None).sort(&mut notes);
+    assert_eq!(titles(&notes), ["apple", "note1", "Note2", "note10"]);
+
+    settings(SortKey::Title, true, Grouping::None).sort(&mut notes);
+    assert_eq!(titles(&notes), ["note10", "Note2", "note1", "apple"]);
+}
+
+#[test]
+fn pinned_notes_stay_on_top() {
+    let mut notes = vec![note("b"), note("a"), note("c")];
+    notes[2].pinned = true;
+    settings(SortKey::Title, false, Grouping::None).sort(&mut notes);
+    assert_eq!(titles(&notes), ["c", "a", "b"]);
+
+    settings(SortKey::Title, true, Grouping::None).sort(&mut notes);
+    assert_eq!(titles(&notes), ["c", "b", "a"]);
+}
+
+#[test]
+fn ties_are_broken_by_title() {
+    let mut notes: Vec<Note> = ["gamma", "alpha", "beta"].into_iter().map(note).collect();
+    notes[0].content = "longer".to_string();
+    settings(SortKey::Size, false, Grouping::None).sort(&mut notes);
+    assert_eq!(titles(&notes), ["alpha", "beta", "gamma"]);
+}
+
+#[test]
+fn manual_order_and_dates() {
+    let day = |day| Local.with_ymd_and_hms(2026, 10, day, 9, 0, 0).unwrap();
+    let mut notes = vec![note("a"), note("b"), note("c")];
+    for (index, (order, created, updated)) in
+        [(2, 1, 5), (0, 3, 4), (1, 2, 6)].into_iter().enumerate()
+    {
+        notes[index].order = order;
+        notes[index].created_at = day(created);
+        notes[index].updated_at = day(updated);
+    }
+
+    settings(SortKey::Manual, false, Grouping::None).sort(&mut notes);
+    assert_eq!(titles(&notes), ["b", "c", "a"]);
+    settings(SortKey::Created, false, Grouping::None).sort(&mut notes);
+    assert_eq!(titles(&notes), ["a", "c", "b"]);
+    settings(SortKey::Updated, true, Grouping::None).sort(&mut notes);
+    assert_eq!(titles(&notes), ["c", "a", "b"]);
+}
+
+#[test]
+fn folders_are_grouped_with_the_root_first() {
+    let mut notes = vec![note("d"), note("c"), note("b"), note("a")];
+    notes[0].folder = "Work".to_string();
+    notes[1].folder = "home".to_string();
+    notes[3].folder = "Work".to_string();
+    let settings = settings(SortKey::Title, false, Grouping::Folder);
+    settings.sort(&mut notes);
+    assert_eq!(titles(&notes), ["b", "c", "a", "d"]);
+
+    let headers: Vec<Option<String>> = (0..notes.len())
+        .map(|index| settings.header(&notes, index))
+        .collect();
+    assert_eq!(
+        headers,
+        [
+            Some("No folder".to_string()),
+            Some("home".to_string()),
+            Some("Work".to_string()),
+            None
+        ]
+    );
+}
+
+#[test]
+fn tags_come_from_metadata_or_the_text() {
+    let mut notes = vec![note("a"), note("b"), note("c")];
+    notes[0].tags = vec!["zeta".to_string()];
+    notes[1].content = "Ideas #alpha".to_string();
+    let settings = settings(SortKey::Title, false, Grouping::Tag);
+    settings.sort(&mut notes);
+    assert_eq!(titles(&notes), ["c", "b", "a"]);
+    assert_eq!(settings.group(&notes[0]).as_deref(), Some("No tag"));
+    assert_eq!(settings.group(&notes[1]).as_deref(), Some("#alpha"));
+}
+
+#[test]
+fn months_are_newest_first() {
+    let month = |month| Local.with_ymd_and_hms(2026, month, 1, 9, 0, 0).unwrap();
+    let mut notes = vec![note("a"), note("b")];
+    notes[0].updated_at = month(3);
+    notes[1].updated_at = month(9);
+    let settings = settings(SortKey::Updated, false, Grouping::Month);
+    settings.sort(&mut notes);
+    assert_eq!(titles(&notes), ["b", "a"]);
+    assert_eq!(settings.group(&notes[0]).as_deref(), Some("September 2026"));
+
+    // Pinned notes have a section of their own
+    notes[1].pinned = true;
+    assert_eq!(settings.group(&notes[1]).as_deref(), Some("Pinned"));
+}
+
+#[test]
+fn settings_are_described_and_named() {
+    assert_eq!(SortSettings::default().describe(), "manual order");
+    assert_eq!(
+        settings(SortKey::Updated, true, Grouping::Folder).describe(),
+        "updated, descending, by folder"
+    );
+
+    let mut key = SortKey::Manual;
+    for _ in 0..5 {
+        assert_eq!(SortKey::from_name(key.label()), Some(key));
+        key = key.next();
+    }
+    assert_eq!(key, SortKey::Manual);
+    assert_eq!(Grouping::from_name("tag"), Some(Grouping::Tag));
+    assert_eq!(Grouping::from_name("colour"), None);
+}
+
+#[test]
+fn settings_are_saved_with_the_notes() {
+    let dir = tempfile::tempdir().unwrap();
+    assert_eq!(SortSettings::load(dir.path()), SortSettings::default());
+    let saved = settings(SortKey::Size, true, Grouping::Month);
+    saved.save(dir.path()).unwrap();
+    assert_eq!(SortSettings::load(dir.path()), saved);
+}
@@ -0,0 +1,147 @@
+use thoth_note::spellcheck::{misspellings, word_at, Dictionary, Misspelling};
+
+const AFF: &str = "SET UTF-8
+TRY esianrtolcdugmphbyfvkwz
+FORBIDDENWORD !
+NOSUGGEST ?
+REP 1
+REP f ph
+PFX U Y 1
+PFX U 0 un .
+SFX S Y 2
+SFX S 0 s [^y]
+SFX S y ies y
+SFX D N 1
+SFX D 0 ed .
+";
+
+const DIC: &str = "15
+note/S
+city/S
+do/U
+lock/UDS
+phone
+colour/!
+darn/?
+don't
+see
+the
+and
+in
+code
+mail
+me
+";
+
+fn dictionary() -> Dictionary {
+    Dictionary::parse(AFF.as_bytes(), DIC.as_bytes())
+}
+
+#[test]
+fn words_and_their_affixes_are_correct() {
+    let dictionary = dictionary();
+    for word in ["note", "notes", "cities", "undo", "locked", "unlocks"] {
+        assert!(dictionary.check(word), "{} should be correct", word);
+    }
+    // `ies` replaces the `y`, and `D` does not combine with prefixes
+    for word in ["citys", "nots", "unlocked", "redo"] {
+        assert!(!dictionary.check(word), "{} should be misspelled", word);
+    }
+}
+
+#[test]
+fn capitals_and_curly_apostrophes_are_accepted() {
+    let dictionary = dictionary();
+    assert!(dictionary.check("Note"));
+    assert!(dictionary.check("NOTES"));
+    assert!(dictionary.check("don’t"));
+}
+
+#[test]
+fn forbidden_words_are_misspelled() {
+    assert!(!dictionary().check("colour"));
+}
+
+#[test]
+fn suggestions_come_from_replacements_and_single_edits() {
+    let dictionary = dictionary();
+    assert_eq!(dictionary.suggest("fone"), ["phone"]);
+    assert_eq!(dictionary.suggest("noet"), ["note"]);
+    assert_eq!(dictionary.suggest("Noet"), ["Note"]);
+    assert_eq!(dictionary.suggest("notephone"), ["note phone"]);
+}
+
+#[test]
+fn words_marked_no_suggest_are_not_offered() {
+    let dictionary = dictionary();
+    assert!(dictionary.check("darn"));
+    assert!(dictionary.suggest("dran").is_empty());
+}
+
+#[test]
+fn numbered_flags_and_aliases_are_read() {
+    let aff = "FLAG num\nAF 1\nAF 10,20\nSFX 10 Y 1\nSFX 10 0 s .\nSFX 20 Y 1\nSFX 20 0 ed .\n";
+    let dictionary = Dictionary::parse(aff.as_bytes(), b"1\nbook/1\n");
+    assert!(dictionary.check("books"));
+    assert!(dictionary.check("booked"));
+}
+
+#[test]
+fn latin_1_dictionaries_are_decoded() {
+    let dictionary = Dictionary::parse(b"SET ISO8859-1\n", b"1\ncaf\xe9\n");
+    assert!(dictionary.check("café"));
+}
+
+#[test]
+fn code_links_and_addresses_are_not_checked() {
+    let dictionary = dictionary();
+    let text = "see the notez in code
+`notez` and ``the `notez` code``
+see [the notez](http://example.com/notez) and [[notez]]
+see https://example.com/notez and www.notez.org <notez>
+mail me@notez.org #notez file_notez notez.toml :notez:
+```
+notez
+```
+the notez";
+    let found = misspellings(text, |word| dictionary.check(word));
+    let misspelling = |row, col| Misspelling {
+        row,
+        col,
+        word: "notez".to_string(),
+    };
+    assert_eq!(
+        found,
+        [misspelling(0, 8), misspelling(2, 9), misspelling(8, 4)]
+    );
+}
+
+#[test]
+fn acronyms_and_single_letters_are_not_checked() {
+    let found = misspellings("A NASA iPhone x", |_| false);
+    assert!(found.is_empty());
+}
+
+#[test]
+fn hyphenated_words_are_checked_separately() {
+    let dictionary = dictionary();
+    let found = misspellings("the note-takng city", |word| dictionary.check(word));
+    assert_eq!(
+        found,
+        [Misspelling {
+            row: 0,
+            col: 9,
+            word: "takng".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn the_word_under_the_cursor_leaves_out_quotes() {
+    let line: Vec<char> = "it's 'fine'  ".chars().collect();
+    assert_eq!(word_at(&line, 0), Some((0, 4)));
+    // Just after the word
+    assert_eq!(word_at(&line, 4), Some((0, 4)));
+    assert_eq!(word_at(&line, 7), Some((6, 10)));
+    assert_eq!(word_at(&line, 12), None);
+}
@@ -0,0 +1,131 @@
+use chrono::{Local, NaiveDate, TimeZone};
+use thoth_note::models::note::Note;
+use thoth_note::stats::{Activity, NoteStats, VaultStats};
+
+const PLAN: &str = "# Plan
+
+Ship the **big** release, see [docs](https://example.com/docs) and [[Roadmap]].
+
+- [ ] write notes
+- [x] test
+- [X] ship
+  - [ ] nested
+
+[ ] not a task
+
+```
+let x = 1;
+```
+";
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, 10, day).unwrap()
+}
+
+#[test]
+fn notes_count_their_parts() {
+    let stats = NoteStats::of(PLAN);
+    assert_eq!(
+        stats,
+        NoteStats {
+            words: 20,
+            characters: PLAN.chars().count(),
+            lines: 14,
+            headings: 1,
+            links: 2,
+            open_tasks: 2,
+            done_tasks: 2,
+        }
+    );
+}
+
+#[test]
+fn characters_are_not_bytes() {
+    let stats = NoteStats::of("café ünd");
+    assert_eq!((stats.characters, stats.words), (8, 2));
+}
+
+#[test]
+fn reading_time_is_rounded_up() {
+    let minutes = |words| {
+        NoteStats {
+            words,
+            ..NoteStats::default()
+        }
+        .reading_minutes()
+    };
+    assert_eq!(minutes(0), 0);
+    assert_eq!(minutes(1), 1);
+    assert_eq!(minutes(200), 1);
+    assert_eq!(minutes(201), 2);
+}
+
+#[test]
+fn the_vault_adds_up_its_notes() {
+    let day = |day| Local.with_ymd_and_hms(2026, 10, day, 9, 0, 0).unwrap();
+    let notes = vec![
+        Note {
+            title: "Plan".to_string(),
+            content: PLAN.to_string(),
+            folder: "work".to_string(),
+            updated_at: day(2),
+            ..Note::default()
+        },
+        Note {
+            title: "Short".to_string(),
+            content: "One #idea".to_string(),
+            tags: vec!["idea".to_string(), "draft".to_string()],
+            updated_at: day(5),
+            ..Note::default()
+        },
+        Note {
+            title: "Secret".to_string(),
+            folder: "work".to_string(),
+            locked: true,
+            updated_at: day(3),
+            ..Note::default()
+        },
+    ];
+
+    let vault = VaultStats::collect(&notes);
+    assert_eq!(vault.notes, 3);
+    assert_eq!(vault.words, 22);
+    assert_eq!((vault.folders, vault.tags), (1, 2));
+    assert_eq!((vault.open_tasks, vault.done_tasks), (2, 2));
+    let recent: Vec<&str> = vault
+        .recent
+        .iter()
+        .map(|(title, _)| title.as_str())
+        .collect();
+    assert_eq!(recent, ["Short", "Secret", "Plan"]);
+    // Locked notes have no size
+    assert_eq!(
+        vault.largest,
+        [("Plan".to_string(), 20), ("Short".to_string(), 2)]
+    );
+}
+
+#[test]
+fn activity_starts_from_the_note_dates() {
+    let dir = tempfile::tempdir().unwrap();
+    let note = Note {
+        created_at: Local.with_ymd_and_hms(2026, 10, 1, 9, 0, 0).unwrap(),
+        updated_at: Local.with_ymd_and_hms(2026, 10, 4, 9, 0, 0).unwrap(),
+        ..Note::default()
+    };
+    let mut activity = Activity::load(dir.path(), &[note]);
+    assert_eq!((activity.edits(date(1)), activity.edits(date(4))), (1, 1));
+
+    activity.record(date(4));
+    activity.record(date(4));
+    activity.record(date(6));
+    assert_eq!(activity.edits(date(4)), 3);
+    assert_eq!(activity.busiest_since(date(5)), 1);
+    assert_eq!(activity.busiest_since(date(7)), 0);
+
+    // Once saved, the notes are not counted again
+    activity.save(dir.path()).unwrap();
+    let loaded = Activity::load(dir.path(), &[]);
+    assert_eq!(loaded.edits(date(4)), 3);
+    assert_eq!(loaded.busiest_since(date(1)), 3);
+}
@@ -0,0 +1,294 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use chrono::{Duration, Local};
+use thoth_note::models::note::Note;
+use thoth_note::storage::fs::FSStorage;
+use thoth_note::storage::Storage;
+use thoth_note::sync::dir::DirRemote;
+use thoth_note::sync::error::SyncResult;
+use thoth_note::sync::{sync, Remote, RemoteEntry, SyncReport, SyncSettings};
+
+fn note(title: &str, content: &str) -> Note {
+    Note {
+        title: title.to_string(),
+        content: content.to_string(),
+        ..Note::default()
+    }
+}
+
+/// A vault on one machine, syncing with a directory shared by all of them
+struct Device {
+    storage: FSStorage,
+    remote: DirRemote,
+    settings: SyncSettings,
+}
+
+impl Device {
+    fn new(dir: &Path, name: &str) -> Self {
+        Self {
+            storage: FSStorage::with_root_dir(dir.join(name)),
+            remote: DirRemote::new(dir.join("remote")),
+            settings: SyncSettings::default(),
+        }
+    }
+
+    fn sync(&mut self) -> SyncReport {
+        sync(&self.storage, &self.remote, &mut self.settings, &mut |_| {}).unwrap()
+    }
+
+    fn titles(&self) -> Vec<String> {
+        let mut titles: Vec<String> = self
+            .storage
+            .list_notes()
+            .unwrap()
+            .into_iter()
+            .map(|note| note.title)
+            .collect();
+        titles.sort();
+        titles
+    }
+
+    fn content(&self, title: &str) -> String {
+        self.storage.read_note(title).unwrap().content
+    }
+}
+
+#[test]
+fn notes_are_uploaded_and_downloaded() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut laptop = Device::new(dir.path(), "laptop");
+    let mut desktop = Device::new(dir.path(), "desktop");
+
+    laptop.storage.write_note(&note("Ideas", "First")).unwrap();
+    let report = laptop.sync();
+    assert_eq!((report.uploaded, report.downloaded), (1, 0));
+    assert!(dir.path().join("remote/Ideas.json").exists());
+
+    let report = desktop.sync();
+    assert_eq!((report.uploaded, report.downloaded), (0, 1));
+    assert_eq!(desktop.content("Ideas"), "First");
+
+    desktop
+        .storage
+        .write_note(&note("Ideas", "Second"))
+        .unwrap();
+    assert_eq!(desktop.sync().uploaded, 1);
+    assert_eq!(laptop.sync().downloaded, 1);
+    assert_eq!(laptop.content("Ideas"), "Second");
+
+    // Nothing changed on either side
+    let report = laptop.sync();
+    assert_eq!((report.uploaded, report.downloaded), (0, 0));
+}
+
+#[test]
+fn deletions_reach_the_other_side() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut laptop = Device::new(dir.path(), "laptop");
+    let mut desktop = Device::new(dir.path(), "desktop");
+    laptop
+        .storage
+        .write_note(&note("Old", "Gone soon"))
+        .unwrap();
+    laptop.storage.write_note(&note("Kept", "Stays")).unwrap();
+    laptop.sync();
+    desktop.sync();
+
+    laptop.storage.delete_note("Old").unwrap();
+    assert_eq!(laptop.sync().deleted_remote, 1);
+    assert!(!dir.path().join("remote/Old.json").exists());
+
+    assert_eq!(desktop.sync().deleted_local, 1);
+    assert_eq!(desktop.titles(), ["Kept"]);
+}
+
+#[test]
+fn a_note_edited_after_it_was_deleted_elsewhere_is_kept() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut laptop = Device::new(dir.path(), "laptop");
+    let mut desktop = Device::new(dir.path(), "desktop");
+    laptop.storage.write_note(&note("Draft", "v1")).unwrap();
+    laptop.sync();
+    desktop.sync();
+
+    laptop.storage.delete_note("Draft").unwrap();
+    laptop.sync();
+    desktop.storage.write_note(&note("Draft", "v2")).unwrap();
+
+    let report = desktop.sync();
+    assert_eq!((report.deleted_local, report.uploaded), (0, 1));
+    laptop.sync();
+    assert_eq!(laptop.content("Draft"), "v2");
+}
+
+#[test]
+fn a_note_changed_on_both_sides_keeps_both_versions() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut laptop = Device::new(dir.path(), "laptop");
+    let mut desktop = Device::new(dir.path(), "desktop");
+    laptop.storage.write_note(&note("Plan", "Start")).unwrap();
+    laptop.sync();
+    desktop.sync();
+
+    let now = Local::now();
+    let older = Note {
+        updated_at: now - Duration::hours(1),
+        ..note("Plan", "Desktop edit")
+    };
+    let newer = Note {
+        updated_at: now,
+        ..note("Plan", "Laptop edit")
+    };
+    desktop.storage.write_note(&older).unwrap();
+    laptop.storage.write_note(&newer).unwrap();
+    desktop.sync();
+
+    let report = laptop.sync();
+    assert_eq!(report.conflicts.len(), 1);
+    let copy = &report.conflicts[0];
+    assert!(copy.starts_with("Plan (conflict "));
+    assert_eq!(laptop.content("Plan"), "Laptop edit");
+    assert_eq!(laptop.content(copy), "Desktop edit");
+
+    // The other side ends up with the same two notes
+    desktop.sync();
+    assert_eq!(desktop.titles(), laptop.titles());
+    assert_eq!(desktop.content("Plan"), "Laptop edit");
+    assert_eq!(desktop.content(copy), "Desktop edit");
+}
+
+#[test]
+fn saving_an_unchanged_encrypted_note_uploads_nothing() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = FSStorage::with_root_dir(dir.path().join("notes"));
+    storage.set_passphrase("correct horse").unwrap();
+    let remote = DirRemote::new(dir.path().join("remote"));
+    let mut settings = SyncSettings::default();
+
+    let secret = Note {
+        encrypted: true,
+        ..note("Secret", "The launch code is 1234")
+    };
+    storage.write_note(&secret).unwrap();
+    let report = sync(&storage, &remote, &mut settings, &mut |_| {}).unwrap();
+    assert_eq!(report.uploaded, 1);
+
+    storage.write_note(&secret).unwrap();
+    let report = sync(&storage, &remote, &mut settings, &mut |_| {}).unwrap();
+    assert_eq!(report.uploaded, 0);
+
+    let edited = Note {
+        content: "The launch code is 5678".to_string(),
+        ..secret
+    };
+    storage.write_note(&edited).unwrap();
+    let report = sync(&storage, &remote, &mut settings, &mut |_| {}).unwrap();
+    assert_eq!(report.uploaded, 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn sync_settings_are_readable_only_by_the_user() {
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    use thoth_note::sync::{RemoteConfig, SYNC_FILE};
+
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join(SYNC_FILE), "{}").unwrap();
+    fs::set_permissions(
+        dir.path().join(SYNC_FILE),
+        fs::Permissions::from_mode(0o644),
+    )
+    .unwrap();
+
+    let mut settings = SyncSettings::load(dir.path()).unwrap();
+    settings.set_remote(RemoteConfig {
+        url: "https://dav.example.com/notes/".to_string(),
+        username: Some("me".to_string()),
+        password: Some("hunter2".to_string()),
+    });
+    settings.save(dir.path()).unwrap();
+
+    let mode = fs::metadata(dir.path().join(SYNC_FILE))
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o600);
+}
+
+/// A remote kept in memory that reports no ETags, like some WebDAV servers
+#[derive(Default)]
+struct MemoryRemote {
+    documents: RefCell<BTreeMap<String, Vec<u8>>>,
+}
+
+impl Remote for MemoryRemote {
+    fn list(&self) -> SyncResult<Vec<RemoteEntry>> {
+        Ok(self
+            .documents
+            .borrow()
+            .keys()
+            .map(|name| RemoteEntry {
+                name: name.clone(),
+                etag: None,
+            })
+            .collect())
+    }
+
+    fn get(&self, name: &str) -> SyncResult<(Vec<u8>, Option<String>)> {
+        Ok((self.documents.borrow()[name].clone(), None))
+    }
+
+    fn put(&self, name: &str, data: &[u8]) -> SyncResult<Option<String>> {
+        self.documents
+            .borrow_mut()
+            .insert(name.to_string(), data.to_vec());
+        Ok(None)
+    }
+
+    fn delete(&self, name: &str) -> SyncResult<()> {
+        self.documents.borrow_mut().remove(name);
+        Ok(())
+    }
+}
+
+#[test]
+fn a_remote_without_etags_is_compared_by_content() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = FSStorage::with_root_dir(dir.path());
+    let remote = MemoryRemote::default();
+    let mut settings = SyncSettings::default();
+    storage.write_note(&note("Ideas", "First")).unwrap();
+    assert_eq!(
+        sync(&storage, &remote, &mut settings, &mut |_| {})
+            .unwrap()
+            .uploaded,
+        1
+    );
+
+    let report = sync(&storage, &remote, &mut settings, &mut |_| {}).unwrap();
+    assert_eq!((report.uploaded, report.downloaded), (0, 0));
+
+    let edited = String::from_utf8(remote.documents.borrow()["Ideas.json"].clone())
+        .unwrap()
+        .replace("First", "Edited remotely");
+    remote.put("Ideas.json", edited.as_bytes()).unwrap();
+    let report = sync(&storage, &remote, &mut settings, &mut |_| {}).unwrap();
+    assert_eq!(report.downloaded, 1);
+    assert_eq!(
+        storage.read_note("Ideas").unwrap().content,
+        "Edited remotely"
+    );
+
+    remote.delete("Ideas.json").unwrap();
+    assert_eq!(
+        sync(&storage, &remote, &mut settings, &mut |_| {})
+            .unwrap()
+            .deleted_local,
+        1
+    );
+    assert!(storage.list_notes().unwrap().is_empty());
+}
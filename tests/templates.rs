@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use thoth_note::templates::{expand, variables, Expanded};
+
+fn now() -> chrono::NaiveDateTime {
+    NaiveDate::from_ymd_opt(2026, 10, 16)
+        .unwrap()
+        .and_hms_opt(14, 30, 5)
+        .unwrap()
+}
+
+fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn cursor_is_found_on_later_lines() {
+    let expanded = expand(
+        "# {{title}}\n\nDate: {{date}}\n- {{cursor}} done {{cursor}}",
+        "Meeting",
+        now(),
+        &HashMap::new(),
+    );
+    assert_eq!(
+        expanded,
+        Expanded {
+            content: "# Meeting\n\nDate: 2026-10-16\n-  done ".to_string(),
+            cursor: Some((3, 2)),
+        }
+    );
+}
+
+#[test]
+fn cursor_column_counts_characters() {
+    let expanded = expand("Café ünd {{cursor}}", "", now(), &HashMap::new());
+    assert_eq!(expanded.cursor, Some((0, 9)));
+}
+
+#[test]
+fn custom_variables_are_listed_once_in_order() {
+    let template = "{{ attendees }} {{title}} {{topic}} {{attendees}} {{date}} {{cursor}}";
+    assert_eq!(variables(template), ["attendees", "topic"]);
+}
+
+#[test]
+fn custom_variables_are_filled_in_and_missing_ones_left_empty() {
+    let expanded = expand(
+        "With {{ attendees }} about [{{topic}}]",
+        "",
+        now(),
+        &values(&[("attendees", "Ana and Bo")]),
+    );
+    assert_eq!(expanded.content, "With Ana and Bo about []");
+    assert_eq!(expanded.cursor, None);
+}
+
+#[test]
+fn dates_and_times_take_a_format() {
+    let expanded = expand(
+        "{{time}} {{date:%Y}} {{ date: %A %d %B }} {{time:%H:%M:%S}}",
+        "",
+        now(),
+        &HashMap::new(),
+    );
+    assert_eq!(expanded.content, "14:30 2026 Friday 16 October 14:30:05");
+    assert!(variables("{{date:%Y}} {{time:%H}}").is_empty());
+}
+
+#[test]
+fn invalid_formats_and_placeholders_stay_as_written() {
+    let template = "{{date:%Q}} {{date:%z}} {{title:%Y}} {{a.b}} {{}} {{open";
+    assert_eq!(
+        expand(template, "T", now(), &HashMap::new()).content,
+        template
+    );
+    assert!(variables(template).is_empty());
+}
@@ -0,0 +1,52 @@
+use std::fs;
+
+use thoth_note::models::note::Note;
+use thoth_note::storage::crypto::is_sealed;
+use thoth_note::storage::fs::FSStorage;
+use thoth_note::storage::Storage;
+
+fn note(title: &str, content: &str) -> Note {
+    Note {
+        title: title.to_string(),
+        content: content.to_string(),
+        ..Note::default()
+    }
+}
+
+#[test]
+fn encrypting_all_notes_seals_plain_notes() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = FSStorage::with_root_dir(dir.path());
+    storage
+        .write_note(&note("Before", "Written in the clear"))
+        .unwrap();
+    storage.set_passphrase("correct horse").unwrap();
+    storage.set_encrypt_all(true).unwrap();
+    assert!(storage.encrypts_all());
+
+    storage.write_note(&note("After", "Also sealed")).unwrap();
+    for title in ["Before", "After"] {
+        let stored = fs::read_to_string(dir.path().join(format!("{}.md", title))).unwrap();
+        assert!(is_sealed(&stored), "{} is not sealed", title);
+    }
+
+    storage.set_encrypt_all(false).unwrap();
+    let stored = fs::read_to_string(dir.path().join("After.md")).unwrap();
+    assert_eq!(stored, "Also sealed");
+}
+
+#[test]
+fn the_vault_config_is_read_again_when_unlocking() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = FSStorage::with_root_dir(dir.path());
+    storage.set_passphrase("correct horse").unwrap();
+    storage.set_encrypt_all(true).unwrap();
+
+    // Notes are written with the settings already known
+    fs::write(dir.path().join("vault.json"), "not json").unwrap();
+    storage.write_note(&note("Secret", "Still sealed")).unwrap();
+    let stored = fs::read_to_string(dir.path().join("Secret.md")).unwrap();
+    assert!(is_sealed(&stored));
+
+    assert!(storage.unlock("correct horse").is_err());
+}
@@ -0,0 +1,254 @@
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use thoth_note::links::percent_decode;
+use thoth_note::models::note::Note;
+use thoth_note::storage::fs::FSStorage;
+use thoth_note::storage::Storage;
+use thoth_note::sync::error::SyncError;
+use thoth_note::sync::webdav::WebDavRemote;
+use thoth_note::sync::{sync, Remote, RemoteEntry, SyncSettings};
+
+/// What the server holds and what it was asked
+#[derive(Default)]
+struct Dav {
+    /// Servers refuse documents until the collection is created
+    collection: bool,
+    /// Documents by name with the version their ETag is made from
+    documents: BTreeMap<String, (Vec<u8>, usize)>,
+    versions: usize,
+    /// Method and path of every request
+    requests: Vec<String>,
+    authorizations: Vec<Option<String>>,
+}
+
+/// A WebDAV server holding a single collection at `/notes/`, in memory
+struct Server {
+    url: String,
+    dav: Arc<Mutex<Dav>>,
+}
+
+impl Server {
+    fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/notes", listener.local_addr().unwrap());
+        let dav = Arc::new(Mutex::new(Dav::default()));
+        let shared = Arc::clone(&dav);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                handle(stream.unwrap(), &shared);
+            }
+        });
+        Self { url, dav }
+    }
+
+    fn requests(&self) -> Vec<String> {
+        std::mem::take(&mut self.dav.lock().unwrap().requests)
+    }
+}
+
+fn handle(mut stream: TcpStream, dav: &Mutex<Dav>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).unwrap();
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut length = 0;
+    let mut authorization = None;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).unwrap();
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let (name, value) = header.split_once(':').unwrap();
+        match name.to_ascii_lowercase().as_str() {
+            "content-length" => length = value.trim().parse().unwrap(),
+            "authorization" => authorization = Some(value.trim().to_string()),
+            _ => {}
+        }
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).unwrap();
+
+    let mut dav = dav.lock().unwrap();
+    dav.requests.push(format!("{} {}", method, path));
+    dav.authorizations.push(authorization);
+    let name = path
+        .strip_prefix("/notes/")
+        .map(percent_decode)
+        .unwrap_or_default();
+
+    let (status, etag, body) = match (method.as_str(), name.as_str()) {
+        ("MKCOL", "") if dav.collection => (405, None, Vec::new()),
+        ("MKCOL", "") => {
+            dav.collection = true;
+            (201, None, Vec::new())
+        }
+        ("PROPFIND", "") if !dav.collection => (404, None, Vec::new()),
+        ("PROPFIND", "") => (207, None, multistatus(&dav).into_bytes()),
+        ("PUT", _) if !dav.collection => (409, None, Vec::new()),
+        ("PUT", name) => {
+            dav.versions += 1;
+            let version = dav.versions;
+            dav.documents.insert(name.to_string(), (body, version));
+            (201, Some(version), Vec::new())
+        }
+        ("GET", name) => match dav.documents.get(name) {
+            Some((data, version)) => (200, Some(*version), data.clone()),
+            None => (404, None, Vec::new()),
+        },
+        ("DELETE", name) => match dav.documents.remove(name) {
+            Some(_) => (204, None, Vec::new()),
+            None => (404, None, Vec::new()),
+        },
+        _ => (405, None, Vec::new()),
+    };
+    drop(dav);
+
+    let mut response = format!(
+        "HTTP/1.1 {} Status\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        body.len()
+    );
+    if let Some(version) = etag {
+        // Weak and quoted, as many servers send them
+        response.push_str(&format!("ETag: W/\"v{}\"\r\n", version));
+    }
+    response.push_str("\r\n");
+    stream.write_all(response.as_bytes()).unwrap();
+    stream.write_all(&body).unwrap();
+}
+
+fn multistatus(dav: &Dav) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\"?><d:multistatus xmlns:d=\"DAV:\">\
+         <d:response><d:href>/notes/</d:href><d:propstat><d:prop>\
+         <d:resourcetype><d:collection/></d:resourcetype></d:prop></d:propstat></d:response>",
+    );
+    for (name, (_, version)) in &dav.documents {
+        xml.push_str(&format!(
+            "<d:response><d:href>/notes/{}</d:href><d:propstat><d:prop>\
+             <d:getetag>\"v{}\"</d:getetag><d:resourcetype/></d:prop></d:propstat></d:response>",
+            name.replace(' ', "%20"),
+            version
+        ));
+    }
+    xml.push_str("</d:multistatus>");
+    xml
+}
+
+fn entry(name: &str, etag: &str) -> RemoteEntry {
+    RemoteEntry {
+        name: name.to_string(),
+        etag: Some(etag.to_string()),
+    }
+}
+
+#[test]
+fn an_empty_server_lists_nothing() {
+    let server = Server::start();
+    let remote = WebDavRemote::new(&server.url, None, None);
+    assert!(remote.list().unwrap().is_empty());
+    assert_eq!(server.requests(), ["PROPFIND /notes/"]);
+}
+
+#[test]
+fn the_first_upload_creates_the_collection() {
+    let server = Server::start();
+    let remote = WebDavRemote::new(&server.url, None, None);
+
+    assert_eq!(
+        remote.put("My Note.json", b"{}").unwrap(),
+        Some("v1".to_string())
+    );
+    assert_eq!(
+        server.requests(),
+        [
+            "PUT /notes/My%20Note.json",
+            "MKCOL /notes/",
+            "PUT /notes/My%20Note.json"
+        ]
+    );
+
+    // Later uploads go straight to the collection
+    assert_eq!(
+        remote.put("Other.json", b"[]").unwrap(),
+        Some("v2".to_string())
+    );
+    assert_eq!(server.requests(), ["PUT /notes/Other.json"]);
+
+    assert_eq!(
+        remote.list().unwrap(),
+        [entry("My Note.json", "v1"), entry("Other.json", "v2")]
+    );
+    assert_eq!(
+        remote.get("My Note.json").unwrap(),
+        (b"{}".to_vec(), Some("v1".to_string()))
+    );
+}
+
+#[test]
+fn deleting_a_missing_document_succeeds() {
+    let server = Server::start();
+    let remote = WebDavRemote::new(&server.url, None, None);
+    remote.put("Gone.json", b"{}").unwrap();
+
+    remote.delete("Gone.json").unwrap();
+    remote.delete("Gone.json").unwrap();
+    assert!(remote.list().unwrap().is_empty());
+}
+
+#[test]
+fn failed_requests_report_the_url_and_status() {
+    let server = Server::start();
+    let remote = WebDavRemote::new(&server.url, None, None);
+
+    match remote.get("Missing.json") {
+        Err(SyncError::Http(url, message)) => {
+            assert_eq!(url, format!("{}/Missing.json", server.url));
+            assert!(message.starts_with("404"));
+        }
+        other => panic!("expected an HTTP error, got {:?}", other),
+    }
+}
+
+#[test]
+fn notes_sync_between_vaults_through_the_server() {
+    let dir = tempfile::tempdir().unwrap();
+    let server = Server::start();
+    let remote = WebDavRemote::new(&server.url, Some("ana".into()), Some("secret".into()));
+    let laptop = FSStorage::with_root_dir(dir.path().join("laptop"));
+    let desktop = FSStorage::with_root_dir(dir.path().join("desktop"));
+    let mut laptop_settings = SyncSettings::default();
+    let mut desktop_settings = SyncSettings::default();
+
+    let note = Note {
+        title: "Ideas".to_string(),
+        content: "First".to_string(),
+        ..Note::default()
+    };
+    laptop.write_note(&note).unwrap();
+    let report = sync(&laptop, &remote, &mut laptop_settings, &mut |_| {}).unwrap();
+    assert_eq!(report.uploaded, 1);
+
+    let report = sync(&desktop, &remote, &mut desktop_settings, &mut |_| {}).unwrap();
+    assert_eq!(report.downloaded, 1);
+    assert_eq!(desktop.read_note("Ideas").unwrap().content, "First");
+
+    // Nothing changed on either side, so nothing moves
+    let report = sync(&laptop, &remote, &mut laptop_settings, &mut |_| {}).unwrap();
+    assert_eq!((report.uploaded, report.downloaded), (0, 0));
+
+    // `ana:secret` in Basic authentication
+    let authorizations = &server.dav.lock().unwrap().authorizations;
+    assert!(authorizations
+        .iter()
+        .all(|authorization| authorization.as_deref() == Some("Basic YW5hOnNlY3JldA==")));
+}